@@ -0,0 +1,40 @@
+#ifndef CODE_SMELLS_H
+#define CODE_SMELLS_H
+
+/* Generated by cbindgen from src/capi.rs; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Analyze the project directory at `path` and return the report as the JSON
+// of `--format json`, or `{"error": "..."}` if it can't be analyzed, including
+// when the analysis panics.
+//
+// `options_json` may be null, or a JSON object with any of `languages`,
+// `checks`, `thresholds`, `excludes`, `max_file_size`, `no_default_skips`, and
+// `workspace_excluded`, e.g. `{"checks": ["functions"], "thresholds": {"func-warn": 40}}`.
+//
+// # Safety
+//
+// `path` must be a valid NUL-terminated string, and `options_json` null or
+// one. Free the result with [`code_smells_free_string`].
+char *code_smells_analyze(const char *path, const char *options_json);
+
+// Free a string returned by [`code_smells_analyze`]; null is ignored.
+//
+// # Safety
+//
+// `s` must be null or a string from this library not yet freed.
+void code_smells_free_string(char *s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CODE_SMELLS_H */
@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Archive types analyzed from a temporary extraction, by file name suffix
+const TAR_SUFFIXES: &[&str] = &[".tar", ".tar.gz", ".tgz", ".tar.bz2", ".tbz2", ".tar.xz", ".txz"];
+const ZIP_SUFFIXES: &[&str] = &[".zip"];
+
+fn has_suffix(path: &Path, suffixes: &[&str]) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+    suffixes.iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// Whether a target is an archive file rather than a directory
+pub fn is_archive(path: &Path) -> bool {
+    path.is_file() && (has_suffix(path, TAR_SUFFIXES) || has_suffix(path, ZIP_SUFFIXES))
+}
+
+/// Extract an archive beneath `dest` and return the project directory in it.
+///
+/// Release archives usually wrap everything in one top-level directory,
+/// e.g. `project-1.2.0/`; that directory is the project.
+pub fn extract(archive: &Path, dest: &Path) -> Result<PathBuf, String> {
+    let fail = |e: String| format!("Cannot extract '{}': {}", archive.display(), e);
+    fs::create_dir_all(dest).map_err(|e| fail(e.to_string()))?;
+    // Both refuse to write outside `dest`
+    let mut command = if has_suffix(archive, ZIP_SUFFIXES) {
+        let mut unzip = Command::new("unzip");
+        unzip.args(["-q", "-o"]).arg(archive).arg("-d").arg(dest);
+        unzip
+    } else {
+        let mut tar = Command::new("tar");
+        tar.arg("-xf").arg(archive).arg("-C").arg(dest);
+        tar
+    };
+    let output = command.output().map_err(|e| fail(format!("cannot run {:?}: {}", command.get_program(), e)))?;
+    if !output.status.success() {
+        return Err(fail(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    Ok(project_root(dest))
+}
+
+/// The single top-level directory of an extraction, or the extraction itself
+fn project_root(dest: &Path) -> PathBuf {
+    let entries: Vec<PathBuf> = match fs::read_dir(dest) {
+        Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+        Err(_) => return dest.to_path_buf(),
+    };
+    match entries.as_slice() {
+        [only] if only.is_dir() => only.clone(),
+        _ => dest.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_archive_suffixes() {
+        assert!(has_suffix(Path::new("dist/app-1.0.TAR.GZ"), TAR_SUFFIXES));
+        assert!(has_suffix(Path::new("bundle.zip"), ZIP_SUFFIXES));
+        assert!(!has_suffix(Path::new("src/tar.rs"), TAR_SUFFIXES));
+    }
+
+    #[test]
+    fn test_project_root_unwraps_single_directory() {
+        let dest = TempDir::new("archive");
+        fs::create_dir_all(dest.join("app-1.0/lib")).unwrap();
+        assert_eq!(project_root(&dest), dest.join("app-1.0"));
+        fs::write(dest.join("README"), "").unwrap();
+        assert_eq!(project_root(&dest), dest.path());
+    }
+}
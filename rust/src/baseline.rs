@@ -0,0 +1,191 @@
+use crate::output::{Issue, Report, Severity};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Baseline looked up in the project directory when `--baseline` is not given
+pub const BASELINE_FILE_NAME: &str = ".code-smells-baseline.json";
+
+const BASELINE_VERSION: u32 = 1;
+
+/// Known findings recorded with `--write-baseline`
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Baseline {
+    version: u32,
+    issues: Vec<BaselineIssue>,
+}
+
+/// A recorded finding. Line numbers are left out so that edits elsewhere in
+/// a file don't turn known issues into new ones.
+#[derive(Debug, Deserialize, Serialize)]
+struct BaselineIssue {
+    file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(rename = "type")]
+    check_type: String,
+    severity: Severity,
+    value: usize,
+}
+
+/// Issues are matched on file, name, and check
+type Key = (String, Option<String>, String);
+
+/// Baseline entries indexed for matching; each accounts for at most one current issue
+pub struct BaselineMatcher<'a> {
+    known: HashMap<Key, Vec<&'a BaselineIssue>>,
+}
+
+impl Baseline {
+    /// Snapshot the warnings and errors of a report
+    pub fn from_report(report: &Report) -> Self {
+        Baseline {
+            version: BASELINE_VERSION,
+            issues: report
+                .issues
+                .iter()
+                .filter(|i| i.severity != Severity::Info)
+                .map(|i| BaselineIssue {
+                    file: i.file.display().to_string(),
+                    name: i.name.clone(),
+                    check_type: i.check_type.to_string(),
+                    severity: i.severity,
+                    value: i.value,
+                })
+                .collect(),
+        }
+    }
+
+    /// Number of recorded issues
+    pub fn len(&self) -> usize {
+        self.issues.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Cannot read '{}': {}", path.display(), e))?;
+        let baseline: Baseline =
+            serde_json::from_str(&content).map_err(|e| format!("Invalid baseline '{}': {}", path.display(), e))?;
+        if baseline.version != BASELINE_VERSION {
+            return Err(format!(
+                "Unsupported baseline version {} in '{}'",
+                baseline.version,
+                path.display()
+            ));
+        }
+        Ok(baseline)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json + "\n").map_err(|e| format!("Cannot write '{}': {}", path.display(), e))
+    }
+
+    /// Index the entries for filtering reports, which may arrive a file at a time
+    pub fn matcher(&self) -> BaselineMatcher<'_> {
+        let mut known: HashMap<Key, Vec<&BaselineIssue>> = HashMap::new();
+        for entry in &self.issues {
+            known
+                .entry((entry.file.clone(), entry.name.clone(), entry.check_type.clone()))
+                .or_default()
+                .push(entry);
+        }
+        BaselineMatcher { known }
+    }
+}
+
+impl BaselineMatcher<'_> {
+    /// Drop the issues already in the baseline, keeping new and worsened ones.
+    ///
+    /// An issue is worsened when its value grew or its severity rose. Entries
+    /// used up by earlier calls no longer match. Returns the number of issues
+    /// dropped, which is also added to `report.baselined`.
+    pub fn filter(&mut self, report: &mut Report) -> usize {
+        let before = report.issues.len();
+        report
+            .issues
+            .retain(|issue| issue.severity == Severity::Info || !self.take_match(issue));
+
+        let dropped = before - report.issues.len();
+        report.baselined += dropped;
+        dropped
+    }
+
+    /// Consume the baseline entry covering an issue, if there is one
+    fn take_match(&mut self, issue: &Issue) -> bool {
+        let key = (issue.file.display().to_string(), issue.name.clone(), issue.check_type.to_string());
+        let Some(entries) = self.known.get_mut(&key) else {
+            return false;
+        };
+        let covered = entries
+            .iter()
+            .position(|e| issue.value <= e.value && issue.severity <= e.severity);
+        match covered {
+            Some(idx) => {
+                entries.remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn issue(file: &str, name: &str, severity: Severity, value: usize) -> Issue {
+        Issue {
+            severity,
+            file: PathBuf::from(file),
+            line: Some(1),
+            column: None,
+            end_line: None,
+            name: Some(name.to_string()),
+            check_type: "function-length",
+            value,
+            limit: 30,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_only_new_and_worsened_issues_remain() {
+        let old = Report {
+            issues: vec![
+                issue("a.py", "parse", Severity::Warning, 40),
+                issue("a.py", "render", Severity::Warning, 35),
+            ],
+            files_scanned: 1,
+            ..Default::default()
+        };
+        let baseline = Baseline::from_report(&old);
+
+        let mut current = Report {
+            issues: vec![
+                // Unchanged, and shrunk: both known
+                issue("a.py", "parse", Severity::Warning, 40),
+                issue("a.py", "render", Severity::Warning, 32),
+                // New
+                issue("b.py", "load", Severity::Warning, 45),
+                // Same key as a known issue, but worse
+                issue("a.py", "parse", Severity::Error, 60),
+            ],
+            files_scanned: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(baseline.matcher().filter(&mut current), 2);
+        let remaining: Vec<(&str, usize)> = current
+            .issues
+            .iter()
+            .map(|i| (i.name.as_deref().unwrap(), i.value))
+            .collect();
+        assert_eq!(remaining, vec![("load", 45), ("parse", 60)]);
+    }
+}
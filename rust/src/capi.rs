@@ -0,0 +1,202 @@
+//! C ABI for editors and other native hosts, behind the `capi` feature.
+//!
+//! `include/code_smells.h` declares these functions; regenerate it with
+//! `cbindgen --config cbindgen.toml --output include/code_smells.h` after
+//! changing them.
+
+use crate::checks::rules::DEFAULT_HELP_URI_BASE;
+use crate::config::{Overrides, ThresholdOverrides};
+use crate::languages::LanguageType;
+use crate::options::{CheckSelection, CheckType, GroupBy, OutputFormat, SeverityFilter};
+use crate::output::{self, ReportOptions};
+use crate::Config;
+use serde::Deserialize;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+/// The `options_json` of [`code_smells_analyze`]; every field may be left out
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Options {
+    /// Language names, as for `--lang`
+    languages: Vec<String>,
+    /// `--check` values
+    checks: Vec<String>,
+    /// Named like the `[thresholds]` keys of `.code-smells.toml`
+    thresholds: ThresholdOverrides,
+    excludes: Vec<String>,
+    /// Bytes
+    max_file_size: Option<u64>,
+    /// As for `--no-default-skips`
+    no_default_skips: bool,
+    /// As for `--workspace-excluded`
+    workspace_excluded: bool,
+}
+
+impl Options {
+    fn into_config(self) -> Result<Config, String> {
+        let languages = self
+            .languages
+            .iter()
+            .map(|name| {
+                LanguageType::ALL
+                    .into_iter()
+                    .find(|l| l.name() == name.to_lowercase())
+                    .ok_or_else(|| format!("Unknown language '{}'", name))
+            })
+            .collect::<Result<_, _>>()?;
+        let checks = if self.checks.is_empty() {
+            CheckSelection::all()
+        } else {
+            let checks: Vec<CheckType> = self
+                .checks
+                .iter()
+                .map(|name| CheckType::from_name(name).ok_or_else(|| format!("Unknown check '{}'", name)))
+                .collect::<Result<_, _>>()?;
+            CheckSelection::new(&checks)
+        };
+        Ok(Config {
+            languages,
+            checks,
+            thresholds: Overrides {
+                thresholds: self.thresholds,
+                ..Default::default()
+            },
+            excludes: self.excludes,
+            max_file_size: self.max_file_size,
+            no_default_skips: self.no_default_skips,
+            workspace_excluded: self.workspace_excluded,
+        })
+    }
+}
+
+/// The `--format json` report for a project directory
+fn report_json(path: &str, options_json: Option<&str>) -> Result<String, String> {
+    let options: Options = match options_json {
+        Some(json) => serde_json::from_str(json).map_err(|e| format!("Invalid options: {}", e))?,
+        None => Options::default(),
+    };
+    let config = options.into_config()?;
+    let project = crate::load_project(Path::new(path), &config)?;
+    let report = project.analyze(&config);
+
+    let options = ReportOptions {
+        project_dir: &project.dir,
+        languages: &project.languages,
+        filter: SeverityFilter::All,
+        group_by: GroupBy::Severity,
+        summary_only: false,
+        help_base: DEFAULT_HELP_URI_BASE,
+        custom: &project.custom,
+        projects: &[],
+        timing: false,
+    };
+    let mut out = Vec::new();
+    output::write_report(&mut out, &report, OutputFormat::Json, &options, false).map_err(|e| e.to_string())?;
+    String::from_utf8(out).map_err(|e| e.to_string())
+}
+
+/// Run `analyze`, turning a panic into an error: unwinding into the C caller
+/// is undefined behavior.
+fn without_unwinding(analyze: impl FnOnce() -> Result<String, String>) -> Result<String, String> {
+    panic::catch_unwind(AssertUnwindSafe(analyze)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown cause".to_string());
+        Err(format!("Analysis panicked: {}", message))
+    })
+}
+
+fn to_c_string(s: String) -> *mut c_char {
+    // JSON escapes control characters, so there is no interior NUL
+    CString::new(s).map(CString::into_raw).unwrap_or(std::ptr::null_mut())
+}
+
+/// Analyze the project directory at `path` and return the report as the JSON
+/// of `--format json`, or `{"error": "..."}` if it can't be analyzed, including
+/// when the analysis panics.
+///
+/// `options_json` may be null, or a JSON object with any of `languages`,
+/// `checks`, `thresholds`, `excludes`, `max_file_size`, `no_default_skips`, and
+/// `workspace_excluded`, e.g. `{"checks": ["functions"], "thresholds": {"func-warn": 40}}`.
+///
+/// # Safety
+///
+/// `path` must be a valid NUL-terminated string, and `options_json` null or
+/// one. Free the result with [`code_smells_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn code_smells_analyze(path: *const c_char, options_json: *const c_char) -> *mut c_char {
+    let text = |ptr: *const c_char| {
+        // SAFETY: the caller passes null or a NUL-terminated string
+        (!ptr.is_null()).then(|| unsafe { CStr::from_ptr(ptr) }.to_str().map_err(|e| e.to_string()))
+    };
+    let result = match (text(path), text(options_json).transpose()) {
+        (Some(Ok(path)), Ok(options)) => without_unwinding(|| report_json(path, options)),
+        (None, _) => Err("No path given".to_string()),
+        (Some(Err(e)), _) | (_, Err(e)) => Err(format!("Invalid UTF-8: {}", e)),
+    };
+    to_c_string(result.unwrap_or_else(|e| serde_json::json!({ "error": e }).to_string()))
+}
+
+/// Free a string returned by [`code_smells_analyze`]; null is ignored.
+///
+/// # Safety
+///
+/// `s` must be null or a string from this library not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn code_smells_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: the string came from `CString::into_raw`
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::fs;
+
+    #[test]
+    fn test_analyze_round_trip() {
+        let dir = TempDir::new("capi");
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+        fs::write(dir.join("src/lib.rs"), "pub fn f() {\n    if a {\n        if b {}\n    }\n}\n").unwrap();
+        let path = CString::new(dir.display().to_string()).unwrap();
+        let options = CString::new(r#"{"checks": ["nesting"], "thresholds": {"nest-warn": 1}}"#).unwrap();
+
+        let json = unsafe {
+            let result = code_smells_analyze(path.as_ptr(), options.as_ptr());
+            let json = CStr::from_ptr(result).to_str().unwrap().to_string();
+            code_smells_free_string(result);
+            json
+        };
+
+        let report: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(report["languages"], serde_json::json!(["rust"]));
+        assert_eq!(report["issues"][0]["type"], "nesting-depth");
+    }
+
+    #[test]
+    fn test_analyze_error() {
+        let options = CString::new(r#"{"checks": ["nope"]}"#).unwrap();
+        let json = unsafe {
+            let result = code_smells_analyze(c".".as_ptr(), options.as_ptr());
+            let json = CStr::from_ptr(result).to_str().unwrap().to_string();
+            code_smells_free_string(result);
+            json
+        };
+        assert_eq!(json, r#"{"error":"Unknown check 'nope'"}"#);
+    }
+
+    #[test]
+    fn test_panic_becomes_error() {
+        assert_eq!(without_unwinding(|| Ok("{}".to_string())), Ok("{}".to_string()));
+        let result = without_unwinding(|| panic!("bad {}", "state"));
+        assert_eq!(result, Err("Analysis panicked: bad state".to_string()));
+    }
+}
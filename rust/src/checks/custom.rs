@@ -0,0 +1,249 @@
+use crate::languages::{FunctionInfo, LanguageType};
+use crate::output::{Issue, Report, Severity};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// What a custom check counts its matches over
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Each line on its own
+    Line,
+    /// Each function body
+    Function,
+    /// The whole file
+    File,
+}
+
+/// A `[[checks]]` entry in the config file
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomCheckDef {
+    pub name: String,
+    /// Regex to count; a check has either a pattern and scope or a command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Scope>,
+    /// External plugin to run instead: the program and its arguments (see `plugin`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+    /// Matches allowed per scope before an issue is reported
+    #[serde(default)]
+    pub threshold: usize,
+    #[serde(default = "default_severity")]
+    pub severity: Severity,
+    /// Languages to run on (default: all)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    /// Description shown in reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Documentation link for JSON and SARIF output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub help_uri: Option<String>,
+}
+
+fn default_severity() -> Severity {
+    Severity::Warning
+}
+
+/// A validated custom check, ready to run
+pub struct CustomCheck {
+    /// Issues carry a `&'static str` rule id; custom checks are loaded once per run
+    pub id: &'static str,
+    /// The compiled pattern, and the scope it counts over; None for a plugin
+    pub regex: Option<(Regex, Scope)>,
+    pub def: CustomCheckDef,
+}
+
+impl CustomCheck {
+    /// Validate a definition and compile its pattern
+    pub fn compile(def: CustomCheckDef) -> Result<Self, String> {
+        let valid_name = !def.name.is_empty()
+            && def
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid_name {
+            return Err(format!(
+                "Invalid check name '{}' (use lowercase letters, digits, '-' and '_')",
+                def.name
+            ));
+        }
+        if super::rules::RULES.iter().any(|r| r.id == def.name) {
+            return Err(format!("Check name '{}' is used by a built-in check", def.name));
+        }
+        if def.severity == Severity::Info {
+            return Err(format!("Check '{}': severity must be 'warning' or 'error'", def.name));
+        }
+        for lang in &def.languages {
+            if !LanguageType::ALL.iter().any(|l| l.name() == lang) {
+                return Err(format!("Check '{}': unknown language '{}'", def.name, lang));
+            }
+        }
+
+        let regex = match (&def.pattern, def.scope, def.command.is_empty()) {
+            (Some(pattern), Some(scope), true) => {
+                let regex =
+                    Regex::new(pattern).map_err(|e| format!("Check '{}': invalid pattern: {}", def.name, e))?;
+                Some((regex, scope))
+            }
+            (None, None, false) => None,
+            (Some(_), None, true) => return Err(format!("Check '{}': a pattern needs a scope", def.name)),
+            _ => {
+                return Err(format!(
+                    "Check '{}': set either pattern and scope, or command",
+                    def.name
+                ))
+            }
+        };
+
+        Ok(CustomCheck {
+            id: Box::leak(def.name.clone().into_boxed_str()),
+            regex,
+            def,
+        })
+    }
+
+    pub fn applies_to(&self, lang: LanguageType) -> bool {
+        self.def.languages.is_empty() || self.def.languages.iter().any(|l| l == lang.name())
+    }
+
+    pub fn description(&self) -> &str {
+        self.def.message.as_deref().unwrap_or(&self.def.name)
+    }
+}
+
+/// Run user-defined regex checks on a file
+pub fn check_custom(
+    checks: &[CustomCheck],
+    lang: LanguageType,
+    content: &str,
+    functions: &[FunctionInfo],
+    rel_path: &Path,
+    report: &mut Report,
+) {
+    let lines: Vec<&str> = content.lines().collect();
+
+    for check in checks.iter().filter(|c| c.applies_to(lang)) {
+        // Plugins see every file at once, after the per-file checks
+        let (regex, scope) = match &check.regex {
+            Some((regex, scope)) => (regex, *scope),
+            None => continue,
+        };
+        let count_in = |lines: &[&str]| -> usize { lines.iter().map(|l| regex.find_iter(l).count()).sum() };
+
+        // (line, function name, match count) for each scope unit
+        let units: Vec<(Option<usize>, Option<String>, usize)> = match scope {
+            Scope::Line => lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| (Some(i + 1), None, count_in(&[line])))
+                .collect(),
+            Scope::Function => functions
+                .iter()
+                .map(|func| {
+                    let (_, body) = super::function_body(func, &lines);
+                    (Some(func.start_line), Some(func.name.clone()), count_in(body))
+                })
+                .collect(),
+            Scope::File => vec![(None, None, count_in(&lines))],
+        };
+
+        for (line, name, count) in units {
+            if count <= check.def.threshold {
+                continue;
+            }
+
+            let location = match (line, &name) {
+                (Some(l), Some(n)) => format!("{}:{} {}", rel_path.display(), l, n),
+                (Some(l), None) => format!("{}:{}", rel_path.display(), l),
+                _ => rel_path.display().to_string(),
+            };
+            report.add_issue(Issue {
+                severity: check.def.severity,
+                file: rel_path.to_path_buf(),
+                line,
+                column: None,
+                end_line: None,
+                name,
+                check_type: check.id,
+                value: count,
+                limit: check.def.threshold,
+                message: format!("{} ({}: {} matches, limit: {})", location, check.description(), count, check.def.threshold),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def(scope: Scope, threshold: usize) -> CustomCheckDef {
+        CustomCheckDef {
+            name: "no-print".to_string(),
+            pattern: Some(r"\bprint\(".to_string()),
+            scope: Some(scope),
+            command: Vec::new(),
+            threshold,
+            severity: Severity::Warning,
+            languages: vec!["python".to_string()],
+            message: None,
+            help_uri: None,
+        }
+    }
+
+    #[test]
+    fn test_scopes() {
+        let code = "def f():\n    print(1)\n    print(2)\n";
+        let functions = vec![FunctionInfo {
+            name: "f".to_string(),
+            start_line: 1,
+            line_count: 3,
+            max_nesting: 0,
+        }];
+
+        let run = |scope, threshold| {
+            let checks = vec![CustomCheck::compile(def(scope, threshold)).unwrap()];
+            let mut report = Report::default();
+            check_custom(&checks, LanguageType::Python, code, &functions, Path::new("a.py"), &mut report);
+            report.issues.iter().map(|i| (i.line, i.value)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(Scope::Line, 0), vec![(Some(2), 1), (Some(3), 1)]);
+        assert_eq!(run(Scope::Function, 1), vec![(Some(1), 2)]);
+        assert_eq!(run(Scope::File, 2), vec![]);
+    }
+
+    #[test]
+    fn test_language_filter() {
+        let checks = vec![CustomCheck::compile(def(Scope::File, 0)).unwrap()];
+        let mut report = Report::default();
+        check_custom(&checks, LanguageType::Rust, "print(1)", &[], Path::new("a.rs"), &mut report);
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_definitions_rejected() {
+        let mut bad = def(Scope::Line, 0);
+        bad.pattern = Some("(".to_string());
+        assert!(CustomCheck::compile(bad).is_err());
+
+        let mut both = def(Scope::Line, 0);
+        both.command = vec!["./lint".to_string()];
+        assert!(CustomCheck::compile(both).is_err());
+
+        let mut plugin = def(Scope::Line, 0);
+        plugin.pattern = None;
+        plugin.scope = None;
+        plugin.command = vec!["./lint".to_string()];
+        assert!(CustomCheck::compile(plugin).is_ok());
+
+        let mut builtin = def(Scope::Line, 0);
+        builtin.name = "file-length".to_string();
+        assert!(CustomCheck::compile(builtin).is_err());
+    }
+}
@@ -0,0 +1,36 @@
+use crate::config::Thresholds;
+use crate::languages::TypeInfo;
+use crate::output::{Issue, Report, Severity};
+use std::path::Path;
+
+/// Check for enums and union types with too many variants
+pub fn check_enum_variants(types: &[TypeInfo], rel_path: &Path, thresholds: &Thresholds, report: &mut Report) {
+    for ty in types {
+        let (severity, limit) = if ty.variant_count > thresholds.variant_error {
+            (Severity::Error, thresholds.variant_error)
+        } else if ty.variant_count > thresholds.variant_warn {
+            (Severity::Warning, thresholds.variant_warn)
+        } else {
+            continue;
+        };
+
+        report.add_issue(Issue {
+            severity,
+            file: rel_path.to_path_buf(),
+            line: Some(ty.start_line),
+            column: None,
+            end_line: None,
+            name: Some(ty.name.clone()),
+            check_type: "enum-variants",
+            value: ty.variant_count,
+            limit,
+            message: format!(
+                "{}:{} {} ({} variants)",
+                rel_path.display(),
+                ty.start_line,
+                ty.name,
+                ty.variant_count
+            ),
+        });
+    }
+}
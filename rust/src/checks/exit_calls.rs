@@ -0,0 +1,144 @@
+use crate::config::Thresholds;
+use crate::languages::{FunctionInfo, LanguageType};
+use crate::output::{Issue, Report, Severity};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+
+static RUST_EXITS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(process::exit\s*\(|process::abort\s*\(|panic!\s*\()").unwrap());
+
+static PYTHON_EXITS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(\bsys\.exit\s*\(|\bos\._exit\s*\(|(^|[^.\w])(exit|quit)\s*\(|\braise\s+SystemExit\b)").unwrap()
+});
+
+static TYPESCRIPT_EXITS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\bprocess\.exit\s*\(|\bthrow\b)").unwrap());
+
+static DART_EXITS: Lazy<Regex> = Lazy::new(|| Regex::new(r"((^|[^.\w])exit\s*\(|\bthrow\b)").unwrap());
+
+static GO_EXITS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\bos\.Exit\s*\(|(^|[^.\w])panic\s*\(|\blog\.(Fatal|Panic)(f|ln)?\s*\()").unwrap());
+
+static JAVA_EXITS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\bSystem\.exit\s*\(|\.halt\s*\(|\bthrow\b)").unwrap());
+
+static SWIFT_EXITS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(\b(fatalError|preconditionFailure)\s*\(|(^|[^.\w])exit\s*\(|\bthrow\s)").unwrap()
+});
+
+static JULIA_EXITS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"((^|[^.\w])(exit|throw|error)\s*\()").unwrap());
+
+static ELIXIR_EXITS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(\bSystem\.(halt|stop)\s*\(|(^|[^.\w])exit\s*\(|\braise\b|\bthrow\b)").unwrap()
+});
+
+fn exit_pattern(lang: LanguageType) -> Option<(&'static Regex, &'static str)> {
+    match lang {
+        LanguageType::Rust => Some((&RUST_EXITS, "//")),
+        LanguageType::Python => Some((&PYTHON_EXITS, "#")),
+        LanguageType::TypeScript | LanguageType::JavaScript => Some((&TYPESCRIPT_EXITS, "//")),
+        LanguageType::Dart => Some((&DART_EXITS, "//")),
+        LanguageType::Elixir => Some((&ELIXIR_EXITS, "#")),
+        LanguageType::Go => Some((&GO_EXITS, "//")),
+        LanguageType::Java => Some((&JAVA_EXITS, "//")),
+        LanguageType::Swift => Some((&SWIFT_EXITS, "//")),
+        LanguageType::Julia => Some((&JULIA_EXITS, "#")),
+        LanguageType::Terraform | LanguageType::Generic => None,
+    }
+}
+
+/// Entry points are expected to exit, so they are not checked
+fn is_entry_point(rel_path: &Path) -> bool {
+    let stem = rel_path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    matches!(stem, "main" | "__main__" | "cli")
+        || rel_path.components().any(|c| c.as_os_str() == "bin")
+}
+
+/// Check for functions that exit, panic, or throw more often than allowed
+pub fn check_exit_calls(
+    lang: LanguageType,
+    functions: &[FunctionInfo],
+    lines: &[&str],
+    rel_path: &Path,
+    thresholds: &Thresholds,
+    report: &mut Report,
+) {
+    if is_entry_point(rel_path) {
+        return;
+    }
+
+    for func in functions {
+        if func.name == "main" {
+            continue;
+        }
+
+        let (_, body) = super::function_body(func, lines);
+        let count = count_exit_calls(lang, body);
+
+        let (severity, limit) = if count > thresholds.exit_error {
+            (Severity::Error, thresholds.exit_error)
+        } else if count > thresholds.exit_warn {
+            (Severity::Warning, thresholds.exit_warn)
+        } else {
+            continue;
+        };
+
+        report.add_issue(Issue {
+            severity,
+            file: rel_path.to_path_buf(),
+            line: Some(func.start_line),
+            column: Some(func.name_column(lines)),
+            end_line: Some(func.end_line()),
+            name: Some(func.name.clone()),
+            check_type: "exit-calls",
+            value: count,
+            limit,
+            message: format!(
+                "{}:{} {} ({} exit/panic/throw calls)",
+                rel_path.display(),
+                func.start_line,
+                func.name,
+                count
+            ),
+        });
+    }
+}
+
+fn count_exit_calls(lang: LanguageType, body: &[&str]) -> usize {
+    let Some((pattern, comment)) = exit_pattern(lang) else {
+        return 0;
+    };
+    body.iter()
+        .map(|line| {
+            let code = line.split(comment).next().unwrap_or("");
+            pattern.find_iter(code).count()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_exit_calls() {
+        let rust = ["if bad { panic!(\"no\") }", "std::process::exit(1); // process::exit(2)"];
+        assert_eq!(count_exit_calls(LanguageType::Rust, &rust), 2);
+
+        let python = ["    sys.exit(1)", "    exit(2)", "    self.exit(3)", "    raise SystemExit"];
+        assert_eq!(count_exit_calls(LanguageType::Python, &python), 3);
+
+        let ts = ["if (!x) throw new Error('x');", "process.exit(1);"];
+        assert_eq!(count_exit_calls(LanguageType::TypeScript, &ts), 2);
+    }
+
+    #[test]
+    fn test_entry_points_skipped() {
+        assert!(is_entry_point(Path::new("src/main.rs")));
+        assert!(is_entry_point(Path::new("bin/tool.dart")));
+        assert!(is_entry_point(Path::new("pkg/__main__.py")));
+        assert!(!is_entry_point(Path::new("src/lib.rs")));
+    }
+}
@@ -0,0 +1,113 @@
+use super::markers::comment_start;
+use super::suppress::DIRECTIVE_LINES;
+use crate::languages::LanguageType;
+use std::io::{self, BufRead};
+
+/// Comment text marking a file as generated, unless `generated-markers` says otherwise
+pub const DEFAULT_GENERATED_MARKERS: &[&str] = &["DO NOT EDIT", "@generated"];
+
+/// Average line length above which a JavaScript or TypeScript file counts as minified
+const MINIFIED_LINE_LENGTH: usize = 200;
+
+/// What the skip rules, the ignore-file directive, and the file-length check
+/// need to know about a file, which a streamed read can give as well
+#[derive(Debug, Default, PartialEq)]
+pub struct FileShape {
+    /// The first `DIRECTIVE_LINES` lines
+    pub head: String,
+    pub bytes: usize,
+    pub lines: usize,
+    pub binary: bool,
+}
+
+impl FileShape {
+    pub fn of(content: &str) -> Self {
+        let head: usize = content.split_inclusive('\n').take(DIRECTIVE_LINES).map(str::len).sum();
+        FileShape {
+            head: content[..head].to_string(),
+            bytes: content.len(),
+            lines: content.lines().count(),
+            binary: content.contains('\0'),
+        }
+    }
+
+    /// Read a file a line at a time, holding no more than its longest line
+    pub fn stream(mut reader: impl BufRead) -> io::Result<Self> {
+        let mut shape = FileShape::default();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 {
+                return Ok(shape);
+            }
+            shape.bytes += read;
+            shape.lines += 1;
+            shape.binary |= line.contains('\0');
+            if shape.lines <= DIRECTIVE_LINES {
+                shape.head.push_str(&line);
+            }
+        }
+    }
+}
+
+/// Why a file is not worth checking: binary, minified, or generated
+pub fn skip_reason(lang: LanguageType, shape: &FileShape, markers: &[String]) -> Option<&'static str> {
+    if shape.binary {
+        return Some("binary");
+    }
+    if is_minified(lang, shape) {
+        return Some("minified");
+    }
+    let generated = shape.head.lines().any(|line| {
+        comment_start(lang, line).is_some_and(|start| markers.iter().any(|m| line[start..].contains(m.as_str())))
+    });
+    generated.then_some("generated")
+}
+
+fn is_minified(lang: LanguageType, shape: &FileShape) -> bool {
+    matches!(lang, LanguageType::JavaScript | LanguageType::TypeScript)
+        && shape.bytes / shape.lines.max(1) > MINIFIED_LINE_LENGTH
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults() -> Vec<String> {
+        DEFAULT_GENERATED_MARKERS.iter().map(|m| m.to_string()).collect()
+    }
+
+    #[test]
+    fn test_generated_marker() {
+        let go = "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage pb\n";
+        assert_eq!(skip_reason(LanguageType::Go, &FileShape::of(go), &defaults()), Some("generated"));
+
+        let docblock = "/**\n * @generated\n */\nexport const a = 1;\n";
+        assert_eq!(skip_reason(LanguageType::TypeScript, &FileShape::of(docblock), &defaults()), Some("generated"));
+
+        // Only in a comment near the top, and only the configured markers
+        let string = "fn main() {\n    println!(\"DO NOT EDIT\");\n}\n";
+        assert_eq!(skip_reason(LanguageType::Rust, &FileShape::of(string), &defaults()), None);
+        assert_eq!(skip_reason(LanguageType::Go, &FileShape::of(go), &[]), None);
+        assert_eq!(skip_reason(LanguageType::Go, &FileShape::of(go), &["protoc-gen-go".to_string()]), Some("generated"));
+    }
+
+    #[test]
+    fn test_minified_and_binary() {
+        let minified = format!("{}\n", "var a=1;".repeat(100));
+        assert_eq!(skip_reason(LanguageType::JavaScript, &FileShape::of(&minified), &[]), Some("minified"));
+        assert_eq!(skip_reason(LanguageType::Python, &FileShape::of(&minified), &[]), None);
+        assert_eq!(skip_reason(LanguageType::JavaScript, &FileShape::of("const a = 1;\n"), &[]), None);
+        assert_eq!(skip_reason(LanguageType::Generic, &FileShape::of("PK\u{3}\u{4}\0\0"), &[]), Some("binary"));
+    }
+
+    #[test]
+    fn test_stream_matches_content() {
+        let many = "x\r\n".repeat(DIRECTIVE_LINES + 5);
+        for content in ["", "a", "a\n", "a\n\nb", many.as_str()] {
+            assert_eq!(FileShape::stream(content.as_bytes()).unwrap(), FileShape::of(content), "{:?}", content);
+        }
+        assert!(FileShape::stream(&b"caf\xe9\n"[..]).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use crate::languages::{char_offset, LanguageType};
+use crate::output::{Issue, Report, Severity};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Lexer state carried across lines
+#[derive(Default)]
+struct LexState {
+    in_block_comment: bool,
+    /// Closing delimiter of a multi-line string (`"""`, `'''`, or a backtick)
+    in_multiline_string: Option<&'static str>,
+}
+
+/// Comment and string syntax needed to find identifiers in code
+struct Syntax {
+    line_comment: &'static str,
+    block_comments: bool,
+    triple_quotes: bool,
+    backtick_strings: bool,
+    single_quote_strings: bool,
+}
+
+fn syntax_for(lang: LanguageType) -> Syntax {
+    match lang {
+        LanguageType::Python => Syntax {
+            line_comment: "#",
+            block_comments: false,
+            triple_quotes: true,
+            backtick_strings: false,
+            single_quote_strings: true,
+        },
+        LanguageType::Elixir => Syntax {
+            line_comment: "#",
+            block_comments: false,
+            triple_quotes: true,
+            backtick_strings: false,
+            single_quote_strings: true,
+        },
+        LanguageType::TypeScript | LanguageType::JavaScript | LanguageType::Go => Syntax {
+            line_comment: "//",
+            block_comments: true,
+            triple_quotes: false,
+            backtick_strings: true,
+            single_quote_strings: true,
+        },
+        LanguageType::Dart | LanguageType::Java | LanguageType::Generic => Syntax {
+            line_comment: "//",
+            block_comments: true,
+            triple_quotes: true,
+            backtick_strings: false,
+            single_quote_strings: true,
+        },
+        LanguageType::Swift => Syntax {
+            line_comment: "//",
+            block_comments: true,
+            triple_quotes: true,
+            backtick_strings: false,
+            single_quote_strings: false,
+        },
+        LanguageType::Julia => Syntax {
+            line_comment: "#",
+            block_comments: false,
+            triple_quotes: true,
+            backtick_strings: false,
+            // `'` is also the adjoint operator, as in `A'`
+            single_quote_strings: false,
+        },
+        LanguageType::Terraform => Syntax {
+            line_comment: "#",
+            block_comments: true,
+            triple_quotes: false,
+            backtick_strings: false,
+            single_quote_strings: false,
+        },
+        LanguageType::Rust => Syntax {
+            line_comment: "//",
+            block_comments: true,
+            triple_quotes: false,
+            backtick_strings: false,
+            // `'` starts lifetimes as well as char literals
+            single_quote_strings: false,
+        },
+    }
+}
+
+/// Zero-width and invisible formatting characters that can hide inside identifiers
+fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' | '\u{00AD}')
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$' || is_invisible(c)
+}
+
+/// An identifier containing non-ASCII characters
+#[derive(Debug, PartialEq, Eq)]
+struct SuspiciousIdent {
+    line: usize,
+    ident: String,
+    /// Mixes ASCII letters with non-ASCII ones, or contains invisible characters
+    confusable: bool,
+}
+
+/// Check for identifiers containing non-ASCII or confusable characters
+pub fn check_identifiers(lang: LanguageType, content: &str, rel_path: &Path, report: &mut Report) {
+    for found in find_suspicious_identifiers(lang, content) {
+        // Greek letters and math symbols are idiomatic in Julia; only lookalikes are suspicious
+        if lang == LanguageType::Julia && !found.confusable {
+            continue;
+        }
+        let (severity, kind) = if found.confusable {
+            (Severity::Error, "confusable identifier")
+        } else {
+            (Severity::Warning, "non-ASCII identifier")
+        };
+        let code_points: Vec<String> = found
+            .ident
+            .chars()
+            .filter(|c| !c.is_ascii())
+            .map(|c| format!("U+{:04X}", c as u32))
+            .collect();
+
+        report.add_issue(Issue {
+            severity,
+            file: rel_path.to_path_buf(),
+            line: Some(found.line),
+            column: None,
+            end_line: None,
+            name: Some(found.ident.clone()),
+            check_type: "non-ascii-identifier",
+            value: code_points.len(),
+            limit: 0,
+            message: format!(
+                "{}:{} {} ({}: {})",
+                rel_path.display(),
+                found.line,
+                found.ident.escape_default(),
+                kind,
+                code_points.join(" ")
+            ),
+        });
+    }
+}
+
+/// Find identifiers with non-ASCII characters, reporting each once per file
+fn find_suspicious_identifiers(lang: LanguageType, content: &str) -> Vec<SuspiciousIdent> {
+    let syntax = syntax_for(lang);
+    let mut state = LexState::default();
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+
+    for (line_num, line) in content.lines().enumerate() {
+        for ident in code_identifiers(line, &syntax, &mut state) {
+            if ident.is_ascii() || !seen.insert(ident.clone()) {
+                continue;
+            }
+            let has_ascii_letters = ident.chars().any(|c| c.is_ascii_alphabetic());
+            let has_other_letters = ident.chars().any(|c| !c.is_ascii() && c.is_alphabetic());
+            let confusable = ident.chars().any(is_invisible) || (has_ascii_letters && has_other_letters);
+            found.push(SuspiciousIdent {
+                line: line_num + 1,
+                ident,
+                confusable,
+            });
+        }
+    }
+
+    found
+}
+
+/// Extract identifiers from the code portion of a line, skipping strings and comments
+fn code_identifiers(line: &str, syntax: &Syntax, state: &mut LexState) -> Vec<String> {
+    let mut idents = Vec::new();
+    let mut current = String::new();
+    let mut in_string: Option<char> = None;
+    let mut i = 0;
+
+    let starts_with = |i: usize, pat: &str| line[i..].starts_with(pat);
+    let after = |i: usize, n: usize| char_offset(line, i, n);
+
+    while let Some(c) = line[i..].chars().next() {
+
+        if state.in_block_comment {
+            if starts_with(i, "*/") {
+                state.in_block_comment = false;
+                i += 2;
+            } else {
+                i = after(i, 1);
+            }
+            continue;
+        }
+
+        if let Some(close) = state.in_multiline_string {
+            if c == '\\' {
+                i = after(i, 2);
+            } else if starts_with(i, close) {
+                state.in_multiline_string = None;
+                i += close.len();
+            } else {
+                i = after(i, 1);
+            }
+            continue;
+        }
+
+        if let Some(quote) = in_string {
+            if c == '\\' {
+                i = after(i, 2);
+                continue;
+            }
+            if c == quote {
+                in_string = None;
+            }
+            i = after(i, 1);
+            continue;
+        }
+
+        if is_ident_char(c) {
+            current.push(c);
+            i += c.len_utf8();
+            continue;
+        }
+
+        if !current.is_empty() {
+            idents.push(std::mem::take(&mut current));
+        }
+
+        if starts_with(i, syntax.line_comment) {
+            break;
+        }
+        if syntax.block_comments && starts_with(i, "/*") {
+            state.in_block_comment = true;
+            i += 2;
+            continue;
+        }
+        if syntax.triple_quotes {
+            if let Some(close) = ["\"\"\"", "'''"].into_iter().find(|q| starts_with(i, q)) {
+                state.in_multiline_string = Some(close);
+                i += 3;
+                continue;
+            }
+        }
+        if syntax.backtick_strings && c == '`' {
+            state.in_multiline_string = Some("`");
+            i += 1;
+            continue;
+        }
+        if c == '"' || (c == '\'' && syntax.single_quote_strings) {
+            in_string = Some(c);
+        } else if c == '\'' && line[i..].chars().nth(2) == Some('\'') {
+            // Rust char literal such as 'é'
+            i = after(i, 3);
+            continue;
+        }
+        i = after(i, 1);
+    }
+
+    if !current.is_empty() {
+        idents.push(current);
+    }
+
+    idents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_code_is_clean() {
+        let code = "def greet(name):\n    return f\"héllo {name}\"  # café\n";
+        assert!(find_suspicious_identifiers(LanguageType::Python, code).is_empty());
+    }
+
+    #[test]
+    fn test_cyrillic_lookalike_is_confusable() {
+        // The second `a` in `pаssword` is CYRILLIC SMALL LETTER A (U+0430)
+        let code = "let p\u{0430}ssword = input();\n";
+        let found = find_suspicious_identifiers(LanguageType::TypeScript, code);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].line, 1);
+        assert!(found[0].confusable);
+    }
+
+    #[test]
+    fn test_non_latin_identifier_is_warning() {
+        let found = find_suspicious_identifiers(LanguageType::Rust, "fn 計算() {}\n");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].ident, "計算");
+        assert!(!found[0].confusable);
+    }
+
+    #[test]
+    fn test_multiline_strings_and_comments_skipped() {
+        let code = "/*\n * Ünïcode in a comment\n */\nconst s = `\n  naïve\n`;\n";
+        assert!(find_suspicious_identifiers(LanguageType::TypeScript, code).is_empty());
+    }
+}
@@ -0,0 +1,224 @@
+use crate::config::Thresholds;
+use crate::languages::FunctionInfo;
+use crate::output::{Issue, Report, Severity};
+use std::path::Path;
+
+/// The outermost element of a JSX expression, and how deeply elements nest within it
+#[derive(Debug, PartialEq)]
+struct JsxTree {
+    line: usize,
+    /// Column of the opening `<`, from 1
+    column: usize,
+    /// Line of the `>` closing the tree
+    end_line: usize,
+    depth: usize,
+}
+
+/// The tag being read: `<Name ...>`, `</Name>`, or a fragment
+struct Tag {
+    closing: bool,
+    /// Depth of `{}` attribute expressions, where `>` doesn't end the tag
+    braces: usize,
+    quote: Option<u8>,
+}
+
+#[derive(Default)]
+struct Scanner {
+    /// Elements open around the current position
+    open: usize,
+    tag: Option<Tag>,
+    tree: Option<JsxTree>,
+    trees: Vec<JsxTree>,
+    /// The last non-space byte read, which tells a tag from a comparison or generic
+    prev: u8,
+    /// `prev` ends the keyword `return`, as in `return <div>`
+    returned: bool,
+}
+
+impl Scanner {
+    fn line(&mut self, line_num: usize, line: &str) {
+        let trimmed = line.trim_start();
+        if self.tag.is_none() && (trimmed.starts_with("//") || trimmed.starts_with("/*") || trimmed.starts_with('*')) {
+            return;
+        }
+        let bytes = line.as_bytes();
+        for (i, &c) in bytes.iter().enumerate() {
+            if let Some(tag) = &mut self.tag {
+                match (tag.quote, c) {
+                    (Some(q), _) if c == q => tag.quote = None,
+                    (Some(_), _) => {}
+                    (None, b'"' | b'\'' | b'`') => tag.quote = Some(c),
+                    (None, b'{') => tag.braces += 1,
+                    (None, b'}') => tag.braces = tag.braces.saturating_sub(1),
+                    (None, b'>') if tag.braces == 0 => {
+                        let closing = tag.closing;
+                        self.tag = None;
+                        if let Some(tree) = &mut self.tree {
+                            tree.end_line = line_num;
+                        }
+                        self.end_tag(closing, self.prev == b'/');
+                    }
+                    _ => {}
+                }
+            } else if c == b'<' && self.starts_tag(bytes, i) {
+                let closing = bytes.get(i + 1) == Some(&b'/');
+                if !closing && self.tree.is_none() {
+                    let column = line[..i].chars().count() + 1;
+                    self.tree = Some(JsxTree { line: line_num, column, end_line: line_num, depth: 0 });
+                }
+                self.tag = Some(Tag { closing, braces: 0, quote: None });
+            }
+            if !c.is_ascii_whitespace() {
+                self.returned = ends_return(&bytes[..=i]);
+                self.prev = c;
+            }
+        }
+    }
+
+    /// Whether the `<` at `i` opens a tag rather than comparing or starting type arguments
+    fn starts_tag(&self, bytes: &[u8], i: usize) -> bool {
+        let name_start = match bytes.get(i + 1) {
+            Some(b'>') => return true,
+            Some(b'/') => i + 2,
+            _ => i + 1,
+        };
+        let name_end = bytes[name_start..]
+            .iter()
+            .position(|b| !(b.is_ascii_alphanumeric() || matches!(b, b'_' | b'$' | b'.' | b':' | b'-')))
+            .map_or(bytes.len(), |n| name_start + n);
+        // `</>` closes a fragment
+        if name_end == name_start {
+            return name_start == i + 2 && bytes.get(name_end) == Some(&b'>');
+        }
+        if !bytes[name_start].is_ascii_alphabetic()
+            || !matches!(bytes.get(name_end), None | Some(b' ' | b'\t' | b'>' | b'/'))
+        {
+            return false;
+        }
+        // Children may follow text; elsewhere a tag starts an expression
+        self.open > 0
+            || self.returned
+            || matches!(self.prev, 0 | b'(' | b'{' | b'}' | b'>' | b'?' | b':' | b'&' | b'|' | b'=' | b',' | b'[')
+    }
+
+    fn end_tag(&mut self, closing: bool, self_closing: bool) {
+        if closing {
+            self.open = self.open.saturating_sub(1);
+        } else {
+            let depth = self.open + 1;
+            if let Some(tree) = &mut self.tree {
+                tree.depth = tree.depth.max(depth);
+            }
+            if !self_closing {
+                self.open = depth;
+            }
+        }
+        if self.open == 0 {
+            self.trees.extend(self.tree.take());
+        }
+    }
+}
+
+fn ends_return(bytes: &[u8]) -> bool {
+    bytes.strip_suffix(b"return").is_some_and(|before| {
+        !before.last().is_some_and(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'$' | b'.'))
+    })
+}
+
+fn jsx_trees(lines: &[&str]) -> Vec<JsxTree> {
+    let mut scanner = Scanner::default();
+    for (i, line) in lines.iter().enumerate() {
+        scanner.line(i + 1, line);
+    }
+    scanner.trees.extend(scanner.tree.take());
+    scanner.trees
+}
+
+/// Whether a file may hold JSX; in `.ts` and `.js` files a `<` is a type
+/// argument, an assertion, or a comparison
+pub fn has_jsx(rel_path: &Path) -> bool {
+    matches!(rel_path.extension().and_then(|e| e.to_str()), Some("tsx" | "jsx"))
+}
+
+/// Check for JSX elements nested too deeply, once per element tree, named by
+/// the function rendering it
+pub fn check_jsx_nesting(
+    functions: &[FunctionInfo],
+    lines: &[&str],
+    rel_path: &Path,
+    thresholds: &Thresholds,
+    report: &mut Report,
+) {
+    for tree in jsx_trees(lines) {
+        let (severity, limit) = if tree.depth > thresholds.jsx_nesting_error {
+            (Severity::Error, thresholds.jsx_nesting_error)
+        } else if tree.depth > thresholds.jsx_nesting_warn {
+            (Severity::Warning, thresholds.jsx_nesting_warn)
+        } else {
+            continue;
+        };
+
+        let func = functions.iter().rfind(|f| f.start_line <= tree.line && tree.line < f.start_line + f.line_count);
+        let message = match func {
+            Some(func) => format!("{}:{} {} (JSX depth: {})", rel_path.display(), tree.line, func.name, tree.depth),
+            None => format!("{}:{} (JSX depth: {})", rel_path.display(), tree.line, tree.depth),
+        };
+        report.add_issue(Issue {
+            severity,
+            file: rel_path.to_path_buf(),
+            line: Some(tree.line),
+            column: Some(tree.column),
+            end_line: Some(tree.end_line),
+            name: func.map(|f| f.name.clone()),
+            check_type: "jsx-nesting",
+            value: tree.depth,
+            limit,
+            message,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trees(code: &str) -> Vec<(usize, usize)> {
+        let lines: Vec<&str> = code.lines().collect();
+        jsx_trees(&lines).into_iter().map(|t| (t.line, t.depth)).collect()
+    }
+
+    #[test]
+    fn test_jsx_tree_depth() {
+        let code = r#"export function Page({ items }: Props) {
+    const count = items.length < 10 ? items.length : 10;
+    const sorted = useMemo<Item[]>(() => sort(items), [items]);
+    if (!items) return <Spinner size="small" />;
+    return (
+        <Layout title="a > b">
+            <Card onClick={() => count > 1 && open()}>
+                <>
+                    {items.map((item) => (
+                        <Row key={item.id}>
+                            Don't <b>stop</b>
+                        </Row>
+                    ))}
+                </>
+            </Card>
+            <Footer />
+        </Layout>
+    );
+}
+"#;
+        assert_eq!(trees(code), vec![(4, 1), (6, 5)]);
+        let lines: Vec<&str> = code.lines().collect();
+        let spans: Vec<(usize, usize)> = jsx_trees(&lines).into_iter().map(|t| (t.column, t.end_line)).collect();
+        assert_eq!(spans, vec![(24, 4), (9, 17)]);
+    }
+
+    #[test]
+    fn test_multiline_tags() {
+        let code = "const Form = () => (\n    <form\n        onSubmit={(e) => {\n            if (a > b) submit();\n        }}\n    >\n        <input\n            value={value}\n        />\n    </form>\n);\n";
+        assert_eq!(trees(code), vec![(2, 2)]);
+        assert!(trees("const a = b<c;\nconst d = e < f;\nlet g: Array<string> = [];\n").is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+use crate::config::Thresholds;
+use crate::languages::LanguageType;
+use crate::output::{Issue, Report, Severity};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static MARKER_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(TODO|FIXME|HACK|XXX)\b[:(\s]*(.*)").unwrap());
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A TODO-style marker found in a comment
+#[derive(Debug, PartialEq, Eq)]
+struct Marker {
+    line: usize,
+    kind: String,
+    text: String,
+}
+
+fn comment_tokens(lang: LanguageType) -> &'static [&'static str] {
+    match lang {
+        LanguageType::Python | LanguageType::Elixir | LanguageType::Julia => &["#"],
+        LanguageType::TypeScript
+        | LanguageType::JavaScript
+        | LanguageType::Dart
+        | LanguageType::Rust
+        | LanguageType::Go
+        | LanguageType::Java
+        | LanguageType::Swift => &["//", "/*", "*"],
+        LanguageType::Terraform => &["#", "//", "/*", "*"],
+        LanguageType::Generic => &["//", "#", "/*", "*", "--", ";"],
+    }
+}
+
+/// Check for TODO/FIXME/HACK/XXX markers, optionally escalating old ones using git blame
+pub fn check_markers(
+    lang: LanguageType,
+    content: &str,
+    path: &Path,
+    rel_path: &Path,
+    thresholds: &Thresholds,
+    report: &mut Report,
+) {
+    let markers = find_markers(lang, content);
+    if markers.is_empty() {
+        return;
+    }
+
+    // Only shell out to git when ages are needed
+    let ages = match thresholds.todo_max_age_days {
+        Some(_) => blame_ages(path),
+        None => HashMap::new(),
+    };
+
+    for marker in markers {
+        let age = ages.get(&marker.line).copied();
+        let (severity, value, limit, age_note) = match (age, thresholds.todo_max_age_days) {
+            (Some(days), Some(max)) if days > max => {
+                (Severity::Error, days as usize, max as usize, format!(" ({} days old)", days))
+            }
+            (Some(days), Some(max)) => (Severity::Warning, days as usize, max as usize, String::new()),
+            _ => (Severity::Warning, 0, 0, String::new()),
+        };
+
+        report.add_issue(Issue {
+            severity,
+            file: rel_path.to_path_buf(),
+            line: Some(marker.line),
+            column: None,
+            end_line: None,
+            name: Some(marker.kind.clone()),
+            check_type: "todo-marker",
+            value,
+            limit,
+            message: format!(
+                "{}:{} {}{}: {}",
+                rel_path.display(),
+                marker.line,
+                marker.kind,
+                age_note,
+                marker.text
+            ),
+        });
+    }
+}
+
+/// Byte offset of a comment starting the line or trailing the code
+pub(super) fn comment_start(lang: LanguageType, line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    comment_tokens(lang)
+        .iter()
+        .filter_map(|t| {
+            if trimmed.starts_with(t) {
+                Some(line.len() - trimmed.len())
+            } else if *t != "*" {
+                line.find(&format!(" {}", t))
+            } else {
+                None
+            }
+        })
+        .min()
+}
+
+/// Opening and closing delimiters of a block comment, if the language has them
+fn block_comment(lang: LanguageType) -> Option<(&'static str, &'static str)> {
+    match lang {
+        LanguageType::Python | LanguageType::Elixir => None,
+        LanguageType::Julia => Some(("#=", "=#")),
+        _ => Some(("/*", "*/")),
+    }
+}
+
+/// Number of lines that are neither blank nor only a comment.
+///
+/// A block comment is only recognized at the start of a line, so a `/*`
+/// inside a string such as a glob doesn't hide the code after it.
+pub(super) fn logical_line_count(lang: LanguageType, lines: &[&str]) -> usize {
+    // `*` only continues a block comment, which is tracked itself
+    let tokens: Vec<&str> = comment_tokens(lang).iter().copied().filter(|t| *t != "*").collect();
+    let block = block_comment(lang);
+    let mut in_block = false;
+    lines
+        .iter()
+        .filter(|line| {
+            let mut rest = line.trim();
+            if let Some((open, close)) = block {
+                if in_block {
+                    match rest.find(close) {
+                        Some(end) => rest = rest[end + close.len()..].trim_start(),
+                        None => return false,
+                    }
+                    in_block = false;
+                }
+                while let Some(after) = rest.strip_prefix(open) {
+                    match after.find(close) {
+                        Some(end) => rest = after[end + close.len()..].trim_start(),
+                        None => {
+                            in_block = true;
+                            return false;
+                        }
+                    }
+                }
+            }
+            !rest.is_empty() && !tokens.iter().any(|t| rest.starts_with(t))
+        })
+        .count()
+}
+
+fn find_markers(lang: LanguageType, content: &str) -> Vec<Marker> {
+    let mut markers = Vec::new();
+
+    for (line_num, line) in content.lines().enumerate() {
+        let Some(start) = comment_start(lang, line) else {
+            continue;
+        };
+
+        if let Some(caps) = MARKER_PATTERN.captures(&line[start..]) {
+            markers.push(Marker {
+                line: line_num + 1,
+                kind: caps[1].to_string(),
+                text: caps[2].trim().trim_end_matches("*/").trim().to_string(),
+            });
+        }
+    }
+
+    markers
+}
+
+/// Age in days of each line of a file, keyed by 1-based line number.
+///
+/// Returns an empty map when the file is not tracked by git or git is unavailable.
+fn blame_ages(path: &Path) -> HashMap<usize, u64> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["blame", "--line-porcelain", "--"])
+        .arg(path)
+        .output();
+
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        _ => return HashMap::new(),
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    parse_blame(&String::from_utf8_lossy(&output.stdout), now)
+}
+
+fn parse_blame(porcelain: &str, now: u64) -> HashMap<usize, u64> {
+    let mut ages = HashMap::new();
+    let mut current_line: Option<usize> = None;
+
+    for line in porcelain.lines() {
+        if line.starts_with('\t') {
+            current_line = None;
+            continue;
+        }
+        let mut parts = line.split(' ');
+        let first = parts.next().unwrap_or("");
+        if first.len() == 40 && first.chars().all(|c| c.is_ascii_hexdigit()) {
+            // `<sha> <original line> <final line> [<group size>]`
+            current_line = parts.nth(1).and_then(|n| n.parse().ok());
+        } else if first == "author-time" {
+            if let (Some(line_num), Some(time)) = (current_line, parts.next().and_then(|t| t.parse::<u64>().ok())) {
+                ages.insert(line_num, now.saturating_sub(time) / SECONDS_PER_DAY);
+            }
+        }
+    }
+
+    ages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_markers_in_comments_only() {
+        let code = "let todo = 1; // TODO: rename\n/* FIXME handle errors */\nlet s = \"TODO\";\n";
+        let markers = find_markers(LanguageType::TypeScript, code);
+        assert_eq!(
+            markers,
+            vec![
+                Marker {
+                    line: 1,
+                    kind: "TODO".to_string(),
+                    text: "rename".to_string()
+                },
+                Marker {
+                    line: 2,
+                    kind: "FIXME".to_string(),
+                    text: "handle errors".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_logical_line_count() {
+        let rust = "/// Docs\nfn f() {\n\n    // note\n    /* a\n     * b\n     */ let x = 1;\n    *y = 2; /* trailing */\n}\n";
+        let lines: Vec<&str> = rust.lines().collect();
+        assert_eq!(logical_line_count(LanguageType::Rust, &lines), 4);
+
+        let julia = ["#= block", "still =#", "x = 1  # trailing", "# only"];
+        assert_eq!(logical_line_count(LanguageType::Julia, &julia), 1);
+        assert_eq!(logical_line_count(LanguageType::Python, &["", "# c", "x = '/*'", "y = 2"]), 2);
+    }
+
+    #[test]
+    fn test_parse_blame() {
+        let sha = "a".repeat(40);
+        let porcelain = format!(
+            "{sha} 1 1 2\nauthor x\nauthor-time 1000\n\tfirst\n{sha} 2 2\nauthor x\nauthor-time {}\n\tsecond\n",
+            1000 + 10 * SECONDS_PER_DAY
+        );
+        let ages = parse_blame(&porcelain, 1000 + 30 * SECONDS_PER_DAY);
+        assert_eq!(ages.get(&1), Some(&30));
+        assert_eq!(ages.get(&2), Some(&20));
+    }
+}
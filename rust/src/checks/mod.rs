@@ -1,239 +1,690 @@
+mod custom;
+mod enum_variants;
+mod exit_calls;
+mod generated;
+mod identifiers;
+mod jsx_nesting;
+mod markers;
+mod module_nesting;
+mod nested_loops;
+mod plugin;
+pub mod rules;
+mod security;
+mod spread;
+mod string_concat;
+mod suppress;
+
+pub use custom::{CustomCheck, CustomCheckDef};
+pub use generated::DEFAULT_GENERATED_MARKERS;
+pub use module_nesting::check_module_nesting;
+pub use plugin::check_plugins;
+pub use spread::{check_identifier_spread, SpreadIndex};
+
+use suppress::Suppression;
+
 use crate::config::Thresholds;
-use crate::languages::{FunctionInfo, LanguageType};
+use crate::languages::python::PythonParser;
+#[cfg(feature = "python-ast")]
+use crate::languages::python_ast::PythonAstParser;
+use crate::languages::{drop_nested, FunctionInfo, LanguageParser, LanguageType};
+use crate::options::{CheckSelection, CheckType, CountMode, Decorators, NestingMode, TestCode};
 use crate::output::{Issue, Report, Severity};
-use std::fs;
+use crate::scan::SourceFile;
+use crate::trace;
+use generated::FileShape;
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::BufReader;
 use std::path::Path;
-use walkdir::WalkDir;
+use std::time::Instant;
+
+/// Run the selected checks on every file.
+///
+/// Files of all languages share one work-stealing pool, so a project dominated
+/// by one language still keeps every thread busy. Each file's report is handed
+/// to `on_file` on its worker thread as soon as the file is done, then merged
+/// in scan order, keeping the final report deterministic. Binary, minified,
+/// and generated files (with one of `generated_markers` in a comment near
+/// the top) are skipped.
+pub fn check_files(
+    files: &[SourceFile],
+    selection: &CheckSelection,
+    thresholds_for: impl Fn(&SourceFile) -> Thresholds + Sync,
+    custom: &[CustomCheck],
+    generated_markers: &[String],
+    on_file: impl Fn(&mut Report) + Sync,
+) -> (Report, SpreadIndex) {
+    let results: Vec<(Report, SpreadIndex)> = files
+        .par_iter()
+        .map(|file| {
+            let mut report = Report::default();
+            let mut spread = SpreadIndex::default();
+            let thresholds = thresholds_for(file);
+            let start = Instant::now();
+            let suppression = check_file(file, selection, &thresholds, custom, generated_markers, &mut spread, &mut report);
+            report.language_time.insert(file.language.name(), start.elapsed());
+            if let Some(suppression) = suppression {
+                let before = report.issues.len();
+                report.issues.retain(|issue| !suppression.covers(issue.check_type));
+                report.suppressed += before - report.issues.len();
+            }
+            on_file(&mut report);
+            (report, spread)
+        })
+        .collect();
+
+    let mut report = Report::default();
+    let mut spread = SpreadIndex::default();
+    for (file_report, file_spread) in results {
+        report.merge(file_report);
+        spread.merge(file_spread);
+    }
+    (report, spread)
+}
+
+/// What the checks of a single file work from
+struct FileContext<'a> {
+    file: &'a SourceFile,
+    rel_path: &'a Path,
+    content: &'a str,
+    lines: Vec<&'a str>,
+    functions: Vec<FunctionInfo>,
+    parser: &'a dyn LanguageParser,
+    thresholds: &'a Thresholds,
+    custom: &'a [CustomCheck],
+}
+
+/// A check that looks at one file at a time
+struct FileCheck {
+    check: CheckType,
+    /// Its time is reported under this id with `--verbose`
+    id: &'static str,
+    /// Works from the file's parsed functions
+    functions: bool,
+    /// Meaningful for the generic fallback, which has no real function boundaries
+    generic: bool,
+    run: fn(&FileContext, &mut Report),
+}
 
-/// Check file lengths in a directory for a given language
-pub fn check_file_length(
-    source_dir: &Path,
-    lang: LanguageType,
+/// Every per-file check, in the order their issues are reported.
+///
+/// A new check only needs an entry here (and its `CheckType`); the cross-file
+/// checks run from `analyze_files` in lib.rs once every file has been seen.
+const FILE_CHECKS: &[FileCheck] = &[
+    FileCheck {
+        check: CheckType::FileLength,
+        id: "file-length",
+        functions: false,
+        generic: true,
+        run: |cx, report| {
+            let length = match cx.thresholds.count_mode {
+                CountMode::Physical => cx.lines.len(),
+                CountMode::Logical => markers::logical_line_count(cx.file.language, &cx.lines),
+            };
+            check_file_length(cx.rel_path, length, cx.thresholds, report)
+        },
+    },
+    FileCheck {
+        check: CheckType::Identifiers,
+        id: "non-ascii-identifier",
+        functions: false,
+        generic: true,
+        run: |cx, report| identifiers::check_identifiers(cx.file.language, cx.content, cx.rel_path, report),
+    },
+    FileCheck {
+        check: CheckType::Security,
+        id: "security-pattern",
+        functions: false,
+        generic: true,
+        run: |cx, report| security::check_security(cx.file.language, cx.content, cx.rel_path, report),
+    },
+    FileCheck {
+        check: CheckType::Markers,
+        id: "todo-marker",
+        functions: false,
+        generic: true,
+        run: |cx, report| {
+            markers::check_markers(cx.file.language, cx.content, &cx.file.path, cx.rel_path, cx.thresholds, report)
+        },
+    },
+    FileCheck {
+        check: CheckType::Custom,
+        id: "custom",
+        functions: true,
+        generic: true,
+        run: |cx, report| {
+            custom::check_custom(cx.custom, cx.file.language, cx.content, &cx.functions, cx.rel_path, report)
+        },
+    },
+    FileCheck {
+        check: CheckType::EnumVariants,
+        id: "enum-variants",
+        functions: false,
+        generic: false,
+        run: |cx, report| {
+            let types = cx.parser.parse_types(cx.content);
+            enum_variants::check_enum_variants(&types, cx.rel_path, cx.thresholds, report);
+        },
+    },
+    FileCheck {
+        check: CheckType::Functions,
+        id: "function-length",
+        functions: true,
+        generic: false,
+        run: |cx, report| {
+            let known = match cx.thresholds.decorators {
+                Decorators::Exclude => None,
+                Decorators::Include => cx.parser.decorators(cx.content),
+            };
+            for func in &cx.functions {
+                let decorators = match (cx.thresholds.decorators, &known) {
+                    (Decorators::Exclude, _) => 0,
+                    (Decorators::Include, Some(known)) => {
+                        known.iter().find(|&&(line, _)| line == func.start_line).map_or(0, |&(_, lines)| lines)
+                    }
+                    (Decorators::Include, None) => decorator_lines(cx.parser, &cx.lines, func),
+                };
+                let length = match cx.thresholds.count_mode {
+                    CountMode::Physical => func.line_count + decorators,
+                    CountMode::Logical => {
+                        let (start, body) = function_body(func, &cx.lines);
+                        markers::logical_line_count(cx.file.language, &cx.lines[start - decorators..start + body.len()])
+                    }
+                };
+                check_function(func, length, &cx.lines, cx.rel_path, cx.thresholds, report);
+            }
+        },
+    },
+    FileCheck {
+        check: CheckType::Nesting,
+        id: "nesting-depth",
+        functions: true,
+        // Whole-file nesting still means something without function boundaries
+        generic: true,
+        run: |cx, report| {
+            for func in &cx.functions {
+                let control_flow = match cx.thresholds.nesting_mode {
+                    NestingMode::Blocks => None,
+                    NestingMode::ControlFlow => cx.parser.find_control_flow(function_body(func, &cx.lines).1),
+                };
+                // Counting the body as a level, as the block depth does
+                let depth = control_flow.map_or(func.max_nesting, |spans| {
+                    1 + spans.iter().map(|span| span.depth).max().unwrap_or(0)
+                });
+                check_nesting(func, depth, &cx.lines, cx.rel_path, cx.thresholds, report);
+            }
+        },
+    },
+    FileCheck {
+        check: CheckType::JsxNesting,
+        id: "jsx-nesting",
+        functions: true,
+        generic: false,
+        run: |cx, report| {
+            if jsx_nesting::has_jsx(cx.rel_path) {
+                jsx_nesting::check_jsx_nesting(&cx.functions, &cx.lines, cx.rel_path, cx.thresholds, report)
+            }
+        },
+    },
+    FileCheck {
+        check: CheckType::NestedLoops,
+        id: "nested-loops",
+        functions: true,
+        generic: false,
+        run: |cx, report| {
+            nested_loops::check_nested_loops(&cx.functions, &cx.lines, cx.parser, cx.rel_path, cx.thresholds, report)
+        },
+    },
+    FileCheck {
+        check: CheckType::ExitCalls,
+        id: "exit-calls",
+        functions: true,
+        generic: false,
+        run: |cx, report| {
+            exit_calls::check_exit_calls(cx.file.language, &cx.functions, &cx.lines, cx.rel_path, cx.thresholds, report)
+        },
+    },
+    FileCheck {
+        check: CheckType::StringConcat,
+        id: "string-concat",
+        functions: true,
+        generic: false,
+        run: |cx, report| {
+            string_concat::check_string_concat(cx.file.language, &cx.functions, &cx.lines, cx.parser, cx.rel_path, report)
+        },
+    },
+];
+
+/// Run the selected checks on a single file.
+///
+/// The file is read and parsed once, and every check works from the same
+/// content and function list. When file-length is the only check to run, the
+/// file is streamed for its line count instead. Returns the checks the file's
+/// ignore-file directive turns off, for the caller to drop from its findings.
+fn check_file(
+    file: &SourceFile,
+    selection: &CheckSelection,
     thresholds: &Thresholds,
+    custom: &[CustomCheck],
+    generated_markers: &[String],
+    spread: &mut SpreadIndex,
     report: &mut Report,
-) {
-    let parser = crate::languages::get_parser(lang);
-    let extensions = lang.extensions();
-
-    for entry in WalkDir::new(source_dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-    {
-        let path = entry.path();
-
-        // Check extension
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-        if !extensions.contains(&ext) {
-            continue;
-        }
+) -> Option<Suppression> {
+    let generic = file.language == LanguageType::Generic;
+    let shotgun_surgery = !generic && selection.runs(CheckType::ShotgunSurgery);
+    let checks: Vec<&FileCheck> = FILE_CHECKS
+        .iter()
+        .filter(|c| selection.runs(c.check) && (c.generic || !generic))
+        .collect();
 
-        // Check if should skip
-        if parser.should_skip(path) {
-            continue;
+    // A line count is all file-length needs, so a huge file isn't loaded for it alone
+    let streamed = file.content.is_none()
+        && !shotgun_surgery
+        && thresholds.count_mode == CountMode::Physical
+        && checks.iter().all(|c| c.check == CheckType::FileLength);
+    let loaded = if streamed {
+        File::open(&file.path)
+            .and_then(|f| FileShape::stream(BufReader::new(f)))
+            .map(|shape| (String::new(), shape))
+    } else {
+        file.read().map(|content| {
+            let shape = FileShape::of(&content);
+            (content, shape)
+        })
+    };
+    let (content, shape) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            trace::log(format_args!("skip {}: cannot read: {}", file.rel_path.display(), e));
+            return None;
         }
+    };
 
-        report.files_scanned += 1;
+    if let Some(reason) = generated::skip_reason(file.language, &shape, generated_markers) {
+        trace::log(format_args!("skip {}: {}", file.rel_path.display(), reason));
+        return None;
+    }
 
-        // Count lines
-        let content = match fs::read_to_string(path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
+    let suppression = suppress::file_suppression(file.language, &shape.head);
+    if suppression == Some(Suppression::All) {
+        trace::log(format_args!("skip {}: ignore-file directive", file.rel_path.display()));
+        return None;
+    }
 
-        let line_count = content.lines().count();
-        let rel_path = path.strip_prefix(source_dir).unwrap_or(path);
-
-        if line_count > thresholds.file_error {
-            report.add_issue(Issue {
-                severity: Severity::Error,
-                file: rel_path.to_path_buf(),
-                line: None,
-                name: None,
-                check_type: "file-length",
-                value: line_count,
-                limit: thresholds.file_error,
-                message: format!(
-                    "{} ({} lines, limit: {})",
-                    rel_path.display(),
-                    line_count,
-                    thresholds.file_error
-                ),
-            });
-        } else if line_count > thresholds.file_warn {
-            report.add_issue(Issue {
-                severity: Severity::Warning,
-                file: rel_path.to_path_buf(),
-                line: None,
-                name: None,
-                check_type: "file-length",
-                value: line_count,
-                limit: thresholds.file_warn,
-                message: format!(
-                    "{} ({} lines, limit: {})",
-                    rel_path.display(),
-                    line_count,
-                    thresholds.file_warn
-                ),
+    trace::log(format_args!("scan {} ({} parser)", file.rel_path.display(), file.language.name()));
+    report.files_scanned += 1;
+
+    if streamed {
+        if !checks.is_empty() {
+            timed(report, "file-length", |report| {
+                check_file_length(&file.rel_path, shape.lines, thresholds, report)
             });
         }
+        return suppression;
     }
-}
 
-/// Check function lengths in a directory for a given language
-pub fn check_function_length(
-    source_dir: &Path,
-    lang: LanguageType,
-    thresholds: &Thresholds,
-    report: &mut Report,
-) {
-    let parser = crate::languages::get_parser(lang);
-    let extensions = lang.extensions();
-
-    for entry in WalkDir::new(source_dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-    {
-        let path = entry.path();
-
-        // Check extension
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-        if !extensions.contains(&ext) {
-            continue;
-        }
+    let parser = parser_for(file.language, thresholds);
+    let mut cx = FileContext {
+        file,
+        rel_path: file.rel_path.as_path(),
+        content: &content,
+        lines: content.lines().collect(),
+        functions: Vec::new(),
+        parser: parser.as_ref(),
+        thresholds,
+        custom,
+    };
+    if shotgun_surgery || checks.iter().any(|c| c.functions) {
+        timed(report, "parse", |_| {
+            cx.functions = parser.parse_functions(&content);
+            if !thresholds.nested_functions {
+                drop_nested(&mut cx.functions);
+            }
+            if thresholds.test_code == TestCode::Skip {
+                let spans = parser.test_spans(&content);
+                cx.functions.retain(|f| !spans.iter().any(|&(start, end)| (start..=end).contains(&f.start_line)));
+            }
+        });
+    }
 
-        // Check if should skip
-        if parser.should_skip(path) {
-            continue;
-        }
+    for check in checks {
+        timed(report, check.id, |report| (check.run)(&cx, report));
+    }
 
-        // Parse functions
-        let content = match fs::read_to_string(path) {
-            Ok(c) => c,
-            Err(_) => continue,
+    if shotgun_surgery {
+        // A file opting out still references other files' functions
+        let defined = match &suppression {
+            Some(s) if s.covers("shotgun-surgery") => &[][..],
+            _ => &cx.functions[..],
         };
+        timed(report, "shotgun-surgery", |_| spread.add_file(cx.rel_path, file.language, &content, defined));
+    }
+
+    suppression
+}
 
-        let functions = parser.parse_functions(&content);
-        let rel_path = path.strip_prefix(source_dir).unwrap_or(path);
+/// At most this many lines make up one decorator, so an unbalanced bracket
+/// can't send `decorator_lines` far up the file
+const MAX_DECORATOR_LINES: usize = 50;
 
-        for func in functions {
-            check_function(&func, rel_path, thresholds, report);
+/// The parser for a language, set up by the thresholds that change how it parses
+fn parser_for(lang: LanguageType, thresholds: &Thresholds) -> Box<dyn LanguageParser> {
+    match lang {
+        #[cfg(not(feature = "python-ast"))]
+        LanguageType::Python => Box::new(PythonParser { indent_width: thresholds.python_indent.width() }),
+        // The indent width only matters to the files it can't parse
+        #[cfg(feature = "python-ast")]
+        LanguageType::Python => Box::new(PythonAstParser::new(PythonParser { indent_width: thresholds.python_indent.width() })),
+        _ => crate::languages::get_parser(lang),
+    }
+}
+
+/// Run one check, adding the time it took to the report's total for that check
+pub fn timed(report: &mut Report, check: &'static str, run: impl FnOnce(&mut Report)) {
+    let start = Instant::now();
+    run(report);
+    *report.check_time.entry(check).or_default() += start.elapsed();
+}
+
+/// Get the lines of a function body along with the index of its first line
+fn function_body<'a>(func: &FunctionInfo, lines: &'a [&'a str]) -> (usize, &'a [&'a str]) {
+    let start = func.start_line.saturating_sub(1).min(lines.len());
+    let end = (start + func.line_count).min(lines.len());
+    (start, &lines[start..end])
+}
+
+/// How many lines of decorators or attributes sit directly above a function,
+/// including those continued over several lines inside brackets
+fn decorator_lines(parser: &dyn LanguageParser, lines: &[&str], func: &FunctionInfo) -> usize {
+    let start = function_body(func, lines).0;
+    let mut top = start;
+    'decorators: while top > 0 {
+        // Read upward from its last line, a decorator starts where its brackets balance
+        let mut depth = 0i32;
+        for i in (top.saturating_sub(MAX_DECORATOR_LINES)..top).rev() {
+            for c in lines[i].chars() {
+                match c {
+                    ')' | ']' | '}' => depth += 1,
+                    '(' | '[' | '{' => depth -= 1,
+                    _ => {}
+                }
+            }
+            if depth <= 0 {
+                if depth == 0 && parser.is_decorator(lines[i]) {
+                    top = i;
+                    continue 'decorators;
+                }
+                break 'decorators;
+            }
         }
+        break;
     }
+    start - top
 }
 
-fn check_function(func: &FunctionInfo, rel_path: &Path, thresholds: &Thresholds, report: &mut Report) {
-    if func.line_count > thresholds.func_error {
+/// Check the length of a file
+fn check_file_length(rel_path: &Path, line_count: usize, thresholds: &Thresholds, report: &mut Report) {
+    if line_count > thresholds.file_error {
+        report.add_issue(Issue {
+            severity: Severity::Error,
+            file: rel_path.to_path_buf(),
+            line: None,
+            column: None,
+            end_line: None,
+            name: None,
+            check_type: "file-length",
+            value: line_count,
+            limit: thresholds.file_error,
+            message: format!(
+                "{} ({} lines, limit: {})",
+                rel_path.display(),
+                line_count,
+                thresholds.file_error
+            ),
+        });
+    } else if line_count > thresholds.file_warn {
+        report.add_issue(Issue {
+            severity: Severity::Warning,
+            file: rel_path.to_path_buf(),
+            line: None,
+            column: None,
+            end_line: None,
+            name: None,
+            check_type: "file-length",
+            value: line_count,
+            limit: thresholds.file_warn,
+            message: format!(
+                "{} ({} lines, limit: {})",
+                rel_path.display(),
+                line_count,
+                thresholds.file_warn
+            ),
+        });
+    } else if thresholds.is_near_miss(line_count, thresholds.file_warn) {
+        report.add_issue(Issue {
+            severity: Severity::Info,
+            file: rel_path.to_path_buf(),
+            line: None,
+            column: None,
+            end_line: None,
+            name: None,
+            check_type: "file-length",
+            value: line_count,
+            limit: thresholds.file_warn,
+            message: format!(
+                "{} ({} lines, warning at: {})",
+                rel_path.display(),
+                line_count,
+                thresholds.file_warn
+            ),
+        });
+    }
+}
+
+fn check_function(
+    func: &FunctionInfo,
+    length: usize,
+    lines: &[&str],
+    rel_path: &Path,
+    thresholds: &Thresholds,
+    report: &mut Report,
+) {
+    if length > thresholds.func_error {
         report.add_issue(Issue {
             severity: Severity::Error,
             file: rel_path.to_path_buf(),
             line: Some(func.start_line),
+            column: Some(func.name_column(lines)),
+            end_line: Some(func.end_line()),
             name: Some(func.name.clone()),
             check_type: "function-length",
-            value: func.line_count,
+            value: length,
             limit: thresholds.func_error,
             message: format!(
                 "{}:{} {} ({} lines)",
                 rel_path.display(),
                 func.start_line,
                 func.name,
-                func.line_count
+                length
             ),
         });
-    } else if func.line_count > thresholds.func_warn {
+    } else if length > thresholds.func_warn {
         report.add_issue(Issue {
             severity: Severity::Warning,
             file: rel_path.to_path_buf(),
             line: Some(func.start_line),
+            column: Some(func.name_column(lines)),
+            end_line: Some(func.end_line()),
             name: Some(func.name.clone()),
             check_type: "function-length",
-            value: func.line_count,
+            value: length,
             limit: thresholds.func_warn,
             message: format!(
                 "{}:{} {} ({} lines)",
                 rel_path.display(),
                 func.start_line,
                 func.name,
-                func.line_count
+                length
+            ),
+        });
+    } else if thresholds.is_near_miss(length, thresholds.func_warn) {
+        report.add_issue(Issue {
+            severity: Severity::Info,
+            file: rel_path.to_path_buf(),
+            line: Some(func.start_line),
+            column: Some(func.name_column(lines)),
+            end_line: Some(func.end_line()),
+            name: Some(func.name.clone()),
+            check_type: "function-length",
+            value: length,
+            limit: thresholds.func_warn,
+            message: format!(
+                "{}:{} {} ({} lines, warning at: {})",
+                rel_path.display(),
+                func.start_line,
+                func.name,
+                length,
+                thresholds.func_warn
             ),
         });
     }
 }
 
-/// Check nesting depth in a directory for a given language
-pub fn check_nesting_depth(
-    source_dir: &Path,
-    lang: LanguageType,
+fn check_nesting(
+    func: &FunctionInfo,
+    depth: usize,
+    lines: &[&str],
+    rel_path: &Path,
     thresholds: &Thresholds,
     report: &mut Report,
 ) {
-    let parser = crate::languages::get_parser(lang);
-    let extensions = lang.extensions();
-
-    for entry in WalkDir::new(source_dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-    {
-        let path = entry.path();
-
-        // Check extension
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-        if !extensions.contains(&ext) {
-            continue;
-        }
+    if depth > thresholds.nest_error {
+        report.add_issue(Issue {
+            severity: Severity::Error,
+            file: rel_path.to_path_buf(),
+            line: Some(func.start_line),
+            column: Some(func.name_column(lines)),
+            end_line: Some(func.end_line()),
+            name: Some(func.name.clone()),
+            check_type: "nesting-depth",
+            value: depth,
+            limit: thresholds.nest_error,
+            message: format!(
+                "{}:{} {} (depth: {})",
+                rel_path.display(),
+                func.start_line,
+                func.name,
+                depth
+            ),
+        });
+    } else if depth > thresholds.nest_warn {
+        report.add_issue(Issue {
+            severity: Severity::Warning,
+            file: rel_path.to_path_buf(),
+            line: Some(func.start_line),
+            column: Some(func.name_column(lines)),
+            end_line: Some(func.end_line()),
+            name: Some(func.name.clone()),
+            check_type: "nesting-depth",
+            value: depth,
+            limit: thresholds.nest_warn,
+            message: format!(
+                "{}:{} {} (depth: {})",
+                rel_path.display(),
+                func.start_line,
+                func.name,
+                depth
+            ),
+        });
+    } else if thresholds.is_near_miss(depth, thresholds.nest_warn) {
+        report.add_issue(Issue {
+            severity: Severity::Info,
+            file: rel_path.to_path_buf(),
+            line: Some(func.start_line),
+            column: Some(func.name_column(lines)),
+            end_line: Some(func.end_line()),
+            name: Some(func.name.clone()),
+            check_type: "nesting-depth",
+            value: depth,
+            limit: thresholds.nest_warn,
+            message: format!(
+                "{}:{} {} (depth: {}, warning at: {})",
+                rel_path.display(),
+                func.start_line,
+                func.name,
+                depth,
+                thresholds.nest_warn
+            ),
+        });
+    }
+}
 
-        // Check if should skip
-        if parser.should_skip(path) {
-            continue;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::languages::get_parser;
+
+    fn decorators(lang: LanguageType, code: &str) -> Vec<usize> {
+        let parser = get_parser(lang);
+        let lines: Vec<&str> = code.lines().collect();
+        let mut functions = parser.parse_functions(code);
+        drop_nested(&mut functions);
+        functions.iter().map(|f| decorator_lines(parser.as_ref(), &lines, f)).collect()
+    }
 
-        // Parse functions
-        let content = match fs::read_to_string(path) {
-            Ok(c) => c,
-            Err(_) => continue,
+    #[test]
+    fn test_decorator_lines() {
+        let python = "@app.route(\n    \"/items\",\n    methods=[\"GET\"],\n)\n@login_required\ndef items():\n    pass\n\n\ndef plain():\n    pass\n";
+        assert_eq!(decorators(LanguageType::Python, python), [5, 0]);
+
+        let rust = "#[test]\n#[cfg_attr(\n    miri,\n    ignore\n)]\nfn slow() {\n}\n/// Docs end the attributes\n#[inline]\nfn fast() {\n}\nfn after() {\n}\n";
+        assert_eq!(decorators(LanguageType::Rust, rust), [5, 1, 0]);
+    }
+
+    fn near_misses(thresholds: &Thresholds, check: impl Fn(&Thresholds, &mut Report)) -> Vec<Severity> {
+        let mut report = Report::default();
+        check(thresholds, &mut report);
+        report.issues.iter().map(|issue| issue.severity).collect()
+    }
+
+    #[test]
+    fn test_near_miss_boundaries() {
+        let path = Path::new("app.py");
+        let lines = ["def f():"];
+        let func = |line_count, max_nesting| FunctionInfo {
+            name: "f".to_string(),
+            start_line: 1,
+            line_count,
+            max_nesting,
+        };
+        let thresholds = Thresholds {
+            near_miss_pct: Some(10),
+            ..Thresholds::default()
         };
 
-        let functions = parser.parse_functions(&content);
-        let rel_path = path.strip_prefix(source_dir).unwrap_or(path);
-
-        for func in functions {
-            if func.max_nesting > thresholds.nest_error {
-                report.add_issue(Issue {
-                    severity: Severity::Error,
-                    file: rel_path.to_path_buf(),
-                    line: Some(func.start_line),
-                    name: Some(func.name.clone()),
-                    check_type: "nesting-depth",
-                    value: func.max_nesting,
-                    limit: thresholds.nest_error,
-                    message: format!(
-                        "{}:{} {} (depth: {})",
-                        rel_path.display(),
-                        func.start_line,
-                        func.name,
-                        func.max_nesting
-                    ),
-                });
-            } else if func.max_nesting > thresholds.nest_warn {
-                report.add_issue(Issue {
-                    severity: Severity::Warning,
-                    file: rel_path.to_path_buf(),
-                    line: Some(func.start_line),
-                    name: Some(func.name.clone()),
-                    check_type: "nesting-depth",
-                    value: func.max_nesting,
-                    limit: thresholds.nest_warn,
-                    message: format!(
-                        "{}:{} {} (depth: {})",
-                        rel_path.display(),
-                        func.start_line,
-                        func.name,
-                        func.max_nesting
-                    ),
-                });
-            }
-        }
+        let file = |count| near_misses(&thresholds, |t, r| check_file_length(path, count, t, r));
+        assert_eq!(file(270), [Severity::Info]);
+        assert_eq!(file(269), []);
+        assert_eq!(file(300), [Severity::Info]);
+        assert_eq!(file(301), [Severity::Warning]);
+
+        let function = |length| near_misses(&thresholds, |t, r| check_function(&func(length, 0), length, &lines, path, t, r));
+        assert_eq!(function(27), [Severity::Info]);
+        assert_eq!(function(26), []);
+        assert_eq!(function(31), [Severity::Warning]);
+
+        let nesting = |depth| near_misses(&thresholds, |t, r| check_nesting(&func(1, depth), depth, &lines, path, t, r));
+        assert_eq!(nesting(4), [Severity::Info]);
+        assert_eq!(nesting(3), []);
+        assert_eq!(nesting(5), [Severity::Warning]);
+
+        // At 0% only a value right at the warning threshold is a near miss
+        let exact = Thresholds {
+            near_miss_pct: Some(0),
+            ..Thresholds::default()
+        };
+        assert_eq!(near_misses(&exact, |t, r| check_function(&func(30, 0), 30, &lines, path, t, r)), [Severity::Info]);
+        assert_eq!(near_misses(&exact, |t, r| check_function(&func(29, 0), 29, &lines, path, t, r)), []);
+        assert_eq!(near_misses(&Thresholds::default(), |t, r| check_file_length(path, 300, t, r)), []);
     }
 }
@@ -0,0 +1,190 @@
+use super::suppress::file_suppression;
+use crate::config::Thresholds;
+use crate::languages::{get_parser, LanguageType};
+use crate::output::{Issue, Report, Severity};
+use crate::scan::SourceFile;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+// Local module sources; registry and git sources are outside the project
+static SOURCE_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^\s*source\s*=\s*"(\.\.?/[^"]*)""#).unwrap());
+
+/// A `module` block calling a module within the project
+struct ModuleCall {
+    rel_path: PathBuf,
+    line: usize,
+    name: String,
+    /// Directory of the called module, relative to the project root
+    target: PathBuf,
+    /// The calling file opts out of this check
+    suppressed: bool,
+}
+
+/// Report Terraform module calls nested too deeply below a root module.
+///
+/// A root module is a directory of `.tf` files that no other module in the
+/// project calls. Each call is reported with the deepest level it is reached at.
+pub fn check_module_nesting(files: &[SourceFile], thresholds: &Thresholds, report: &mut Report) {
+    let mut module_dirs: BTreeSet<PathBuf> = BTreeSet::new();
+    let mut calls: Vec<ModuleCall> = Vec::new();
+
+    let parser = get_parser(LanguageType::Terraform);
+    for file in files.iter().filter(|f| f.language == LanguageType::Terraform) {
+        let dir = file.rel_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        module_dirs.insert(dir.clone());
+
+        let Ok(content) = file.read() else {
+            continue;
+        };
+        // The module still counts as a level for the modules it calls
+        let suppressed =
+            file_suppression(LanguageType::Terraform, &content).is_some_and(|s| s.covers("module-nesting"));
+        let lines: Vec<&str> = content.lines().collect();
+        for block in parser.parse_functions(&content) {
+            let Some(name) = block.name.strip_prefix("module.") else {
+                continue;
+            };
+            let start = block.start_line - 1;
+            let body = &lines[start..(start + block.line_count).min(lines.len())];
+            if let Some(source) = body.iter().find_map(|l| SOURCE_PATTERN.captures(l)) {
+                calls.push(ModuleCall {
+                    rel_path: file.rel_path.clone(),
+                    line: block.start_line,
+                    name: name.to_string(),
+                    target: normalize(&dir.join(&source[1])),
+                    suppressed,
+                });
+            }
+        }
+    }
+
+    let mut calls_by_dir: HashMap<&Path, Vec<usize>> = HashMap::new();
+    for (idx, call) in calls.iter().enumerate() {
+        calls_by_dir
+            .entry(call.rel_path.parent().unwrap_or(Path::new("")))
+            .or_default()
+            .push(idx);
+    }
+
+    let mut depths = vec![0usize; calls.len()];
+    for root in module_dirs.iter().filter(|d| !calls.iter().any(|c| &c.target == *d)) {
+        let mut stack = vec![root.as_path()];
+        walk(root, 1, &calls, &calls_by_dir, &mut depths, &mut stack);
+    }
+
+    for (call, depth) in calls.iter().zip(depths).filter(|(c, _)| !c.suppressed) {
+        let (severity, limit) = if depth > thresholds.module_nesting_error {
+            (Severity::Error, thresholds.module_nesting_error)
+        } else if depth > thresholds.module_nesting_warn {
+            (Severity::Warning, thresholds.module_nesting_warn)
+        } else {
+            continue;
+        };
+        report.add_issue(Issue {
+            severity,
+            file: call.rel_path.clone(),
+            line: Some(call.line),
+            column: None,
+            end_line: None,
+            name: Some(format!("module.{}", call.name)),
+            check_type: "module-nesting",
+            value: depth,
+            limit,
+            message: format!(
+                "{}:{} module.{} (module depth: {})",
+                call.rel_path.display(),
+                call.line,
+                call.name,
+                depth
+            ),
+        });
+    }
+}
+
+/// Record the depth of every call made from `dir` and descend into the called modules
+fn walk<'a>(
+    dir: &Path,
+    depth: usize,
+    calls: &'a [ModuleCall],
+    calls_by_dir: &HashMap<&Path, Vec<usize>>,
+    depths: &mut [usize],
+    stack: &mut Vec<&'a Path>,
+) {
+    let Some(indices) = calls_by_dir.get(dir) else {
+        return;
+    };
+    for &idx in indices {
+        depths[idx] = depths[idx].max(depth);
+        let target = calls[idx].target.as_path();
+        // Module cycles are invalid Terraform; stop rather than loop
+        if stack.contains(&target) {
+            continue;
+        }
+        stack.push(target);
+        walk(target, depth + 1, calls, calls_by_dir, depths, stack);
+        stack.pop();
+    }
+}
+
+/// Resolve `.` and `..` components without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::fs;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(Path::new("envs/prod/../../modules/./vpc")), PathBuf::from("modules/vpc"));
+    }
+
+    #[test]
+    fn test_nested_module_calls() {
+        let dir = TempDir::new("modules");
+        let write = |rel: &str, content: &str| {
+            let path = dir.join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+            SourceFile {
+                path,
+                rel_path: PathBuf::from(rel),
+                language: LanguageType::Terraform,
+                content: None,
+            }
+        };
+        let files = vec![
+            write("main.tf", "module \"app\" {\n  source = \"./modules/app\"\n}\n"),
+            write("modules/app/main.tf", "module \"db\" {\n  source = \"../db\"\n}\n"),
+            write("modules/db/main.tf", "module \"net\" {\n  source = \"../net\"\n}\n"),
+            write("modules/net/main.tf", "resource \"null_resource\" \"x\" {}\n"),
+        ];
+
+        let thresholds = Thresholds {
+            module_nesting_warn: 2,
+            module_nesting_error: 3,
+            ..Default::default()
+        };
+        let mut report = Report::default();
+        check_module_nesting(&files, &thresholds, &mut report);
+
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].name.as_deref(), Some("module.net"));
+        assert_eq!(report.issues[0].value, 3);
+    }
+}
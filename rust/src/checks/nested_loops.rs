@@ -0,0 +1,107 @@
+use crate::config::Thresholds;
+use crate::languages::{FunctionInfo, LanguageParser};
+use crate::output::{Issue, Report, Severity};
+use std::path::Path;
+
+/// Check for loops nested inside other loops
+pub fn check_nested_loops(
+    functions: &[FunctionInfo],
+    lines: &[&str],
+    parser: &dyn LanguageParser,
+    rel_path: &Path,
+    thresholds: &Thresholds,
+    report: &mut Report,
+) {
+    for func in functions {
+        let (start, body) = super::function_body(func, lines);
+
+        // Report the first of the deepest loops in the function
+        let deepest = parser
+            .find_loops(body)
+            .into_iter()
+            .rev()
+            .max_by_key(|l| l.depth);
+        let Some(deepest) = deepest else {
+            continue;
+        };
+
+        let (severity, limit) = if deepest.depth > thresholds.loop_error {
+            (Severity::Error, thresholds.loop_error)
+        } else if deepest.depth > thresholds.loop_warn {
+            (Severity::Warning, thresholds.loop_warn)
+        } else {
+            continue;
+        };
+
+        let line = start + deepest.start + 1;
+        let header = body[deepest.start];
+        let indent = header.len() - header.trim_start().len();
+        report.add_issue(Issue {
+            severity,
+            file: rel_path.to_path_buf(),
+            line: Some(line),
+            column: Some(header[..indent].chars().count() + 1),
+            end_line: Some(start + deepest.end + 1),
+            name: Some(func.name.clone()),
+            check_type: "nested-loops",
+            value: deepest.depth,
+            limit,
+            message: format!(
+                "{}:{} {} (loop depth: {})",
+                rel_path.display(),
+                line,
+                func.name,
+                deepest.depth
+            ),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::languages::{get_parser, LanguageType};
+
+    fn max_loop_depth(lang: LanguageType, code: &str) -> usize {
+        let lines: Vec<&str> = code.lines().collect();
+        get_parser(lang)
+            .find_loops(&lines)
+            .iter()
+            .map(|l| l.depth)
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_rust_triple_loop() {
+        let code = "fn f() {\n    for a in xs {\n        while x {\n            loop {\n                break;\n            }\n        }\n    }\n}";
+        assert_eq!(max_loop_depth(LanguageType::Rust, code), 3);
+    }
+
+    #[test]
+    fn test_sequential_loops_are_not_nested() {
+        let code = "def f(xs):\n    for a in xs:\n        pass\n    for b in xs:\n        while b:\n            b -= 1\n";
+        assert_eq!(max_loop_depth(LanguageType::Python, code), 2);
+    }
+
+    #[test]
+    fn test_elixir_comprehension_in_enum_each() {
+        let code = "def f(xs) do\n  Enum.each(xs, fn x ->\n    for y <- x do\n      for z <- y, do: IO.puts(z)\n    end\n  end)\nend";
+        assert_eq!(max_loop_depth(LanguageType::Elixir, code), 3);
+    }
+
+    #[test]
+    fn test_generated_depth_is_capped() {
+        use crate::languages::MAX_OPEN_LOOPS;
+        let depth = MAX_OPEN_LOOPS * 3;
+        let code = format!("{}{}", "for x in xs {\n".repeat(depth), "}\n".repeat(depth));
+        assert_eq!(max_loop_depth(LanguageType::Rust, &code), MAX_OPEN_LOOPS + 1);
+        let code: String = (0..depth).map(|d| format!("{}for x in xs:\n", " ".repeat(d))).collect();
+        assert_eq!(max_loop_depth(LanguageType::Python, &code), MAX_OPEN_LOOPS + 1);
+    }
+
+    #[test]
+    fn test_typescript_braceless_loop_body() {
+        let code = "function f() {\n    for (const a of xs)\n        for (const b of a)\n            use(b);\n    while (x) { x--; }\n}";
+        assert_eq!(max_loop_depth(LanguageType::TypeScript, code), 2);
+    }
+}
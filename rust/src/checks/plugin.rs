@@ -0,0 +1,236 @@
+//! External check plugins: custom checks with a `command` instead of a pattern.
+//!
+//! A plugin runs once per analysis, from the project root. It reads one JSON
+//! object per line on stdin, one per file in its languages:
+//!
+//! ```json
+//! {"path": "src/app.py", "language": "python", "content": "...", "functions": [{"name": "main", "line": 3, "length": 12, "nesting": 2}]}
+//! ```
+//!
+//! and, once stdin is closed, writes one JSON object per line on stdout for
+//! each issue it finds:
+//!
+//! ```json
+//! {"path": "src/app.py", "line": 3, "name": "main", "message": "missing docstring", "severity": "error", "value": 1}
+//! ```
+//!
+//! Only `path` is required; `severity` defaults to the check's. A plugin may
+//! also give a `column` and an `end_line`.
+
+use super::CustomCheck;
+use crate::languages::{drop_nested, get_parser, LanguageType};
+use crate::output::{Issue, Report, Severity};
+use crate::scan::SourceFile;
+use crate::trace;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+#[derive(Serialize)]
+struct FileRequest<'a> {
+    path: &'a Path,
+    language: &'static str,
+    content: &'a str,
+    functions: Vec<FunctionRecord>,
+}
+
+#[derive(Serialize)]
+struct FunctionRecord {
+    name: String,
+    line: usize,
+    length: usize,
+    nesting: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PluginIssue {
+    path: PathBuf,
+    #[serde(default)]
+    line: Option<usize>,
+    #[serde(default)]
+    column: Option<usize>,
+    #[serde(default)]
+    end_line: Option<usize>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    severity: Option<Severity>,
+    #[serde(default)]
+    value: usize,
+}
+
+/// The project root, recovered from a file's absolute and relative paths
+fn project_root(files: &[SourceFile]) -> Option<PathBuf> {
+    let file = files.first()?;
+    let depth = file.rel_path.components().count();
+    file.path.ancestors().nth(depth).map(Path::to_path_buf)
+}
+
+fn request_line(file: &SourceFile) -> Option<String> {
+    let content = file.read().ok()?;
+    let functions = if file.language == LanguageType::Generic {
+        Vec::new()
+    } else {
+        let mut functions = get_parser(file.language).parse_functions(&content);
+        drop_nested(&mut functions);
+        functions
+    };
+    let request = FileRequest {
+        path: &file.rel_path,
+        language: file.language.name(),
+        content: &content,
+        functions: functions
+            .into_iter()
+            .map(|func| FunctionRecord {
+                name: func.name,
+                line: func.start_line,
+                length: func.line_count,
+                nesting: func.max_nesting,
+            })
+            .collect(),
+    };
+    serde_json::to_string(&request).ok()
+}
+
+/// Run one plugin over the files, returning its issues or why it failed
+fn run_plugin(check: &CustomCheck, files: &[SourceFile], root: &Path) -> Result<Vec<Issue>, String> {
+    let (program, args) = check.def.command.split_first().ok_or("empty command")?;
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("cannot run '{}': {}", program, e))?;
+
+    let mut stdin = child.stdin.take().ok_or("no stdin")?;
+    let stdout = child.stdout.take().ok_or("no stdout")?;
+    let lines: Vec<String> = std::thread::scope(|scope| {
+        // Feed stdin while reading stdout, so neither side blocks on a full pipe
+        scope.spawn(move || {
+            for file in files.iter().filter(|f| check.applies_to(f.language)) {
+                if let Some(line) = request_line(file) {
+                    if writeln!(stdin, "{}", line).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        BufReader::new(stdout).lines().map_while(Result::ok).collect()
+    });
+
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("'{}' exited with {}", program, status));
+    }
+    lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let found: PluginIssue =
+                serde_json::from_str(line).map_err(|e| format!("invalid output line '{}': {}", line, e))?;
+            Ok(to_issue(check, found))
+        })
+        .collect()
+}
+
+fn to_issue(check: &CustomCheck, found: PluginIssue) -> Issue {
+    let location = match (found.line, &found.name) {
+        (Some(l), Some(n)) => format!("{}:{} {}", found.path.display(), l, n),
+        (Some(l), None) => format!("{}:{}", found.path.display(), l),
+        _ => found.path.display().to_string(),
+    };
+    let description = found.message.as_deref().unwrap_or(check.description());
+    Issue {
+        // Near misses are ours to report; a plugin reports findings
+        severity: found.severity.filter(|s| *s != Severity::Info).unwrap_or(check.def.severity),
+        message: format!("{} ({})", location, description),
+        file: found.path,
+        line: found.line,
+        column: found.column,
+        end_line: found.end_line,
+        name: found.name,
+        check_type: check.id,
+        value: found.value,
+        limit: check.def.threshold,
+    }
+}
+
+/// Run every plugin among the custom checks over all the files
+pub fn check_plugins(checks: &[CustomCheck], files: &[SourceFile], report: &mut Report) {
+    let root = match project_root(files) {
+        Some(root) => root,
+        None => return,
+    };
+    for check in checks.iter().filter(|c| c.regex.is_none()) {
+        trace::log(format_args!("plugin {}: {}", check.id, check.def.command.join(" ")));
+        match run_plugin(check, files, &root) {
+            Ok(issues) => {
+                for issue in issues {
+                    report.add_issue(issue);
+                }
+            }
+            // The built-in checks' results still stand
+            Err(e) => eprintln!("Warning: custom check '{}' failed: {}", check.id, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checks::CustomCheckDef;
+    use crate::test_support::TempDir;
+    use std::fs;
+
+    #[test]
+    fn test_project_root() {
+        let files = vec![SourceFile {
+            path: PathBuf::from("/work/app/src/lib/a.py"),
+            rel_path: PathBuf::from("src/lib/a.py"),
+            language: LanguageType::Python,
+            content: None,
+        }];
+        assert_eq!(project_root(&files), Some(PathBuf::from("/work/app")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_plugin_round_trip() {
+        let dir = TempDir::new("plugin");
+        fs::write(dir.join("a.py"), "def f():\n    pass\n").unwrap();
+        let def = CustomCheckDef {
+            name: "echo-path".to_string(),
+            pattern: None,
+            scope: None,
+            // Report each file it is sent, by the path field of the request
+            command: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                r#"sed 's/^{"path":\("[^"]*"\).*/{"path":\1,"line":1,"message":"seen"}/'"#.to_string(),
+            ],
+            threshold: 0,
+            severity: Severity::Error,
+            languages: Vec::new(),
+            message: None,
+            help_uri: None,
+        };
+        let checks = vec![CustomCheck::compile(def).unwrap()];
+        let files = vec![SourceFile {
+            path: dir.join("a.py"),
+            rel_path: PathBuf::from("a.py"),
+            language: LanguageType::Python,
+            content: None,
+        }];
+        let mut report = Report::default();
+        check_plugins(&checks, &files, &mut report);
+
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].message, "a.py:1 (seen)");
+        assert_eq!(report.issues[0].severity, Severity::Error);
+    }
+}
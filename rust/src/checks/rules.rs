@@ -0,0 +1,247 @@
+use crate::languages::LanguageType;
+use crate::options::CheckType;
+use crate::output::Severity;
+
+/// Metadata about a check, shared by all output formats
+pub struct Rule {
+    /// Identifier used as the issue `type` in reports
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Code Climate category: Bug Risk, Clarity, Complexity, Performance, Security, or Style
+    pub category: &'static str,
+}
+
+/// Default location of the check documentation; `{check}` is replaced by the rule id
+pub const DEFAULT_HELP_URI_BASE: &str =
+    "https://github.com/byronsalty/code-smells/blob/main/docs/checks.md#{check}";
+
+/// Every check that can produce issues
+pub const RULES: &[Rule] = &[
+    Rule {
+        id: "file-length",
+        name: "File length",
+        description: "File has too many lines",
+        category: "Complexity",
+    },
+    Rule {
+        id: "function-length",
+        name: "Function length",
+        description: "Function or method has too many lines",
+        category: "Complexity",
+    },
+    Rule {
+        id: "nesting-depth",
+        name: "Nesting depth",
+        description: "Function contains deeply nested blocks",
+        category: "Complexity",
+    },
+    Rule {
+        id: "nested-loops",
+        name: "Nested loops",
+        description: "Loops are nested too deeply inside each other",
+        category: "Complexity",
+    },
+    Rule {
+        id: "string-concat",
+        name: "String concatenation in loop",
+        description: "String is built by repeated concatenation inside a loop",
+        category: "Performance",
+    },
+    Rule {
+        id: "non-ascii-identifier",
+        name: "Non-ASCII identifier",
+        description: "Identifier contains non-ASCII or confusable characters",
+        category: "Clarity",
+    },
+    Rule {
+        id: "enum-variants",
+        name: "Enum variants",
+        description: "Enum or union type has too many variants",
+        category: "Complexity",
+    },
+    Rule {
+        id: "exit-calls",
+        name: "Exit calls",
+        description: "Function exits, panics, or throws too often outside an entry point",
+        category: "Bug Risk",
+    },
+    Rule {
+        id: "shotgun-surgery",
+        name: "Identifier spread",
+        description: "Function is referenced from an unusually large number of files",
+        category: "Complexity",
+    },
+    Rule {
+        id: "module-nesting",
+        name: "Module nesting",
+        description: "Terraform module is called too many levels below a root module",
+        category: "Complexity",
+    },
+    Rule {
+        id: "jsx-nesting",
+        name: "JSX nesting",
+        description: "JSX elements are nested too deeply",
+        category: "Complexity",
+    },
+    Rule {
+        id: "todo-marker",
+        name: "TODO marker",
+        description: "Comment contains a TODO, FIXME, HACK, or XXX marker",
+        category: "Style",
+    },
+    Rule {
+        id: "security-pattern",
+        name: "Security-sensitive pattern",
+        description: "Code uses a construct that is a common source of security bugs",
+        category: "Security",
+    },
+];
+
+/// A risky construct reported by the `security-pattern` check
+pub struct SecurityPattern {
+    /// Reported as the issue name
+    pub id: &'static str,
+    pub languages: &'static [LanguageType],
+    pub pattern: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+}
+
+/// The security check pack; unlike maintainability checks, each pattern has its own severity
+pub const SECURITY_PATTERNS: &[SecurityPattern] = &[
+    SecurityPattern {
+        id: "python-eval",
+        languages: &[LanguageType::Python],
+        pattern: r"(^|[^.\w])(eval|exec)\s*\(",
+        severity: Severity::Error,
+        description: "eval/exec runs arbitrary code",
+    },
+    SecurityPattern {
+        id: "python-pickle",
+        languages: &[LanguageType::Python],
+        pattern: r"\b(c?pickle|marshal)\.loads?\s*\(",
+        severity: Severity::Error,
+        description: "unpickling untrusted data runs arbitrary code",
+    },
+    SecurityPattern {
+        id: "python-shell",
+        languages: &[LanguageType::Python],
+        pattern: r"\bos\.system\s*\(|\bshell\s*=\s*True\b",
+        severity: Severity::Warning,
+        description: "shell command built from a string",
+    },
+    SecurityPattern {
+        id: "javascript-eval",
+        languages: &[LanguageType::TypeScript, LanguageType::JavaScript],
+        pattern: r"(^|[^.\w])eval\s*\(|\bnew\s+Function\s*\(",
+        severity: Severity::Error,
+        description: "eval/new Function runs arbitrary code",
+    },
+    SecurityPattern {
+        id: "javascript-raw-html",
+        languages: &[LanguageType::TypeScript, LanguageType::JavaScript],
+        pattern: r"\bdangerouslySetInnerHTML\b|\.(inner|outer)HTML\s*=[^=]",
+        severity: Severity::Warning,
+        description: "raw HTML injection can lead to XSS",
+    },
+    SecurityPattern {
+        id: "rust-transmute",
+        languages: &[LanguageType::Rust],
+        pattern: r"\btransmute(_copy)?\s*(::<|\()",
+        severity: Severity::Warning,
+        description: "std::mem::transmute bypasses type safety",
+    },
+    SecurityPattern {
+        id: "elixir-eval",
+        languages: &[LanguageType::Elixir],
+        pattern: r"\bCode\.eval_(string|quoted|file)\b",
+        severity: Severity::Error,
+        description: "Code.eval_* runs arbitrary code",
+    },
+    SecurityPattern {
+        id: "elixir-atom-exhaustion",
+        languages: &[LanguageType::Elixir],
+        pattern: r"\bString\.to_atom\s*\(|:erlang\.binary_to_term\s*\(",
+        severity: Severity::Warning,
+        description: "creating atoms or terms from input can exhaust the atom table",
+    },
+    SecurityPattern {
+        id: "java-exec",
+        languages: &[LanguageType::Java],
+        pattern: r"\bRuntime\.getRuntime\(\)\.exec\s*\(|\bnew\s+ObjectInputStream\s*\(",
+        severity: Severity::Warning,
+        description: "process execution or Java deserialization of untrusted input",
+    },
+    SecurityPattern {
+        id: "go-unsafe",
+        languages: &[LanguageType::Go],
+        pattern: r"\bunsafe\.Pointer\b",
+        severity: Severity::Warning,
+        description: "unsafe.Pointer bypasses type safety",
+    },
+];
+
+/// Ids of the rules run for a `--check` selection
+pub fn rules_for(check: CheckType) -> Vec<&'static str> {
+    let ids: &[&str] = match check {
+        CheckType::All => &[
+            "file-length",
+            "function-length",
+            "nesting-depth",
+            "nested-loops",
+            "string-concat",
+            "non-ascii-identifier",
+            "enum-variants",
+            "shotgun-surgery",
+            "exit-calls",
+            "module-nesting",
+            "jsx-nesting",
+        ],
+        CheckType::FileLength => &["file-length"],
+        CheckType::Functions => &["function-length"],
+        CheckType::Nesting => &["nesting-depth"],
+        CheckType::NestedLoops => &["nested-loops"],
+        CheckType::StringConcat => &["string-concat"],
+        CheckType::Identifiers => &["non-ascii-identifier"],
+        CheckType::EnumVariants => &["enum-variants"],
+        CheckType::ShotgunSurgery => &["shotgun-surgery"],
+        CheckType::ExitCalls => &["exit-calls"],
+        CheckType::ModuleNesting => &["module-nesting"],
+        CheckType::JsxNesting => &["jsx-nesting"],
+        CheckType::Markers => &["todo-marker"],
+        CheckType::Security => &["security-pattern"],
+        // Ids come from the config file
+        CheckType::Custom => &[],
+    };
+    ids.to_vec()
+}
+
+/// Build the documentation link for a check.
+///
+/// A `{check}` placeholder in `base` is replaced by the rule id; otherwise the
+/// id is appended.
+pub fn help_uri(base: &str, id: &str) -> String {
+    if base.contains("{check}") {
+        base.replace("{check}", id)
+    } else {
+        format!("{}{}", base, id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_uri() {
+        assert_eq!(
+            help_uri(DEFAULT_HELP_URI_BASE, "file-length"),
+            "https://github.com/byronsalty/code-smells/blob/main/docs/checks.md#file-length"
+        );
+        assert_eq!(
+            help_uri("https://wiki.example.com/smells/", "nesting-depth"),
+            "https://wiki.example.com/smells/nesting-depth"
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use super::rules::SECURITY_PATTERNS;
+use crate::languages::LanguageType;
+use crate::output::{Issue, Report};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+
+/// `SECURITY_PATTERNS`, compiled in the same order
+static COMPILED: Lazy<Vec<Regex>> = Lazy::new(|| {
+    SECURITY_PATTERNS
+        .iter()
+        .map(|p| Regex::new(p.pattern).unwrap())
+        .collect()
+});
+
+/// Check for security-sensitive constructs such as `eval` or `pickle.loads`
+pub fn check_security(lang: LanguageType, content: &str, rel_path: &Path, report: &mut Report) {
+    let patterns: Vec<_> = SECURITY_PATTERNS
+        .iter()
+        .zip(COMPILED.iter())
+        .filter(|(p, _)| p.languages.contains(&lang))
+        .collect();
+    if patterns.is_empty() {
+        return;
+    }
+
+    for (line_num, line) in content.lines().enumerate() {
+        let code = match super::markers::comment_start(lang, line) {
+            Some(start) => &line[..start],
+            None => line,
+        };
+        for (pattern, regex) in &patterns {
+            let Some(found) = regex.find(code) else {
+                continue;
+            };
+            // Past a pattern's leading `(^|[^.\w])` or `.`, to the word itself
+            let word = found.as_str().find(|c: char| c.is_alphanumeric() || c == '_').unwrap_or(0);
+            report.add_issue(Issue {
+                severity: pattern.severity,
+                file: rel_path.to_path_buf(),
+                line: Some(line_num + 1),
+                column: Some(code[..found.start() + word].chars().count() + 1),
+                end_line: None,
+                name: Some(pattern.id.to_string()),
+                check_type: "security-pattern",
+                value: 1,
+                limit: 0,
+                message: format!(
+                    "{}:{} {} ({})",
+                    rel_path.display(),
+                    line_num + 1,
+                    pattern.id,
+                    pattern.description
+                ),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Severity;
+
+    fn found(lang: LanguageType, code: &str) -> Vec<(usize, String, Severity)> {
+        let mut report = Report::default();
+        check_security(lang, code, Path::new("x"), &mut report);
+        report
+            .issues
+            .into_iter()
+            .map(|i| (i.line.unwrap(), i.name.unwrap(), i.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_python_patterns() {
+        let code = "data = pickle.loads(blob)\nmodel.eval()\nx = literal_eval(s)\n# eval(s)\nr = eval(s)\n";
+        assert_eq!(
+            found(LanguageType::Python, code),
+            vec![
+                (1, "python-pickle".to_string(), Severity::Error),
+                (5, "python-eval".to_string(), Severity::Error),
+            ]
+        );
+        let mut report = Report::default();
+        check_security(LanguageType::Python, code, Path::new("x"), &mut report);
+        let columns: Vec<Option<usize>> = report.issues.iter().map(|i| i.column).collect();
+        assert_eq!(columns, [Some(8), Some(5)]);
+    }
+
+    #[test]
+    fn test_patterns_scoped_to_language() {
+        let code = "let x: u32 = unsafe { std::mem::transmute(y) };\n";
+        assert_eq!(found(LanguageType::Rust, code).len(), 1);
+        assert!(found(LanguageType::TypeScript, code).is_empty());
+    }
+
+    #[test]
+    fn test_all_patterns_compile() {
+        assert_eq!(COMPILED.len(), SECURITY_PATTERNS.len());
+    }
+}
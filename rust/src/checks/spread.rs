@@ -0,0 +1,217 @@
+use crate::config::Thresholds;
+use crate::languages::{FunctionInfo, LanguageType};
+use crate::output::{Issue, Report, Severity};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+// Identifiers in call position, e.g. `name(`, `obj.name (` or `module::name(`
+static CALL_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"([A-Za-z_][A-Za-z0-9_]*)\s*\(").unwrap());
+
+/// Only report this many of the most widely referenced functions
+const TOP_OFFENDERS: usize = 10;
+
+/// Names too short or too generic to tell apart from unrelated uses
+const MIN_NAME_LEN: usize = 4;
+
+/// The name a call uses, without the classes of a method such as `Shape.area`
+fn bare_name(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+/// Function definitions and call sites collected across files
+#[derive(Default)]
+pub struct SpreadIndex {
+    files: Vec<FileSymbols>,
+}
+
+struct FileSymbols {
+    rel_path: PathBuf,
+    language: LanguageType,
+    defined: Vec<(String, usize)>,
+    calls: HashSet<String>,
+}
+
+impl SpreadIndex {
+    /// Append the files recorded in another index
+    pub fn merge(&mut self, other: SpreadIndex) {
+        self.files.extend(other.files);
+    }
+
+    /// Record the functions defined in a file and the names it calls
+    pub fn add_file(&mut self, rel_path: &Path, language: LanguageType, content: &str, functions: &[FunctionInfo]) {
+        self.files.push(FileSymbols {
+            rel_path: rel_path.to_path_buf(),
+            language,
+            defined: functions
+                .iter()
+                .map(|f| (f.name.clone(), f.start_line))
+                .collect(),
+            calls: CALL_PATTERN
+                .captures_iter(content)
+                .map(|caps| caps[1].to_string())
+                .collect(),
+        });
+    }
+}
+
+/// A function referenced from many other files
+struct Spread<'a> {
+    file: &'a FileSymbols,
+    name: &'a str,
+    line: usize,
+    referencing_files: usize,
+}
+
+/// Report functions referenced from an unusually large number of files.
+///
+/// Changing such a function tends to require touching all of those files.
+pub fn check_identifier_spread(index: &SpreadIndex, thresholds_for: impl Fn(LanguageType) -> Thresholds, report: &mut Report) {
+    // Count definitions per (language, name); names defined in several files are ambiguous
+    let mut definitions: HashMap<(LanguageType, &str), usize> = HashMap::new();
+    for file in &index.files {
+        let names: HashSet<&str> = file.defined.iter().map(|(n, _)| bare_name(n)).collect();
+        for name in names {
+            *definitions.entry((file.language, name)).or_default() += 1;
+        }
+    }
+
+    // Files calling each name, counted once so the pass stays linear in the size of the tree
+    let mut callers: HashMap<(LanguageType, &str), usize> = HashMap::new();
+    for file in &index.files {
+        for name in &file.calls {
+            *callers.entry((file.language, name.as_str())).or_default() += 1;
+        }
+    }
+
+    let mut spreads = Vec::new();
+    for file in &index.files {
+        for (name, line) in &file.defined {
+            let called = bare_name(name);
+            if called.len() < MIN_NAME_LEN || definitions.get(&(file.language, called)) != Some(&1) {
+                continue;
+            }
+            // Calls from the defining file itself don't count
+            let callers = callers.get(&(file.language, called)).copied().unwrap_or(0);
+            let referencing_files = callers - usize::from(file.calls.contains(called));
+            spreads.push(Spread {
+                file,
+                name,
+                line: *line,
+                referencing_files,
+            });
+        }
+    }
+
+    spreads.sort_by(|a, b| {
+        b.referencing_files
+            .cmp(&a.referencing_files)
+            .then_with(|| a.file.rel_path.cmp(&b.file.rel_path))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+
+    let mut reported = 0;
+    for spread in spreads {
+        if reported == TOP_OFFENDERS {
+            break;
+        }
+        let thresholds = thresholds_for(spread.file.language);
+        let (severity, limit) = if spread.referencing_files > thresholds.spread_error {
+            (Severity::Error, thresholds.spread_error)
+        } else if spread.referencing_files > thresholds.spread_warn {
+            (Severity::Warning, thresholds.spread_warn)
+        } else {
+            continue;
+        };
+
+        reported += 1;
+        let rel_path = &spread.file.rel_path;
+        report.add_issue(Issue {
+            severity,
+            file: rel_path.clone(),
+            line: Some(spread.line),
+            column: None,
+            end_line: None,
+            name: Some(spread.name.to_string()),
+            check_type: "shotgun-surgery",
+            value: spread.referencing_files,
+            limit,
+            message: format!(
+                "{}:{} {} (referenced in {} files)",
+                rel_path.display(),
+                spread.line,
+                spread.name,
+                spread.referencing_files
+            ),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn func(name: &str) -> FunctionInfo {
+        FunctionInfo {
+            name: name.to_string(),
+            start_line: 1,
+            line_count: 3,
+            max_nesting: 0,
+        }
+    }
+
+    #[test]
+    fn test_widely_referenced_function_reported() {
+        let mut index = SpreadIndex::default();
+        index.add_file(Path::new("util.py"), LanguageType::Python, "def format_money(x):\n", &[func("format_money")]);
+        for i in 0..4 {
+            let path = format!("view{}.py", i);
+            index.add_file(Path::new(&path), LanguageType::Python, "print(format_money(1))\n", &[]);
+        }
+
+        let mut report = Report::default();
+        let thresholds = |_| Thresholds {
+            spread_warn: 3,
+            ..Thresholds::default()
+        };
+        check_identifier_spread(&index, thresholds, &mut report);
+
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].value, 4);
+        assert_eq!(report.issues[0].name.as_deref(), Some("format_money"));
+    }
+
+    #[test]
+    fn test_methods_match_calls_by_name() {
+        let mut index = SpreadIndex::default();
+        index.add_file(Path::new("money.py"), LanguageType::Python, "", &[func("Money.format_money")]);
+        index.add_file(Path::new("view.py"), LanguageType::Python, "m.format_money()\n", &[]);
+
+        let mut report = Report::default();
+        let thresholds = |_| Thresholds {
+            spread_warn: 0,
+            ..Thresholds::default()
+        };
+        check_identifier_spread(&index, thresholds, &mut report);
+
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].name.as_deref(), Some("Money.format_money"));
+    }
+
+    #[test]
+    fn test_other_languages_do_not_count() {
+        let mut index = SpreadIndex::default();
+        index.add_file(Path::new("util.py"), LanguageType::Python, "", &[func("format_money")]);
+        index.add_file(Path::new("app.ts"), LanguageType::TypeScript, "format_money()", &[]);
+
+        let mut report = Report::default();
+        let thresholds = |_| Thresholds {
+            spread_warn: 0,
+            ..Thresholds::default()
+        };
+        check_identifier_spread(&index, thresholds, &mut report);
+
+        assert!(report.issues.is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::languages::{FunctionInfo, LanguageParser, LanguageType};
+use crate::output::{Issue, Report, Severity};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+// `name += ...`
+static APPEND_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*([a-zA-Z_$][a-zA-Z0-9_$]*)\s*\+=\s*(.*)$").unwrap());
+
+// `name = name + ...`
+static REASSIGN_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*([a-zA-Z_$][a-zA-Z0-9_$]*)\s*=\s*([a-zA-Z_$][a-zA-Z0-9_$]*)\s*\+\s*(.*)$")
+        .unwrap()
+});
+
+// A variable initialized with a string literal, e.g. `s = ""`, `let s: string = ''`,
+// `String s = ""`, `var s = ```
+static STRING_INIT_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"^\s*(?:(?:let|var|const|final|String)\s+)?([a-zA-Z_$][a-zA-Z0-9_$]*)\s*(?::\s*(?:str|string)\s*)?=\s*[fFrRbBuU]?["'`]"#,
+    )
+    .unwrap()
+});
+
+/// Languages where string concatenation in loops is checked
+fn is_supported(lang: LanguageType) -> bool {
+    matches!(
+        lang,
+        LanguageType::Python | LanguageType::TypeScript | LanguageType::JavaScript | LanguageType::Dart
+    )
+}
+
+/// Check for strings built by concatenation inside loops
+pub fn check_string_concat(
+    lang: LanguageType,
+    functions: &[FunctionInfo],
+    lines: &[&str],
+    parser: &dyn LanguageParser,
+    rel_path: &Path,
+    report: &mut Report,
+) {
+    if !is_supported(lang) {
+        return;
+    }
+
+    for func in functions {
+        check_function(func, lines, parser, rel_path, report);
+    }
+}
+
+fn check_function(
+    func: &FunctionInfo,
+    lines: &[&str],
+    parser: &dyn LanguageParser,
+    rel_path: &Path,
+    report: &mut Report,
+) {
+    let (start, body) = super::function_body(func, lines);
+
+    // Report each offending loop once, keyed by its header line
+    let mut offenders: BTreeMap<usize, usize> = BTreeMap::new();
+    for (loop_line, count) in find_concat_in_loops(body, parser) {
+        *offenders.entry(start + loop_line + 1).or_default() += count;
+    }
+
+    for (line, count) in offenders {
+        report.add_issue(Issue {
+            severity: Severity::Warning,
+            file: rel_path.to_path_buf(),
+            line: Some(line),
+            column: None,
+            end_line: None,
+            name: Some(func.name.clone()),
+            check_type: "string-concat",
+            value: count,
+            limit: 0,
+            message: format!(
+                "{}:{} {} (string concatenation in loop)",
+                rel_path.display(),
+                line,
+                func.name
+            ),
+        });
+    }
+}
+
+/// Find string concatenations inside loops.
+///
+/// Returns `(loop header index, concatenation count)` pairs, attributing each
+/// concatenation to its innermost enclosing loop.
+fn find_concat_in_loops(body: &[&str], parser: &dyn LanguageParser) -> Vec<(usize, usize)> {
+    let loops = parser.find_loops(body);
+    if loops.is_empty() {
+        return Vec::new();
+    }
+
+    let string_vars: HashSet<&str> = body
+        .iter()
+        .filter_map(|line| STRING_INIT_PATTERN.captures(line))
+        .filter_map(|caps| caps.get(1).map(|m| m.as_str()))
+        .collect();
+
+    let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+    for (i, line) in body.iter().enumerate() {
+        if !is_string_concat(line, &string_vars) {
+            continue;
+        }
+        let innermost = loops
+            .iter()
+            .filter(|l| l.start < i && i <= l.end)
+            .max_by_key(|l| l.start);
+        if let Some(l) = innermost {
+            *counts.entry(l.start).or_default() += 1;
+        }
+    }
+
+    counts.into_iter().collect()
+}
+
+fn is_string_concat(line: &str, string_vars: &HashSet<&str>) -> bool {
+    if let Some(caps) = APPEND_PATTERN.captures(line) {
+        let var = caps.get(1).map_or("", |m| m.as_str());
+        let rhs = caps.get(2).map_or("", |m| m.as_str());
+        return string_vars.contains(var) || starts_with_string_literal(rhs);
+    }
+
+    if let Some(caps) = REASSIGN_PATTERN.captures(line) {
+        let var = caps.get(1).map_or("", |m| m.as_str());
+        let operand = caps.get(2).map_or("", |m| m.as_str());
+        let rhs = caps.get(3).map_or("", |m| m.as_str());
+        return var == operand && (string_vars.contains(var) || starts_with_string_literal(rhs));
+    }
+
+    false
+}
+
+fn starts_with_string_literal(expr: &str) -> bool {
+    let expr = expr.trim_start();
+    let expr = expr
+        .strip_prefix(['f', 'F', 'r', 'R', 'b', 'B', 'u', 'U'])
+        .filter(|rest| rest.starts_with(['"', '\'']))
+        .unwrap_or(expr);
+    expr.starts_with(['"', '\'', '`'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::languages::get_parser;
+
+    fn concat_loops(lang: LanguageType, code: &str) -> Vec<(usize, usize)> {
+        let lines: Vec<&str> = code.lines().collect();
+        find_concat_in_loops(&lines, get_parser(lang).as_ref())
+    }
+
+    #[test]
+    fn test_python_concat_in_loop() {
+        let code = "def build(items):\n    out = \"\"\n    for item in items:\n        out += item\n    return out\n";
+        assert_eq!(concat_loops(LanguageType::Python, code), vec![(2, 1)]);
+    }
+
+    #[test]
+    fn test_typescript_concat_in_nested_loop() {
+        let code = r#"function build(rows) {
+    let html = "";
+    for (const row of rows) {
+        for (const cell of row) {
+            html = html + "<td>" + cell + "</td>";
+        }
+    }
+    return html;
+}"#;
+        assert_eq!(concat_loops(LanguageType::TypeScript, code), vec![(3, 1)]);
+    }
+
+    #[test]
+    fn test_numeric_accumulator_not_flagged() {
+        let code = "int total(List<int> xs) {\n  var sum = 0;\n  for (var x in xs) {\n    sum += x;\n  }\n  return sum;\n}";
+        assert!(concat_loops(LanguageType::Dart, code).is_empty());
+    }
+
+    #[test]
+    fn test_concat_outside_loop_not_flagged() {
+        let code = "function f() {\n    let s = \"\";\n    s += \"a\";\n    while (x) {\n        n += 1;\n    }\n}";
+        assert!(concat_loops(LanguageType::TypeScript, code).is_empty());
+    }
+}
@@ -0,0 +1,72 @@
+use super::markers::comment_start;
+use crate::languages::LanguageType;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// How many lines at the top of a file are searched for the directive
+pub const DIRECTIVE_LINES: usize = 10;
+
+// `code-smells:ignore-file`, optionally followed by check ids, e.g.
+// `code-smells:ignore-file exit-calls, nesting-depth`
+static DIRECTIVE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"code-smells:ignore-file\b(?:[ \t]+([a-z0-9_-]+(?:[ \t]*,[ \t]*[a-z0-9_-]+)*))?").unwrap()
+});
+
+/// What a file's `code-smells:ignore-file` directive excludes
+#[derive(Debug, PartialEq, Eq)]
+pub enum Suppression {
+    /// The whole file
+    All,
+    /// Only these check ids
+    Checks(Vec<String>),
+}
+
+impl Suppression {
+    pub fn covers(&self, check_id: &str) -> bool {
+        match self {
+            Suppression::All => true,
+            Suppression::Checks(ids) => ids.iter().any(|id| id == check_id),
+        }
+    }
+}
+
+/// Find an ignore-file directive in a comment near the top of a file
+pub fn file_suppression(lang: LanguageType, content: &str) -> Option<Suppression> {
+    content.lines().take(DIRECTIVE_LINES).find_map(|line| {
+        let start = comment_start(lang, line)?;
+        let caps = DIRECTIVE_PATTERN.captures(&line[start..])?;
+        Some(match caps.get(1) {
+            Some(ids) => Suppression::Checks(ids.as_str().split(',').map(|id| id.trim().to_string()).collect()),
+            None => Suppression::All,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whole_file_directive() {
+        let code = "# Generated by protoc. DO NOT EDIT.\n# code-smells:ignore-file\nimport os\n";
+        assert_eq!(file_suppression(LanguageType::Python, code), Some(Suppression::All));
+    }
+
+    #[test]
+    fn test_check_list_directive() {
+        let code = "/* code-smells:ignore-file exit-calls, nesting-depth */\nfn main() {}\n";
+        let suppression = file_suppression(LanguageType::Rust, code).unwrap();
+        assert!(suppression.covers("exit-calls"));
+        assert!(suppression.covers("nesting-depth"));
+        assert!(!suppression.covers("file-length"));
+    }
+
+    #[test]
+    fn test_directive_outside_comment_or_header_ignored() {
+        let code = "let s = \"code-smells:ignore-file\";\n";
+        assert_eq!(file_suppression(LanguageType::TypeScript, code), None);
+
+        let late = format!("{}// code-smells:ignore-file\n", "let x = 1;\n".repeat(DIRECTIVE_LINES));
+        assert_eq!(file_suppression(LanguageType::TypeScript, &late), None);
+    }
+}
@@ -1,82 +1,759 @@
-use clap::{Parser, ValueEnum};
+use clap::builder::{BoolishValueParser, EnumValueParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use code_smells::config::{Overrides, ThresholdOverrides};
+use code_smells::languages::LanguageType;
+use code_smells::options::{
+    Budget, CheckSelection, CheckType, ColorChoice, CountMode, Decorators, ExitCodeMap, GroupBy, NestingMode,
+    OutputFormat, PythonIndent, SeverityFilter, Shard, SortKey, SourceDirOverride, TestCode,
+};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "code-smells")]
 #[command(about = "Detect code smells across multiple programming languages")]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Cli {
-    /// Directory to analyze (default: current directory)
-    #[arg(default_value = ".")]
-    pub directory: PathBuf,
+    #[command(subcommand)]
+    pub command: Option<Command>,
 
-    /// Check type: all, file-length, functions, nesting
-    #[arg(short = 'c', long = "check", default_value = "all")]
-    pub check_type: CheckType,
+    #[command(flatten)]
+    pub project: ProjectArgs,
 
-    /// Comma-separated languages (default: auto-detect)
-    #[arg(short = 'l', long = "lang")]
-    pub languages: Option<String>,
+    /// Checks to run, comma-separated: all, file-length, functions, nesting, nested-loops, string-concat, identifiers, enum-variants, shotgun-surgery, exit-calls, module-nesting, jsx-nesting, markers, custom, security
+    #[arg(short = 'c', long = "check", env = "CODE_SMELLS_CHECK", value_delimiter = ',', default_value = "all", value_parser = value_of::<CheckArg, CheckType>())]
+    pub checks: Vec<CheckType>,
 
-    /// Output format: text, json
-    #[arg(short = 'f', long = "format", default_value = "text")]
+    /// Output format: text, json, sarif, junit, codeclimate, ndjson, compact
+    #[arg(short = 'f', long = "format", env = "CODE_SMELLS_FORMAT", default_value = "text", value_parser = value_of::<FormatArg, OutputFormat>())]
     pub format: OutputFormat,
 
+    /// Also write the report to PATH, in FORMAT (default: --format); the terminal then gets the text report (repeatable)
+    #[arg(short = 'o', long = "output", value_name = "[FORMAT=]PATH", value_parser = parse_output_target, env = "CODE_SMELLS_OUTPUT", value_delimiter = ',')]
+    pub outputs: Vec<OutputTarget>,
+
+    /// With --format json, print the JSON Schema of the report instead of running the checks
+    #[arg(long = "schema")]
+    pub schema: bool,
+
+    /// When to color the text report: auto (terminal, honoring NO_COLOR and CLICOLOR_FORCE), always, never
+    #[arg(long = "color", value_name = "WHEN", default_value = "auto", env = "CODE_SMELLS_COLOR", value_parser = value_of::<ColorArg, ColorChoice>())]
+    pub color: ColorChoice,
+
+    /// Group the text report's issues by severity, file, check, directory, or project
+    /// (default: project with --recursive-projects, severity otherwise)
+    #[arg(long = "group-by", value_name = "KEY", env = "CODE_SMELLS_GROUP_BY", value_parser = value_of::<GroupByArg, GroupBy>())]
+    pub group_by: Option<GroupBy>,
+
+    /// Order issues by severity, file, value, or check (default: by check, then file)
+    #[arg(long = "sort", value_name = "KEY", env = "CODE_SMELLS_SORT", value_parser = value_of::<SortArg, SortKey>())]
+    pub sort: Option<SortKey>,
+
+    /// Base URL for check documentation links in JSON and SARIF output
+    /// ({check} is replaced by the check id, otherwise the id is appended)
+    #[arg(long = "help-uri-base", env = "CODE_SMELLS_HELP_URI_BASE", value_name = "URL", default_value = code_smells::checks::rules::DEFAULT_HELP_URI_BASE)]
+    pub help_uri_base: String,
+
+    /// Exit codes per outcome, e.g. warnings=0,errors=1,critical=3
+    /// (outcomes: clean, warnings, errors, critical, failure; default: 0,1,2,errors,3)
+    #[arg(long = "exit-code-map", env = "CODE_SMELLS_EXIT_CODE_MAP", value_name = "MAP", value_parser = parse_exit_code_map, default_value = "")]
+    pub exit_code_map: ExitCodeMap,
+
+    /// Fail on errors only when there are more than N
+    #[arg(long = "max-errors", env = "CODE_SMELLS_MAX_ERRORS", value_name = "N")]
+    pub max_errors: Option<usize>,
+
+    /// Fail on warnings only when there are more than N
+    #[arg(long = "max-warnings", env = "CODE_SMELLS_MAX_WARNINGS", value_name = "N")]
+    pub max_warnings: Option<usize>,
+
+    /// Write a manifest (tool version, configuration, git ref, file hashes) to FILE
+    #[arg(long = "emit-manifest", env = "CODE_SMELLS_EMIT_MANIFEST", value_name = "FILE")]
+    pub emit_manifest: Option<PathBuf>,
+
+    /// Print the effective configuration (TOML, or JSON with --format json) after validating it, then exit
+    #[arg(long = "print-config")]
+    pub print_config: bool,
+
+    /// Browse the issues interactively, with filters and a source preview
+    #[arg(long = "tui", conflicts_with_all = ["format", "outputs", "write_baseline"])]
+    pub tui: bool,
+
+    /// List the N longest files, longest functions, and most deeply nested
+    /// functions, regardless of thresholds, then exit
+    #[arg(long = "top", value_name = "N", env = "CODE_SMELLS_TOP")]
+    pub top: Option<NonZeroUsize>,
+
+    /// Print the files that would be analyzed for each language, after skip
+    /// rules and excludes, without running any checks, then exit
+    #[arg(long = "list-files", conflicts_with_all = ["top", "tui"])]
+    pub list_files: bool,
+
+    /// Record the current warnings and errors in a baseline FILE, then exit
+    #[arg(long = "write-baseline", value_name = "FILE")]
+    pub write_baseline: Option<PathBuf>,
+
+    /// Only report issues that are new or worse than in this baseline
+    /// (default: .code-smells-baseline.json in DIRECTORY, if present)
+    #[arg(long = "baseline", env = "CODE_SMELLS_BASELINE", value_name = "FILE", conflicts_with = "no_baseline")]
+    pub baseline: Option<PathBuf>,
+
+    /// Report every issue, even when a baseline exists
+    #[arg(long = "no-baseline", env = "CODE_SMELLS_NO_BASELINE", value_parser = BoolishValueParser::new())]
+    pub no_baseline: bool,
+
+    /// Also run the security check pack alongside the selected checks
+    #[arg(long = "security", env = "CODE_SMELLS_SECURITY", value_parser = BoolishValueParser::new())]
+    pub security: bool,
+
+    /// Number of worker threads (default: RAYON_NUM_THREADS, or one per available CPU)
+    #[arg(short = 'j', long = "jobs", visible_alias = "threads", env = "CODE_SMELLS_JOBS", value_name = "N")]
+    pub jobs: Option<NonZeroUsize>,
+
     /// Show only errors (no warnings)
-    #[arg(short = 'e', long = "errors", conflicts_with = "warnings_only")]
+    #[arg(short = 'e', long = "errors", env = "CODE_SMELLS_ERRORS", conflicts_with = "warnings_only", value_parser = BoolishValueParser::new())]
     pub errors_only: bool,
 
     /// Show only warnings (no errors)
-    #[arg(short = 'w', long = "warnings")]
+    #[arg(short = 'w', long = "warnings", env = "CODE_SMELLS_WARNINGS", value_parser = BoolishValueParser::new())]
     pub warnings_only: bool,
 
+    /// Print only the summary of the text report, and nothing when no issues are found
+    #[arg(short = 'q', long = "quiet", env = "CODE_SMELLS_QUIET", conflicts_with = "summary_only", value_parser = BoolishValueParser::new())]
+    pub quiet: bool,
+
+    /// Print only the summary of the text report
+    #[arg(long = "summary-only", env = "CODE_SMELLS_SUMMARY_ONLY", value_parser = BoolishValueParser::new())]
+    pub summary_only: bool,
+
+    #[command(flatten)]
+    pub thresholds: ThresholdArgs,
+}
+
+/// What to analyze, shared by the main command and `whatif`
+#[derive(Args)]
+pub struct ProjectArgs {
+    /// Directory to analyze, git repository URL to clone, or .zip/.tar.gz archive
+    /// to extract (default: current directory)
+    #[arg(default_value = ".")]
+    pub directory: PathBuf,
+
+    /// Comma-separated languages (default: auto-detect)
+    #[arg(short = 'l', long = "lang", env = "CODE_SMELLS_LANG")]
+    pub languages: Option<String>,
+
+    /// Source directory to scan, as DIR for all languages or LANG=DIR for one (repeatable)
+    #[arg(long = "source-dir", env = "CODE_SMELLS_SOURCE_DIR", value_delimiter = ',', value_name = "[LANG=]DIR", value_parser = parse_source_dir)]
+    pub source_dirs: Vec<SourceDirOverride>,
+
+    /// Config file (default: .code-smells.toml in DIRECTORY, if present)
+    #[arg(long = "config", env = "CODE_SMELLS_CONFIG", value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Trace each file scanned or skipped, and the time spent per check and per language, on stderr
+    #[arg(short = 'v', long = "verbose", env = "CODE_SMELLS_VERBOSE", value_parser = BoolishValueParser::new())]
+    pub verbose: bool,
+
+    /// Only analyze files changed since the current branch forked from this git ref, e.g. origin/main
+    #[arg(long = "diff-base", value_name = "REF", env = "CODE_SMELLS_DIFF_BASE")]
+    pub diff_base: Option<String>,
+
+    /// Only analyze the files staged in git, as staged (for pre-commit hooks)
+    #[arg(long = "staged", env = "CODE_SMELLS_STAGED", value_parser = BoolishValueParser::new(), conflicts_with = "diff_base")]
+    pub staged: bool,
+
+    /// Analyze the project as it was at this git commit, branch, or tag, without checking it out
+    #[arg(long = "rev", value_name = "REV", env = "CODE_SMELLS_REV", conflicts_with_all = ["diff_base", "staged"])]
+    pub rev: Option<String>,
+
+    /// Find projects throughout the tree, not just at the root, and analyze each
+    /// one's source directories
+    #[arg(long = "recursive-projects", env = "CODE_SMELLS_RECURSIVE_PROJECTS", value_parser = BoolishValueParser::new(), conflicts_with_all = ["source_dirs"])]
+    pub recursive_projects: bool,
+
+    /// Skip files matching a gitignore-style glob, in addition to .smellsignore (repeatable)
+    #[arg(long = "exclude", env = "CODE_SMELLS_EXCLUDE", value_delimiter = ',', value_name = "PATTERN")]
+    pub excludes: Vec<String>,
+
+    /// Skip only the directories listed in the root config's [skip-dirs], none of
+    /// the languages' defaults (node_modules, target, deps, ...)
+    #[arg(long = "no-default-skips", env = "CODE_SMELLS_NO_DEFAULT_SKIPS", value_parser = BoolishValueParser::new())]
+    pub no_default_skips: bool,
+
+    /// Also analyze the crates a Rust workspace lists under [workspace] exclude
+    #[arg(long = "workspace-excluded", env = "CODE_SMELLS_WORKSPACE_EXCLUDED", value_parser = BoolishValueParser::new())]
+    pub workspace_excluded: bool,
+
+    /// Parser for files with a --fallback-ext extension that no language claims
+    #[arg(long = "fallback-parser", env = "CODE_SMELLS_FALLBACK_PARSER", value_name = "PARSER", requires = "fallback_extensions")]
+    pub fallback_parser: Option<FallbackParser>,
+
+    /// Extensions to analyze with the --fallback-parser, e.g. proto,thrift (repeatable)
+    #[arg(long = "fallback-ext", env = "CODE_SMELLS_FALLBACK_EXT", value_delimiter = ',', value_name = "EXT", requires = "fallback_parser")]
+    pub fallback_extensions: Vec<String>,
+
+    /// Skip files larger than this, in bytes or with a K, M, or G suffix
+    #[arg(long = "max-file-size", env = "CODE_SMELLS_MAX_FILE_SIZE", value_name = "SIZE", value_parser = parse_size, default_value = "2M")]
+    pub max_file_size: u64,
+
+    /// Only analyze shard I of N, e.g. 2/4, splitting the files the same way on every machine
+    #[arg(long = "shard", env = "CODE_SMELLS_SHARD", value_name = "I/N", value_parser = parse_shard)]
+    pub shard: Option<Shard>,
+}
+
+/// Parsers for extensions without a dedicated one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum FallbackParser {
+    /// Whole-file units, nesting from braces (or indentation)
+    Generic,
+}
+
+/// Threshold overrides, shared by the main command and `whatif`
+#[derive(Args, Default)]
+pub struct ThresholdArgs {
     /// File length warning threshold
-    #[arg(long = "file-warn")]
+    #[arg(long = "file-warn", env = "CODE_SMELLS_FILE_WARN")]
     pub file_warn: Option<usize>,
 
     /// File length error threshold
-    #[arg(long = "file-error")]
+    #[arg(long = "file-error", env = "CODE_SMELLS_FILE_ERROR")]
     pub file_error: Option<usize>,
 
     /// Function length warning threshold
-    #[arg(long = "func-warn")]
+    #[arg(long = "func-warn", env = "CODE_SMELLS_FUNC_WARN")]
     pub func_warn: Option<usize>,
 
     /// Function length error threshold
-    #[arg(long = "func-error")]
+    #[arg(long = "func-error", env = "CODE_SMELLS_FUNC_ERROR")]
     pub func_error: Option<usize>,
 
     /// Nesting depth warning threshold
-    #[arg(long = "nest-warn")]
+    #[arg(long = "nest-warn", env = "CODE_SMELLS_NEST_WARN")]
     pub nest_warn: Option<usize>,
 
     /// Nesting depth error threshold
-    #[arg(long = "nest-error")]
+    #[arg(long = "nest-error", env = "CODE_SMELLS_NEST_ERROR")]
     pub nest_error: Option<usize>,
+
+    /// Loop nesting depth warning threshold
+    #[arg(long = "loop-warn", env = "CODE_SMELLS_LOOP_WARN")]
+    pub loop_warn: Option<usize>,
+
+    /// Loop nesting depth error threshold
+    #[arg(long = "loop-error", env = "CODE_SMELLS_LOOP_ERROR")]
+    pub loop_error: Option<usize>,
+
+    /// Enum/union variant count warning threshold
+    #[arg(long = "variants-warn", env = "CODE_SMELLS_VARIANTS_WARN")]
+    pub variant_warn: Option<usize>,
+
+    /// Enum/union variant count error threshold
+    #[arg(long = "variants-error", env = "CODE_SMELLS_VARIANTS_ERROR")]
+    pub variant_error: Option<usize>,
+
+    /// Warn when a function is referenced from more than N other files
+    #[arg(long = "spread-warn", env = "CODE_SMELLS_SPREAD_WARN")]
+    pub spread_warn: Option<usize>,
+
+    /// Error when a function is referenced from more than N other files
+    #[arg(long = "spread-error", env = "CODE_SMELLS_SPREAD_ERROR")]
+    pub spread_error: Option<usize>,
+
+    /// Exit/panic/throw calls per function warning threshold
+    #[arg(long = "exit-warn", env = "CODE_SMELLS_EXIT_WARN")]
+    pub exit_warn: Option<usize>,
+
+    /// Exit/panic/throw calls per function error threshold
+    #[arg(long = "exit-error", env = "CODE_SMELLS_EXIT_ERROR")]
+    pub exit_error: Option<usize>,
+
+    /// Terraform module call depth warning threshold
+    #[arg(long = "module-nesting-warn", env = "CODE_SMELLS_MODULE_NESTING_WARN")]
+    pub module_nesting_warn: Option<usize>,
+
+    /// Terraform module call depth error threshold
+    #[arg(long = "module-nesting-error", env = "CODE_SMELLS_MODULE_NESTING_ERROR")]
+    pub module_nesting_error: Option<usize>,
+
+    /// JSX element nesting depth warning threshold
+    #[arg(long = "jsx-nesting-warn", env = "CODE_SMELLS_JSX_NESTING_WARN")]
+    pub jsx_nesting_warn: Option<usize>,
+
+    /// JSX element nesting depth error threshold
+    #[arg(long = "jsx-nesting-error", env = "CODE_SMELLS_JSX_NESTING_ERROR")]
+    pub jsx_nesting_error: Option<usize>,
+
+    /// With --check markers, report TODO markers older than DAYS (per git blame) as errors
+    #[arg(long = "todo-max-age", env = "CODE_SMELLS_TODO_MAX_AGE", value_name = "DAYS")]
+    pub todo_max_age: Option<u64>,
+
+    /// Also report files and functions just below the warning thresholds
+    #[arg(long = "near-misses", env = "CODE_SMELLS_NEAR_MISSES", value_parser = BoolishValueParser::new())]
+    pub near_misses: bool,
+
+    /// How far below a warning threshold counts as a near miss, in percent; 0
+    /// reports only values right at the threshold
+    #[arg(long = "near-miss-pct", env = "CODE_SMELLS_NEAR_MISS_PCT", default_value = "10", value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub near_miss_pct: u8,
+
+    /// Lines counted for file and function length: physical (every line, the
+    /// default) or logical (not blank and not only a comment)
+    #[arg(long = "count-mode", env = "CODE_SMELLS_COUNT_MODE", value_name = "MODE", value_parser = value_of::<CountModeArg, CountMode>())]
+    pub count_mode: Option<CountMode>,
+
+    /// What nesting depth counts: blocks (every block, the default) or
+    /// control-flow (conditionals, loops, match/switch, and try blocks)
+    #[arg(long = "nesting-mode", env = "CODE_SMELLS_NESTING_MODE", value_name = "MODE", value_parser = value_of::<NestingModeArg, NestingMode>())]
+    pub nesting_mode: Option<NestingMode>,
+
+    /// Also check functions nested in other functions on their own; they
+    /// always count toward the length and nesting of the function around them
+    #[arg(long = "nested-functions", env = "CODE_SMELLS_NESTED_FUNCTIONS", value_parser = BoolishValueParser::new())]
+    pub nested_functions: bool,
+
+    /// Function checks on test code: check, or skip Rust `#[test]` functions
+    /// and `#[cfg(test)]` modules
+    #[arg(long = "test-code", env = "CODE_SMELLS_TEST_CODE", value_name = "MODE", value_parser = value_of::<TestCodeArg, TestCode>())]
+    pub test_code: Option<TestCode>,
+
+    /// Columns of indentation per level of Python nesting (default: 4), or
+    /// auto to take each file's most common indentation step
+    #[arg(long = "python-indent", env = "CODE_SMELLS_PYTHON_INDENT", value_name = "WIDTH")]
+    pub python_indent: Option<PythonIndent>,
+
+    /// Function length of decorated functions: exclude or include the
+    /// decorator, annotation, and attribute lines above them
+    #[arg(long = "decorators", env = "CODE_SMELLS_DECORATORS", value_name = "MODE", value_parser = value_of::<DecoratorsArg, Decorators>())]
+    pub decorators: Option<Decorators>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
-pub enum CheckType {
+impl ThresholdArgs {
+    /// The overrides these flags give, for the library
+    pub fn overrides(&self) -> Overrides {
+        Overrides {
+            thresholds: ThresholdOverrides {
+                file_warn: self.file_warn,
+                file_error: self.file_error,
+                func_warn: self.func_warn,
+                func_error: self.func_error,
+                nest_warn: self.nest_warn,
+                nest_error: self.nest_error,
+                loop_warn: self.loop_warn,
+                loop_error: self.loop_error,
+                variant_warn: self.variant_warn,
+                variant_error: self.variant_error,
+                spread_warn: self.spread_warn,
+                spread_error: self.spread_error,
+                exit_warn: self.exit_warn,
+                exit_error: self.exit_error,
+                module_nesting_warn: self.module_nesting_warn,
+                module_nesting_error: self.module_nesting_error,
+                jsx_nesting_warn: self.jsx_nesting_warn,
+                jsx_nesting_error: self.jsx_nesting_error,
+                test_code: self.test_code,
+                python_indent: self.python_indent,
+                decorators: self.decorators,
+                count_mode: self.count_mode,
+                nesting_mode: self.nesting_mode,
+                // Without the flag, the config files decide
+                nested_functions: self.nested_functions.then_some(true),
+            },
+            todo_max_age_days: self.todo_max_age,
+            near_miss_pct: self.near_misses.then_some(self.near_miss_pct as usize),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List the issues fixed and introduced between two JSON reports, and the net change per check
+    Compare {
+        /// Earlier report, produced with --format json
+        old: PathBuf,
+
+        /// Later report, produced with --format json
+        new: PathBuf,
+
+        /// Output format: text, json
+        #[arg(short = 'f', long = "format", default_value = "text", value_parser = value_of::<FormatArg, OutputFormat>())]
+        format: OutputFormat,
+    },
+    /// Render a static HTML dashboard from JSON reports
+    Dashboard {
+        /// JSON reports produced with --format json
+        #[arg(short = 'i', long = "input", required = true, num_args = 1..)]
+        inputs: Vec<PathBuf>,
+
+        /// Directory to write the dashboard into
+        #[arg(short = 'o', long = "output", default_value = "site")]
+        output: PathBuf,
+    },
+    /// Work with JSON reports
+    Report {
+        #[command(subcommand)]
+        command: ReportCommand,
+    },
+    /// Detect the project's languages and write a starter .code-smells.toml with the default thresholds
+    Init {
+        /// Directory to write the config into (default: current directory)
+        #[arg(default_value = ".")]
+        directory: PathBuf,
+
+        /// Comma-separated languages (default: auto-detect)
+        #[arg(short = 'l', long = "lang")]
+        languages: Option<String>,
+
+        /// Overwrite an existing config file
+        #[arg(long = "force")]
+        force: bool,
+    },
+    /// Run all checks and append the summary to a history file, for `trend`
+    Record {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// History file (default: .code-smells-history.jsonl in DIRECTORY)
+        #[arg(long = "history", value_name = "FILE")]
+        history: Option<PathBuf>,
+
+        /// Also record every issue, not just the counts
+        #[arg(long = "issues")]
+        issues: bool,
+    },
+    /// Show how the error and warning counts moved over the recorded runs
+    Trend {
+        /// Directory holding the history file (default: current directory)
+        #[arg(default_value = ".")]
+        directory: PathBuf,
+
+        /// History file (default: .code-smells-history.jsonl in DIRECTORY)
+        #[arg(long = "history", value_name = "FILE")]
+        history: Option<PathBuf>,
+
+        /// Only show the last N runs
+        #[arg(long = "last", value_name = "N")]
+        last: Option<usize>,
+
+        /// Output format: text, json
+        #[arg(short = 'f', long = "format", default_value = "text", value_parser = value_of::<FormatArg, OutputFormat>())]
+        format: OutputFormat,
+    },
+    /// List every check with its category and default thresholds per language
+    Checks {
+        /// Output format: text, json
+        #[arg(short = 'f', long = "format", default_value = "text", value_parser = value_of::<FormatArg, OutputFormat>())]
+        format: OutputFormat,
+    },
+    /// Explain what a check measures, why it matters, and how to configure it
+    Explain {
+        /// Check id (e.g. function-length) or --check value (e.g. functions)
+        check: String,
+    },
+    /// Report the distribution (min, median, p90, max) of file lengths, function
+    /// lengths, and nesting depth per language, regardless of thresholds
+    Stats {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// Output format: text, json
+        #[arg(short = 'f', long = "format", default_value = "text", value_parser = value_of::<FormatArg, OutputFormat>())]
+        format: OutputFormat,
+    },
+    /// List every parsed function with its length and nesting depth, regardless
+    /// of thresholds
+    Inventory {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// Output format: text, json, ndjson
+        #[arg(short = 'f', long = "format", default_value = "text", value_parser = value_of::<FormatArg, OutputFormat>())]
+        format: OutputFormat,
+    },
+    /// Report how many issues proposed thresholds would add or remove, without failing
+    Whatif {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// Output format: text, json
+        #[arg(short = 'f', long = "format", default_value = "text", value_parser = value_of::<FormatArg, OutputFormat>())]
+        format: OutputFormat,
+
+        /// Proposed thresholds
+        #[command(flatten)]
+        thresholds: Box<ThresholdArgs>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ReportCommand {
+    /// Combine JSON reports, such as those of each --shard, into one
+    Merge {
+        /// Reports produced with --format json
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+
+        /// File to write the merged report to (default: stdout)
+        #[arg(short = 'o', long = "output", value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Declare a `ValueEnum` for an option type of the library, with the same
+/// variants, converting into it
+macro_rules! value_enum {
+    ($(#[$meta:meta])* $arg:ident => $option:ident { $($(#[$attr:meta])* $variant:ident,)* }) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+        enum $arg {
+            $($(#[$attr])* $variant,)*
+        }
+
+        impl From<$arg> for $option {
+            fn from(value: $arg) -> Self {
+                match value {
+                    $($arg::$variant => $option::$variant,)*
+                }
+            }
+        }
+    };
+}
+
+/// Parse an option type of the library by the names of its `ValueEnum`
+fn value_of<A, T>() -> impl TypedValueParser<Value = T>
+where
+    A: ValueEnum + Clone + Send + Sync + Into<T> + 'static,
+    T: Clone + Send + Sync + 'static,
+{
+    EnumValueParser::<A>::new().map(A::into)
+}
+
+value_enum!(CheckArg => CheckType {
     All,
     #[value(name = "file-length")]
     FileLength,
     Functions,
     Nesting,
-}
+    #[value(name = "nested-loops")]
+    NestedLoops,
+    #[value(name = "string-concat")]
+    StringConcat,
+    Identifiers,
+    #[value(name = "enum-variants")]
+    EnumVariants,
+    #[value(name = "shotgun-surgery")]
+    ShotgunSurgery,
+    #[value(name = "exit-calls")]
+    ExitCalls,
+    /// Terraform modules calling modules too deeply
+    #[value(name = "module-nesting")]
+    ModuleNesting,
+    /// JSX elements nested too deeply in .tsx and .jsx files
+    #[value(name = "jsx-nesting")]
+    JsxNesting,
+    /// TODO/FIXME markers (opt-in, not part of `all`)
+    Markers,
+    /// Only the checks defined in the config file
+    Custom,
+    /// Security-sensitive patterns such as eval (opt-in, not part of `all`)
+    Security,
+});
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
-pub enum OutputFormat {
+value_enum!(FormatArg => OutputFormat {
     Text,
     Json,
+    Sarif,
+    Junit,
+    /// Code Climate engine issues, NUL-separated
+    Codeclimate,
+    /// One JSON object per issue as files finish, then a summary line
+    Ndjson,
+    /// `path:line:col: severity: check: message` lines for editors
+    Compact,
+});
+
+value_enum!(CountModeArg => CountMode {
+    /// Every line
+    Physical,
+    /// Lines that aren't blank or only a comment
+    Logical,
+});
+
+value_enum!(NestingModeArg => NestingMode {
+    /// Blocks of every kind, including struct literals, closures, and match arms
+    Blocks,
+    /// Conditionals, loops, match/switch, and try blocks, in the languages that
+    /// tell them apart; blocks elsewhere
+    ControlFlow,
+});
+
+value_enum!(TestCodeArg => TestCode {
+    /// Check test functions like any other
+    Check,
+    /// Leave out test functions and the functions of test modules
+    Skip,
+});
+
+value_enum!(DecoratorsArg => Decorators {
+    /// From the `def` or `fn` line
+    Exclude,
+    /// From the first decorator, annotation, or attribute line
+    Include,
+});
+
+value_enum!(GroupByArg => GroupBy {
+    /// ERRORS, WARNINGS, and NEAR MISSES sections
+    Severity,
+    File,
+    Check,
+    /// The directory containing the file
+    Directory,
+    /// The nested project containing the file, with --recursive-projects
+    Project,
+});
+
+value_enum!(SortArg => SortKey {
+    /// Errors first, then warnings, then near misses
+    Severity,
+    /// By path, then line
+    File,
+    /// Largest measured value first
+    Value,
+    /// By check id
+    Check,
+});
+
+value_enum!(ColorArg => ColorChoice {
+    Auto,
+    Always,
+    Never,
+});
+
+/// An `--output` value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputTarget {
+    /// Format to write (default: --format)
+    pub format: Option<OutputFormat>,
+    pub path: PathBuf,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum SeverityFilter {
-    All,
-    ErrorsOnly,
-    WarningsOnly,
+/// Parse `I/N`, with I from 1 to N
+fn parse_shard(input: &str) -> Result<Shard, String> {
+    let parsed = input
+        .split_once('/')
+        .and_then(|(i, n)| Some((i.trim().parse::<u64>().ok()?, n.trim().parse::<u64>().ok()?)));
+    match parsed {
+        Some((index, count)) if (1..=count).contains(&index) => Ok(Shard { index, count }),
+        _ => Err(format!("invalid shard '{}' (expected I/N with I from 1 to N, e.g. 2/4)", input)),
+    }
+}
+
+/// Parse `PATH` or `FORMAT=PATH`; a prefix that isn't a format name is part of the path
+fn parse_output_target(input: &str) -> Result<OutputTarget, String> {
+    let (format, path) = match input.split_once('=') {
+        Some((name, path)) => match FormatArg::from_str(name.trim(), true) {
+            Ok(format) => (Some(format.into()), path.trim()),
+            Err(_) => (None, input.trim()),
+        },
+        None => (None, input.trim()),
+    };
+    if path.is_empty() {
+        return Err("expected a file path".to_string());
+    }
+    Ok(OutputTarget {
+        format,
+        path: PathBuf::from(path),
+    })
+}
+
+/// Parse a byte count such as `500000`, `512K`, or `10M` (binary multiples)
+fn parse_size(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let upper = input.to_uppercase();
+    let digits = upper.trim_end_matches('B');
+    let (number, multiplier) = match digits.chars().last() {
+        Some('K') => (&digits[..digits.len() - 1], 1 << 10),
+        Some('M') => (&digits[..digits.len() - 1], 1 << 20),
+        Some('G') => (&digits[..digits.len() - 1], 1 << 30),
+        _ => (digits, 1),
+    };
+    number
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid size '{}' (expected e.g. 500000, 512K, or 2M)", input))
+}
+
+/// Parse `DIR` or `LANG=DIR`
+fn parse_source_dir(input: &str) -> Result<SourceDirOverride, String> {
+    let (language, dir) = match input.split_once('=') {
+        Some((name, dir)) => {
+            let name = name.trim().to_lowercase();
+            let lang = LanguageType::ALL
+                .into_iter()
+                .find(|l| l.name() == name)
+                .ok_or_else(|| format!("unknown language '{}'", name))?;
+            (Some(lang), dir.trim())
+        }
+        None => (None, input.trim()),
+    };
+    if dir.is_empty() {
+        return Err("expected a directory".to_string());
+    }
+    Ok(SourceDirOverride {
+        language,
+        dir: dir.to_string(),
+    })
+}
+
+/// Parse `outcome=code` pairs, starting from the default mapping
+fn parse_exit_code_map(input: &str) -> Result<ExitCodeMap, String> {
+    let mut map = ExitCodeMap::default();
+
+    for pair in input.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (outcome, code) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("expected outcome=code, got '{}'", pair))?;
+        let code: i32 = code
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid exit code '{}' for '{}'", code.trim(), outcome.trim()))?;
+        match outcome.trim() {
+            "clean" => map.clean = code,
+            "warnings" => map.warnings = code,
+            "errors" => map.errors = code,
+            "critical" => map.critical = Some(code),
+            "failure" => map.failure = code,
+            other => {
+                return Err(format!(
+                    "unknown outcome '{}' (expected clean, warnings, errors, critical, failure)",
+                    other
+                ))
+            }
+        }
+    }
+
+    Ok(map)
 }
 
 impl Cli {
+    /// The checks selected with `--check`, plus the security pack with `--security`
+    pub fn selection(&self) -> CheckSelection {
+        let selection = CheckSelection::new(&self.checks);
+        if self.security {
+            selection.with(CheckType::Security)
+        } else {
+            selection
+        }
+    }
+
+    pub fn budget(&self) -> Budget {
+        Budget {
+            errors: self.max_errors.unwrap_or(0),
+            warnings: self.max_warnings.unwrap_or(0),
+        }
+    }
+
     pub fn severity_filter(&self) -> SeverityFilter {
         if self.errors_only {
             SeverityFilter::ErrorsOnly
@@ -87,3 +764,108 @@ impl Cli {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_selection() {
+        let all = CheckSelection::all();
+        assert!(all.runs(CheckType::Functions));
+        assert!(!all.runs(CheckType::Markers));
+        assert!(all.with(CheckType::Security).runs(CheckType::Security));
+
+        let cli = Cli::try_parse_from(["code-smells", "--check", "functions,markers"]).unwrap();
+        let selection = cli.selection();
+        assert!(selection.runs(CheckType::Markers));
+        assert!(!selection.runs(CheckType::Nesting));
+    }
+
+    #[test]
+    fn test_value_names_match_the_library() {
+        for check in CheckArg::value_variants() {
+            assert_eq!(check.to_possible_value().unwrap().get_name(), CheckType::from(*check).name());
+        }
+        for format in FormatArg::value_variants() {
+            assert_eq!(format.to_possible_value().unwrap().get_name(), OutputFormat::from(*format).name());
+        }
+    }
+
+    #[test]
+    fn test_parse_exit_code_map() {
+        assert_eq!(parse_exit_code_map(""), Ok(ExitCodeMap::default()));
+        assert_eq!(
+            parse_exit_code_map("warnings=0, errors=1"),
+            Ok(ExitCodeMap {
+                clean: 0,
+                warnings: 0,
+                errors: 1,
+                critical: None,
+                failure: 3,
+            })
+        );
+        let map = parse_exit_code_map("warnings=0,errors=1,critical=3").unwrap();
+        assert_eq!((map.warnings, map.errors, map.critical), (0, 1, Some(3)));
+        assert!(parse_exit_code_map("fatal=3").is_err());
+        assert!(parse_exit_code_map("errors").is_err());
+        assert!(parse_exit_code_map("errors=x").is_err());
+    }
+
+    #[test]
+    fn test_parse_output_target() {
+        assert_eq!(
+            parse_output_target("json=reports/smells.json"),
+            Ok(OutputTarget {
+                format: Some(OutputFormat::Json),
+                path: PathBuf::from("reports/smells.json"),
+            })
+        );
+        assert_eq!(
+            parse_output_target("out/a=b.txt"),
+            Ok(OutputTarget {
+                format: None,
+                path: PathBuf::from("out/a=b.txt"),
+            })
+        );
+        assert!(parse_output_target("sarif=").is_err());
+    }
+
+    #[test]
+    fn test_parse_source_dir() {
+        assert_eq!(
+            parse_source_dir("app"),
+            Ok(SourceDirOverride {
+                language: None,
+                dir: "app".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_source_dir("Elixir=apps/core/lib"),
+            Ok(SourceDirOverride {
+                language: Some(LanguageType::Elixir),
+                dir: "apps/core/lib".to_string(),
+            })
+        );
+        assert!(parse_source_dir("cobol=src").is_err());
+        assert!(parse_source_dir("rust=").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("500000"), Ok(500_000));
+        assert_eq!(parse_size("512k"), Ok(512 * 1024));
+        assert_eq!(parse_size("2MB"), Ok(2 * 1024 * 1024));
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("lots").is_err());
+    }
+
+    #[test]
+    fn test_parse_shard() {
+        assert_eq!(parse_shard("2/4"), Ok(Shard { index: 2, count: 4 }));
+        assert_eq!(parse_shard("1/1"), Ok(Shard { index: 1, count: 1 }));
+        assert!(parse_shard("0/4").is_err());
+        assert!(parse_shard("5/4").is_err());
+        assert!(parse_shard("2").is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::options::OutputFormat;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// The parts of a `--format json` report needed to compare it
+#[derive(Deserialize)]
+struct ReportFile {
+    #[serde(default)]
+    issues: Vec<IssueRecord>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct IssueRecord {
+    severity: String,
+    file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(rename = "type")]
+    check_type: String,
+    value: usize,
+    #[serde(default)]
+    message: String,
+}
+
+impl IssueRecord {
+    /// Like the baseline, leaves out the line so moved code still matches
+    fn key(&self) -> (&str, Option<&str>, &str) {
+        (&self.file, self.name.as_deref(), &self.check_type)
+    }
+
+    fn is_finding(&self) -> bool {
+        self.severity == "error" || self.severity == "warning"
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct CheckChange {
+    old: usize,
+    new: usize,
+}
+
+#[derive(Serialize)]
+struct Comparison {
+    fixed: Vec<IssueRecord>,
+    introduced: Vec<IssueRecord>,
+    by_check: BTreeMap<String, CheckChange>,
+}
+
+fn load(path: &Path) -> Result<Vec<IssueRecord>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Cannot read '{}': {}", path.display(), e))?;
+    let report: ReportFile =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid report '{}': {}", path.display(), e))?;
+    // Near misses are informational and come and go with small edits
+    Ok(report.issues.into_iter().filter(IssueRecord::is_finding).collect())
+}
+
+/// Pair up the warnings and errors of two reports.
+///
+/// Issues match on file, name, and check; each old issue accounts for at
+/// most one new one, so a second long function of the same name still shows.
+fn compare(old: &[IssueRecord], new: &[IssueRecord]) -> Comparison {
+    let mut unmatched: HashMap<_, usize> = HashMap::new();
+    for issue in old {
+        *unmatched.entry(issue.key()).or_default() += 1;
+    }
+    let mut introduced = Vec::new();
+    for issue in new {
+        match unmatched.get_mut(&issue.key()) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => introduced.push(issue.clone()),
+        }
+    }
+    // The old issues left over, taken from the end so earlier ones count as matched
+    let mut fixed = Vec::new();
+    for issue in old.iter().rev() {
+        if let Some(count) = unmatched.get_mut(&issue.key()).filter(|c| **c > 0) {
+            *count -= 1;
+            fixed.push(issue.clone());
+        }
+    }
+    fixed.reverse();
+
+    let mut by_check: BTreeMap<String, CheckChange> = BTreeMap::new();
+    for issue in old {
+        by_check.entry(issue.check_type.clone()).or_default().old += 1;
+    }
+    for issue in new {
+        by_check.entry(issue.check_type.clone()).or_default().new += 1;
+    }
+    by_check.retain(|_, change| change.old != change.new);
+
+    Comparison {
+        fixed,
+        introduced,
+        by_check,
+    }
+}
+
+fn describe(issue: &IssueRecord) -> String {
+    if !issue.message.is_empty() {
+        return issue.message.clone();
+    }
+    // Reports written before messages were included
+    let location = match issue.line {
+        Some(line) => format!("{}:{}", issue.file, line),
+        None => issue.file.clone(),
+    };
+    match &issue.name {
+        Some(name) => format!("{} {} ({}: {})", location, name, issue.check_type, issue.value),
+        None => format!("{} ({}: {})", location, issue.check_type, issue.value),
+    }
+}
+
+fn print_issues(title: &str, issues: &[IssueRecord]) {
+    if issues.is_empty() {
+        return;
+    }
+    println!();
+    println!("--- {} ({}) ---", title, issues.len());
+    for issue in issues {
+        let label = if issue.severity == "error" { "ERROR" } else { "WARN " };
+        println!("{}  {}", label, describe(issue));
+    }
+}
+
+/// Print the issues fixed and introduced between two JSON reports
+pub fn print_comparison(old_path: &Path, new_path: &Path, format: OutputFormat) -> Result<(), String> {
+    let old = load(old_path)?;
+    let new = load(new_path)?;
+    let comparison = compare(&old, &new);
+
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&comparison).unwrap());
+        return Ok(());
+    }
+
+    println!("=== Report Comparison ===");
+    println!("Old: {} ({} issues)", old_path.display(), old.len());
+    println!("New: {} ({} issues)", new_path.display(), new.len());
+    println!(
+        "Fixed: {}, introduced: {}, net change: {}",
+        comparison.fixed.len(),
+        comparison.introduced.len(),
+        signed(old.len(), new.len())
+    );
+
+    if !comparison.by_check.is_empty() {
+        println!();
+        println!("--- BY CHECK ---");
+        for (check, change) in &comparison.by_check {
+            println!("{}: {} -> {} ({})", check, change.old, change.new, signed(change.old, change.new));
+        }
+    }
+    print_issues("FIXED", &comparison.fixed);
+    print_issues("INTRODUCED", &comparison.introduced);
+    Ok(())
+}
+
+fn signed(old: usize, new: usize) -> String {
+    if new >= old {
+        format!("+{}", new - old)
+    } else {
+        format!("-{}", old - new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(file: &str, name: &str, line: usize) -> IssueRecord {
+        IssueRecord {
+            severity: "warning".to_string(),
+            file: file.to_string(),
+            line: Some(line),
+            name: Some(name.to_string()),
+            check_type: "function-length".to_string(),
+            value: 45,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let old = vec![issue("a.rs", "run", 10), issue("a.rs", "parse", 40), issue("b.rs", "new", 1)];
+        // `run` moved, `parse` was fixed, and a second `new` appeared
+        let new = vec![issue("a.rs", "run", 25), issue("b.rs", "new", 1), issue("b.rs", "new", 30)];
+        let comparison = compare(&old, &new);
+
+        let names = |issues: &[IssueRecord]| -> Vec<(String, Option<usize>)> {
+            issues.iter().map(|i| (i.name.clone().unwrap(), i.line)).collect()
+        };
+        assert_eq!(names(&comparison.fixed), [("parse".to_string(), Some(40))]);
+        assert_eq!(names(&comparison.introduced), [("new".to_string(), Some(30))]);
+        assert!(comparison.by_check.is_empty());
+        assert_eq!(describe(&old[0]), "a.rs:10 run (function-length: 45)");
+    }
+}
@@ -1,7 +1,402 @@
-use crate::cli::Cli;
+use crate::checks::rules::RULES;
+use crate::checks::{CustomCheck, CustomCheckDef, DEFAULT_GENERATED_MARKERS};
 use crate::languages::LanguageType;
+use crate::options::{CountMode, Decorators, NestingMode, PythonIndent, TestCode};
+use crate::output::Severity;
+use crate::scan::{ExtensionMap, ScanRules, SourceFile};
+use globset::{GlobBuilder, GlobMatcher};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
 
-#[derive(Clone, Debug)]
+/// Config file looked up in the project directory when `--config` is not given
+pub const CONFIG_FILE_NAME: &str = ".code-smells.toml";
+
+/// Contents of a `.code-smells.toml` file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// User-defined regex checks
+    #[serde(default)]
+    pub checks: Vec<CustomCheckDef>,
+    /// Threshold overrides for the files beneath this config file
+    #[serde(default)]
+    pub thresholds: ThresholdOverrides,
+    /// Check ids to turn off for the files beneath this config file
+    #[serde(default)]
+    pub disable: Vec<String>,
+    /// Severity to report each listed check at, whatever threshold it crossed
+    #[serde(default)]
+    pub severity: BTreeMap<String, Severity>,
+    /// Threshold overrides for files matching a glob, applied in order after `thresholds`
+    #[serde(default)]
+    pub paths: Vec<PathRule>,
+    /// Comment text marking a generated file to skip, in place of the defaults
+    #[serde(default, rename = "generated-markers")]
+    pub generated_markers: Option<Vec<String>>,
+    /// Language each listed file extension belongs to, or `"skip"`
+    #[serde(default)]
+    pub extensions: BTreeMap<String, String>,
+    /// Directories to skip for each listed language, in addition to its defaults
+    #[serde(default, rename = "skip-dirs")]
+    pub skip_dirs: BTreeMap<String, Vec<String>>,
+    /// Whether to skip the languages' default directories, such as `node_modules`
+    #[serde(default, rename = "default-skips")]
+    pub default_skips: Option<bool>,
+}
+
+/// A `[[paths]]` entry: thresholds for the files matching a glob
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PathRule {
+    /// Glob relative to the config file's directory, e.g. `**/tests/**`
+    #[serde(deserialize_with = "deserialize_glob")]
+    pub glob: GlobMatcher,
+    #[serde(default)]
+    pub thresholds: ThresholdOverrides,
+}
+
+/// Compile a glob in which `*` doesn't cross directories, as in gitignore
+fn deserialize_glob<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GlobMatcher, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+    GlobBuilder::new(&pattern)
+        .literal_separator(true)
+        .build()
+        .map(|glob| glob.compile_matcher())
+        .map_err(serde::de::Error::custom)
+}
+
+/// The `[thresholds]` table of a config file, named like the CLI flags
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ThresholdOverrides {
+    pub file_warn: Option<usize>,
+    pub file_error: Option<usize>,
+    pub func_warn: Option<usize>,
+    pub func_error: Option<usize>,
+    pub nest_warn: Option<usize>,
+    pub nest_error: Option<usize>,
+    pub loop_warn: Option<usize>,
+    pub loop_error: Option<usize>,
+    #[serde(rename = "variants-warn")]
+    pub variant_warn: Option<usize>,
+    #[serde(rename = "variants-error")]
+    pub variant_error: Option<usize>,
+    pub spread_warn: Option<usize>,
+    pub spread_error: Option<usize>,
+    pub exit_warn: Option<usize>,
+    pub exit_error: Option<usize>,
+    pub module_nesting_warn: Option<usize>,
+    pub module_nesting_error: Option<usize>,
+    pub jsx_nesting_warn: Option<usize>,
+    pub jsx_nesting_error: Option<usize>,
+    pub test_code: Option<TestCode>,
+    pub python_indent: Option<PythonIndent>,
+    pub decorators: Option<Decorators>,
+    pub count_mode: Option<CountMode>,
+    pub nesting_mode: Option<NestingMode>,
+    pub nested_functions: Option<bool>,
+}
+
+/// Overrides for a run, from the command line or a library caller; they win over the config files
+#[derive(Clone, Debug, Default)]
+pub struct Overrides {
+    pub thresholds: ThresholdOverrides,
+    /// Escalate TODO markers older than this many days (per git blame) to errors
+    pub todo_max_age_days: Option<u64>,
+    /// Report values within this percentage below a warning threshold as near misses
+    pub near_miss_pct: Option<usize>,
+}
+
+impl ConfigFile {
+    /// Load the config file given on the command line, or the project's own if it has one
+    pub fn load(project_dir: &Path, explicit: Option<&Path>) -> Result<Self, String> {
+        let path = match explicit {
+            Some(path) => path.to_path_buf(),
+            None => {
+                let path = project_dir.join(CONFIG_FILE_NAME);
+                if !path.is_file() {
+                    return Ok(ConfigFile::default());
+                }
+                path
+            }
+        };
+
+        Self::read(&path)
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Cannot read '{}': {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("Invalid config '{}': {}", path.display(), e))
+    }
+
+    /// The `[extensions]` table, with each language looked up
+    pub fn extension_map(&self) -> Result<ExtensionMap, String> {
+        self.extensions
+            .iter()
+            .map(|(ext, name)| {
+                let language = match name.as_str() {
+                    "skip" => None,
+                    _ => Some(LanguageType::ALL.into_iter().find(|l| l.name() == name).ok_or_else(|| {
+                        format!("Unknown language '{}' for extension '{}' (or \"skip\")", name, ext)
+                    })?),
+                };
+                Ok((ext.trim_start_matches('.').to_string(), language))
+            })
+            .collect()
+    }
+
+    /// The file collection settings: `[extensions]`, `[skip-dirs]`, and `default-skips`
+    pub fn scan_rules(&self) -> Result<ScanRules, String> {
+        let mut skip_dirs = HashMap::new();
+        for (name, dirs) in &self.skip_dirs {
+            let language = LanguageType::ALL
+                .into_iter()
+                .find(|l| l.name() == name)
+                .ok_or_else(|| format!("Unknown language '{}' in skip-dirs", name))?;
+            let dirs = dirs
+                .iter()
+                .map(|dir| {
+                    let (keep, path) = dir.strip_prefix('!').map_or(("", dir.as_str()), |path| ("!", path));
+                    match path.trim_matches('/') {
+                        "" => Err(format!("Invalid skip-dirs entry '{}' for {}", dir, name)),
+                        path => Ok(format!("{}{}", keep, path)),
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            skip_dirs.insert(language, dirs);
+        }
+        Ok(ScanRules {
+            extensions: self.extension_map()?,
+            skip_dirs,
+            no_default_skips: self.default_skips == Some(false),
+        })
+    }
+
+    /// Validate and compile the custom checks
+    pub fn custom_checks(&self) -> Result<Vec<CustomCheck>, String> {
+        let mut checks: Vec<CustomCheck> = Vec::new();
+        for def in &self.checks {
+            if checks.iter().any(|c| c.id == def.name) {
+                return Err(format!("Check '{}' is defined more than once", def.name));
+            }
+            checks.push(CustomCheck::compile(def.clone())?);
+        }
+        Ok(checks)
+    }
+
+    /// Check that `disable` and `severity` only name existing checks
+    fn check_ids(&self, known: impl Fn(&str) -> bool) -> Result<(), String> {
+        if let Some(id) = self.disable.iter().find(|id| !known(id)) {
+            return Err(format!("Cannot disable unknown check '{}'", id));
+        }
+        for (id, severity) in &self.severity {
+            if !known(id) {
+                return Err(format!("Cannot set the severity of unknown check '{}'", id));
+            }
+            if *severity == Severity::Info {
+                return Err(format!("Check '{}': severity must be 'warning' or 'error'", id));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The root config file plus the config files nested in subdirectories.
+///
+/// A nested `.code-smells.toml` applies to the files beneath its directory.
+/// Its thresholds override those of the config files above it, and its
+/// `disable` list adds to theirs.
+#[derive(Default)]
+pub struct ProjectConfig {
+    root: ConfigFile,
+    /// Nested config files keyed by directory relative to the project root, outermost first
+    nested: Vec<(PathBuf, ConfigFile)>,
+}
+
+impl ProjectConfig {
+    /// Load the root config, then every nested config file above the given files
+    pub fn load(project_dir: &Path, explicit: Option<&Path>, files: &[SourceFile]) -> Result<Self, String> {
+        let root = ConfigFile::load(project_dir, explicit)?;
+
+        let dirs: BTreeSet<&Path> = files
+            .iter()
+            .flat_map(|f| f.rel_path.ancestors().skip(1))
+            .filter(|d| !d.as_os_str().is_empty())
+            .collect();
+        let mut nested = Vec::new();
+        for dir in dirs {
+            let path = project_dir.join(dir).join(CONFIG_FILE_NAME);
+            if !path.is_file() {
+                continue;
+            }
+            let config = ConfigFile::read(&path)?;
+            if !config.extensions.is_empty() {
+                return Err(format!(
+                    "Invalid config '{}': extensions can only be mapped in the root config",
+                    path.display()
+                ));
+            }
+            if !config.skip_dirs.is_empty() || config.default_skips.is_some() {
+                return Err(format!(
+                    "Invalid config '{}': skip-dirs and default-skips can only be set in the root config",
+                    path.display()
+                ));
+            }
+            if config.generated_markers.is_some() {
+                return Err(format!(
+                    "Invalid config '{}': generated-markers can only be set in the root config",
+                    path.display()
+                ));
+            }
+            if !config.checks.is_empty() {
+                return Err(format!(
+                    "Invalid config '{}': custom checks can only be defined in the root config",
+                    path.display()
+                ));
+            }
+            nested.push((dir.to_path_buf(), config));
+        }
+
+        Ok(ProjectConfig { root, nested })
+    }
+
+    /// Validate and compile the custom checks, and check that every check the configs refer to exists
+    pub fn custom_checks(&self) -> Result<Vec<CustomCheck>, String> {
+        let custom = self.root.custom_checks()?;
+        let known = |id: &str| RULES.iter().any(|r| r.id == id) || custom.iter().any(|c| c.id == id);
+        self.root.check_ids(known)?;
+        for (dir, config) in &self.nested {
+            config
+                .check_ids(known)
+                .map_err(|e| format!("{} in '{}'", e, dir.join(CONFIG_FILE_NAME).display()))?;
+        }
+        Ok(custom)
+    }
+
+    /// Comment text marking a generated file: the root config's, or the defaults
+    pub fn generated_markers(&self) -> Vec<String> {
+        match &self.root.generated_markers {
+            Some(markers) => markers.clone(),
+            None => DEFAULT_GENERATED_MARKERS.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    /// Thresholds for a file: language defaults, then config files from the root down, then the overrides.
+    ///
+    /// Each config file contributes its `[thresholds]` table followed by the
+    /// `[[paths]]` rules whose glob matches the file.
+    pub fn thresholds(&self, lang: LanguageType, rel_path: &Path, overrides: &Overrides) -> Thresholds {
+        self.configured(lang, rel_path).with_overrides(overrides)
+    }
+
+    /// Thresholds for a file in `dir`, a config file's directory, that the
+    /// `[[paths]]` rule `rule` of that config file matches
+    pub fn rule_thresholds(&self, lang: LanguageType, dir: &Path, rule: &PathRule, overrides: &Overrides) -> Thresholds {
+        self.configured(lang, dir).with_config(&rule.thresholds).with_overrides(overrides)
+    }
+
+    /// `thresholds` before the overrides
+    fn configured(&self, lang: LanguageType, rel_path: &Path) -> Thresholds {
+        let mut thresholds = Thresholds::for_language(lang);
+        for (dir, config) in self.chain_with_dirs(rel_path) {
+            thresholds = thresholds.with_config(&config.thresholds);
+            let below = rel_path.strip_prefix(dir).unwrap_or(rel_path);
+            for rule in config.paths.iter().filter(|rule| rule.glob.is_match(below)) {
+                thresholds = thresholds.with_config(&rule.thresholds);
+            }
+        }
+        thresholds
+    }
+
+    /// Reject thresholds no value can satisfy, for any of `languages` in the
+    /// directory of any config file or under any of its `[[paths]]` rules
+    pub fn validate(&self, languages: &[LanguageType], overrides: &Overrides) -> Result<(), String> {
+        for dir in self.dirs() {
+            let label = dir_label(dir);
+            for &lang in languages {
+                self.thresholds(lang, dir, overrides)
+                    .validate()
+                    .map_err(|e| format!("Impossible {} thresholds in '{}': {}", lang.name(), label, e))?;
+                for rule in self.path_rules(dir) {
+                    self.rule_thresholds(lang, dir, rule, overrides).validate().map_err(|e| {
+                        format!("Impossible {} thresholds for '{}' in '{}': {}", lang.name(), rule.glob.glob(), label, e)
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a config file at or above a file turns a check off
+    pub fn is_disabled(&self, rel_path: &Path, check_id: &str) -> bool {
+        self.chain(rel_path).any(|config| config.disable.iter().any(|id| id == check_id))
+    }
+
+    /// Severity a config file at or above a file assigns to a check; the innermost one wins
+    pub fn severity_for(&self, rel_path: &Path, check_id: &str) -> Option<Severity> {
+        self.chain(rel_path)
+            .filter_map(|config| config.severity.get(check_id).copied())
+            .last()
+    }
+
+    /// Every check turned off for a file by the config files at or above it
+    pub fn disabled(&self, rel_path: &Path) -> BTreeSet<String> {
+        self.chain(rel_path).flat_map(|config| config.disable.iter().cloned()).collect()
+    }
+
+    /// Severity overrides applying to a file, with inner config files winning
+    pub fn severities(&self, rel_path: &Path) -> BTreeMap<String, Severity> {
+        self.chain(rel_path)
+            .flat_map(|config| config.severity.iter().map(|(id, s)| (id.clone(), *s)))
+            .collect()
+    }
+
+    /// Directories holding a nested config file
+    pub fn nested_dirs(&self) -> impl Iterator<Item = &Path> {
+        self.nested.iter().map(|(dir, _)| dir.as_path())
+    }
+
+    /// The project root (as an empty path), then each directory holding a nested config file
+    pub fn dirs(&self) -> impl Iterator<Item = &Path> {
+        std::iter::once(Path::new("")).chain(self.nested_dirs())
+    }
+
+    /// The `[[paths]]` rules of the config file in `dir`, one of `dirs`
+    pub fn path_rules(&self, dir: &Path) -> &[PathRule] {
+        if dir.as_os_str().is_empty() {
+            return &self.root.paths;
+        }
+        self.nested.iter().find(|(d, _)| d == dir).map_or(&[], |(_, config)| &config.paths)
+    }
+
+    /// Config files applying to a file, outermost first
+    fn chain<'a>(&'a self, rel_path: &'a Path) -> impl Iterator<Item = &'a ConfigFile> + 'a {
+        self.chain_with_dirs(rel_path).map(|(_, config)| config)
+    }
+
+    /// Like `chain`, with the directory of each config file relative to the project root
+    fn chain_with_dirs<'a>(&'a self, rel_path: &'a Path) -> impl Iterator<Item = (&'a Path, &'a ConfigFile)> + 'a {
+        std::iter::once((Path::new(""), &self.root)).chain(
+            self.nested
+                .iter()
+                .filter(move |(dir, _)| rel_path.starts_with(dir))
+                .map(|(dir, config)| (dir.as_path(), config)),
+        )
+    }
+}
+
+/// A config file's directory as reports and messages show it: `.` for the project root
+pub fn dir_label(dir: &Path) -> String {
+    if dir.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        dir.display().to_string()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
 pub struct Thresholds {
     pub file_warn: usize,
     pub file_error: usize,
@@ -9,6 +404,70 @@ pub struct Thresholds {
     pub func_error: usize,
     pub nest_warn: usize,
     pub nest_error: usize,
+    pub loop_warn: usize,
+    pub loop_error: usize,
+    pub variant_warn: usize,
+    pub variant_error: usize,
+    /// Number of other files referencing a function
+    pub spread_warn: usize,
+    pub spread_error: usize,
+    /// Exit/panic/throw calls per function
+    pub exit_warn: usize,
+    pub exit_error: usize,
+    /// Depth of Terraform module calls below a root module
+    pub module_nesting_warn: usize,
+    pub module_nesting_error: usize,
+    pub jsx_nesting_warn: usize,
+    pub jsx_nesting_error: usize,
+    /// Whether the function checks look at test code
+    pub test_code: TestCode,
+    /// Columns of indentation per level of Python nesting
+    pub python_indent: PythonIndent,
+    /// Whether function length counts the decorators above a function
+    pub decorators: Decorators,
+    /// Escalate TODO markers older than this many days (per git blame) to errors
+    pub todo_max_age_days: Option<u64>,
+    /// Report values within this percentage below a warning threshold as near misses
+    pub near_miss_pct: Option<usize>,
+    /// Lines counted for file and function length
+    pub count_mode: CountMode,
+    /// What nesting depth counts
+    pub nesting_mode: NestingMode,
+    /// Check nested functions on their own as well as part of the function around them
+    pub nested_functions: bool,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds {
+            file_warn: 300,
+            file_error: 500,
+            func_warn: 30,
+            func_error: 50,
+            nest_warn: 4,
+            nest_error: 6,
+            loop_warn: 2,
+            loop_error: 3,
+            variant_warn: 20,
+            variant_error: 40,
+            spread_warn: 20,
+            spread_error: 40,
+            exit_warn: 2,
+            exit_error: 4,
+            module_nesting_warn: 2,
+            module_nesting_error: 3,
+            jsx_nesting_warn: 8,
+            jsx_nesting_error: 12,
+            test_code: TestCode::Check,
+            python_indent: PythonIndent::Width(4),
+            decorators: Decorators::Exclude,
+            todo_max_age_days: None,
+            near_miss_pct: None,
+            count_mode: CountMode::Physical,
+            nesting_mode: NestingMode::Blocks,
+            nested_functions: false,
+        }
+    }
 }
 
 impl Thresholds {
@@ -22,6 +481,7 @@ impl Thresholds {
                 func_error: 50,
                 nest_warn: 4,
                 nest_error: 6,
+                ..Default::default()
             },
             LanguageType::Dart => Thresholds {
                 file_warn: 400,
@@ -30,14 +490,20 @@ impl Thresholds {
                 func_error: 70,
                 nest_warn: 4,
                 nest_error: 6,
+                exit_warn: 3,
+                exit_error: 6,
+                ..Default::default()
             },
-            LanguageType::TypeScript => Thresholds {
+            LanguageType::TypeScript | LanguageType::JavaScript => Thresholds {
                 file_warn: 250,
                 file_error: 400,
                 func_warn: 50,
                 func_error: 80,
                 nest_warn: 4,
                 nest_error: 6,
+                exit_warn: 3,
+                exit_error: 6,
+                ..Default::default()
             },
             LanguageType::Python => Thresholds {
                 file_warn: 300,
@@ -46,6 +512,7 @@ impl Thresholds {
                 func_error: 50,
                 nest_warn: 4,
                 nest_error: 6,
+                ..Default::default()
             },
             LanguageType::Rust => Thresholds {
                 file_warn: 400,
@@ -54,30 +521,411 @@ impl Thresholds {
                 func_error: 60,
                 nest_warn: 4,
                 nest_error: 6,
+                ..Default::default()
+            },
+            // Explicit error handling makes Go functions longer
+            LanguageType::Go => Thresholds {
+                file_warn: 500,
+                file_error: 800,
+                func_warn: 50,
+                func_error: 80,
+                nest_warn: 4,
+                nest_error: 6,
+                ..Default::default()
+            },
+            LanguageType::Java => Thresholds {
+                file_warn: 400,
+                file_error: 700,
+                func_warn: 40,
+                func_error: 70,
+                nest_warn: 4,
+                nest_error: 6,
+                exit_warn: 3,
+                exit_error: 6,
+                ..Default::default()
+            },
+            LanguageType::Swift => Thresholds {
+                file_warn: 400,
+                file_error: 600,
+                func_warn: 40,
+                func_error: 60,
+                nest_warn: 4,
+                nest_error: 6,
+                exit_warn: 3,
+                exit_error: 6,
+                ..Default::default()
+            },
+            // Blocks are declarative, so they may run longer than functions
+            LanguageType::Terraform => Thresholds {
+                file_warn: 300,
+                file_error: 500,
+                func_warn: 60,
+                func_error: 100,
+                nest_warn: 4,
+                nest_error: 6,
+                ..Default::default()
+            },
+            LanguageType::Julia => Thresholds {
+                file_warn: 400,
+                file_error: 600,
+                func_warn: 40,
+                func_error: 60,
+                nest_warn: 4,
+                nest_error: 6,
+                exit_warn: 3,
+                exit_error: 6,
+                ..Default::default()
+            },
+            LanguageType::Generic => Thresholds {
+                file_warn: 400,
+                file_error: 600,
+                nest_warn: 4,
+                nest_error: 6,
+                ..Default::default()
             },
         }
     }
 
-    /// Apply CLI overrides to thresholds
-    pub fn with_overrides(mut self, cli: &Cli) -> Self {
-        if let Some(v) = cli.file_warn {
+    /// Apply the `[thresholds]` table of a config file
+    pub fn with_config(mut self, config: &ThresholdOverrides) -> Self {
+        if let Some(v) = config.file_warn {
             self.file_warn = v;
         }
-        if let Some(v) = cli.file_error {
+        if let Some(v) = config.file_error {
             self.file_error = v;
         }
-        if let Some(v) = cli.func_warn {
+        if let Some(v) = config.func_warn {
             self.func_warn = v;
         }
-        if let Some(v) = cli.func_error {
+        if let Some(v) = config.func_error {
             self.func_error = v;
         }
-        if let Some(v) = cli.nest_warn {
+        if let Some(v) = config.nest_warn {
             self.nest_warn = v;
         }
-        if let Some(v) = cli.nest_error {
+        if let Some(v) = config.nest_error {
             self.nest_error = v;
         }
+        if let Some(v) = config.loop_warn {
+            self.loop_warn = v;
+        }
+        if let Some(v) = config.loop_error {
+            self.loop_error = v;
+        }
+        if let Some(v) = config.variant_warn {
+            self.variant_warn = v;
+        }
+        if let Some(v) = config.variant_error {
+            self.variant_error = v;
+        }
+        if let Some(v) = config.spread_warn {
+            self.spread_warn = v;
+        }
+        if let Some(v) = config.spread_error {
+            self.spread_error = v;
+        }
+        if let Some(v) = config.exit_warn {
+            self.exit_warn = v;
+        }
+        if let Some(v) = config.exit_error {
+            self.exit_error = v;
+        }
+        if let Some(v) = config.module_nesting_warn {
+            self.module_nesting_warn = v;
+        }
+        if let Some(v) = config.module_nesting_error {
+            self.module_nesting_error = v;
+        }
+        if let Some(v) = config.jsx_nesting_warn {
+            self.jsx_nesting_warn = v;
+        }
+        if let Some(v) = config.jsx_nesting_error {
+            self.jsx_nesting_error = v;
+        }
+        if let Some(v) = config.test_code {
+            self.test_code = v;
+        }
+        if let Some(v) = config.python_indent {
+            self.python_indent = v;
+        }
+        if let Some(v) = config.decorators {
+            self.decorators = v;
+        }
+        if let Some(v) = config.count_mode {
+            self.count_mode = v;
+        }
+        if let Some(v) = config.nesting_mode {
+            self.nesting_mode = v;
+        }
+        if let Some(v) = config.nested_functions {
+            self.nested_functions = v;
+        }
         self
     }
+
+    /// Reject a warning threshold above its error threshold, which no value can satisfy
+    pub fn validate(&self) -> Result<(), String> {
+        let pairs = [
+            ("file", self.file_warn, self.file_error),
+            ("func", self.func_warn, self.func_error),
+            ("nest", self.nest_warn, self.nest_error),
+            ("loop", self.loop_warn, self.loop_error),
+            ("variants", self.variant_warn, self.variant_error),
+            ("spread", self.spread_warn, self.spread_error),
+            ("exit", self.exit_warn, self.exit_error),
+            ("module-nesting", self.module_nesting_warn, self.module_nesting_error),
+            ("jsx-nesting", self.jsx_nesting_warn, self.jsx_nesting_error),
+        ];
+        match pairs.iter().find(|(_, warn, error)| warn > error) {
+            Some((name, warn, error)) => Err(format!("{0}-warn ({1}) is above {0}-error ({2})", name, warn, error)),
+            None => Ok(()),
+        }
+    }
+
+    /// These thresholds in config file form
+    pub fn to_overrides(&self) -> ThresholdOverrides {
+        ThresholdOverrides {
+            file_warn: Some(self.file_warn),
+            file_error: Some(self.file_error),
+            func_warn: Some(self.func_warn),
+            func_error: Some(self.func_error),
+            nest_warn: Some(self.nest_warn),
+            nest_error: Some(self.nest_error),
+            loop_warn: Some(self.loop_warn),
+            loop_error: Some(self.loop_error),
+            variant_warn: Some(self.variant_warn),
+            variant_error: Some(self.variant_error),
+            spread_warn: Some(self.spread_warn),
+            spread_error: Some(self.spread_error),
+            exit_warn: Some(self.exit_warn),
+            exit_error: Some(self.exit_error),
+            module_nesting_warn: Some(self.module_nesting_warn),
+            module_nesting_error: Some(self.module_nesting_error),
+            jsx_nesting_warn: Some(self.jsx_nesting_warn),
+            jsx_nesting_error: Some(self.jsx_nesting_error),
+            test_code: Some(self.test_code),
+            python_indent: Some(self.python_indent),
+            decorators: Some(self.decorators),
+            count_mode: Some(self.count_mode),
+            nesting_mode: Some(self.nesting_mode),
+            nested_functions: Some(self.nested_functions),
+        }
+    }
+
+    /// Apply a run's overrides to thresholds
+    pub fn with_overrides(mut self, overrides: &Overrides) -> Self {
+        self = self.with_config(&overrides.thresholds);
+        if let Some(v) = overrides.todo_max_age_days {
+            self.todo_max_age_days = Some(v);
+        }
+        if let Some(v) = overrides.near_miss_pct {
+            self.near_miss_pct = Some(v);
+        }
+        self
+    }
+
+    /// Check whether a value falls just below a warning threshold
+    pub fn is_near_miss(&self, value: usize, warn: usize) -> bool {
+        match self.near_miss_pct {
+            Some(pct) => value > 0 && value <= warn && value * 100 >= warn * (100 - pct.min(100)),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_nested_config_cascade() {
+        let dir = TempDir::new("config");
+        fs::create_dir_all(dir.join("legacy/old")).unwrap();
+        fs::write(dir.join(CONFIG_FILE_NAME), "[thresholds]\nfunc-warn = 40\n").unwrap();
+        fs::write(
+            dir.join("legacy").join(CONFIG_FILE_NAME),
+            "disable = [\"exit-calls\"]\n\n[thresholds]\nfunc-warn = 80\nfile-warn = 900\n",
+        )
+        .unwrap();
+        fs::write(dir.join("legacy/old").join(CONFIG_FILE_NAME), "[thresholds]\nfunc-warn = 120\n").unwrap();
+        let file = |rel: &str| SourceFile {
+            path: dir.join(rel),
+            rel_path: PathBuf::from(rel),
+            language: LanguageType::Python,
+            content: None,
+        };
+        let files = vec![file("app.py"), file("legacy/old/util.py")];
+
+        let config = ProjectConfig::load(&dir, None, &files).unwrap();
+        let cli = Overrides::default();
+
+        let root = config.thresholds(LanguageType::Python, Path::new("app.py"), &cli);
+        assert_eq!((root.func_warn, root.file_warn), (40, 300));
+        let old = config.thresholds(LanguageType::Python, Path::new("legacy/old/util.py"), &cli);
+        assert_eq!((old.func_warn, old.file_warn), (120, 900));
+
+        assert!(config.is_disabled(Path::new("legacy/old/util.py"), "exit-calls"));
+        assert!(!config.is_disabled(Path::new("app.py"), "exit-calls"));
+        // A sibling directory whose name shares a prefix is not beneath `legacy/`
+        assert!(!config.is_disabled(Path::new("legacy2/a.py"), "exit-calls"));
+    }
+
+    #[test]
+    fn test_path_rules() {
+        let dir = TempDir::new("paths");
+        fs::create_dir_all(dir.join("pkg")).unwrap();
+        fs::write(
+            dir.join(CONFIG_FILE_NAME),
+            "[[paths]]\nglob = \"**/tests/**\"\nthresholds = { func-warn = 80 }\n\n\
+             [[paths]]\nglob = \"src/core/*.py\"\nthresholds = { func-warn = 20 }\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("pkg").join(CONFIG_FILE_NAME),
+            "[[paths]]\nglob = \"tests/**\"\nthresholds = { func-error = 90 }\n",
+        )
+        .unwrap();
+        let file = |rel: &str| SourceFile {
+            path: dir.join(rel),
+            rel_path: PathBuf::from(rel),
+            language: LanguageType::Python,
+            content: None,
+        };
+        let files = vec![file("pkg/tests/test_a.py")];
+
+        let config = ProjectConfig::load(&dir, None, &files).unwrap();
+        let cli = Overrides::default();
+        let func = |rel: &str| {
+            let t = config.thresholds(LanguageType::Python, Path::new(rel), &cli);
+            (t.func_warn, t.func_error)
+        };
+
+        assert_eq!(func("tests/test_app.py"), (80, 50));
+        assert_eq!(func("src/core/engine.py"), (20, 50));
+        // `*` stops at directory separators
+        assert_eq!(func("src/core/sub/engine.py"), (30, 50));
+        // Nested globs are relative to their own config file
+        assert_eq!(func("pkg/tests/test_a.py"), (80, 90));
+        assert_eq!(func("app.py"), (30, 50));
+    }
+
+    #[test]
+    fn test_scan_rules() {
+        let config: ConfigFile =
+            toml::from_str("default-skips = false\n[skip-dirs]\njavascript = [\"coverage/\", \"!/dist\"]\n").unwrap();
+        let rules = config.scan_rules().unwrap();
+        assert!(rules.no_default_skips);
+        assert_eq!(rules.skip_dirs[&LanguageType::JavaScript], ["coverage", "!dist"]);
+        assert!(!ConfigFile::default().scan_rules().unwrap().no_default_skips);
+
+        let config: ConfigFile = toml::from_str("[skip-dirs]\nelixr = [\"priv\"]\n").unwrap();
+        assert!(config.scan_rules().unwrap_err().contains("'elixr'"));
+        let config: ConfigFile = toml::from_str("[skip-dirs]\nelixir = [\"/\"]\n").unwrap();
+        assert!(config.scan_rules().is_err());
+    }
+
+    #[test]
+    fn test_extension_map() {
+        let config: ConfigFile = toml::from_str("[extensions]\n\".pyx\" = \"python\"\nheex = \"skip\"\n").unwrap();
+        let map = config.extension_map().unwrap();
+        assert_eq!(map.get("pyx"), Some(&Some(LanguageType::Python)));
+        assert_eq!(map.get("heex"), Some(&None));
+
+        let config: ConfigFile = toml::from_str("[extensions]\nmts = \"typescipt\"\n").unwrap();
+        assert!(config.extension_map().unwrap_err().contains("'typescipt'"));
+    }
+
+    #[test]
+    fn test_python_indent_setting() {
+        let parse = |s: &str| toml::from_str::<ThresholdOverrides>(s).map(|t| t.python_indent);
+        assert_eq!(parse("python-indent = 2").unwrap(), Some(PythonIndent::Width(2)));
+        assert_eq!(parse("python-indent = \"auto\"").unwrap(), Some(PythonIndent::Auto));
+        assert!(parse("python-indent = 0").is_err());
+        assert!(parse("python-indent = \"tabs\"").is_err());
+    }
+
+    #[test]
+    fn test_validate_warn_above_error() {
+        assert!(Thresholds::default().validate().is_ok());
+        let thresholds = Thresholds {
+            nest_warn: 7,
+            ..Default::default()
+        };
+        assert_eq!(thresholds.validate(), Err("nest-warn (7) is above nest-error (6)".to_string()));
+    }
+
+    #[test]
+    fn test_validate_project_config() {
+        let dir = TempDir::new("validate");
+        fs::create_dir_all(dir.join("pkg")).unwrap();
+        fs::write(dir.join(CONFIG_FILE_NAME), "[thresholds]\nnest-warn = 5\n").unwrap();
+        fs::write(
+            dir.join("pkg").join(CONFIG_FILE_NAME),
+            "[[paths]]\nglob = \"gen/**\"\nthresholds = { nest-warn = 7 }\n",
+        )
+        .unwrap();
+        let files = vec![SourceFile {
+            path: dir.join("pkg/app.py"),
+            rel_path: PathBuf::from("pkg/app.py"),
+            language: LanguageType::Python,
+            content: None,
+        }];
+        let config = ProjectConfig::load(&dir, None, &files).unwrap();
+        let python = [LanguageType::Python];
+
+        assert_eq!(
+            config.validate(&python, &Overrides::default()),
+            Err("Impossible python thresholds for 'gen/**' in 'pkg': nest-warn (7) is above nest-error (6)".to_string())
+        );
+        let mut overrides = Overrides::default();
+        overrides.thresholds.nest_error = Some(8);
+        assert!(config.validate(&python, &overrides).is_ok());
+        overrides.thresholds.nest_warn = Some(9);
+        assert_eq!(
+            config.validate(&python, &overrides),
+            Err("Impossible python thresholds in '.': nest-warn (9) is above nest-error (8)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_severity_overrides() {
+        let dir = TempDir::new("severity");
+        fs::create_dir_all(dir.join("legacy")).unwrap();
+        fs::write(dir.join(CONFIG_FILE_NAME), "[severity]\nnesting-depth = \"error\"\n").unwrap();
+        fs::write(dir.join("legacy").join(CONFIG_FILE_NAME), "[severity]\nnesting-depth = \"warning\"\n").unwrap();
+        let files = vec![SourceFile {
+            path: dir.join("legacy/a.py"),
+            rel_path: PathBuf::from("legacy/a.py"),
+            language: LanguageType::Python,
+            content: None,
+        }];
+
+        let config = ProjectConfig::load(&dir, None, &files).unwrap();
+        assert!(config.custom_checks().is_ok());
+        assert_eq!(config.severity_for(Path::new("app.py"), "nesting-depth"), Some(Severity::Error));
+        assert_eq!(config.severity_for(Path::new("legacy/a.py"), "nesting-depth"), Some(Severity::Warning));
+        assert_eq!(config.severity_for(Path::new("app.py"), "file-length"), None);
+
+        fs::write(dir.join(CONFIG_FILE_NAME), "[severity]\nfile-length = \"info\"\n").unwrap();
+        let config = ProjectConfig::load(&dir, None, &files).unwrap();
+        assert!(config.custom_checks().is_err());
+    }
+
+    #[test]
+    fn test_is_near_miss() {
+        let mut thresholds = Thresholds::default();
+        assert!(!thresholds.is_near_miss(30, 30));
+
+        thresholds.near_miss_pct = Some(10);
+        assert!(thresholds.is_near_miss(30, 30));
+        assert!(thresholds.is_near_miss(27, 30));
+        assert!(!thresholds.is_near_miss(26, 30));
+        assert!(!thresholds.is_near_miss(31, 30));
+        assert!(!thresholds.is_near_miss(0, 0));
+
+        thresholds.near_miss_pct = Some(0);
+        assert!(thresholds.is_near_miss(30, 30));
+        assert!(!thresholds.is_near_miss(29, 30));
+    }
 }
@@ -0,0 +1,254 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A JSON report as written by `--format json`
+#[derive(Deserialize)]
+struct ReportFile {
+    project: String,
+    #[serde(default)]
+    languages: Vec<String>,
+    #[serde(default)]
+    issues: Vec<IssueRecord>,
+    summary: SummaryRecord,
+}
+
+#[derive(Deserialize)]
+struct IssueRecord {
+    severity: String,
+    file: String,
+    #[serde(default)]
+    line: Option<usize>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(rename = "type")]
+    check_type: String,
+    value: usize,
+}
+
+#[derive(Deserialize)]
+struct SummaryRecord {
+    files: usize,
+    errors: usize,
+    warnings: usize,
+}
+
+/// All reports seen for one project, in input order
+struct ProjectHistory<'a> {
+    runs: Vec<&'a ReportFile>,
+}
+
+impl ProjectHistory<'_> {
+    fn latest(&self) -> &ReportFile {
+        self.runs.last().expect("project history is never empty")
+    }
+}
+
+/// Render a static HTML dashboard from one or more JSON reports.
+///
+/// Reports for the same project are treated as its history, oldest first.
+pub fn generate(inputs: &[PathBuf], output_dir: &Path) -> Result<PathBuf, String> {
+    let mut reports = Vec::new();
+    for input in inputs {
+        let content = fs::read_to_string(input)
+            .map_err(|e| format!("Cannot read '{}': {}", input.display(), e))?;
+        let report: ReportFile = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid report '{}': {}", input.display(), e))?;
+        reports.push(report);
+    }
+
+    if reports.is_empty() {
+        return Err("No input reports given".to_string());
+    }
+
+    let mut projects: BTreeMap<&str, ProjectHistory> = BTreeMap::new();
+    for report in &reports {
+        projects
+            .entry(report.project.as_str())
+            .or_insert_with(|| ProjectHistory { runs: Vec::new() })
+            .runs
+            .push(report);
+    }
+
+    fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Cannot create '{}': {}", output_dir.display(), e))?;
+    let index = output_dir.join("index.html");
+    fs::write(&index, render(&projects))
+        .map_err(|e| format!("Cannot write '{}': {}", index.display(), e))?;
+
+    Ok(index)
+}
+
+/// Letter grade from weighted issues per file
+fn grade(summary: &SummaryRecord) -> char {
+    let weighted = (summary.errors * 3 + summary.warnings) as f64;
+    let density = weighted / summary.files.max(1) as f64;
+    match density {
+        d if d < 0.1 => 'A',
+        d if d < 0.25 => 'B',
+        d if d < 0.5 => 'C',
+        d if d < 1.0 => 'D',
+        _ => 'F',
+    }
+}
+
+fn trend(history: &ProjectHistory) -> String {
+    if history.runs.len() < 2 {
+        return "&ndash;".to_string();
+    }
+    let counts: Vec<String> = history
+        .runs
+        .iter()
+        .map(|r| (r.summary.errors + r.summary.warnings).to_string())
+        .collect();
+    let previous = &history.runs[history.runs.len() - 2].summary;
+    let latest = &history.latest().summary;
+    let arrow = match (latest.errors + latest.warnings).cmp(&(previous.errors + previous.warnings)) {
+        std::cmp::Ordering::Less => "&darr;",
+        std::cmp::Ordering::Greater => "&uarr;",
+        std::cmp::Ordering::Equal => "&rarr;",
+    };
+    format!("{} {}", counts.join(" &rarr; "), arrow)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render(projects: &BTreeMap<&str, ProjectHistory>) -> String {
+    let mut html = String::new();
+    html.push_str(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Code Smells Dashboard</title>\n<style>\n\
+         body { font-family: -apple-system, BlinkMacSystemFont, sans-serif; margin: 2em; color: #222; }\n\
+         table { border-collapse: collapse; margin-bottom: 2em; }\n\
+         th, td { border: 1px solid #ddd; padding: 0.4em 0.8em; text-align: left; }\n\
+         th { background: #f4f4f4; }\n\
+         .grade { font-weight: bold; text-align: center; }\n\
+         .grade-A { color: #1a7f37; } .grade-B { color: #4c9a2a; } .grade-C { color: #9a6700; }\n\
+         .grade-D { color: #bc4c00; } .grade-F { color: #cf222e; }\n\
+         </style>\n</head>\n<body>\n<h1>Code Smells Dashboard</h1>\n",
+    );
+
+    // Per-project summary
+    html.push_str(
+        "<h2>Projects</h2>\n<table>\n<tr><th>Project</th><th>Languages</th><th>Grade</th>\
+         <th>Files</th><th>Errors</th><th>Warnings</th><th>Trend (issues)</th></tr>\n",
+    );
+    for (project, history) in projects {
+        let latest = history.latest();
+        let g = grade(&latest.summary);
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td class=\"grade grade-{}\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(project),
+            escape(&latest.languages.join(", ")),
+            g,
+            g,
+            latest.summary.files,
+            latest.summary.errors,
+            latest.summary.warnings,
+            trend(history)
+        );
+    }
+    html.push_str("</table>\n");
+
+    // Files with the most findings across all projects
+    let mut file_counts: BTreeMap<(&str, &str), (usize, usize)> = BTreeMap::new();
+    for (project, history) in projects {
+        for issue in &history.latest().issues {
+            let entry = file_counts.entry((project, &issue.file)).or_default();
+            match issue.severity.as_str() {
+                "error" => entry.0 += 1,
+                "warning" => entry.1 += 1,
+                _ => {}
+            }
+        }
+    }
+    let mut top_files: Vec<_> = file_counts
+        .into_iter()
+        .filter(|(_, (e, w))| e + w > 0)
+        .collect();
+    top_files.sort_by_key(|(_, (e, w))| std::cmp::Reverse((*e, *w)));
+
+    html.push_str(
+        "<h2>Top offending files</h2>\n<table>\n<tr><th>Project</th><th>File</th><th>Errors</th><th>Warnings</th></tr>\n",
+    );
+    for ((project, file), (errors, warnings)) in top_files.iter().take(10) {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(project),
+            escape(file),
+            errors,
+            warnings
+        );
+    }
+    html.push_str("</table>\n");
+
+    // Longest functions across all projects
+    let mut functions: Vec<(&str, &IssueRecord)> = projects
+        .iter()
+        .flat_map(|(project, history)| {
+            history
+                .latest()
+                .issues
+                .iter()
+                .filter(|i| i.check_type == "function-length" && i.severity != "info")
+                .map(move |i| (*project, i))
+        })
+        .collect();
+    functions.sort_by_key(|(_, i)| std::cmp::Reverse(i.value));
+
+    html.push_str(
+        "<h2>Longest functions</h2>\n<table>\n<tr><th>Project</th><th>Location</th><th>Function</th><th>Lines</th></tr>\n",
+    );
+    for (project, issue) in functions.iter().take(10) {
+        let location = match issue.line {
+            Some(line) => format!("{}:{}", issue.file, line),
+            None => issue.file.clone(),
+        };
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(project),
+            escape(&location),
+            escape(issue.name.as_deref().unwrap_or("")),
+            issue.value
+        );
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(files: usize, errors: usize, warnings: usize) -> SummaryRecord {
+        SummaryRecord {
+            files,
+            errors,
+            warnings,
+        }
+    }
+
+    #[test]
+    fn test_grade() {
+        assert_eq!(grade(&summary(100, 0, 5)), 'A');
+        assert_eq!(grade(&summary(100, 5, 5)), 'B');
+        assert_eq!(grade(&summary(10, 4, 0)), 'F');
+        assert_eq!(grade(&summary(0, 0, 0)), 'A');
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("<a href=\"x\">&</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+    }
+}
@@ -1,5 +1,20 @@
 use crate::languages::LanguageType;
+use crate::options::SourceDirOverride;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+#[cfg(feature = "fs")]
+use ignore::gitignore::Gitignore;
+use serde::Deserialize;
 use std::path::Path;
+#[cfg(feature = "fs")]
+use crate::scan::walker;
+
+/// Directories never searched for nested projects: dependencies and build output
+#[cfg(feature = "fs")]
+const NON_PROJECT_DIRS: &[&str] = &["node_modules", "target", "vendor", "build", "dist", "_build", "deps"];
+
+/// How deep beneath a Rust workspace root to look for its member crates
+#[cfg(feature = "fs")]
+const MAX_MEMBER_DEPTH: usize = 4;
 
 /// Detected language with its source directory
 #[derive(Debug)]
@@ -8,8 +23,66 @@ pub struct DetectedLanguage {
     pub source_dir: String,
 }
 
+/// A project found with `--recursive-projects`, with source directories relative to the root
+#[derive(Debug)]
+pub struct DetectedProject {
+    /// Project directory relative to the root, `.` for the root itself
+    pub dir: String,
+    pub languages: Vec<DetectedLanguage>,
+}
+
+/// Detect the languages of every project in the tree, by their marker files.
+///
+/// Hidden, gitignored, excluded, dependency, and build directories are not searched.
+#[cfg(feature = "fs")]
+pub fn detect_projects(root: &Path, excludes: &Gitignore, workspace_excluded: bool) -> Vec<DetectedProject> {
+    let excludes = excludes.clone();
+    walker(root)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |e| {
+            let name = e.file_name().to_string_lossy();
+            e.depth() == 0
+                || (e.file_type().is_some_and(|t| t.is_dir())
+                    && !NON_PROJECT_DIRS.contains(&name.as_ref())
+                    && !excludes.matched(e.path(), true).is_ignore())
+        })
+        .build()
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let mut languages = detect_languages_with(entry.path(), workspace_excluded);
+            if languages.is_empty() {
+                return None;
+            }
+            let rel = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            if rel.as_os_str().is_empty() {
+                return Some(DetectedProject {
+                    dir: ".".to_string(),
+                    languages,
+                });
+            }
+            for det in &mut languages {
+                det.source_dir = rel.join(&det.source_dir).display().to_string();
+                // `sub/.` would name the same directory less readably
+                if let Some(dir) = det.source_dir.strip_suffix("/.") {
+                    det.source_dir = dir.to_string();
+                }
+            }
+            Some(DetectedProject {
+                dir: rel.display().to_string(),
+                languages,
+            })
+        })
+        .collect()
+}
+
 /// Detect languages in a project directory by looking for marker files
 pub fn detect_languages(project_dir: &Path) -> Vec<DetectedLanguage> {
+    detect_languages_with(project_dir, false)
+}
+
+/// [`detect_languages`], also scanning the crates a Rust workspace lists under
+/// `exclude` when `workspace_excluded` is set
+pub fn detect_languages_with(project_dir: &Path, workspace_excluded: bool) -> Vec<DetectedLanguage> {
     let mut detected = Vec::new();
 
     // Elixir - look for mix.exs
@@ -41,6 +114,19 @@ pub fn detect_languages(project_dir: &Path) -> Vec<DetectedLanguage> {
         });
     }
 
+    // JavaScript - package.json without tsconfig.json
+    if project_dir.join("package.json").exists() && !project_dir.join("tsconfig.json").exists() {
+        let source_dir = if project_dir.join("src").is_dir() {
+            "src"
+        } else {
+            "."
+        };
+        detected.push(DetectedLanguage {
+            language: LanguageType::JavaScript,
+            source_dir: source_dir.to_string(),
+        });
+    }
+
     // Python - look for setup.py, pyproject.toml, or requirements.txt
     if project_dir.join("setup.py").exists()
         || project_dir.join("pyproject.toml").exists()
@@ -57,17 +143,178 @@ pub fn detect_languages(project_dir: &Path) -> Vec<DetectedLanguage> {
         });
     }
 
-    // Rust - look for Cargo.toml
+    // Rust - look for Cargo.toml; a workspace adds its member crates
     if project_dir.join("Cargo.toml").exists() {
+        for source_dir in rust_source_dirs(project_dir, workspace_excluded) {
+            detected.push(DetectedLanguage {
+                language: LanguageType::Rust,
+                source_dir,
+            });
+        }
+    }
+
+    // Go - look for go.mod
+    if project_dir.join("go.mod").exists() {
+        detected.push(DetectedLanguage {
+            language: LanguageType::Go,
+            source_dir: ".".to_string(),
+        });
+    }
+
+    // Java - look for pom.xml or a Gradle build
+    if project_dir.join("pom.xml").exists()
+        || project_dir.join("build.gradle").exists()
+        || project_dir.join("build.gradle.kts").exists()
+    {
+        let source_dir = if project_dir.join("src/main/java").is_dir() {
+            "src/main/java"
+        } else if project_dir.join("src").is_dir() {
+            "src"
+        } else {
+            "."
+        };
         detected.push(DetectedLanguage {
-            language: LanguageType::Rust,
-            source_dir: "src".to_string(),
+            language: LanguageType::Java,
+            source_dir: source_dir.to_string(),
+        });
+    }
+
+    // Swift - look for Package.swift or an Xcode project
+    if project_dir.join("Package.swift").exists() || has_root_entry_with_extension(project_dir, "xcodeproj") {
+        let source_dir = if project_dir.join("Sources").is_dir() {
+            "Sources"
+        } else {
+            "."
+        };
+        detected.push(DetectedLanguage {
+            language: LanguageType::Swift,
+            source_dir: source_dir.to_string(),
+        });
+    }
+
+    // Julia - look for Project.toml
+    if project_dir.join("Project.toml").exists() {
+        let source_dir = if project_dir.join("src").is_dir() {
+            "src"
+        } else {
+            "."
+        };
+        detected.push(DetectedLanguage {
+            language: LanguageType::Julia,
+            source_dir: source_dir.to_string(),
+        });
+    }
+
+    // Terraform - any .tf file in the root; modules live in subdirectories
+    if has_root_entry_with_extension(project_dir, "tf") {
+        detected.push(DetectedLanguage {
+            language: LanguageType::Terraform,
+            source_dir: ".".to_string(),
         });
     }
 
     detected
 }
 
+/// The `[workspace]` table of a Cargo.toml
+#[derive(Default, Deserialize)]
+struct CargoWorkspace {
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CargoManifest {
+    package: Option<toml::Value>,
+    workspace: Option<CargoWorkspace>,
+}
+
+/// Source directories of a Rust project: `src`, or for a workspace, the
+/// `src` of each member crate (and of the root package, if there is one).
+///
+/// Members may be globs such as `crates/*`; excluded members are left out,
+/// as cargo does, unless `include_excluded` is set.
+fn rust_source_dirs(project_dir: &Path, include_excluded: bool) -> Vec<String> {
+    let manifest: Option<CargoManifest> = std::fs::read_to_string(project_dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| toml::from_str(&content).ok());
+    let (has_package, workspace) = match manifest {
+        Some(CargoManifest {
+            package,
+            workspace: Some(workspace),
+        }) if !workspace.members.is_empty() => (package.is_some(), workspace),
+        // A plain crate, or a manifest we can't read
+        _ => return vec!["src".to_string()],
+    };
+
+    let matcher = |patterns: &[String]| {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            if let Ok(glob) = GlobBuilder::new(pattern.trim_end_matches('/')).literal_separator(true).build() {
+                builder.add(glob);
+            }
+        }
+        builder.build().unwrap_or_else(|_| GlobSet::empty())
+    };
+    let members = matcher(&workspace.members);
+    let excluded = matcher(&workspace.exclude);
+
+    let mut dirs = Vec::new();
+    if has_package {
+        dirs.push("src".to_string());
+    }
+    dirs.extend(member_dirs(project_dir, &members, &excluded, include_excluded));
+    dirs
+}
+
+/// Source directories of the workspace members beneath `project_dir`
+#[cfg(feature = "fs")]
+fn member_dirs(project_dir: &Path, members: &GlobSet, excluded: &GlobSet, include_excluded: bool) -> Vec<String> {
+    let mut dirs = Vec::new();
+    for entry in walker(project_dir)
+        .max_depth(Some(MAX_MEMBER_DEPTH))
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|e| e.file_type().is_some_and(|t| t.is_dir()) && e.file_name() != "target")
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.depth() > 0)
+    {
+        let rel = entry.path().strip_prefix(project_dir).unwrap_or(entry.path());
+        let wanted = if include_excluded {
+            members.is_match(rel) || excluded.is_match(rel)
+        } else {
+            members.is_match(rel) && !excluded.is_match(rel)
+        };
+        if !wanted || !entry.path().join("Cargo.toml").is_file() {
+            continue;
+        }
+        let src = rel.join("src");
+        let dir = if project_dir.join(&src).is_dir() { src } else { rel.to_path_buf() };
+        dirs.push(dir.display().to_string());
+    }
+    dirs
+}
+
+/// Without directory walking, only the root package is found
+#[cfg(not(feature = "fs"))]
+fn member_dirs(_project_dir: &Path, _members: &GlobSet, _excluded: &GlobSet, _include_excluded: bool) -> Vec<String> {
+    Vec::new()
+}
+
+/// Check if the project root contains a file or directory with the given extension,
+/// e.g. an `.xcodeproj` bundle
+fn has_root_entry_with_extension(project_dir: &Path, extension: &str) -> bool {
+    std::fs::read_dir(project_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .any(|e| e.path().extension().is_some_and(|ext| ext == extension))
+        })
+        .unwrap_or(false)
+}
+
 /// Check if a project has TypeScript files (when package.json exists but no tsconfig.json)
 fn has_typescript_files(project_dir: &Path) -> bool {
     if !project_dir.join("package.json").exists() {
@@ -94,24 +341,85 @@ fn has_typescript_files(project_dir: &Path) -> bool {
     false
 }
 
+impl DetectedLanguage {
+    /// A language in its conventional source directory, as when chosen with `--lang`
+    pub fn with_default_dir(language: LanguageType) -> Self {
+        let source_dir = match language {
+            LanguageType::Elixir | LanguageType::Dart => "lib",
+            LanguageType::TypeScript | LanguageType::JavaScript | LanguageType::Rust => "src",
+            LanguageType::Java | LanguageType::Julia => "src",
+            LanguageType::Python | LanguageType::Go | LanguageType::Swift => ".",
+            LanguageType::Terraform | LanguageType::Generic => ".",
+        };
+        DetectedLanguage {
+            language,
+            source_dir: source_dir.to_string(),
+        }
+    }
+}
+
 /// Parse a comma-separated language list from CLI
 pub fn parse_language_list(input: &str) -> Vec<DetectedLanguage> {
     input
         .split(',')
         .filter_map(|s| {
             let name = s.trim().to_lowercase();
-            let (lang, source_dir) = match name.as_str() {
-                "elixir" => (LanguageType::Elixir, "lib"),
-                "dart" => (LanguageType::Dart, "lib"),
-                "typescript" => (LanguageType::TypeScript, "src"),
-                "python" => (LanguageType::Python, "."),
-                "rust" => (LanguageType::Rust, "src"),
-                _ => return None,
-            };
-            Some(DetectedLanguage {
-                language: lang,
-                source_dir: source_dir.to_string(),
-            })
+            let language = LanguageType::ALL.into_iter().find(|l| l.name() == name)?;
+            Some(DetectedLanguage::with_default_dir(language))
         })
         .collect()
 }
+
+/// Replace source directories with the ones given by `--source-dir`.
+///
+/// Language-specific overrides win over ones given for every language.
+pub fn apply_source_dirs(detected: &mut [DetectedLanguage], overrides: &[SourceDirOverride]) {
+    for det in detected.iter_mut() {
+        let chosen = overrides
+            .iter()
+            .rev()
+            .find(|o| o.language == Some(det.language))
+            .or_else(|| overrides.iter().rev().find(|o| o.language.is_none()));
+        if let Some(o) = chosen {
+            det.source_dir = o.dir.clone();
+        }
+    }
+}
+
+/// Languages whose source directory does not exist and will therefore be skipped
+pub fn missing_source_dirs<'a>(project_dir: &Path, detected: &'a [DetectedLanguage]) -> Vec<&'a DetectedLanguage> {
+    detected
+        .iter()
+        .filter(|d| !project_dir.join(&d.source_dir).is_dir())
+        .collect()
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::fs;
+
+    #[test]
+    fn test_rust_workspace_members() {
+        let dir = TempDir::new("workspace");
+        for member in ["crates/core", "crates/legacy", "xtask"] {
+            fs::create_dir_all(dir.join(member).join("src")).unwrap();
+            fs::write(dir.join(member).join("Cargo.toml"), "[package]\nname = \"m\"\n").unwrap();
+        }
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\", \"xtask\"]\nexclude = [\"crates/legacy\"]\n",
+        )
+        .unwrap();
+        let virtual_workspace = rust_source_dirs(&dir, false);
+        let with_excluded = rust_source_dirs(&dir, true);
+
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"root\"\n").unwrap();
+        let plain_crate = rust_source_dirs(&dir, true);
+
+        assert_eq!(virtual_workspace, ["crates/core/src", "xtask/src"]);
+        assert_eq!(with_excluded, ["crates/core/src", "crates/legacy/src", "xtask/src"]);
+        assert_eq!(plain_crate, ["src"]);
+    }
+}
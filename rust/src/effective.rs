@@ -0,0 +1,134 @@
+use crate::checks::CustomCheck;
+use crate::config::{dir_label, Overrides, ProjectConfig, ThresholdOverrides, Thresholds};
+use crate::detect::DetectedLanguage;
+use crate::languages::{get_parser, LanguageType};
+use crate::options::OutputFormat;
+use crate::output::Severity;
+use crate::scan::ScanRules;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Configuration in effect after defaults, config files, and the CLI are combined
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct EffectiveConfig<'a> {
+    custom_checks: Vec<&'a str>,
+    generated_markers: Vec<String>,
+    /// Source directories per language
+    languages: BTreeMap<&'static str, Vec<&'a str>>,
+    /// The language of each extension the root config maps, or `skip`
+    extensions: BTreeMap<&'a str, &'static str>,
+    /// The directories skipped per language, the defaults included
+    skip_dirs: BTreeMap<&'static str, Vec<&'a str>>,
+    /// The project root (`.`) and each directory with its own config file
+    directories: BTreeMap<String, DirectoryConfig<'a>>,
+}
+
+#[derive(Serialize)]
+struct DirectoryConfig<'a> {
+    disable: BTreeSet<String>,
+    severity: BTreeMap<String, Severity>,
+    thresholds: BTreeMap<&'static str, Settings>,
+    /// The directory's `[[paths]]` rules, with the thresholds of the files each matches
+    paths: Vec<PathConfig<'a>>,
+}
+
+#[derive(Serialize)]
+struct PathConfig<'a> {
+    glob: &'a str,
+    thresholds: BTreeMap<&'static str, Settings>,
+}
+
+/// One language's thresholds and the settings that only the CLI sets
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Settings {
+    #[serde(flatten)]
+    thresholds: ThresholdOverrides,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    near_miss_pct: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    todo_max_age_days: Option<u64>,
+}
+
+impl From<Thresholds> for Settings {
+    fn from(thresholds: Thresholds) -> Self {
+        Settings {
+            thresholds: thresholds.to_overrides(),
+            near_miss_pct: thresholds.near_miss_pct,
+            todo_max_age_days: thresholds.todo_max_age_days,
+        }
+    }
+}
+
+/// Resolve and validate the configuration, rendered as TOML (text format) or JSON
+pub fn render(
+    detected: &[DetectedLanguage],
+    config: &ProjectConfig,
+    rules: &ScanRules,
+    custom: &[CustomCheck],
+    overrides: &Overrides,
+    format: OutputFormat,
+) -> Result<String, String> {
+    let mut langs: Vec<LanguageType> = Vec::new();
+    for det in detected {
+        if !langs.contains(&det.language) {
+            langs.push(det.language);
+        }
+    }
+    config.validate(&langs, overrides)?;
+
+    let mut directories = BTreeMap::new();
+    for dir in config.dirs() {
+        let mut thresholds = BTreeMap::new();
+        for &lang in &langs {
+            thresholds.insert(lang.name(), config.thresholds(lang, dir, overrides).into());
+        }
+        let paths = config
+            .path_rules(dir)
+            .iter()
+            .map(|rule| PathConfig {
+                glob: rule.glob.glob().glob(),
+                thresholds: langs
+                    .iter()
+                    .map(|&lang| (lang.name(), config.rule_thresholds(lang, dir, rule, overrides).into()))
+                    .collect(),
+            })
+            .collect();
+        directories.insert(
+            dir_label(dir),
+            DirectoryConfig {
+                disable: config.disabled(dir),
+                severity: config.severities(dir),
+                thresholds,
+                paths,
+            },
+        );
+    }
+
+    let mut languages: BTreeMap<&'static str, Vec<&str>> = BTreeMap::new();
+    for det in detected {
+        languages.entry(det.language.name()).or_default().push(&det.source_dir);
+    }
+    let effective = EffectiveConfig {
+        custom_checks: custom.iter().map(|c| c.id).collect(),
+        generated_markers: config.generated_markers(),
+        languages,
+        extensions: rules
+            .extensions
+            .iter()
+            .map(|(ext, lang)| (ext.as_str(), lang.map_or("skip", |l| l.name())))
+            .collect(),
+        skip_dirs: langs
+            .iter()
+            .map(|&lang| (lang.name(), rules.skipped_dirs(lang, &*get_parser(lang))))
+            .collect(),
+        directories,
+    };
+
+    match format {
+        OutputFormat::Text => toml::to_string(&effective).map_err(|e| e.to_string()),
+        OutputFormat::Json => serde_json::to_string_pretty(&effective).map_err(|e| e.to_string()),
+        _ => Err("--print-config supports text (TOML) and json output".to_string()),
+    }
+}
@@ -0,0 +1,181 @@
+use crate::checks::rules::{rules_for, Rule, RULES, SECURITY_PATTERNS};
+use crate::config::Thresholds;
+use crate::languages::LanguageType;
+use crate::options::{CheckType, OutputFormat};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Reference for every check, shown by `explain`
+const CHECKS_DOC: &str = include_str!("../../docs/checks.md");
+
+type Limits = fn(&Thresholds) -> (usize, usize);
+
+/// Checks with warn/error thresholds: check id, flag prefix, and the default limits
+const CHECK_THRESHOLDS: &[(&str, &str, Limits)] = &[
+    ("file-length", "file", |t| (t.file_warn, t.file_error)),
+    ("function-length", "func", |t| (t.func_warn, t.func_error)),
+    ("nesting-depth", "nest", |t| (t.nest_warn, t.nest_error)),
+    ("nested-loops", "loop", |t| (t.loop_warn, t.loop_error)),
+    ("enum-variants", "variants", |t| (t.variant_warn, t.variant_error)),
+    ("shotgun-surgery", "spread", |t| (t.spread_warn, t.spread_error)),
+    ("exit-calls", "exit", |t| (t.exit_warn, t.exit_error)),
+    ("module-nesting", "module-nesting", |t| (t.module_nesting_warn, t.module_nesting_error)),
+    ("jsx-nesting", "jsx-nesting", |t| (t.jsx_nesting_warn, t.jsx_nesting_error)),
+];
+
+#[derive(Serialize)]
+struct Limit {
+    warn: usize,
+    error: usize,
+}
+
+#[derive(Serialize)]
+struct CheckInfo {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    category: &'static str,
+    /// `--check` value that selects it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    select: Option<String>,
+    /// Default thresholds per language
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    thresholds: BTreeMap<&'static str, Limit>,
+}
+
+/// The `--check` value that runs only this rule
+fn selector(id: &str) -> Option<String> {
+    let check = CheckType::ALL.into_iter().find(|c| rules_for(*c) == [id])?;
+    Some(check.name().to_string())
+}
+
+fn check_info(rule: &Rule) -> CheckInfo {
+    let mut thresholds = BTreeMap::new();
+    if let Some((_, _, limits)) = CHECK_THRESHOLDS.iter().find(|(id, _, _)| *id == rule.id) {
+        for lang in LanguageType::ALL {
+            let (warn, error) = limits(&Thresholds::for_language(lang));
+            thresholds.insert(lang.name(), Limit { warn, error });
+        }
+    }
+    CheckInfo {
+        id: rule.id,
+        name: rule.name,
+        description: rule.description,
+        category: rule.category,
+        select: selector(rule.id),
+        thresholds,
+    }
+}
+
+/// Languages grouped by their shared `warn/error` defaults, e.g. `30/50: elixir, rust`
+fn grouped_limits(thresholds: &BTreeMap<&'static str, Limit>) -> Vec<String> {
+    let mut groups: BTreeMap<(usize, usize), Vec<&str>> = BTreeMap::new();
+    for (lang, limit) in thresholds {
+        groups.entry((limit.warn, limit.error)).or_default().push(lang);
+    }
+    groups
+        .into_iter()
+        .map(|((warn, error), langs)| format!("{}/{}: {}", warn, error, langs.join(", ")))
+        .collect()
+}
+
+/// The section of docs/checks.md about a check, without its heading
+fn doc_section(id: &str) -> Option<&'static str> {
+    let heading = format!("## {}\n", id);
+    let start = CHECKS_DOC.find(&heading)? + heading.len();
+    let rest = &CHECKS_DOC[start..];
+    let end = rest.find("\n## ").unwrap_or(rest.len());
+    Some(rest[..end].trim())
+}
+
+/// Print every available check with its default thresholds
+pub fn print_checks(format: OutputFormat) {
+    let checks: Vec<CheckInfo> = RULES.iter().map(check_info).collect();
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&checks).unwrap());
+        return;
+    }
+
+    println!("=== Available Checks ===");
+    for check in &checks {
+        println!();
+        println!("{}  [{}]", check.id, check.category);
+        println!("    {}", check.description);
+        if let Some(select) = &check.select {
+            println!("    Run alone: --check {}", select);
+        }
+        for group in grouped_limits(&check.thresholds) {
+            println!("    Defaults (warn/error) {}", group);
+        }
+    }
+    println!();
+    println!("Run `code-smells explain <check>` for details on a check.");
+}
+
+/// Print what a check measures, why it matters, and how to configure it
+pub fn print_explanation(name: &str) -> Result<(), String> {
+    // Accept the `--check` value as well as the issue type
+    let rule = RULES
+        .iter()
+        .find(|r| r.id == name)
+        .or_else(|| {
+            let check = CheckType::from_name(name)?;
+            RULES.iter().find(|r| rules_for(check) == [r.id])
+        })
+        .ok_or_else(|| format!("Unknown check '{}'; run `code-smells checks` to list them", name))?;
+    let info = check_info(rule);
+
+    println!("{}: {}  [{}]", info.id, info.name, info.category);
+    println!();
+    println!("{}", doc_section(rule.id).unwrap_or(rule.description));
+
+    if rule.id == "security-pattern" {
+        println!();
+        println!("Patterns:");
+        for pattern in SECURITY_PATTERNS {
+            let langs: Vec<&str> = pattern.languages.iter().map(|l| l.name()).collect();
+            println!("    {} ({}): {}", pattern.id, langs.join(", "), pattern.description);
+        }
+    }
+
+    if let Some((_, flag, _)) = CHECK_THRESHOLDS.iter().find(|(id, _, _)| *id == rule.id) {
+        println!();
+        println!("Default thresholds (warn/error):");
+        for group in grouped_limits(&info.thresholds) {
+            println!("    {}", group);
+        }
+        println!();
+        println!("Configure with --{0}-warn N and --{0}-error N, or in .code-smells.toml:", flag);
+        println!();
+        println!("    [thresholds]");
+        println!("    {}-warn = N", flag);
+        println!("    {}-error = N", flag);
+    }
+
+    println!();
+    if let Some(select) = &info.select {
+        println!("Run it alone with --check {}.", select);
+    }
+    println!("Turn it off with disable = [\"{}\"] in .code-smells.toml.", rule.id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selector() {
+        assert_eq!(selector("function-length").as_deref(), Some("functions"));
+        assert_eq!(selector("todo-marker").as_deref(), Some("markers"));
+        assert_eq!(selector("security-pattern").as_deref(), Some("security"));
+    }
+
+    #[test]
+    fn test_every_rule_is_documented() {
+        for rule in RULES {
+            assert!(doc_section(rule.id).is_some(), "{} missing from docs/checks.md", rule.id);
+        }
+        assert!(doc_section("file-length").unwrap().starts_with("Files with too many lines"));
+    }
+}
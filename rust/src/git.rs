@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Run git in the project directory, returning its output or its error message
+fn git(project_dir: &Path, args: &[&str]) -> Result<Vec<u8>, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(project_dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Cannot run git: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(output.stdout)
+}
+
+/// Split NUL-separated paths, as printed by git with `-z`
+fn split_paths(output: &[u8]) -> HashSet<PathBuf> {
+    output
+        .split(|&b| b == 0)
+        .filter(|p| !p.is_empty())
+        .map(|p| PathBuf::from(String::from_utf8_lossy(p).into_owned()))
+        .collect()
+}
+
+/// Files added or modified since the point where the current branch left `base`.
+///
+/// Uncommitted changes count; deleted files don't. Paths are relative to the
+/// project directory, and files outside it are left out.
+pub fn changed_files(project_dir: &Path, base: &str) -> Result<HashSet<PathBuf>, String> {
+    let fail = |e: String| format!("Cannot diff against '{}': {}", base, e);
+    let merge_base = git(project_dir, &["merge-base", base, "HEAD"]).map_err(fail)?;
+    let merge_base = String::from_utf8_lossy(&merge_base).trim().to_string();
+    let changed = git(
+        project_dir,
+        &["diff", "--name-only", "-z", "--relative", "--diff-filter=d", &merge_base, "--"],
+    )
+    .map_err(fail)?;
+    Ok(split_paths(&changed))
+}
+
+/// Files added or modified in the index, relative to the project directory
+pub fn staged_files(project_dir: &Path) -> Result<HashSet<PathBuf>, String> {
+    let staged = git(
+        project_dir,
+        &["diff", "--cached", "--name-only", "-z", "--relative", "--diff-filter=d"],
+    )
+    .map_err(|e| format!("Cannot list staged files: {}", e))?;
+    Ok(split_paths(&staged))
+}
+
+/// Abbreviated hash of a commit, e.g. `HEAD`, if the project is in a git repository
+pub fn short_commit(project_dir: &Path, rev: &str) -> Option<String> {
+    let head = git(project_dir, &["rev-parse", "--short", rev]).ok()?;
+    Some(String::from_utf8_lossy(&head).trim().to_string())
+}
+
+/// The URL when a target is a remote repository rather than a local path
+pub fn remote_url(target: &Path) -> Option<&str> {
+    let target = target.to_str()?;
+    let is_url = ["https://", "http://", "ssh://", "git://", "file://"]
+        .iter()
+        .any(|scheme| target.starts_with(scheme));
+    // scp-like syntax, e.g. git@github.com:org/repo.git
+    let is_scp = target
+        .split_once(':')
+        .is_some_and(|(host, _)| host.contains('@') && !host.contains('/'));
+    (is_url || is_scp).then_some(target)
+}
+
+/// Clone `url` into `dest`; a shallow clone only fetches the latest commit
+pub fn clone(url: &str, dest: &Path, shallow: bool) -> Result<(), String> {
+    let output = Command::new("git")
+        .args(["clone", "--quiet"])
+        .args(if shallow { ["--depth", "1"].as_slice() } else { ["--no-checkout"].as_slice() })
+        .arg("--")
+        .arg(url)
+        .arg(dest)
+        // Fail rather than wait for credentials nobody will type
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Cannot run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Cannot clone '{}': {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Full hash of the commit `rev` names, e.g. a branch, tag, or abbreviated hash
+pub fn resolve_commit(project_dir: &Path, rev: &str) -> Result<String, String> {
+    let commit = git(project_dir, &["rev-parse", "--verify", "--end-of-options", &format!("{}^{{commit}}", rev)])
+        .map_err(|e| format!("Unknown revision '{}': {}", rev, e))?;
+    Ok(String::from_utf8_lossy(&commit).trim().to_string())
+}
+
+/// Write the project directory as it was at `commit` beneath `dest`,
+/// without touching the working tree or the index
+pub fn export_commit(project_dir: &Path, commit: &str, dest: &Path) -> Result<(), String> {
+    let fail = |e: String| format!("Cannot read revision '{}': {}", commit, e);
+    // Run from the project directory, git archive only includes that subtree
+    let archive = git(project_dir, &["archive", "--format=tar", commit]).map_err(fail)?;
+    std::fs::create_dir_all(dest).map_err(|e| fail(e.to_string()))?;
+    let mut child = Command::new("tar")
+        .arg("-x")
+        .arg("-C")
+        .arg(dest)
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| fail(format!("cannot run tar: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&archive).map_err(|e| fail(e.to_string()))?;
+    }
+
+    let output = child.wait_with_output().map_err(|e| fail(e.to_string()))?;
+    if !output.status.success() {
+        return Err(fail(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    Ok(())
+}
+
+/// Write the staged content of `paths` beneath `dest`, keeping their relative paths
+pub fn checkout_staged<'a>(
+    project_dir: &Path,
+    paths: impl Iterator<Item = &'a Path>,
+    dest: &Path,
+) -> Result<(), String> {
+    let fail = |e: String| format!("Cannot read staged files: {}", e);
+    // checkout-index appends paths to the prefix as is
+    let prefix = format!("--prefix={}/", dest.display());
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(project_dir)
+        .args(["checkout-index", "--force", "-z", "--stdin"])
+        .arg(&prefix)
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| fail(e.to_string()))?;
+
+    let mut input = Vec::new();
+    for path in paths {
+        input.extend_from_slice(path.to_string_lossy().as_bytes());
+        input.push(0);
+    }
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&input).map_err(|e| fail(e.to_string()))?;
+    }
+
+    let output = child.wait_with_output().map_err(|e| fail(e.to_string()))?;
+    if !output.status.success() {
+        return Err(fail(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_paths() {
+        let paths = split_paths(b"src/a.rs\0dir with space/b.py\0");
+        assert_eq!(paths.len(), 2);
+        assert!(paths.contains(Path::new("dir with space/b.py")));
+    }
+
+    #[test]
+    fn test_remote_url() {
+        for url in ["https://github.com/org/repo", "git@github.com:org/repo.git", "file:///srv/repo"] {
+            assert_eq!(remote_url(Path::new(url)), Some(url));
+        }
+        for path in [".", "src/lib", "C:/code/repo", "./a@b:c", "user@host/dir:x"] {
+            assert_eq!(remote_url(Path::new(path)), None, "{}", path);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use crate::options::OutputFormat;
+use crate::output::Report;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// History file looked up in the project directory when `--history` is not given
+pub const HISTORY_FILE_NAME: &str = ".code-smells-history.jsonl";
+
+/// One recorded run, stored as a line of JSON
+#[derive(Debug, Deserialize, Serialize)]
+struct Run {
+    /// Seconds since the Unix epoch
+    recorded_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+    files: usize,
+    errors: usize,
+    warnings: usize,
+    #[serde(default)]
+    near_misses: usize,
+    /// Warnings and errors per check
+    #[serde(default)]
+    by_check: BTreeMap<String, usize>,
+    /// The full issues, when recorded with `--issues`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    issues: Vec<serde_json::Value>,
+}
+
+impl Run {
+    fn new(report: &Report, commit: Option<String>, with_issues: bool) -> Self {
+        let mut by_check = BTreeMap::new();
+        for issue in report.issues.iter().filter(|i| i.severity != crate::output::Severity::Info) {
+            *by_check.entry(issue.check_type.to_string()).or_default() += 1;
+        }
+        Run {
+            recorded_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            commit,
+            files: report.files_scanned,
+            errors: report.error_count(),
+            warnings: report.warning_count(),
+            near_misses: report.near_miss_count(),
+            by_check,
+            issues: if with_issues {
+                report.issues.iter().filter_map(|i| serde_json::to_value(i).ok()).collect()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+}
+
+/// Append a run's summary (and its issues, if `with_issues`) to the history file
+pub fn record(path: &Path, report: &Report, commit: Option<String>, with_issues: bool) -> Result<(), String> {
+    let line = serde_json::to_string(&Run::new(report, commit, with_issues)).map_err(|e| e.to_string())?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|e| format!("Cannot write '{}': {}", path.display(), e))
+}
+
+fn load(path: &Path) -> Result<Vec<Run>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Cannot read '{}': {}", path.display(), e))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| format!("Invalid history '{}' line {}: {}", path.display(), i + 1, e))
+        })
+        .collect()
+}
+
+/// `YYYY-MM-DD HH:MM` in UTC
+fn format_time(secs: u64) -> String {
+    // Days to civil date, after Howard Hinnant's `civil_from_days`
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    let minutes = secs % 86_400 / 60;
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, minutes / 60, minutes % 60)
+}
+
+fn signed(old: usize, new: usize) -> String {
+    if new >= old {
+        format!("+{}", new - old)
+    } else {
+        format!("-{}", old - new)
+    }
+}
+
+/// Print how the error and warning counts moved over the recorded runs
+pub fn print_trend(path: &Path, last: Option<usize>, format: OutputFormat) -> Result<(), String> {
+    let mut runs = load(path)?;
+    if let Some(last) = last {
+        runs.drain(..runs.len().saturating_sub(last));
+    }
+    // The trend is about counts; the recorded issues are for other tools
+    for run in &mut runs {
+        run.issues.clear();
+    }
+
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&runs).unwrap());
+        return Ok(());
+    }
+
+    println!("=== Trend ===");
+    println!("History: {} ({} runs)", path.display(), runs.len());
+    if runs.is_empty() {
+        return Ok(());
+    }
+    println!();
+    println!("{:<16}  {:<10} {:>6} {:>7} {:>9}", "Recorded (UTC)", "Commit", "Files", "Errors", "Warnings");
+    for run in &runs {
+        println!(
+            "{:<16}  {:<10} {:>6} {:>7} {:>9}",
+            format_time(run.recorded_at),
+            run.commit.as_deref().unwrap_or("-"),
+            run.files,
+            run.errors,
+            run.warnings
+        );
+    }
+
+    if let (Some(first), Some(latest)) = (runs.first(), runs.last()) {
+        if runs.len() > 1 {
+            println!();
+            println!(
+                "Since {}: errors {}, warnings {}",
+                format_time(first.recorded_at),
+                signed(first.errors, latest.errors),
+                signed(first.warnings, latest.warnings)
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0), "1970-01-01 00:00");
+        assert_eq!(format_time(951_827_696), "2000-02-29 12:34");
+    }
+
+    #[test]
+    fn test_record_appends() {
+        let dir = TempDir::new("history");
+        let path = dir.join("history.jsonl");
+        let report = Report {
+            files_scanned: 3,
+            ..Default::default()
+        };
+        record(&path, &report, Some("abc1234".to_string()), false).unwrap();
+        record(&path, &report, None, true).unwrap();
+        let runs = load(&path).unwrap();
+
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].commit.as_deref(), Some("abc1234"));
+        assert_eq!(runs[1].files, 3);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::config::{Thresholds, CONFIG_FILE_NAME};
+use crate::detect::DetectedLanguage;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Reads one threshold from a language's defaults
+type Getter = fn(&Thresholds) -> usize;
+
+/// Config keys with their descriptions and default values
+const THRESHOLD_KEYS: &[(&str, &str, Getter)] = &[
+    ("file-warn", "File length warning threshold", |t| t.file_warn),
+    ("file-error", "File length error threshold", |t| t.file_error),
+    ("func-warn", "Function length warning threshold", |t| t.func_warn),
+    ("func-error", "Function length error threshold", |t| t.func_error),
+    ("nest-warn", "Nesting depth warning threshold", |t| t.nest_warn),
+    ("nest-error", "Nesting depth error threshold", |t| t.nest_error),
+    ("loop-warn", "Loop nesting depth warning threshold", |t| t.loop_warn),
+    ("loop-error", "Loop nesting depth error threshold", |t| t.loop_error),
+    ("variants-warn", "Enum/union variant count warning threshold", |t| t.variant_warn),
+    ("variants-error", "Enum/union variant count error threshold", |t| t.variant_error),
+    ("spread-warn", "Warn when a function is referenced from more than N other files", |t| t.spread_warn),
+    ("spread-error", "Error when a function is referenced from more than N other files", |t| t.spread_error),
+    ("exit-warn", "Exit/panic/throw calls per function warning threshold", |t| t.exit_warn),
+    ("exit-error", "Exit/panic/throw calls per function error threshold", |t| t.exit_error),
+    ("module-nesting-warn", "Terraform module call depth warning threshold", |t| t.module_nesting_warn),
+    ("module-nesting-error", "Terraform module call depth error threshold", |t| t.module_nesting_error),
+    ("jsx-nesting-warn", "JSX element nesting depth warning threshold", |t| t.jsx_nesting_warn),
+    ("jsx-nesting-error", "JSX element nesting depth error threshold", |t| t.jsx_nesting_error),
+];
+
+/// Render a starter config holding the default thresholds of the detected languages.
+///
+/// A setting whose default differs between the detected languages is left
+/// commented out, since uncommenting it applies one value to all of them.
+pub fn starter_config(detected: &[DetectedLanguage]) -> String {
+    let names: Vec<&str> = detected.iter().map(|d| d.language.name()).collect();
+    let defaults: Vec<Thresholds> = detected.iter().map(|d| Thresholds::for_language(d.language)).collect();
+
+    let mut out = String::new();
+    out.push_str("# code-smells configuration, generated by `code-smells init`\n");
+    out.push_str(&format!("# Detected languages: {}\n", names.join(", ")));
+    out.push_str("#\n");
+    out.push_str("# The thresholds below are the built-in defaults. Settings commented out\n");
+    out.push_str("# differ between languages; uncommenting one applies it to all of them.\n");
+    out.push('\n');
+    out.push_str("# Check ids to turn off, e.g. [\"exit-calls\", \"identifiers\"]\n");
+    out.push_str("# disable = []\n");
+    out.push('\n');
+    out.push_str("[thresholds]\n");
+
+    for (key, description, value) in THRESHOLD_KEYS {
+        let values: Vec<usize> = defaults.iter().map(value).collect();
+        if values.windows(2).all(|w| w[0] == w[1]) {
+            out.push_str(&format!("# {}\n{} = {}\n", description, key, values[0]));
+        } else {
+            let per_language: Vec<String> = names
+                .iter()
+                .zip(&values)
+                .map(|(name, v)| format!("{}: {}", name, v))
+                .collect();
+            out.push_str(&format!(
+                "# {} ({})\n# {} = {}\n",
+                description,
+                per_language.join(", "),
+                key,
+                values[0]
+            ));
+        }
+    }
+
+    out.push_str("# Function checks on test code: check, or skip Rust tests\n");
+    out.push_str("# test-code = \"check\"\n");
+    out.push_str("# Columns of indentation per level of Python nesting, or \"auto\" per file\n");
+    out.push_str("# python-indent = 4\n");
+    out.push_str("# Function length of decorated functions: exclude or include the decorators\n");
+    out.push_str("# decorators = \"exclude\"\n");
+    out.push_str("# Lines counted for file and function length: physical, or logical without blank and comment lines\n");
+    out.push_str("# count-mode = \"physical\"\n");
+    out.push_str("# What nesting depth counts: blocks, or control-flow for conditionals, loops, and try blocks only\n");
+    out.push_str("# nesting-mode = \"blocks\"\n");
+    out.push_str("# Also check functions nested in other functions on their own\n");
+    out.push_str("# nested-functions = false\n");
+
+    out.push('\n');
+    out.push_str("# Thresholds for the files matching a glob, applied after [thresholds]\n");
+    out.push_str("# [[paths]]\n");
+    out.push_str("# glob = \"**/tests/**\"\n");
+    out.push_str("# thresholds = { func-warn = 80 }\n");
+    out.push('\n');
+    out.push_str("# Report a check at a fixed severity, whatever threshold it crosses\n");
+    out.push_str("# [severity]\n");
+    out.push_str("# nesting-depth = \"error\"\n");
+    out
+}
+
+/// Write a starter config into the project directory, refusing to replace one unless `force` is set
+pub fn write_starter_config(project_dir: &Path, detected: &[DetectedLanguage], force: bool) -> Result<PathBuf, String> {
+    let path = project_dir.join(CONFIG_FILE_NAME);
+    if path.exists() && !force {
+        return Err(format!("'{}' already exists (use --force to overwrite)", path.display()));
+    }
+    fs::write(&path, starter_config(detected)).map_err(|e| format!("Cannot write '{}': {}", path.display(), e))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigFile;
+    use crate::languages::LanguageType;
+
+    fn detected(languages: &[LanguageType]) -> Vec<DetectedLanguage> {
+        languages
+            .iter()
+            .map(|&language| DetectedLanguage {
+                language,
+                source_dir: ".".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_starter_config_parses_with_defaults() {
+        let content = starter_config(&detected(&[LanguageType::Rust]));
+        let config: ConfigFile = toml::from_str(&content).unwrap();
+        let defaults = Thresholds::for_language(LanguageType::Rust);
+        assert_eq!(config.thresholds.func_warn, Some(defaults.func_warn));
+        assert_eq!(config.thresholds.module_nesting_error, Some(defaults.module_nesting_error));
+    }
+
+    #[test]
+    fn test_differing_defaults_left_commented() {
+        let content = starter_config(&detected(&[LanguageType::Python, LanguageType::Terraform]));
+        let config: ConfigFile = toml::from_str(&content).unwrap();
+        // Python and Terraform disagree on function length but share the loop defaults
+        assert_eq!(config.thresholds.func_warn, None);
+        assert!(content.contains("(python: "));
+        assert_eq!(
+            config.thresholds.loop_warn,
+            Some(Thresholds::for_language(LanguageType::Python).loop_warn)
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use crate::options::OutputFormat;
+use crate::scan::SourceFile;
+use crate::top;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// A parsed function and its measurements, whether or not it breaks a threshold
+#[derive(Debug, Serialize)]
+pub struct FunctionRecord {
+    file: PathBuf,
+    language: &'static str,
+    name: String,
+    line: usize,
+    length: usize,
+    nesting: usize,
+}
+
+/// Every function the parsers find, in scan order
+pub fn collect(files: &[SourceFile]) -> Vec<FunctionRecord> {
+    let per_file: Vec<Vec<FunctionRecord>> = files
+        .par_iter()
+        .map(|file| {
+            let functions = top::parse_file(file).map(|(_, functions)| functions).unwrap_or_default();
+            functions
+                .into_iter()
+                .map(|func| FunctionRecord {
+                    file: file.rel_path.clone(),
+                    language: file.language.name(),
+                    name: func.name,
+                    line: func.start_line,
+                    length: func.line_count,
+                    nesting: func.max_nesting,
+                })
+                .collect()
+        })
+        .collect();
+    per_file.into_iter().flatten().collect()
+}
+
+#[derive(Serialize)]
+struct JsonInventory<'a> {
+    project: String,
+    functions: &'a [FunctionRecord],
+}
+
+/// Print the inventory: a table, a JSON document, or one JSON object per function
+pub fn print_inventory(project_dir: &Path, functions: &[FunctionRecord], format: OutputFormat) {
+    match format {
+        OutputFormat::Json => {
+            let inventory = JsonInventory {
+                project: project_dir.display().to_string(),
+                functions,
+            };
+            println!("{}", serde_json::to_string_pretty(&inventory).unwrap());
+        }
+        OutputFormat::Ndjson => {
+            for func in functions {
+                println!("{}", serde_json::to_string(func).unwrap());
+            }
+        }
+        _ => {
+            println!("=== Function Inventory ===");
+            println!("Project: {}", project_dir.display());
+            println!();
+            println!("{:>6} {:>7}  Function", "Length", "Nesting");
+            for func in functions {
+                println!(
+                    "{:>6} {:>7}  {}:{} {}",
+                    func.length,
+                    func.nesting,
+                    func.file.display(),
+                    func.line,
+                    func.name
+                );
+            }
+            println!();
+            println!("Total: {} functions", functions.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::languages::LanguageType;
+    use crate::test_support::TempDir;
+    use std::fs;
+
+    #[test]
+    fn test_collect_lists_every_function() {
+        let dir = TempDir::new("inventory");
+        let path = dir.join("app.py");
+        fs::write(&path, "def short():\n    return 1\n\ndef nested(x):\n    if x:\n        return 2\n").unwrap();
+        let files = vec![SourceFile {
+            path: path.clone(),
+            rel_path: PathBuf::from("app.py"),
+            language: LanguageType::Python,
+            content: None,
+        }];
+        let functions = collect(&files);
+
+        let names: Vec<(&str, usize)> = functions.iter().map(|f| (f.name.as_str(), f.line)).collect();
+        assert_eq!(names, [("short", 1), ("nested", 4)]);
+        assert!(functions[1].nesting > functions[0].nesting);
+    }
+}
@@ -1,4 +1,7 @@
-use super::{FunctionInfo, LanguageParser};
+use super::{
+    braced_block, find_brace_loops, split_top_level, ClassScopes, FunctionInfo, FunctionStack, LanguageParser, LoopSpan,
+    TypeInfo,
+};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::path::Path;
@@ -12,106 +15,117 @@ static METHOD_PATTERN: Lazy<Regex> = Lazy::new(|| {
     ).unwrap()
 });
 
+// `Point(...)`, `Point.fromJson(...)`, or a factory, when the name is the enclosing class
+static CONSTRUCTOR_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(const\s+)?(factory\s+)?([a-zA-Z_$][a-zA-Z0-9_$]*)(\.[a-zA-Z_$][a-zA-Z0-9_$]*)?\s*\(").unwrap()
+});
+
+static OPERATOR_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*([a-zA-Z_][a-zA-Z0-9_<>,?\s]*\s+)?operator\s*(\[\]=?|==|~/|<<|>>>?|[<>]=?|[-+*/%~^&|])\s*\(").unwrap()
+});
+
+// `int get sum =>`, `String get label {`, or `set label(String value) {`
+static ACCESSOR_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(static\s+)?([a-zA-Z_$][a-zA-Z0-9_$<>,?\s]*\s+)?(get|set)\s+([a-zA-Z_$][a-zA-Z0-9_$]*)\s*(\(|=>|\{|async|sync|$)")
+        .unwrap()
+});
+
+static CLASS_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*((abstract|base|final|sealed|interface)\s+)*((mixin\s+)?class|mixin|extension)\s+([a-zA-Z_][a-zA-Z0-9_]*)")
+        .unwrap()
+});
+
+static ENUM_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*enum\s+([a-zA-Z_][a-zA-Z0-9_]*)").unwrap());
+
+static LOOP_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(await\s+)?(for\s*\(|while\s*\(|do\s*(\{|$))").unwrap()
+});
+
+static CONTROL_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(await\s+)?(if|else|for|while|do|switch|try|catch|finally)\b").unwrap());
+
 impl LanguageParser for DartParser {
     fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
-        let mut functions = Vec::new();
-        let mut in_func = false;
+        let mut functions = FunctionStack::default();
         let mut brace_depth = 0i32;
-        let mut func_name = String::new();
-        let mut func_start = 0usize;
-        let mut base_depth = 0i32;
-        let mut max_nesting = 0usize;
+        let mut classes = ClassScopes::default();
+        // A header whose parameters or initializers run past its line: name and start line
+        let mut pending: Option<(String, usize)> = None;
+        // An open `=>` body: name, start line, base brace depth, and deepest nesting
+        let mut arrow: Option<(String, usize, i32, usize)> = None;
 
         for (line_num, line) in content.lines().enumerate() {
             let line_num = line_num + 1;
 
+            // `extension on Foo` is unnamed
+            if let Some(caps) = CLASS_PATTERN.captures(line).filter(|c| line.contains('{') && &c[5] != "on") {
+                classes.enter(&caps[5], brace_depth);
+            }
+
             // Check for method start
-            if let Some(name) = extract_method_name(line) {
-                // Skip arrow functions (single line)
-                if line.contains("=>") && !line.contains('{') {
-                    continue;
-                }
-                // Skip abstract methods (ending with ;)
-                if line.trim().ends_with(';') {
-                    continue;
-                }
-                // Skip getters
-                if line.contains(" get ") {
-                    continue;
-                }
+            let header = extract_header(line, &classes, brace_depth)
+                .map(|name| (name, line_num))
+                .or_else(|| pending.take());
+            let code = strip_comment(line).trim_end();
+            let (opens, closes) = count_braces(line);
 
-                // If we were in a function, finish it
-                if in_func && func_start > 0 {
-                    functions.push(FunctionInfo {
-                        name: std::mem::take(&mut func_name),
-                        start_line: func_start,
-                        line_count: line_num - func_start,
-                        max_nesting,
-                    });
+            if let Some((name, start)) = header {
+                let brace = code.find('{');
+                if code.find("=>").is_some_and(|arrow_at| brace.is_none_or(|b| arrow_at < b)) {
+                    // An arrow body on one line isn't measured
+                    if !code.ends_with(';') {
+                        arrow = Some((name, start, brace_depth, 0));
+                    }
+                } else if brace.is_some() {
+                    functions.start(name, start, brace_depth);
+                    let header_depth = brace_depth;
+                    brace_depth += opens - closes;
+                    classes.leave(brace_depth);
+                    // A body closed on the header line, e.g. `Greeter() {}`
+                    if opens > 0 && brace_depth <= header_depth {
+                        functions.line(line_num, brace_depth);
+                    }
+                    continue;
+                } else if !code.ends_with(';') {
+                    // Abstract and external methods end with `;` instead
+                    pending = Some((name, start));
                 }
-
-                func_name = name;
-                func_start = line_num;
-                in_func = true;
-                base_depth = brace_depth;
-                max_nesting = 0;
-
-                // Count braces on this line
-                let (opens, closes) = count_braces(line);
-                brace_depth += opens - closes;
-                continue;
             }
 
             // Track braces
-            let (opens, closes) = count_braces(line);
             brace_depth += opens - closes;
+            classes.leave(brace_depth);
 
-            if in_func {
-                let relative_depth = (brace_depth - base_depth).max(0) as usize;
-                if relative_depth > max_nesting {
-                    max_nesting = relative_depth;
-                }
-
-                // Function ends when brace depth returns to base
-                if brace_depth <= base_depth && line_num > func_start {
+            // A `=>` body ends with the `;` outside the closures it passes
+            if let Some((_, _, base, max_nesting)) = &mut arrow {
+                *max_nesting = (*max_nesting).max((brace_depth - *base).max(0) as usize);
+                if code.ends_with(';') && brace_depth <= *base {
+                    let (name, start, _, max_nesting) = arrow.take().unwrap();
                     functions.push(FunctionInfo {
-                        name: std::mem::take(&mut func_name),
-                        start_line: func_start,
-                        line_count: line_num - func_start + 1,
+                        name,
+                        start_line: start,
+                        line_count: line_num - start + 1,
                         max_nesting,
                     });
-                    in_func = false;
-                    func_start = 0;
-                    max_nesting = 0;
                 }
             }
-        }
 
-        // Handle function at end of file
-        if in_func && func_start > 0 {
-            let total_lines = content.lines().count();
-            functions.push(FunctionInfo {
-                name: func_name,
-                start_line: func_start,
-                line_count: total_lines - func_start + 1,
-                max_nesting,
-            });
+            // Functions end when brace depth returns to their base
+            functions.line(line_num, brace_depth);
         }
 
-        functions
+        functions.finish(content.lines().count())
+    }
+
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        // Tool caches and build output
+        &[".dart_tool", "build", ".git"]
     }
 
     fn should_skip(&self, path: &Path) -> bool {
         let path_str = path.to_string_lossy();
 
-        // Skip common non-source directories
-        if path_str.contains("/.dart_tool/")
-            || path_str.contains("/build/")
-            || path_str.contains("/.git/")
-        {
-            return true;
-        }
-
         // Skip generated files
         if path_str.ends_with(".g.dart")
             || path_str.ends_with(".freezed.dart")
@@ -123,6 +137,78 @@ impl LanguageParser for DartParser {
 
         false
     }
+
+    fn parse_types(&self, content: &str) -> Vec<TypeInfo> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut types = Vec::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            let Some(caps) = ENUM_PATTERN.captures(line) else {
+                continue;
+            };
+            let Some((body, _)) = braced_block(&lines, i) else {
+                continue;
+            };
+            // Enhanced enums list their values before the first `;`
+            let values = split_top_level(&body, ';')[0];
+            types.push(TypeInfo {
+                name: caps[1].to_string(),
+                start_line: i + 1,
+                variant_count: split_top_level(values, ',')
+                    .iter()
+                    .filter(|v| !v.trim().is_empty())
+                    .count(),
+            });
+        }
+
+        types
+    }
+
+    fn find_loops(&self, lines: &[&str]) -> Vec<LoopSpan> {
+        find_brace_loops(lines, &LOOP_PATTERN, count_braces)
+    }
+
+    fn find_control_flow(&self, lines: &[&str]) -> Option<Vec<LoopSpan>> {
+        Some(find_brace_loops(lines, &CONTROL_PATTERN, count_braces))
+    }
+
+    fn is_decorator(&self, line: &str) -> bool {
+        line.trim_start().starts_with('@')
+    }
+}
+
+/// The name of a method, constructor, operator, getter, or setter declared on
+/// a line, with its class; a setter is named like `label=`
+fn extract_header(line: &str, classes: &ClassScopes, depth: i32) -> Option<String> {
+    if let Some(caps) = ACCESSOR_PATTERN.captures(line) {
+        let suffix = if &caps[3] == "set" { "=" } else { "" };
+        return Some(classes.qualify(format!("{}{}", &caps[4], suffix), depth));
+    }
+    if let Some(class) = classes.class_at(depth) {
+        if let Some(caps) = CONSTRUCTOR_PATTERN.captures(line).filter(|caps| &caps[3] == class) {
+            return Some(format!("{}{}", class, caps.get(4).map_or("", |m| m.as_str())));
+        }
+        if let Some(caps) = OPERATOR_PATTERN.captures(line) {
+            return Some(classes.qualify(format!("operator {}", &caps[2]), depth));
+        }
+    }
+    extract_method_name(line).map(|name| classes.qualify(name, depth))
+}
+
+/// A line without its trailing `//` comment
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let bytes = line.as_bytes();
+    for (i, &c) in bytes.iter().enumerate() {
+        match quote {
+            Some(q) if c == q && (i == 0 || bytes[i - 1] != b'\\') => quote = None,
+            Some(_) => {}
+            None if c == b'"' || c == b'\'' => quote = Some(c),
+            None if c == b'/' && bytes.get(i + 1) == Some(&b'/') => return &line[..i],
+            None => {}
+        }
+    }
+    line
 }
 
 fn extract_method_name(line: &str) -> Option<String> {
@@ -137,39 +223,39 @@ fn count_braces(line: &str) -> (i32, i32) {
     let mut in_string = false;
     let mut escape_next = false;
 
-    let chars: Vec<char> = line.chars().collect();
+    let bytes = line.as_bytes();
     let mut i = 0;
 
-    while i < chars.len() {
+    while i < bytes.len() {
         if escape_next {
             escape_next = false;
             i += 1;
             continue;
         }
 
-        let c = chars[i];
+        let c = bytes[i];
 
-        if in_string && c == '\\' {
+        if in_string && c == b'\\' {
             escape_next = true;
             i += 1;
             continue;
         }
 
         // Check for line comment
-        if !in_string && c == '/' && chars.get(i + 1) == Some(&'/') {
+        if !in_string && c == b'/' && bytes.get(i + 1) == Some(&b'/') {
             break;
         }
 
         // Handle strings
-        if c == '"' || c == '\'' {
+        if c == b'"' || c == b'\'' {
             in_string = !in_string;
         }
 
         // Count braces
         if !in_string {
             match c {
-                '{' => opens += 1,
-                '}' => closes += 1,
+                b'{' => opens += 1,
+                b'}' => closes += 1,
                 _ => {}
             }
         }
@@ -179,3 +265,120 @@ fn count_braces(line: &str) -> (i32, i32) {
 
     (opens, closes)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_methods_are_qualified_by_class() {
+        let code = r#"
+class CounterState extends State<Counter> {
+  void increment() {
+    setState(() {});
+  }
+
+  @override
+  Widget build(BuildContext context) {
+    return Text('$count');
+  }
+}
+
+extension on String {
+  bool isBlank() {
+    return trim().isEmpty;
+  }
+}
+
+void main() {
+  runApp(App());
+}
+"#;
+        let names: Vec<String> = DartParser.parse_functions(code).into_iter().map(|f| f.name).collect();
+        assert_eq!(names, ["CounterState.increment", "CounterState.build", "isBlank", "main"]);
+    }
+
+    #[test]
+    fn test_constructors_operators_and_arrow_bodies() {
+        let code = r#"
+class Point {
+  final int x;
+  final int y;
+
+  const Point(this.x, this.y);
+
+  Point.origin()
+      : x = 0,
+        y = 0 {
+    log('origin');
+  }
+
+  factory Point.fromJson(Map<String, dynamic> json) {
+    return Point(json['x'], json['y']);
+  }
+
+  factory Point.parse(String s) =>
+      Point(int.parse(s.split(',')[0]), int.parse(s.split(',')[1]));
+
+  factory Point.other() = _OtherPoint;
+
+  Point.empty() {}
+
+  @override
+  bool operator ==(Object other) {
+    return other is Point && other.x == x;
+  }
+
+  Point operator +(Point o) => Point(x + o.x, y + o.y);
+
+  @override
+  int get hashCode => Object.hash(x, y);
+
+  Widget build(BuildContext context) => Column(
+        children: items.map((item) {
+          return Text(item); // not the end;
+        }).toList(),
+      );
+
+  void move(
+    int dx,
+    int dy,
+  ) {
+    print(dx);
+  }
+
+  void abstractOne(int a);
+
+  int get sum =>
+      x +
+      y;
+
+  String get label {
+    return '$x,$y';
+  }
+
+  set label(String value) {
+    log(value);
+  }
+}
+"#;
+        let functions = DartParser.parse_functions(code);
+        let found: Vec<(&str, usize, usize)> =
+            functions.iter().map(|f| (f.name.as_str(), f.start_line, f.line_count)).collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Point.origin", 8, 5),
+                ("Point.fromJson", 14, 3),
+                ("Point.parse", 18, 2),
+                ("Point.empty", 23, 1),
+                ("Point.operator ==", 26, 3),
+                ("Point.build", 35, 5),
+                ("Point.move", 41, 6),
+                ("Point.sum", 50, 3),
+                ("Point.label", 54, 3),
+                ("Point.label=", 58, 3),
+            ]
+        );
+    }
+}
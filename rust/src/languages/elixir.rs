@@ -1,7 +1,6 @@
-use super::{FunctionInfo, LanguageParser};
+use super::{FunctionInfo, LanguageParser, LoopSpan, MAX_OPEN_LOOPS};
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::path::Path;
 
 pub struct ElixirParser;
 
@@ -10,152 +9,400 @@ static DEF_PATTERN: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^\s*(def|defp|defmacro|defmacrop)\s+([a-z_][a-zA-Z0-9_?!]*)").unwrap()
 });
 
+// Comprehensions and Enum iteration with an anonymous function
+static LOOP_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(^\s*for\s|\b(Enum|Stream)\.(each|map|flat_map|reduce|reduce_while|filter|reject|map_reduce|each_with_index)\b.*\bfn\b)").unwrap()
+});
+
+// Conditionals and the like, also after `=` or `->`, plus loops
+static CONTROL_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(r"^\s*(.*(=|->)\s*)?(if|unless|case|cond|with|try|receive)\b|{}", LOOP_PATTERN.as_str()))
+        .unwrap()
+});
+
+static BLOCK_TOKEN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(do|fn|end)\b|->").unwrap());
+
 impl LanguageParser for ElixirParser {
     fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
         let mut functions = Vec::new();
-        let mut in_func = false;
-        let mut depth = 0i32; // do/end depth
-        let mut func_name = String::new();
-        let mut func_start = 0usize;
-        let mut max_nesting = 0usize;
+        let mut blocks: Vec<Block> = Vec::new();
+        let mut func: Option<OpenFunction> = None;
+        let mut open = Open::None;
 
         for (line_num, line) in content.lines().enumerate() {
             let line_num = line_num + 1;
+            let code = code_text(line, &mut open);
+            let trimmed = code.trim();
 
-            // Check for function start
-            if let Some(caps) = DEF_PATTERN.captures(line) {
-                // Skip single-line functions with ", do:"
-                if line.contains(", do:") {
-                    continue;
+            let def = DEF_PATTERN.captures(&code).filter(|_| !code.contains(", do:"));
+            if let Some(caps) = def {
+                // A def inside a function, as in a `quote`, ends the one before it
+                if let Some(f) = func.take().filter(|f| f.opened) {
+                    functions.push(f.finish(line_num - 1));
                 }
-
-                // If we were in a function, finish it
-                if in_func && func_start > 0 {
-                    functions.push(FunctionInfo {
-                        name: std::mem::take(&mut func_name),
-                        start_line: func_start,
-                        line_count: line_num - func_start,
-                        max_nesting,
-                    });
+                func = Some(OpenFunction {
+                    name: caps[2].to_string(),
+                    start: line_num,
+                    base: blocks.len(),
+                    max_nesting: 0,
+                    opened: false,
+                    head_parens: 0,
+                    head_tail: String::new(),
+                });
+            } else if let Some(f) = func.as_ref().filter(|f| !f.opened && !trimmed.is_empty()) {
+                // A head without a body, as for default arguments, ends at a line not continuing it
+                let continues = f.head_parens > 0
+                    || trimmed.starts_with("when")
+                    || trimmed.starts_with("do")
+                    || f.head_tail.ends_with(',')
+                    || f.head_tail.ends_with("when");
+                if !continues {
+                    func = None;
                 }
+            }
 
-                func_name = caps.get(2).map(|m| m.as_str().to_string()).unwrap_or_default();
-                func_start = line_num;
-                in_func = true;
-                depth = 0;
-                max_nesting = 0;
-
-                // Count do/end on this line
-                let (dos, ends) = count_do_end(line);
-                depth += dos - ends;
+            let peak = track_blocks(&mut blocks, &code);
+            let Some(f) = &mut func else {
                 continue;
+            };
+            f.max_nesting = f.max_nesting.max(peak.saturating_sub(f.base));
+            if !f.opened && !trimmed.is_empty() {
+                f.head_parens += code.matches('(').count() as i32 - code.matches(')').count() as i32;
+                f.head_tail = trimmed.to_string();
             }
+            f.opened |= peak > f.base;
 
-            // Track do/end keywords
-            let (dos, ends) = count_do_end(line);
-            depth += dos - ends;
-
-            if in_func {
-                if depth > 0 {
-                    let relative_depth = depth as usize;
-                    if relative_depth > max_nesting {
-                        max_nesting = relative_depth;
-                    }
-                }
-
-                // Track nesting keywords for depth
-                max_nesting = max_nesting.max(count_nesting_keywords(line));
-
-                // Function ends when depth returns to 0
-                if depth <= 0 && line_num > func_start {
-                    functions.push(FunctionInfo {
-                        name: std::mem::take(&mut func_name),
-                        start_line: func_start,
-                        line_count: line_num - func_start + 1,
-                        max_nesting,
-                    });
-                    in_func = false;
-                    func_start = 0;
-                    max_nesting = 0;
-                }
+            // The function ends with the `end` of its `do`, or with a `do:` on a later line of its head
+            let ended = if f.opened { blocks.len() <= f.base } else { line_num > f.start && code.contains("do:") };
+            if ended {
+                functions.push(func.take().unwrap().finish(line_num));
             }
         }
 
         // Handle function at end of file
-        if in_func && func_start > 0 {
-            let total_lines = content.lines().count();
-            functions.push(FunctionInfo {
-                name: func_name,
-                start_line: func_start,
-                line_count: total_lines - func_start + 1,
-                max_nesting,
-            });
+        if let Some(f) = func {
+            functions.push(f.finish(content.lines().count()));
         }
 
         functions
     }
 
-    fn should_skip(&self, path: &Path) -> bool {
-        let path_str = path.to_string_lossy();
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        // Dependencies and build output
+        &["deps", "_build", ".git"]
+    }
 
-        // Skip common non-source directories
-        path_str.contains("/deps/")
-            || path_str.contains("/_build/")
-            || path_str.contains("/.git/")
+    fn find_loops(&self, lines: &[&str]) -> Vec<LoopSpan> {
+        find_blocks(lines, &LOOP_PATTERN)
+    }
+
+    fn find_control_flow(&self, lines: &[&str]) -> Option<Vec<LoopSpan>> {
+        Some(find_blocks(lines, &CONTROL_PATTERN))
     }
 }
 
-fn count_do_end(line: &str) -> (i32, i32) {
-    let mut dos = 0i32;
-    let mut ends = 0i32;
+/// Find the blocks whose header matches `pattern`, through their `end`
+fn find_blocks(lines: &[&str], pattern: &Regex) -> Vec<LoopSpan> {
+    let mut spans = Vec::new();
+    // Open blocks: (index into spans, block depth before the header line)
+    let mut open: Vec<(usize, i32)> = Vec::new();
+    let mut depth = 0i32;
+    let mut text = Open::None;
 
-    // Skip comments
-    let line = if let Some(idx) = line.find('#') {
-        &line[..idx]
-    } else {
-        line
-    };
-
-    // Count "do" keywords (word boundary check)
-    let words: Vec<&str> = line.split_whitespace().collect();
-    for word in &words {
-        if *word == "do" {
-            dos += 1;
-        } else if *word == "end" {
-            ends += 1;
+    for (i, line) in lines.iter().enumerate() {
+        let code = code_text(line, &mut text);
+        let code = code.as_str();
+        let tokens = block_tokens(code);
+        let closes = tokens.iter().filter(|(token, _)| *token == "end").count() as i32;
+        let opens = tokens.iter().filter(|(token, _)| matches!(*token, "do" | "fn")).count() as i32;
+
+        if pattern.is_match(code) {
+            spans.push(LoopSpan {
+                start: i,
+                end: i,
+                depth: open.len() + 1,
+            });
+            if opens > closes && open.len() < MAX_OPEN_LOOPS {
+                open.push((spans.len() - 1, depth));
+            }
+        }
+
+        depth += opens - closes;
+
+        while let Some(&(idx, base)) = open.last() {
+            if depth <= base {
+                spans[idx].end = i;
+                open.pop();
+            } else {
+                break;
+            }
         }
     }
 
-    // Also check for "do" at end of line after other tokens
-    if line.trim().ends_with(" do") || line.trim() == "do" {
-        // Already counted above
+    let last = lines.len().saturating_sub(1);
+    for (idx, _) in open {
+        spans[idx].end = last;
     }
 
-    (dos, ends)
+    spans
 }
 
-fn count_nesting_keywords(line: &str) -> usize {
-    let mut depth = 0usize;
+/// A string, heredoc, or sigil still open at the end of a line
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Open {
+    None,
+    /// A string, charlist, or sigil, closed by this byte
+    Text(u8),
+    /// A heredoc, `"""` or `'''` (as in `@doc """` or `~H"""`), closed by three of this quote
+    Heredoc(u8),
+}
+
+/// The code of a line, with strings, heredocs, sigils, and the comment
+/// blanked out, so that the `do`, `end`, and `#` in them don't count.
+///
+/// `open` carries a string, heredoc, or sigil across the lines it spans.
+/// Sigil delimiters don't nest, and `#{}` interpolations are part of the text.
+fn code_text(line: &str, open: &mut Open) -> String {
+    let bytes = line.as_bytes();
+    let mut code = bytes.to_vec();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if *open != Open::None {
+            let end = text_end(bytes, i, open);
+            code[i..end].fill(b' ');
+            i = end;
+            continue;
+        }
+
+        let start = i;
+        match bytes[i] {
+            b'#' => {
+                code[i..].fill(b' ');
+                break;
+            }
+            quote @ (b'"' | b'\'') => *open = opening(bytes, &mut i, quote),
+            // A character literal such as `?"` or `?#`, unless ending a name like `valid?`
+            b'?' if !bytes[..i].last().is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_') => {
+                i += 1;
+            }
+            b'~' if bytes.get(i + 1).is_some_and(u8::is_ascii_alphabetic) => {
+                i += 1 + bytes[i + 1..].iter().take_while(|b| b.is_ascii_alphabetic()).count();
+                *open = match bytes.get(i) {
+                    Some(&quote @ (b'"' | b'\'')) => opening(bytes, &mut i, quote),
+                    Some(b'(') => Open::Text(b')'),
+                    Some(b'[') => Open::Text(b']'),
+                    Some(b'{') => Open::Text(b'}'),
+                    Some(b'<') => Open::Text(b'>'),
+                    Some(&delimiter @ (b'/' | b'|')) => Open::Text(delimiter),
+                    _ => Open::None,
+                };
+            }
+            _ => {}
+        }
+        i += 1;
+        if *open != Open::None {
+            code[start..i].fill(b' ');
+        }
+    }
+
+    // Only ASCII bytes were blanked, and only whole characters
+    String::from_utf8(code).unwrap_or_default()
+}
 
-    // Skip comments
-    let line = if let Some(idx) = line.find('#') {
-        &line[..idx]
+/// The text opened by the quote at `i`, leaving `i` on its last quote
+fn opening(bytes: &[u8], i: &mut usize, quote: u8) -> Open {
+    if bytes[*i..].starts_with(&[quote; 3]) {
+        *i += 2;
+        Open::Heredoc(quote)
     } else {
-        line
-    };
-
-    // Check for nesting keywords that indicate depth
-    let keywords = ["case", "cond", "if", "unless", "with", "try", "receive", "for"];
-    for keyword in keywords {
-        if line.contains(keyword) && line.contains("do") {
-            depth += 1;
+        Open::Text(quote)
+    }
+}
+
+/// The end of the open text starting at `i`: just past its closing
+/// delimiter, which closes `open`, or the end of the line
+fn text_end(bytes: &[u8], mut i: usize, open: &mut Open) -> usize {
+    while i < bytes.len() {
+        match *open {
+            _ if bytes[i] == b'\\' => i += 1,
+            Open::Text(close) if bytes[i] == close => {
+                *open = Open::None;
+                return i + 1;
+            }
+            Open::Heredoc(quote) if bytes[i..].starts_with(&[quote; 3]) => {
+                *open = Open::None;
+                return i + 3;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// A block open around a line
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Block {
+    /// `do ... end`
+    Do,
+    /// `fn ... end`, whose clauses don't nest further
+    Fn,
+    /// A clause of a `case`, `cond`, `receive`, and the like whose body
+    /// starts on the line after its `->`
+    Clause,
+}
+
+/// The `do`, `fn`, `end`, and `->` of the code of a line, in order, each
+/// with whether it ends the line. `do:` and `fn:` are keywords, `:end` an
+/// atom, and `.end` a field, so they don't count.
+fn block_tokens(code: &str) -> Vec<(&str, bool)> {
+    BLOCK_TOKEN
+        .find_iter(code)
+        .filter(|m| {
+            m.as_str() == "->" || !(code[m.end()..].starts_with(':') || code[..m.start()].ends_with([':', '.']))
+        })
+        .map(|m| (m.as_str(), code[m.end()..].trim().is_empty()))
+        .collect()
+}
+
+/// Open and close the blocks of a line, returning the most open at once
+fn track_blocks(blocks: &mut Vec<Block>, code: &str) -> usize {
+    let mut peak = blocks.len();
+    for (token, last) in block_tokens(code) {
+        match token {
+            "do" => blocks.push(Block::Do),
+            "fn" => blocks.push(Block::Fn),
+            "end" => {
+                while blocks.last() == Some(&Block::Clause) {
+                    blocks.pop();
+                }
+                blocks.pop();
+            }
+            // A `->` ends the clause before it, and opens one when the body follows
+            _ => {
+                if blocks.last() == Some(&Block::Clause) {
+                    blocks.pop();
+                }
+                if last && blocks.last() == Some(&Block::Do) {
+                    blocks.push(Block::Clause);
+                }
+            }
         }
+        peak = peak.max(blocks.len());
     }
+    peak
+}
+
+/// A function whose `end` hasn't been reached yet
+struct OpenFunction {
+    name: String,
+    start: usize,
+    /// Blocks open around the def
+    base: usize,
+    max_nesting: usize,
+    /// Whether its `do` block has opened
+    opened: bool,
+    /// Parentheses left open by the head so far, and its last line
+    head_parens: i32,
+    head_tail: String,
+}
+
+impl OpenFunction {
+    fn finish(self, end: usize) -> FunctionInfo {
+        FunctionInfo {
+            name: self.name,
+            start_line: self.start,
+            line_count: end - self.start + 1,
+            max_nesting: self.max_nesting,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heredocs_and_sigils() {
+        let code = r##"defmodule PageLive do
+  @moduledoc """
+  Renders a page. Call it with `render(assigns) do ... end`.
+  """
 
-    // Check for fn -> (anonymous functions)
-    if line.contains("fn") && line.contains("->") {
-        depth += 1;
+  def render(assigns) do
+    ~H"""
+    <%= if @open do %>
+      <p class="#{@class}">Done # not a comment</p>
+    """
+  end
+
+  def label(x) do
+    case x do
+      :a -> ~s(do end)
+      _ -> "end" <> ~r/do|end/ <> 'end'
+    end
+  end
+
+  def sep, do: ?#
+end
+"##;
+        let found: Vec<(String, usize, usize)> =
+            ElixirParser.parse_functions(code).into_iter().map(|f| (f.name, f.start_line, f.line_count)).collect();
+        let expected = [("render", 6, 6), ("label", 13, 6)];
+        assert_eq!(found, expected.map(|(name, start, count)| (name.to_string(), start, count)));
     }
 
-    depth
+    #[test]
+    fn test_block_nesting() {
+        let code = r"defmodule Shop do
+  def total(orders) do
+    Enum.reduce(orders, 0, fn order, acc ->
+      case order.status do
+        :paid ->
+          if order.total > 0 do
+            acc + order.total
+          else
+            acc
+          end
+        _ -> acc
+      end
+    end)
+  end
+
+  def span(range), do: {range.first, range.end}
+  def fetch(key, default \\ nil)
+
+  def fetch(key, default)
+      when is_atom(key) do
+    Keyword.get([end: 1, do: 2], key, default)
+  end
+
+  def flat(xs), do: Enum.map(xs, fn x -> x end)
+
+  def two(a,
+          b),
+      do: a + b
+end
+";
+        let found: Vec<(String, usize, usize, usize)> = ElixirParser
+            .parse_functions(code)
+            .into_iter()
+            .map(|f| (f.name, f.start_line, f.line_count, f.max_nesting))
+            .collect();
+        let expected = [("total", 2, 13, 5), ("fetch", 19, 4, 1), ("two", 26, 3, 0)];
+        assert_eq!(found, expected.map(|(name, start, count, depth)| (name.to_string(), start, count, depth)));
+    }
+
+    #[test]
+    fn test_code_text() {
+        let mut open = Open::None;
+        assert_eq!(code_text(r##"x = "a # b" # do"##, &mut open).trim_end(), "x =");
+        assert_eq!(code_text("valid?(x) and ?' == c", &mut open), "valid?(x) and ?' == c");
+        assert_eq!(code_text("y = ~w[do end]a", &mut open), "y =           a");
+        assert_eq!(code_text("s = ~S'''", &mut open).trim_end(), "s =");
+        assert_eq!(open, Open::Heredoc(b'\''));
+        assert_eq!(code_text("  do end ''' |> f()", &mut open), "             |> f()");
+        assert_eq!(open, Open::None);
+    }
 }
@@ -0,0 +1,110 @@
+use super::{FunctionInfo, LanguageParser};
+
+/// Fallback parser for languages without a dedicated one.
+///
+/// It knows nothing about functions, so the whole file is reported as a
+/// single unit whose nesting depth comes from braces when the file uses them,
+/// or from indentation otherwise.
+pub struct GenericParser;
+
+/// Name used for the whole-file unit in reports
+pub const FILE_UNIT_NAME: &str = "(file)";
+
+impl LanguageParser for GenericParser {
+    fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
+        let line_count = content.lines().count();
+        if line_count == 0 {
+            return Vec::new();
+        }
+
+        let max_nesting = if content.contains('{') {
+            brace_nesting(content)
+        } else {
+            indent_nesting(content)
+        };
+
+        vec![FunctionInfo {
+            name: FILE_UNIT_NAME.to_string(),
+            start_line: 1,
+            line_count,
+            max_nesting,
+        }]
+    }
+
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        // Version control, dependency, and build directories of common ecosystems
+        &[".git", "node_modules", "target", "build", "dist", "vendor"]
+    }
+}
+
+fn brace_nesting(content: &str) -> usize {
+    let mut depth = 0i32;
+    let mut max_depth = 0i32;
+    let mut in_string: Option<char> = None;
+
+    for line in content.lines() {
+        let mut escape_next = false;
+        for c in line.chars() {
+            if escape_next {
+                escape_next = false;
+                continue;
+            }
+            match in_string {
+                Some(_) if c == '\\' => escape_next = true,
+                Some(quote) if c == quote => in_string = None,
+                Some(_) => {}
+                None => match c {
+                    '"' => in_string = Some(c),
+                    '{' => {
+                        depth += 1;
+                        max_depth = max_depth.max(depth);
+                    }
+                    '}' => depth = (depth - 1).max(0),
+                    _ => {}
+                },
+            }
+        }
+        in_string = None;
+    }
+
+    max_depth as usize
+}
+
+fn indent_nesting(content: &str) -> usize {
+    let indents: Vec<usize> = content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| {
+            l.chars()
+                .take_while(|c| c.is_whitespace())
+                .map(|c| if c == '\t' { 4 } else { 1 })
+                .sum()
+        })
+        .collect();
+
+    // The smallest non-zero indentation is taken as one level
+    match indents.iter().copied().filter(|&i| i > 0).min() {
+        Some(unit) => indents.iter().map(|i| i / unit).max().unwrap_or(0),
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brace_nesting() {
+        let code = "message A {\n  message B {\n    string s = 1; // \"{\"\n  }\n}\n";
+        let units = GenericParser.parse_functions(code);
+        assert_eq!(units.len(), 1);
+        assert_eq!(units[0].line_count, 5);
+        assert_eq!(units[0].max_nesting, 2);
+    }
+
+    #[test]
+    fn test_indent_nesting() {
+        let code = "a:\n  b:\n    c: 1\n  d: 2\n";
+        assert_eq!(GenericParser.parse_functions(code)[0].max_nesting, 2);
+    }
+}
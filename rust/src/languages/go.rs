@@ -0,0 +1,217 @@
+use super::{find_brace_loops, FunctionInfo, LanguageParser, LoopSpan};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+pub struct GoParser;
+
+// Top-level functions and methods, e.g. `func Name(`, `func (s *Server) Name(`,
+// `func Map[T any](`
+static FUNC_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^func\s+(\([^)]*\)\s*)?([a-zA-Z_][a-zA-Z0-9_]*)\s*[\[(]").unwrap()
+});
+
+// Go has a single loop keyword
+static LOOP_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*(\w+:\s*)?for\b").unwrap());
+
+static CONTROL_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(\w+:\s*)?(if|else|for|switch|select)\b").unwrap());
+
+impl LanguageParser for GoParser {
+    fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
+        let mut functions = Vec::new();
+        let mut in_func = false;
+        let mut brace_depth = 0i32;
+        let mut func_name = String::new();
+        let mut func_start = 0usize;
+        let mut base_depth = 0i32;
+        let mut max_nesting = 0usize;
+        let mut in_raw_string = false;
+
+        for (line_num, line) in content.lines().enumerate() {
+            let line_num = line_num + 1;
+
+            // Check for function start
+            if !in_raw_string {
+                if let Some(caps) = FUNC_PATTERN.captures(line) {
+                    // If we were in a function, finish it
+                    if in_func && func_start > 0 {
+                        functions.push(FunctionInfo {
+                            name: std::mem::take(&mut func_name),
+                            start_line: func_start,
+                            line_count: line_num - func_start,
+                            max_nesting,
+                        });
+                    }
+
+                    func_name = caps.get(2).map(|m| m.as_str().to_string()).unwrap_or_default();
+                    func_start = line_num;
+                    in_func = true;
+                    base_depth = brace_depth;
+                    max_nesting = 0;
+
+                    let (opens, closes) = count_braces_raw(line, &mut in_raw_string);
+                    brace_depth += opens - closes;
+
+                    // Declaration without a body, e.g. implemented in assembly
+                    if opens == 0 && !line.trim_end().ends_with(',') && !line.trim_end().ends_with('(') {
+                        in_func = false;
+                        func_start = 0;
+                    }
+                    continue;
+                }
+            }
+
+            // Track braces
+            let (opens, closes) = count_braces_raw(line, &mut in_raw_string);
+            brace_depth += opens - closes;
+
+            if in_func {
+                let relative_depth = (brace_depth - base_depth).max(0) as usize;
+                if relative_depth > max_nesting {
+                    max_nesting = relative_depth;
+                }
+
+                // Function ends when brace depth returns to base
+                if brace_depth <= base_depth && line_num > func_start && closes > 0 {
+                    functions.push(FunctionInfo {
+                        name: std::mem::take(&mut func_name),
+                        start_line: func_start,
+                        line_count: line_num - func_start + 1,
+                        max_nesting,
+                    });
+                    in_func = false;
+                    func_start = 0;
+                    max_nesting = 0;
+                }
+            }
+        }
+
+        // Handle function at end of file
+        if in_func && func_start > 0 {
+            let total_lines = content.lines().count();
+            functions.push(FunctionInfo {
+                name: func_name,
+                start_line: func_start,
+                line_count: total_lines - func_start + 1,
+                max_nesting,
+            });
+        }
+
+        functions
+    }
+
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        // Vendored dependencies and test fixtures
+        &["vendor", "testdata", ".git"]
+    }
+
+    fn find_loops(&self, lines: &[&str]) -> Vec<LoopSpan> {
+        find_brace_loops(lines, &LOOP_PATTERN, count_braces)
+    }
+
+    fn find_control_flow(&self, lines: &[&str]) -> Option<Vec<LoopSpan>> {
+        Some(find_brace_loops(lines, &CONTROL_PATTERN, count_braces))
+    }
+}
+
+fn count_braces(line: &str) -> (i32, i32) {
+    count_braces_raw(line, &mut false)
+}
+
+/// Count braces outside strings, runes, and comments.
+///
+/// `in_raw_string` carries backtick raw strings, which may span lines.
+fn count_braces_raw(line: &str, in_raw_string: &mut bool) -> (i32, i32) {
+    let mut opens = 0i32;
+    let mut closes = 0i32;
+    let mut in_string: Option<u8> = None;
+    let mut escape_next = false;
+
+    let bytes = line.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        if *in_raw_string {
+            if c == b'`' {
+                *in_raw_string = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        if escape_next {
+            escape_next = false;
+            i += 1;
+            continue;
+        }
+
+        if let Some(quote) = in_string {
+            if c == b'\\' {
+                escape_next = true;
+            } else if c == quote {
+                in_string = None;
+            }
+            i += 1;
+            continue;
+        }
+
+        // Check for line comment
+        if c == b'/' && bytes.get(i + 1) == Some(&b'/') {
+            break;
+        }
+
+        match c {
+            b'"' | b'\'' => in_string = Some(c),
+            b'`' => *in_raw_string = true,
+            b'{' => opens += 1,
+            b'}' => closes += 1,
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    (opens, closes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_functions_and_methods() {
+        let code = r#"package main
+
+func Add(a, b int) int {
+	return a + b
+}
+
+func (s *Server) Handle(w http.ResponseWriter) {
+	if s.ready {
+		for _, h := range s.hooks {
+			h()
+		}
+	}
+}
+
+func Map[T any](xs []T) []T {
+	return xs
+}
+"#;
+        let functions = GoParser.parse_functions(code);
+        let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["Add", "Handle", "Map"]);
+        assert_eq!(functions[1].line_count, 7);
+        assert_eq!(functions[1].max_nesting, 3);
+    }
+
+    #[test]
+    fn test_raw_string_braces_ignored() {
+        let code = "func Tmpl() string {\n\treturn `{{ if .X }}\n}}}\n`\n}\n\nfunc Next() {\n}\n";
+        let functions = GoParser.parse_functions(code);
+        assert_eq!(functions.len(), 2);
+        assert_eq!(functions[0].line_count, 5);
+    }
+}
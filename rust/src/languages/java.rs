@@ -0,0 +1,248 @@
+use super::{braced_block, find_brace_loops, split_top_level, FunctionInfo, LanguageParser, LoopSpan, TypeInfo};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+pub struct JavaParser;
+
+// Methods and constructors: optional modifiers and type parameters, an optional
+// return type, then `name(`
+static METHOD_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*((public|protected|private|static|final|abstract|synchronized|native|default|strictfp)\s+)*(<[^>]+>\s+)?([\w<>\[\],.?]+(\s*<[^()]*>)?(\[\])*\s+)?([a-zA-Z_$][a-zA-Z0-9_$]*)\s*\("
+    ).unwrap()
+});
+
+// Words that look like a return type or a method name in the pattern above
+const NOT_METHODS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "synchronized", "return", "new", "else", "throw", "try",
+    "record", "class", "interface", "enum", "super", "this", "assert",
+];
+
+static ENUM_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*((public|protected|private|static|final)\s+)*enum\s+([a-zA-Z_$][a-zA-Z0-9_$]*)").unwrap()
+});
+
+static LOOP_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(\w+:\s*)?(for\s*\(|while\s*\(|do\s*(\{|$))").unwrap());
+
+static CONTROL_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(\w+:\s*)?(if|else|for|while|do|switch|try|catch|finally)\b").unwrap());
+
+impl LanguageParser for JavaParser {
+    fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
+        let mut functions = Vec::new();
+        let mut in_func = false;
+        // Whether the opening brace of the current method has been seen
+        let mut in_body = false;
+        let mut brace_depth = 0i32;
+        let mut func_name = String::new();
+        let mut func_start = 0usize;
+        let mut base_depth = 0i32;
+        let mut max_nesting = 0usize;
+
+        for (line_num, line) in content.lines().enumerate() {
+            let line_num = line_num + 1;
+            let (opens, closes) = count_braces(line);
+
+            // Methods of anonymous and local classes count towards the enclosing method
+            if !in_func {
+                if let Some(name) = extract_method_name(line) {
+                    func_name = name;
+                    func_start = line_num;
+                    in_func = true;
+                    in_body = false;
+                    base_depth = brace_depth;
+                    max_nesting = 0;
+                }
+            }
+
+            brace_depth += opens - closes;
+
+            if !in_func {
+                continue;
+            }
+
+            if !in_body {
+                if opens > 0 {
+                    in_body = true;
+                } else if line.trim_end().ends_with(';') {
+                    // Abstract or interface method without a body
+                    in_func = false;
+                    continue;
+                } else {
+                    continue;
+                }
+            }
+
+            if line_num > func_start {
+                let relative_depth = (brace_depth - base_depth).max(0) as usize;
+                if relative_depth > max_nesting {
+                    max_nesting = relative_depth;
+                }
+            }
+
+            // Method ends when brace depth returns to base
+            if brace_depth <= base_depth {
+                functions.push(FunctionInfo {
+                    name: std::mem::take(&mut func_name),
+                    start_line: func_start,
+                    line_count: line_num - func_start + 1,
+                    max_nesting,
+                });
+                in_func = false;
+            }
+        }
+
+        // Handle method at end of file
+        if in_func && in_body {
+            let total_lines = content.lines().count();
+            functions.push(FunctionInfo {
+                name: func_name,
+                start_line: func_start,
+                line_count: total_lines - func_start + 1,
+                max_nesting,
+            });
+        }
+
+        functions
+    }
+
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        // Build output and generated sources
+        &["build", "target", "out", ".gradle", "generated", "generated-sources", ".git"]
+    }
+
+    fn parse_types(&self, content: &str) -> Vec<TypeInfo> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut types = Vec::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            let Some(caps) = ENUM_PATTERN.captures(line) else {
+                continue;
+            };
+            let Some((body, _)) = braced_block(&lines, i) else {
+                continue;
+            };
+            // Constants come before the first `;`, followed by fields and methods
+            let values = split_top_level(&body, ';')[0];
+            types.push(TypeInfo {
+                name: caps[3].to_string(),
+                start_line: i + 1,
+                variant_count: split_top_level(values, ',')
+                    .iter()
+                    .filter(|v| !v.trim().is_empty())
+                    .count(),
+            });
+        }
+
+        types
+    }
+
+    fn find_loops(&self, lines: &[&str]) -> Vec<LoopSpan> {
+        find_brace_loops(lines, &LOOP_PATTERN, count_braces)
+    }
+
+    fn find_control_flow(&self, lines: &[&str]) -> Option<Vec<LoopSpan>> {
+        Some(find_brace_loops(lines, &CONTROL_PATTERN, count_braces))
+    }
+
+    fn is_decorator(&self, line: &str) -> bool {
+        line.trim_start().starts_with('@')
+    }
+}
+
+fn extract_method_name(line: &str) -> Option<String> {
+    let caps = METHOD_PATTERN.captures(line)?;
+    let name = caps.get(7)?.as_str();
+    let return_type = caps.get(4).map(|m| m.as_str().trim()).unwrap_or("");
+    if NOT_METHODS.contains(&name) || NOT_METHODS.contains(&return_type) {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+fn count_braces(line: &str) -> (i32, i32) {
+    let mut opens = 0i32;
+    let mut closes = 0i32;
+    let mut in_string: Option<char> = None;
+    let mut escape_next = false;
+
+    for (i, c) in line.char_indices() {
+        if escape_next {
+            escape_next = false;
+            continue;
+        }
+
+        if let Some(quote) = in_string {
+            if c == '\\' {
+                escape_next = true;
+            } else if c == quote {
+                in_string = None;
+            }
+            continue;
+        }
+
+        // Check for line comment
+        if line[i..].starts_with("//") {
+            break;
+        }
+
+        match c {
+            '"' | '\'' => in_string = Some(c),
+            '{' => opens += 1,
+            '}' => closes += 1,
+            _ => {}
+        }
+    }
+
+    (opens, closes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_methods_and_constructors() {
+        let code = r#"public class Service {
+    private final Map<String, List<Item>> items = new HashMap<>();
+
+    public Service(Repo repo) {
+        this.repo = repo;
+    }
+
+    public static <T> List<T> copy(
+            List<T> source) {
+        return new ArrayList<>(source);
+    }
+
+    abstract void reset();
+
+    void run() {
+        executor.submit(new Runnable() {
+            @Override
+            public void run() {
+                if (ready) {
+                    go();
+                }
+            }
+        });
+    }
+}
+"#;
+        let functions = JavaParser.parse_functions(code);
+        let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["Service", "copy", "run"]);
+        assert_eq!(functions[1].line_count, 4);
+        assert_eq!(functions[2].line_count, 10);
+        assert_eq!(functions[2].max_nesting, 4);
+    }
+
+    #[test]
+    fn test_enum_constants() {
+        let code = "enum Color {\n    RED(\"r\"), GREEN(\"g\"), BLUE(\"b\");\n    private final String code;\n}\n";
+        let types = JavaParser.parse_types(code);
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[0].variant_count, 3);
+    }
+}
@@ -0,0 +1,61 @@
+use super::typescript::TypeScriptParser;
+use super::{FunctionInfo, LanguageParser, LoopSpan, TypeInfo};
+use std::path::Path;
+
+/// Plain JavaScript, parsed like TypeScript without the type annotations
+pub struct JavaScriptParser;
+
+impl LanguageParser for JavaScriptParser {
+    fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
+        TypeScriptParser.parse_functions(content)
+    }
+
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        // Dependencies, build output, and coverage reports
+        &["node_modules", "dist", "build", "coverage", ".git"]
+    }
+
+    fn should_skip(&self, path: &Path) -> bool {
+        let path_str = path.to_string_lossy();
+
+        // Skip minified bundles
+        path_str.ends_with(".min.js") || path_str.ends_with(".bundle.js")
+    }
+
+    fn parse_types(&self, content: &str) -> Vec<TypeInfo> {
+        TypeScriptParser.parse_types(content)
+    }
+
+    fn find_loops(&self, lines: &[&str]) -> Vec<LoopSpan> {
+        TypeScriptParser.find_loops(lines)
+    }
+
+    fn find_control_flow(&self, lines: &[&str]) -> Option<Vec<LoopSpan>> {
+        TypeScriptParser.find_control_flow(lines)
+    }
+
+    fn is_decorator(&self, line: &str) -> bool {
+        TypeScriptParser.is_decorator(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commonjs_function() {
+        let code = "const util = require('util');\n\nfunction greet(name) {\n    return 'hi ' + name;\n}\n\nmodule.exports = { greet };\n";
+        let functions = JavaScriptParser.parse_functions(code);
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].name, "greet");
+        assert_eq!(functions[0].start_line, 3);
+    }
+
+    #[test]
+    fn test_skips_bundles() {
+        assert!(JavaScriptParser.skip_dirs().contains(&"node_modules"));
+        assert!(JavaScriptParser.should_skip(Path::new("/p/public/app.min.js")));
+        assert!(!JavaScriptParser.should_skip(Path::new("/p/src/app.js")));
+    }
+}
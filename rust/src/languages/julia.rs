@@ -0,0 +1,393 @@
+use super::{char_offset, FunctionInfo, LanguageParser, LoopSpan, TypeInfo, MAX_OPEN_LOOPS};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+pub struct JuliaParser;
+
+// Long-form definitions, e.g. `function solve!(x)`, `@inline function Base.show(io, x)`, `macro m(ex)`
+static FUNCTION_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(@[\w.]+\s+)*(function|macro)\s+([A-Za-z_][\w!.]*|\([^)]*\))").unwrap()
+});
+
+// Short-form definitions, e.g. `area(r) = π * r^2`, `f(x::T) where {T} = x`
+static SHORT_FUNCTION_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(@[\w.]+\s+)*([A-Za-z_][\w!.]*)(\{[^}]*\})?\(.*\)\s*(::\s*[^=]+?)?\s*(where\s+.+?)?\s*=($|[^=>])").unwrap()
+});
+
+static LOOP_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*(@[\w.]+\s+)*(for|while)\b").unwrap());
+
+static CONTROL_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(@[\w.]+\s+)*(.*=\s*)?(if|for|while|try)\b").unwrap());
+
+// `@enum Color red green blue` or `@enum Color begin ... end`
+static ENUM_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*@enum\s+([A-Za-z_]\w*)(::\w+)?\s*(.*)$").unwrap());
+
+/// Keywords closed by a matching `end`
+const BLOCK_KEYWORDS: &[&str] = &[
+    "function", "macro", "if", "for", "while", "begin", "let", "do", "try", "struct", "module", "baremodule",
+    "quote",
+];
+
+/// Words that look like calls in the short-form pattern but are not definitions
+const NOT_FUNCTIONS: &[&str] = &["if", "elseif", "while", "for", "return", "let", "local", "global", "const"];
+
+/// Lexer state carried across lines
+#[derive(Default)]
+struct LexState {
+    /// `#= ... =#` comments nest in Julia
+    block_comment_depth: usize,
+    in_triple_string: bool,
+    /// Parentheses and brackets still open; `end` inside them is an index, not a block end
+    bracket_depth: i32,
+}
+
+impl LexState {
+    /// Whether the next line starts inside a comment or string
+    fn in_text(&self) -> bool {
+        self.block_comment_depth > 0 || self.in_triple_string
+    }
+}
+
+impl LanguageParser for JuliaParser {
+    fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
+        let mut functions = Vec::new();
+        let mut state = LexState::default();
+        let mut depth = 0i32;
+        let mut in_func = false;
+        let mut func_name = String::new();
+        let mut func_start = 0usize;
+        let mut base_depth = 0i32;
+        let mut max_nesting = 0usize;
+
+        for (line_num, line) in content.lines().enumerate() {
+            let line_num = line_num + 1;
+            let starts_in_text = state.in_text();
+            let (opens, closes) = count_blocks(line, &mut state);
+
+            if !in_func && !starts_in_text {
+                let name = if let Some(caps) = FUNCTION_PATTERN.captures(line) {
+                    Some(caps[3].to_string())
+                } else {
+                    SHORT_FUNCTION_PATTERN
+                        .captures(line)
+                        .filter(|caps| !NOT_FUNCTIONS.contains(&&caps[2]))
+                        .map(|caps| caps[2].to_string())
+                };
+
+                if let Some(name) = name {
+                    if opens > closes {
+                        func_name = name;
+                        func_start = line_num;
+                        in_func = true;
+                        base_depth = depth;
+                        max_nesting = 0;
+                    } else {
+                        // One-liner, e.g. `f(x) = 2x` or `function f() end`
+                        functions.push(FunctionInfo {
+                            name,
+                            start_line: line_num,
+                            line_count: 1,
+                            max_nesting: 0,
+                        });
+                    }
+                    depth += opens - closes;
+                    continue;
+                }
+            }
+
+            depth += opens - closes;
+
+            if in_func {
+                let relative_depth = (depth - base_depth).max(0) as usize;
+                if relative_depth > max_nesting {
+                    max_nesting = relative_depth;
+                }
+
+                // Function ends when its `end` brings the depth back to where it started
+                if depth <= base_depth {
+                    functions.push(FunctionInfo {
+                        name: std::mem::take(&mut func_name),
+                        start_line: func_start,
+                        line_count: line_num - func_start + 1,
+                        max_nesting,
+                    });
+                    in_func = false;
+                    func_start = 0;
+                    max_nesting = 0;
+                }
+            }
+            depth = depth.max(0);
+        }
+
+        // Handle function at end of file
+        if in_func && func_start > 0 {
+            let total_lines = content.lines().count();
+            functions.push(FunctionInfo {
+                name: func_name,
+                start_line: func_start,
+                line_count: total_lines - func_start + 1,
+                max_nesting,
+            });
+        }
+
+        functions
+    }
+
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        // Package build artifacts
+        &["deps", ".git"]
+    }
+
+    fn parse_types(&self, content: &str) -> Vec<TypeInfo> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut types = Vec::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            let Some(caps) = ENUM_PATTERN.captures(line) else {
+                continue;
+            };
+            let rest = strip_comment(&caps[3]).trim();
+            let variant_count = if rest == "begin" {
+                lines[i + 1..]
+                    .iter()
+                    .map(|l| strip_comment(l).trim())
+                    .take_while(|l| *l != "end")
+                    .flat_map(|l| l.split_whitespace())
+                    .count()
+            } else {
+                rest.split_whitespace().count()
+            };
+            types.push(TypeInfo {
+                name: caps[1].to_string(),
+                start_line: i + 1,
+                variant_count,
+            });
+        }
+
+        types
+    }
+
+    fn find_loops(&self, lines: &[&str]) -> Vec<LoopSpan> {
+        find_blocks(lines, &LOOP_PATTERN)
+    }
+
+    fn find_control_flow(&self, lines: &[&str]) -> Option<Vec<LoopSpan>> {
+        Some(find_blocks(lines, &CONTROL_PATTERN))
+    }
+}
+
+/// Find the blocks whose header matches `pattern`, through their `end`
+fn find_blocks(lines: &[&str], pattern: &Regex) -> Vec<LoopSpan> {
+    let mut spans = Vec::new();
+    // Open blocks: (index into spans, block depth before the header line)
+    let mut open: Vec<(usize, i32)> = Vec::new();
+    let mut state = LexState::default();
+    let mut depth = 0i32;
+
+    for (i, line) in lines.iter().enumerate() {
+        let starts_in_text = state.in_text();
+        let (opens, closes) = count_blocks(line, &mut state);
+
+        if !starts_in_text && pattern.is_match(line) {
+            spans.push(LoopSpan {
+                start: i,
+                end: i,
+                depth: open.len() + 1,
+            });
+            if opens > closes && open.len() < MAX_OPEN_LOOPS {
+                open.push((spans.len() - 1, depth));
+            }
+        }
+
+        depth += opens - closes;
+
+        while let Some(&(idx, base)) = open.last() {
+            if depth <= base {
+                spans[idx].end = i;
+                open.pop();
+            } else {
+                break;
+            }
+        }
+    }
+
+    let last = lines.len().saturating_sub(1);
+    for (idx, _) in open {
+        spans[idx].end = last;
+    }
+
+    spans
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find('#') {
+        Some(idx) => &line[..idx],
+        None => line,
+    }
+}
+
+/// Count block keywords and `end`s in the code portion of a line.
+///
+/// Strings, comments, and anything inside brackets are skipped, so
+/// `x[end]` and generators such as `[f(x) for x in xs]` don't count.
+fn count_blocks(line: &str, state: &mut LexState) -> (i32, i32) {
+    let starts_with = |i: usize, pat: &str| line[i..].starts_with(pat);
+    let after = |i: usize, n: usize| char_offset(line, i, n);
+    let mut opens = 0;
+    let mut closes = 0;
+    let mut i = 0;
+
+    while let Some(c) = line[i..].chars().next() {
+        if state.block_comment_depth > 0 {
+            if starts_with(i, "=#") {
+                state.block_comment_depth -= 1;
+                i += 2;
+            } else if starts_with(i, "#=") {
+                state.block_comment_depth += 1;
+                i += 2;
+            } else {
+                i = after(i, 1);
+            }
+            continue;
+        }
+
+        if state.in_triple_string {
+            if c == '\\' {
+                i = after(i, 2);
+            } else if starts_with(i, "\"\"\"") {
+                state.in_triple_string = false;
+                i += 3;
+            } else {
+                i = after(i, 1);
+            }
+            continue;
+        }
+
+        match c {
+            '#' if starts_with(i, "#=") => {
+                state.block_comment_depth = 1;
+                i += 2;
+                continue;
+            }
+            '#' => break,
+            '"' if starts_with(i, "\"\"\"") => {
+                state.in_triple_string = true;
+                i += 3;
+                continue;
+            }
+            '"' | '`' => {
+                // Single-line string or command literal
+                i += 1;
+                while let Some(d) = line[i..].chars().next().filter(|d| *d != c) {
+                    i = after(i, if d == '\\' { 2 } else { 1 });
+                }
+            }
+            '\'' => {
+                // Character literal, unless it is the adjoint operator as in `A'`
+                let after_value = line[..i]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|p| is_ident_char(p) || matches!(p, ')' | ']' | '\''));
+                if !after_value {
+                    i = after(i, if line[i..].chars().nth(1) == Some('\\') { 3 } else { 2 });
+                }
+            }
+            '(' | '[' | '{' => state.bracket_depth += 1,
+            ')' | ']' | '}' => state.bracket_depth = (state.bracket_depth - 1).max(0),
+            _ if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while let Some(d) = line[i..].chars().next().filter(|d| is_ident_char(*d)) {
+                    i += d.len_utf8();
+                }
+                let word = &line[start..i];
+                // Field access (`x.end`) and symbols (`:begin`) are not keywords
+                let mut before = line[..start].chars().rev();
+                let prev = before.next();
+                let quoted = prev == Some('.') || (prev == Some(':') && before.next() != Some(':'));
+                if state.bracket_depth == 0 && !quoted {
+                    if word == "end" {
+                        closes += 1;
+                    } else if BLOCK_KEYWORDS.contains(&word)
+                        || (matches!(word, "abstract" | "primitive") && starts_with(i, " type"))
+                    {
+                        opens += 1;
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+        i = after(i, 1);
+    }
+
+    (opens, closes)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '!'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_and_short_functions() {
+        let parser = JuliaParser;
+        let code = r#"module Shapes
+
+area(r) = π * r^2
+
+function describe(xs::Vector{Int})
+    for x in xs
+        if x > xs[end]
+            println("end $x")
+        end
+    end
+    return [y for y in xs if y > 0]
+end
+
+end
+"#;
+        let functions = parser.parse_functions(code);
+        assert_eq!(functions.len(), 2);
+        assert_eq!(functions[0].name, "area");
+        assert_eq!(functions[0].line_count, 1);
+        assert_eq!(functions[1].name, "describe");
+        assert_eq!(functions[1].start_line, 5);
+        assert_eq!(functions[1].line_count, 8);
+        assert_eq!(functions[1].max_nesting, 3);
+    }
+
+    #[test]
+    fn test_do_block_and_comments() {
+        let parser = JuliaParser;
+        let code = "function run(path)\n    #= an end in a\n       block comment =#\n    open(path) do io\n        read(io, String)  # no end here\n    end\nend\n";
+        let functions = parser.parse_functions(code);
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].line_count, 7);
+        assert_eq!(functions[0].max_nesting, 2);
+    }
+
+    #[test]
+    fn test_non_ascii_literals() {
+        // `'é'` is a character literal, and `Σ'` the adjoint of a variable
+        let code = "function f(Σ)\n    c = 'é'\n    if c == '}'\n        return Σ'\n    end\nend\n";
+        let functions = JuliaParser.parse_functions(code);
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].line_count, 6);
+        assert_eq!(functions[0].max_nesting, 2);
+    }
+
+    #[test]
+    fn test_enum_variants() {
+        let parser = JuliaParser;
+        let code = "@enum Fruit apple banana\n@enum Color begin\n    red\n    green\n    blue\nend\n";
+        let types = parser.parse_types(code);
+        assert_eq!(types.len(), 2);
+        assert_eq!(types[0].variant_count, 2);
+        assert_eq!(types[1].name, "Color");
+        assert_eq!(types[1].variant_count, 3);
+    }
+}
@@ -1,29 +1,70 @@
 pub mod dart;
 pub mod elixir;
+pub mod generic;
+pub mod go;
+pub mod java;
+pub mod javascript;
+pub mod julia;
 pub mod python;
+#[cfg(feature = "python-ast")]
+pub mod python_ast;
 pub mod rust_lang;
+pub mod swift;
+pub mod terraform;
 pub mod typescript;
 
+use regex::Regex;
 use std::path::Path;
 
 /// Supported language types
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum LanguageType {
     Elixir,
     Dart,
     TypeScript,
+    JavaScript,
     Python,
     Rust,
+    Go,
+    Java,
+    Swift,
+    Terraform,
+    Julia,
+    /// Fallback for any text file (opt-in, never auto-detected)
+    Generic,
 }
 
 impl LanguageType {
+    /// Every supported language, including the generic fallback
+    pub const ALL: [LanguageType; 12] = [
+        LanguageType::Elixir,
+        LanguageType::Dart,
+        LanguageType::TypeScript,
+        LanguageType::JavaScript,
+        LanguageType::Python,
+        LanguageType::Rust,
+        LanguageType::Go,
+        LanguageType::Java,
+        LanguageType::Swift,
+        LanguageType::Terraform,
+        LanguageType::Julia,
+        LanguageType::Generic,
+    ];
+
     pub fn name(&self) -> &'static str {
         match self {
             LanguageType::Elixir => "elixir",
             LanguageType::Dart => "dart",
             LanguageType::TypeScript => "typescript",
+            LanguageType::JavaScript => "javascript",
             LanguageType::Python => "python",
             LanguageType::Rust => "rust",
+            LanguageType::Go => "go",
+            LanguageType::Java => "java",
+            LanguageType::Swift => "swift",
+            LanguageType::Terraform => "terraform",
+            LanguageType::Julia => "julia",
+            LanguageType::Generic => "generic",
         }
     }
 
@@ -33,8 +74,15 @@ impl LanguageType {
             LanguageType::Elixir => "Elixir",
             LanguageType::Dart => "Dart",
             LanguageType::TypeScript => "TypeScript",
+            LanguageType::JavaScript => "JavaScript",
             LanguageType::Python => "Python",
             LanguageType::Rust => "Rust",
+            LanguageType::Go => "Go",
+            LanguageType::Java => "Java",
+            LanguageType::Swift => "Swift",
+            LanguageType::Terraform => "Terraform",
+            LanguageType::Julia => "Julia",
+            LanguageType::Generic => "Generic",
         }
     }
 
@@ -43,8 +91,55 @@ impl LanguageType {
             LanguageType::Elixir => &["ex", "exs"],
             LanguageType::Dart => &["dart"],
             LanguageType::TypeScript => &["ts", "tsx"],
+            LanguageType::JavaScript => &["js", "jsx", "mjs", "cjs"],
             LanguageType::Python => &["py"],
             LanguageType::Rust => &["rs"],
+            LanguageType::Go => &["go"],
+            LanguageType::Java => &["java"],
+            LanguageType::Swift => &["swift"],
+            LanguageType::Terraform => &["tf"],
+            LanguageType::Julia => &["jl"],
+            // Any extension; see `matches_extension`
+            LanguageType::Generic => &[],
+        }
+    }
+
+    /// Check whether a file extension belongs to this language
+    pub fn matches_extension(&self, ext: &str) -> bool {
+        match self {
+            LanguageType::Generic => true,
+            _ => self.extensions().contains(&ext),
+        }
+    }
+
+    /// The language a file's extension belongs to; None if no supported language has it
+    pub fn for_path(path: &Path) -> Option<LanguageType> {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        LanguageType::ALL
+            .into_iter()
+            .find(|l| *l != LanguageType::Generic && l.matches_extension(ext))
+    }
+
+    /// The language a script's `#!` line runs it with, as for the
+    /// extensionless files of `bin/`; None for other interpreters, like `bash`
+    pub fn for_shebang(first_line: &str) -> Option<LanguageType> {
+        let mut words = first_line.strip_prefix("#!")?.split_whitespace();
+        let mut program = words.next()?.rsplit('/').next()?;
+        // `#!/usr/bin/env python3`, `#!/usr/bin/env -S deno run`
+        if program == "env" {
+            program = words.find(|w| !w.starts_with('-') && !w.contains('='))?;
+        }
+        // `python3.11` is `python`
+        match program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+            "python" | "pypy" => Some(LanguageType::Python),
+            "node" | "nodejs" | "bun" => Some(LanguageType::JavaScript),
+            "deno" | "ts-node" | "tsx" => Some(LanguageType::TypeScript),
+            "elixir" => Some(LanguageType::Elixir),
+            "julia" => Some(LanguageType::Julia),
+            "dart" => Some(LanguageType::Dart),
+            "swift" => Some(LanguageType::Swift),
+            "rust-script" => Some(LanguageType::Rust),
+            _ => None,
         }
     }
 }
@@ -58,13 +153,98 @@ pub struct FunctionInfo {
     pub max_nesting: usize,
 }
 
+impl FunctionInfo {
+    /// The last line of the function
+    pub fn end_line(&self) -> usize {
+        self.start_line + self.line_count.max(1) - 1
+    }
+
+    /// The column, from 1, of the function's name on its first line, or of
+    /// the first code on it if the name isn't there, as for `async block`
+    pub fn name_column(&self, lines: &[&str]) -> usize {
+        let Some(line) = self.start_line.checked_sub(1).and_then(|i| lines.get(i)) else {
+            return 1;
+        };
+        // `Class.method` is declared as `method`
+        let name = self.name.rsplit('.').next().unwrap_or(&self.name);
+        let is_ident = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let start = line
+            .match_indices(name)
+            .map(|(i, _)| i)
+            .find(|&i| !is_ident(line[..i].chars().next_back()) && !is_ident(line[i + name.len()..].chars().next()))
+            .unwrap_or_else(|| line.len() - line.trim_start().len());
+        line[..start].chars().count() + 1
+    }
+}
+
+/// Information about an enum or union type extracted from source code
+#[derive(Debug)]
+pub struct TypeInfo {
+    pub name: String,
+    pub start_line: usize,
+    pub variant_count: usize,
+}
+
+/// A loop construct found inside a function body, or with
+/// `find_control_flow()` any control-flow block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopSpan {
+    /// Index of the loop header line within the body
+    pub start: usize,
+    /// Index of the last line of the loop body (inclusive)
+    pub end: usize,
+    /// Loop nesting level (1 = outermost loop)
+    pub depth: usize,
+}
+
 /// Trait for language-specific parsers
 pub trait LanguageParser: Send + Sync {
-    /// Parse functions/methods from file content
+    /// Parse functions/methods from file content, in order of their first
+    /// line. Functions nested in another may be listed too, within its span.
     fn parse_functions(&self, content: &str) -> Vec<FunctionInfo>;
 
-    /// Check if a path should be skipped for this language
-    fn should_skip(&self, path: &Path) -> bool;
+    /// Directories whose files are skipped for this language, unless the
+    /// root config's `[skip-dirs]` or `--no-default-skips` says otherwise
+    fn skip_dirs(&self) -> &'static [&'static str];
+
+    /// Check if a file outside the skipped directories should be skipped for this language
+    fn should_skip(&self, _path: &Path) -> bool {
+        false
+    }
+
+    /// Parse enum and union type definitions from file content
+    fn parse_types(&self, _content: &str) -> Vec<TypeInfo> {
+        Vec::new()
+    }
+
+    /// Find loops in the lines of a function body
+    fn find_loops(&self, _lines: &[&str]) -> Vec<LoopSpan> {
+        Vec::new()
+    }
+
+    /// Find conditionals, loops, `match`/`switch`, and `try` blocks in the
+    /// lines of a function body; `None` where nesting depth counts every block
+    fn find_control_flow(&self, _lines: &[&str]) -> Option<Vec<LoopSpan>> {
+        None
+    }
+
+    /// Whether a line starts a decorator, annotation, or attribute, which
+    /// `decorators = "include"` counts into the length of the function below
+    fn is_decorator(&self, _line: &str) -> bool {
+        false
+    }
+
+    /// The first line of each decorated function and how many lines above it
+    /// its decorators take, where the parser reads them itself; `None` to
+    /// find them line by line with `is_decorator`
+    fn decorators(&self, _content: &str) -> Option<Vec<(usize, usize)>> {
+        None
+    }
+
+    /// Find test functions and test modules, as first and last line numbers
+    fn test_spans(&self, _content: &str) -> Vec<(usize, usize)> {
+        Vec::new()
+    }
 }
 
 /// Get a parser for a language
@@ -73,7 +253,294 @@ pub fn get_parser(lang: LanguageType) -> Box<dyn LanguageParser> {
         LanguageType::Elixir => Box::new(elixir::ElixirParser),
         LanguageType::Dart => Box::new(dart::DartParser),
         LanguageType::TypeScript => Box::new(typescript::TypeScriptParser),
-        LanguageType::Python => Box::new(python::PythonParser),
+        LanguageType::JavaScript => Box::new(javascript::JavaScriptParser),
+        #[cfg(not(feature = "python-ast"))]
+        LanguageType::Python => Box::new(python::PythonParser::default()),
+        #[cfg(feature = "python-ast")]
+        LanguageType::Python => Box::new(python_ast::PythonAstParser::default()),
         LanguageType::Rust => Box::new(rust_lang::RustParser),
+        LanguageType::Go => Box::new(go::GoParser),
+        LanguageType::Java => Box::new(java::JavaParser),
+        LanguageType::Swift => Box::new(swift::SwiftParser),
+        LanguageType::Terraform => Box::new(terraform::TerraformParser),
+        LanguageType::Julia => Box::new(julia::JuliaParser),
+        LanguageType::Generic => Box::new(generic::GenericParser),
     }
 }
+
+/// At most this many loops are tracked as enclosing a line, so generated code
+/// can't grow the open-loop stacks without bound; loops nested more deeply
+/// are still found, at one more than this depth
+pub(crate) const MAX_OPEN_LOOPS: usize = 100;
+
+/// Find loops in brace-delimited languages.
+///
+/// A loop whose header has no opening brace (on the same or the next line)
+/// is treated as a single-statement loop covering the following line.
+pub(crate) fn find_brace_loops(
+    lines: &[&str],
+    pattern: &Regex,
+    count_braces: fn(&str) -> (i32, i32),
+) -> Vec<LoopSpan> {
+    let mut spans: Vec<LoopSpan> = Vec::new();
+    // Open loops: (index into spans, brace depth before the loop header)
+    let mut open: Vec<(usize, i32)> = Vec::new();
+    // Single-statement loops that may still cover the current line
+    let mut single: Vec<usize> = Vec::new();
+    let mut brace_depth = 0i32;
+
+    for (i, line) in lines.iter().enumerate() {
+        let (opens, closes) = count_braces(line);
+
+        if pattern.is_match(line) {
+            // Enclosing loops: braced loops still open, plus single-statement
+            // loops whose statement is this line
+            single.retain(|&idx| spans[idx].end >= i);
+            let depth = (open.len() + single.len()).min(MAX_OPEN_LOOPS) + 1;
+            // A header ending in `;` holds its statement, as in `if (done) return;`
+            let has_body = opens > 0
+                || lines
+                    .get(i + 1)
+                    .is_some_and(|next| next.trim_start().starts_with('{'));
+            let one_line = !has_body && line.trim_end().ends_with(';');
+            if has_body && open.len() < MAX_OPEN_LOOPS {
+                open.push((spans.len(), brace_depth));
+                spans.push(LoopSpan { start: i, end: i, depth });
+            } else if !has_body && !one_line && single.len() < MAX_OPEN_LOOPS {
+                let end = (i + 1).min(lines.len().saturating_sub(1));
+                // A single-statement loop whose statement is this loop extends over its body
+                for &idx in &single {
+                    if spans[idx].end == i && spans[idx].start < i {
+                        spans[idx].end = end;
+                    }
+                }
+                single.push(spans.len());
+                spans.push(LoopSpan { start: i, end, depth });
+            } else {
+                spans.push(LoopSpan { start: i, end: i, depth });
+            }
+        }
+
+        brace_depth += opens - closes;
+
+        // Close loops whose body has ended on this line
+        while let Some(&(idx, base)) = open.last() {
+            if brace_depth <= base && (i > spans[idx].start || opens > 0) {
+                spans[idx].end = i;
+                open.pop();
+            } else {
+                break;
+            }
+        }
+    }
+
+    // Loops still open at the end of the body run to the last line
+    let last = lines.len().saturating_sub(1);
+    for (idx, _) in open {
+        spans[idx].end = last;
+    }
+
+    spans
+}
+
+/// Get the text between the first `{` at or after line `start` and its matching `}`.
+///
+/// Returns the block contents and the index of the line holding the closing
+/// brace. Strings and `//` comments are skipped when matching braces.
+pub(crate) fn braced_block(lines: &[&str], start: usize) -> Option<(String, usize)> {
+    let mut body = String::new();
+    let mut depth = 0usize;
+    let mut in_string: Option<char> = None;
+
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if let Some(quote) = in_string {
+                if c == '\\' {
+                    body.push(c);
+                    if let Some(next) = chars.next() {
+                        body.push(next);
+                    }
+                    continue;
+                }
+                if c == quote {
+                    in_string = None;
+                }
+            } else if c == '/' && chars.peek() == Some(&'/') {
+                break;
+            } else if c == '"' || c == '`' {
+                in_string = Some(c);
+            } else if c == '{' {
+                depth += 1;
+                if depth == 1 {
+                    continue;
+                }
+            } else if c == '}' && depth > 0 {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((body, i));
+                }
+            }
+            if depth > 0 {
+                body.push(c);
+            }
+        }
+        // Strings do not span lines in the languages using this helper
+        in_string = None;
+        if depth > 0 {
+            body.push('\n');
+        }
+    }
+
+    None
+}
+
+/// The byte index `n` characters after byte index `i` of `line`, or its length
+/// if the line ends first, for lexers that step through a line by characters
+/// without collecting them
+pub(crate) fn char_offset(line: &str, i: usize, n: usize) -> usize {
+    line[i..].char_indices().nth(n).map_or(line.len(), |(offset, _)| i + offset)
+}
+
+/// The classes enclosing a line of a brace-based language, for reporting
+/// methods as `Class.method`; TypeScript also tracks object literals with it
+#[derive(Default)]
+pub(crate) struct ClassScopes {
+    /// Name and body brace depth of each open class, outermost first
+    open: Vec<(String, i32)>,
+}
+
+impl ClassScopes {
+    /// A class whose header and opening brace start at brace depth `depth`
+    pub(crate) fn enter(&mut self, name: &str, depth: i32) {
+        self.open.push((name.to_string(), depth + 1));
+    }
+
+    /// Close the classes whose body a line ending at `depth` has left
+    pub(crate) fn leave(&mut self, depth: i32) {
+        while self.open.last().is_some_and(|(_, body)| *body > depth) {
+            self.open.pop();
+        }
+    }
+
+    /// Whether a line starting at `depth` is directly in a class body, declaring a member
+    pub(crate) fn in_body(&self, depth: i32) -> bool {
+        self.open.last().is_some_and(|(_, body)| *body == depth)
+    }
+
+    /// The class whose body a line starting at `depth` is directly in
+    pub(crate) fn class_at(&self, depth: i32) -> Option<&str> {
+        self.open.last().filter(|(_, body)| *body == depth).map(|(class, _)| class.as_str())
+    }
+
+    /// `name` prefixed with its classes if it is declared directly in a class
+    /// body; unnamed scopes are left out
+    pub(crate) fn qualify(&self, name: String, depth: i32) -> String {
+        if !self.in_body(depth) {
+            return name;
+        }
+        let mut parts: Vec<&str> =
+            self.open.iter().map(|(class, _)| class.as_str()).filter(|class| !class.is_empty()).collect();
+        parts.push(&name);
+        parts.join(".")
+    }
+}
+
+/// The functions open at a line of a brace-based language, innermost last, so
+/// that a nested function doesn't end the one around it
+#[derive(Default)]
+pub(crate) struct FunctionStack {
+    /// Name, start line, brace depth before the header, and deepest nesting
+    open: Vec<(String, usize, i32, usize)>,
+    done: Vec<FunctionInfo>,
+}
+
+impl FunctionStack {
+    /// A function header on line `line_num`, at brace depth `depth` before the line.
+    /// Open functions it isn't nested in end on the line before.
+    pub(crate) fn start(&mut self, name: String, line_num: usize, depth: i32) {
+        self.close(depth, line_num - 1);
+        self.open.push((name, line_num, depth, 0));
+    }
+
+    /// A line after the header lines, with brace depth `depth` after it
+    pub(crate) fn line(&mut self, line_num: usize, depth: i32) {
+        for (_, _, base, max_nesting) in &mut self.open {
+            *max_nesting = (*max_nesting).max((depth - *base).max(0) as usize);
+        }
+        self.close(depth, line_num);
+    }
+
+    /// A function that ended other than by its closing brace, such as a `=>` body
+    pub(crate) fn push(&mut self, func: FunctionInfo) {
+        self.done.push(func);
+    }
+
+    /// End the open functions whose base depth `depth` is back at, on line `end`
+    fn close(&mut self, depth: i32, end: usize) {
+        while self.open.last().is_some_and(|(_, _, base, _)| *base >= depth) {
+            let (name, start, _, max_nesting) = self.open.pop().unwrap();
+            self.done.push(FunctionInfo { name, start_line: start, line_count: end + 1 - start, max_nesting });
+        }
+    }
+
+    /// All functions found, in order of their first line; those still open
+    /// run to the end of a file of `total_lines` lines
+    pub(crate) fn finish(mut self, total_lines: usize) -> Vec<FunctionInfo> {
+        self.close(i32::MIN, total_lines);
+        self.done.sort_by_key(|f| f.start_line);
+        self.done
+    }
+}
+
+/// Leave out the functions nested in another, which are measured as part of
+/// it; `functions` must be in order of their first line
+pub fn drop_nested(functions: &mut Vec<FunctionInfo>) {
+    let mut outer_end = 0;
+    functions.retain(|f| {
+        let nested = f.start_line < outer_end;
+        outer_end = outer_end.max(f.start_line + f.line_count);
+        !nested
+    });
+}
+
+/// Split text on `sep` wherever it appears outside brackets and strings
+pub(crate) fn split_top_level(text: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut in_string: Option<char> = None;
+    let mut escape_next = false;
+    let mut last = 0;
+    let mut prev = ' ';
+
+    for (i, c) in text.char_indices() {
+        let before = std::mem::replace(&mut prev, c);
+        if escape_next {
+            escape_next = false;
+            continue;
+        }
+        if let Some(quote) = in_string {
+            if c == '\\' {
+                escape_next = true;
+            } else if c == quote {
+                in_string = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' | '`' => in_string = Some(c),
+            '(' | '[' | '{' | '<' => depth += 1,
+            // `=>` and `->` are arrows, not closing angle brackets
+            '>' if before == '=' || before == '-' => {}
+            ')' | ']' | '}' | '>' => depth -= 1,
+            _ if c == sep && depth == 0 => {
+                parts.push(&text[last..i]);
+                last = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[last..]);
+
+    parts
+}
@@ -1,123 +1,273 @@
-use super::{FunctionInfo, LanguageParser};
+use super::{FunctionInfo, LanguageParser, LoopSpan, MAX_OPEN_LOOPS};
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::path::Path;
+use std::collections::HashMap;
 
-pub struct PythonParser;
+pub struct PythonParser {
+    /// Columns of indentation per level of nesting, or `None` to detect it per file
+    pub indent_width: Option<usize>,
+}
+
+impl Default for PythonParser {
+    fn default() -> Self {
+        PythonParser { indent_width: Some(4) }
+    }
+}
 
 // Compiled regex pattern for Python function definitions
 static DEF_PATTERN: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^(\s*)(async\s+)?def\s+([a-zA-Z_][a-zA-Z0-9_]*)\s*\(").unwrap()
 });
 
+static CLASS_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*class\s+([a-zA-Z_][a-zA-Z0-9_]*)").unwrap());
+
+static LOOP_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\s*)(async\s+)?(for|while)\b.*:\s*(#.*)?$").unwrap()
+});
+
+// A `case` is part of its `match`, not a block within it
+static CONTROL_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*((async\s+)?(if|elif|else|for|while|try|except|finally)\b|match\s+[^=\s]).*:").unwrap()
+});
+
 impl LanguageParser for PythonParser {
     fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
         let mut functions = Vec::new();
-        let mut in_func = false;
-        let mut func_indent = 0usize;
-        let mut func_name = String::new();
-        let mut func_start = 0usize;
-        let mut max_nesting = 0usize;
-        let mut base_indent = 0usize;
+        // Name, start line, indentation, and deepest nesting of the open defs, innermost last
+        let mut open: Vec<(String, usize, usize, usize)> = Vec::new();
+        let mut classes: Vec<OpenClass> = Vec::new();
+        let width = self.indent_width.unwrap_or_else(|| detect_indent(content)).max(1);
 
         for (line_num, line) in content.lines().enumerate() {
             let line_num = line_num + 1;
+            track_classes(&mut classes, line);
 
-            // Check for function start
-            if let Some(caps) = DEF_PATTERN.captures(line) {
-                // If we were in a function, finish it
-                if in_func && func_start > 0 {
-                    functions.push(FunctionInfo {
-                        name: std::mem::take(&mut func_name),
-                        start_line: func_start,
-                        line_count: line_num - func_start,
-                        max_nesting,
-                    });
-                }
-
-                let indent = caps.get(1).map(|m| m.as_str().len()).unwrap_or(0);
-                func_name = caps.get(3).map(|m| m.as_str().to_string()).unwrap_or_default();
-                func_start = line_num;
-                func_indent = indent;
-                base_indent = indent;
-                in_func = true;
-                max_nesting = 0;
+            // Skip empty lines and comments for determining function end
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
                 continue;
             }
 
-            if in_func {
-                // Skip empty lines and comments for determining function end
-                let trimmed = line.trim();
-                if trimmed.is_empty() || trimmed.starts_with('#') {
-                    continue;
-                }
-
-                let current_indent = measure_indent(line);
-
-                // Function ends when we see a non-empty, non-comment line
-                // with indentation <= function's indentation
-                if current_indent <= func_indent && line_num > func_start {
-                    functions.push(FunctionInfo {
-                        name: std::mem::take(&mut func_name),
-                        start_line: func_start,
-                        line_count: line_num - func_start,
-                        max_nesting,
-                    });
-                    in_func = false;
-                    func_start = 0;
-                    max_nesting = 0;
-
-                    // Check if this line starts a new function
-                    if let Some(caps) = DEF_PATTERN.captures(line) {
-                        let indent = caps.get(1).map(|m| m.as_str().len()).unwrap_or(0);
-                        func_name = caps.get(3).map(|m| m.as_str().to_string()).unwrap_or_default();
-                        func_start = line_num;
-                        func_indent = indent;
-                        base_indent = indent;
-                        in_func = true;
-                        max_nesting = 0;
-                    }
-                    continue;
-                }
-
-                // Track nesting depth based on indentation
-                if current_indent > base_indent {
-                    // Calculate depth: assume 4 spaces per level
-                    let depth = (current_indent - base_indent) / 4;
-                    if depth > max_nesting {
-                        max_nesting = depth;
-                    }
-                }
+            // A function ends before a line at or left of its def's indentation
+            let current_indent = measure_indent(line);
+            while open.last().is_some_and(|(_, _, indent, _)| current_indent <= *indent) {
+                let (name, start, _, max_nesting) = open.pop().unwrap();
+                functions.push(FunctionInfo {
+                    name,
+                    start_line: start,
+                    line_count: line_num - start,
+                    max_nesting,
+                });
+            }
+
+            // Track nesting depth based on indentation, `width` columns per level
+            for (_, _, indent, max_nesting) in &mut open {
+                *max_nesting = (*max_nesting).max((current_indent - *indent) / width);
+            }
+
+            if let Some(caps) = DEF_PATTERN.captures(line) {
+                let indent = caps.get(1).map(|m| m.as_str().len()).unwrap_or(0);
+                open.push((qualified(&classes, &caps[3], indent), line_num, indent, 0));
             }
         }
 
-        // Handle function at end of file
-        if in_func && func_start > 0 {
-            let total_lines = content.lines().count();
+        // Handle functions open at end of file
+        let total_lines = content.lines().count();
+        for (name, start, _, max_nesting) in open {
             functions.push(FunctionInfo {
-                name: func_name,
-                start_line: func_start,
-                line_count: total_lines - func_start + 1,
+                name,
+                start_line: start,
+                line_count: total_lines - start + 1,
                 max_nesting,
             });
         }
 
+        functions.sort_by_key(|f| f.start_line);
         functions
     }
 
-    fn should_skip(&self, path: &Path) -> bool {
-        let path_str = path.to_string_lossy();
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        // Bytecode caches, virtual environments, and installed packages
+        &["__pycache__", ".venv", "venv", "env", ".git", "site-packages"]
+    }
+
+    fn find_loops(&self, lines: &[&str]) -> Vec<LoopSpan> {
+        find_blocks(lines, &LOOP_PATTERN)
+    }
+
+    fn find_control_flow(&self, lines: &[&str]) -> Option<Vec<LoopSpan>> {
+        Some(find_blocks(lines, &CONTROL_PATTERN))
+    }
+
+    fn is_decorator(&self, line: &str) -> bool {
+        line.trim_start().starts_with('@')
+    }
+}
+
+/// Find the blocks whose header matches `pattern`, each ending before the
+/// line that dedents it
+fn find_blocks(lines: &[&str], pattern: &Regex) -> Vec<LoopSpan> {
+    let mut spans: Vec<LoopSpan> = Vec::new();
+    // Open blocks: (index into spans, indentation of the header)
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    let mut last_code = 0;
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        // A line at or left of a block's header indentation ends that block
+        let indent = measure_indent(line);
+        while let Some(&(idx, header_indent)) = open.last() {
+            if indent <= header_indent {
+                spans[idx].end = last_code;
+                open.pop();
+            } else {
+                break;
+            }
+        }
+        last_code = i;
 
-        // Skip common non-source directories
-        path_str.contains("/__pycache__/")
-            || path_str.contains("/.venv/")
-            || path_str.contains("/venv/")
-            || path_str.contains("/env/")
-            || path_str.contains("/.git/")
-            || path_str.contains("/site-packages/")
+        if pattern.is_match(line) {
+            let depth = open.len() + 1;
+            if open.len() < MAX_OPEN_LOOPS {
+                open.push((spans.len(), indent));
+            }
+            spans.push(LoopSpan { start: i, end: i, depth });
+        }
+    }
+    for (idx, _) in open {
+        spans[idx].end = last_code;
     }
+
+    spans
 }
 
 fn measure_indent(line: &str) -> usize {
     line.chars().take_while(|c| c.is_whitespace()).count()
 }
+
+/// The most common step by which a line of code is indented past the one
+/// before it, the smaller on a tie, or 4 if no line is
+fn detect_indent(content: &str) -> usize {
+    let mut steps: HashMap<usize, usize> = HashMap::new();
+    let mut previous = 0;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = measure_indent(line);
+        if indent > previous {
+            *steps.entry(indent - previous).or_default() += 1;
+        }
+        previous = indent;
+    }
+    steps
+        .into_iter()
+        .max_by_key(|&(step, count)| (count, std::cmp::Reverse(step)))
+        .map_or(4, |(step, _)| step)
+}
+
+/// A class whose body hasn't been dedented out of yet
+struct OpenClass {
+    name: String,
+    indent: usize,
+    /// Indentation of the first line of the body, once seen
+    body_indent: Option<usize>,
+}
+
+/// Close the classes a line of code dedents out of, and open the one it declares
+fn track_classes(classes: &mut Vec<OpenClass>, line: &str) {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return;
+    }
+    let indent = measure_indent(line);
+    while classes.last().is_some_and(|c| indent <= c.indent) {
+        classes.pop();
+    }
+    if let Some(class) = classes.last_mut() {
+        class.body_indent.get_or_insert(indent);
+    }
+    if let Some(caps) = CLASS_PATTERN.captures(line) {
+        classes.push(OpenClass {
+            name: caps[1].to_string(),
+            indent,
+            body_indent: None,
+        });
+    }
+}
+
+/// `name` prefixed with its classes, as in `Outer.Inner.method`, if it is
+/// defined directly in a class body
+fn qualified(classes: &[OpenClass], name: &str, indent: usize) -> String {
+    match classes.last() {
+        Some(class) if class.body_indent == Some(indent) => {
+            let mut parts: Vec<&str> = classes.iter().map(|c| c.name.as_str()).collect();
+            parts.push(name);
+            parts.join(".")
+        }
+        _ => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_methods_are_qualified_by_class() {
+        let code = "class Shape:\n    def area(self):\n        return 0\n\n    class Meta:\n        def build(self):\n            pass\n\n    def render(self):\n        pass\n\ndef build():\n    pass\n";
+        let functions = PythonParser::default().parse_functions(code);
+        let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["Shape.area", "Shape.Meta.build", "Shape.render", "build"]);
+
+        // Ranges point at the unqualified name
+        let lines: Vec<&str> = code.lines().collect();
+        let ranges: Vec<(usize, usize)> = functions.iter().map(|f| (f.name_column(&lines), f.end_line())).collect();
+        assert_eq!(ranges, vec![(9, 4), (13, 8), (9, 11), (5, 13)]);
+    }
+
+    #[test]
+    fn test_indent_width() {
+        let two = "def f(xs):\n  for x in xs:\n    if x:\n      print(x)\n";
+        let tabs = "def f(xs):\n\tfor x in xs:\n\t\tif x:\n\t\t\tprint(x)\n";
+        let nesting = |width, code| PythonParser { indent_width: width }.parse_functions(code)[0].max_nesting;
+        assert_eq!(nesting(Some(4), two), 1);
+        assert_eq!(nesting(Some(2), two), 3);
+        assert_eq!(nesting(None, two), 3);
+        assert_eq!(nesting(None, tabs), 3);
+
+        // Continuation lines don't outweigh the blocks
+        let aligned = "def f(a,\n      b):\n    if a:\n        return b\n    return a\n";
+        assert_eq!(detect_indent(aligned), 4);
+        assert_eq!(detect_indent("x = 1\n"), 4);
+    }
+
+    #[test]
+    fn test_inner_defs_do_not_end_outer() {
+        let code = "def outer(items):\n    def key(item):\n        return item.name\n\n    items.sort(key=key)\n    for item in items:\n        print(item)\n\ndef after():\n    pass\n";
+        let mut functions = PythonParser::default().parse_functions(code);
+        let found: Vec<(&str, usize, usize, usize)> = functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.start_line, f.line_count, f.max_nesting))
+            .collect();
+        assert_eq!(found, vec![("outer", 1, 8, 2), ("key", 2, 3, 1), ("after", 9, 2, 1)]);
+
+        crate::languages::drop_nested(&mut functions);
+        let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["outer", "after"]);
+    }
+
+    #[test]
+    fn test_control_flow_blocks() {
+        let code = "def handle(event):\n    match event.kind:\n        case 'click':\n            if event.ok: return\n            try:\n                send(event)\n            except OSError:\n                for attempt in range(3):\n                    retry()\n        case _:\n            match = None\n";
+        let lines: Vec<&str> = code.lines().collect();
+        let spans = PythonParser::default().find_control_flow(&lines).unwrap();
+        let found: Vec<(usize, usize, usize)> = spans.iter().map(|s| (s.start, s.end, s.depth)).collect();
+        assert_eq!(found, vec![(1, 10, 1), (3, 3, 2), (4, 5, 2), (6, 8, 2), (7, 8, 3)]);
+    }
+}
@@ -0,0 +1,594 @@
+//! Python read as statements and the blocks they open, behind the
+//! `python-ast` feature. Function ends, decorators, and nesting come from the
+//! syntax tree of the file's compound statements, so they don't depend on
+//! how wide its indentation is, and a string or bracketed expression that
+//! runs over several lines doesn't end a block.
+
+use super::python::PythonParser;
+use super::{char_offset, FunctionInfo, LanguageParser, LoopSpan, MAX_OPEN_LOOPS};
+use std::ops::Range;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// The Python parser of the `python-ast` feature
+pub struct PythonAstParser {
+    /// Parses the files whose strings, brackets, or indentation don't
+    /// balance, as they wouldn't for a syntax error
+    fallback: PythonParser,
+    /// The file `parse_functions` read last, whose functions' lines the
+    /// checks then pass to `find_loops` and `find_control_flow`
+    last: Mutex<Option<Parsed>>,
+}
+
+impl PythonAstParser {
+    pub fn new(fallback: PythonParser) -> Self {
+        PythonAstParser {
+            fallback,
+            last: Mutex::new(None),
+        }
+    }
+
+    fn last(&self) -> MutexGuard<'_, Option<Parsed>> {
+        self.last.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The blocks of the kinds `wanted` picks among a function's lines, from
+    /// the file they were taken from if it was parsed last; `None` if the
+    /// lines don't parse
+    fn spans(&self, lines: &[&str], wanted: fn(Kind) -> bool) -> Option<Vec<LoopSpan>> {
+        if let Some((blocks, first)) = self.last().as_ref().and_then(|parsed| Some((parsed.blocks.as_ref()?, parsed.locate(lines)?))) {
+            return Some(spans(blocks, first, lines.len(), wanted));
+        }
+        let blocks = blocks(&lines.join("\n"))?;
+        Some(spans(&blocks, 1, lines.len(), wanted))
+    }
+}
+
+impl Default for PythonAstParser {
+    fn default() -> Self {
+        PythonAstParser::new(PythonParser::default())
+    }
+}
+
+impl LanguageParser for PythonAstParser {
+    fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
+        let parsed = Parsed::new(content);
+        let functions = match &parsed.blocks {
+            Some(blocks) => blocks
+                .iter()
+                .filter(|b| b.kind == Kind::Def)
+                .map(|b| FunctionInfo {
+                    name: qualified(blocks, b),
+                    start_line: b.first,
+                    line_count: b.last - b.first + 1,
+                    max_nesting: b.deepest - b.depth,
+                })
+                .collect(),
+            None => self.fallback.parse_functions(content),
+        };
+        *self.last() = Some(parsed);
+        functions
+    }
+
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        self.fallback.skip_dirs()
+    }
+
+    fn find_loops(&self, lines: &[&str]) -> Vec<LoopSpan> {
+        self.spans(lines, Kind::is_loop).unwrap_or_else(|| self.fallback.find_loops(lines))
+    }
+
+    fn find_control_flow(&self, lines: &[&str]) -> Option<Vec<LoopSpan>> {
+        self.spans(lines, Kind::is_control_flow).or_else(|| self.fallback.find_control_flow(lines))
+    }
+
+    fn is_decorator(&self, line: &str) -> bool {
+        self.fallback.is_decorator(line)
+    }
+
+    fn decorators(&self, content: &str) -> Option<Vec<(usize, usize)>> {
+        let decorated = |blocks: &[Block]| {
+            blocks
+                .iter()
+                .filter(|b| b.kind == Kind::Def && b.decorators > 0)
+                .map(|b| (b.first, b.decorators))
+                .collect()
+        };
+        if let Some(parsed) = self.last().as_ref().filter(|parsed| parsed.content == content) {
+            return parsed.blocks.as_deref().map(decorated);
+        }
+        blocks(content).as_deref().map(decorated)
+    }
+}
+
+/// A file's blocks, with its text to find a function's lines in
+struct Parsed {
+    content: String,
+    /// Byte range of each line of `content`
+    lines: Vec<Range<usize>>,
+    /// `None` if the file doesn't parse
+    blocks: Option<Vec<Block>>,
+}
+
+impl Parsed {
+    fn new(content: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for line in content.lines() {
+            lines.push(start..start + line.len());
+            start = content[start..].find('\n').map_or(content.len(), |newline| start + newline + 1);
+        }
+        Parsed {
+            content: content.to_string(),
+            lines,
+            blocks: blocks(content),
+        }
+    }
+
+    fn line(&self, line_num: usize) -> Option<&str> {
+        let range = self.lines.get(line_num.checked_sub(1)?)?;
+        Some(&self.content[range.clone()])
+    }
+
+    /// The line, from 1, of the function whose lines `lines` are; a function
+    /// with the same text elsewhere in the file has the same blocks
+    fn locate(&self, lines: &[&str]) -> Option<usize> {
+        let header = *lines.first()?;
+        self.blocks
+            .as_ref()?
+            .iter()
+            .filter(|b| b.kind == Kind::Def && self.line(b.first) == Some(header))
+            .map(|b| b.first)
+            .find(|&first| lines.iter().enumerate().all(|(i, line)| self.line(first + i) == Some(*line)))
+    }
+}
+
+/// A compound statement, by the keyword of its header
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Def,
+    Class,
+    If,
+    Elif,
+    Else,
+    For,
+    While,
+    Try,
+    Except,
+    Finally,
+    With,
+    Match,
+    Case,
+}
+
+impl Kind {
+    fn from_keyword(word: &str) -> Option<Kind> {
+        Some(match word {
+            "def" => Kind::Def,
+            "class" => Kind::Class,
+            "if" => Kind::If,
+            "elif" => Kind::Elif,
+            "else" => Kind::Else,
+            "for" => Kind::For,
+            "while" => Kind::While,
+            "try" => Kind::Try,
+            "except" => Kind::Except,
+            "finally" => Kind::Finally,
+            "with" => Kind::With,
+            "match" => Kind::Match,
+            "case" => Kind::Case,
+            _ => return None,
+        })
+    }
+
+    fn is_loop(self) -> bool {
+        matches!(self, Kind::For | Kind::While)
+    }
+
+    /// A `case` is part of its `match`, not a block within it
+    fn is_control_flow(self) -> bool {
+        !matches!(self, Kind::Def | Kind::Class | Kind::With | Kind::Case)
+    }
+}
+
+/// A logical line: a simple statement or a compound statement's header,
+/// over one or more physical lines
+struct Statement {
+    /// First and last physical line, from 1
+    first: usize,
+    last: usize,
+    /// Columns of indentation of the first line
+    indent: usize,
+    /// The code, with each string literal reduced to `""` and the comments dropped
+    code: String,
+}
+
+/// The logical lines of `content`; `None` if it ends inside a string,
+/// brackets, or a `\` continuation, or closes a bracket it didn't open
+fn statements(content: &str) -> Option<Vec<Statement>> {
+    let mut statements = Vec::new();
+    let mut current: Option<Statement> = None;
+    // The quote of the string open, and whether it is tripled
+    let mut string: Option<(u8, bool)> = None;
+    let mut depth = 0usize;
+
+    for (i, line) in content.lines().enumerate() {
+        if current.is_none() {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+        }
+        let statement = current.get_or_insert_with(|| Statement {
+            first: i + 1,
+            last: i + 1,
+            indent: measure_indent(line),
+            code: String::new(),
+        });
+
+        // Everything the tokenizer looks for is ASCII, so it steps by byte,
+        // and by whole characters where it copies the code or skips an escape
+        let bytes = line.as_bytes();
+        let mut continued = false;
+        let mut j = 0;
+        while j < bytes.len() {
+            let c = bytes[j];
+            if let Some((quote, triple)) = string {
+                if c == b'\\' {
+                    // An escape, or in a one-quote string, a continuation onto the next line
+                    continued = j + 1 == bytes.len();
+                    j = char_offset(line, j, 2);
+                    continue;
+                }
+                if c == quote && (!triple || (bytes.get(j + 1) == Some(&quote) && bytes.get(j + 2) == Some(&quote))) {
+                    string = None;
+                    j += if triple { 3 } else { 1 };
+                    continue;
+                }
+                j += 1;
+                continue;
+            }
+            match c {
+                b'#' => break,
+                b'\'' | b'"' => {
+                    let triple = bytes.get(j + 1) == Some(&c) && bytes.get(j + 2) == Some(&c);
+                    string = Some((c, triple));
+                    statement.code.push_str("\"\"");
+                    j += if triple { 3 } else { 1 };
+                    continue;
+                }
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' | b'}' => depth = depth.checked_sub(1)?,
+                b'\\' if j + 1 == bytes.len() => continued = true,
+                _ => {}
+            }
+            let next = char_offset(line, j, 1);
+            statement.code.push_str(&line[j..next]);
+            j = next;
+        }
+
+        // A one-quote string ends with its line unless the line continues it
+        if string.is_some_and(|(_, triple)| !triple) && !continued {
+            return None;
+        }
+        statement.code.push(' ');
+        statement.last = i + 1;
+        if string.is_none() && depth == 0 && !continued {
+            statements.extend(current.take());
+        }
+    }
+
+    current.is_none().then_some(statements)
+}
+
+/// Columns of indentation, a tab reaching the next multiple of 8 as in
+/// Python's tokenizer
+fn measure_indent(line: &str) -> usize {
+    let mut columns = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => columns += 1,
+            '\t' => columns = columns / 8 * 8 + 8,
+            '\x0c' => columns = 0,
+            _ => break,
+        }
+    }
+    columns
+}
+
+/// What a logical line is
+enum Line {
+    Decorator,
+    /// A compound statement's header, with the name a `def` or `class`
+    /// defines, and whether its body follows the `:` on the same line
+    Header { kind: Kind, name: String, inline: bool },
+    Simple,
+}
+
+/// Read a logical line's code; `case` is a keyword only directly in a `match`
+fn classify(code: &str, in_match: bool) -> Line {
+    let code = code.trim();
+    if code.starts_with('@') {
+        return Line::Decorator;
+    }
+    let (mut word, mut rest) = split_word(code);
+    let is_async = word == "async";
+    if is_async {
+        (word, rest) = split_word(rest.trim_start());
+    }
+    let Some(kind) = Kind::from_keyword(word) else {
+        return Line::Simple;
+    };
+    let Some(colon) = header_colon(rest) else {
+        return Line::Simple;
+    };
+    let head = rest[..colon].trim();
+    let inline = !rest[colon + 1..].trim().is_empty();
+    let valid = match kind {
+        Kind::Def | Kind::For | Kind::With => true,
+        _ if is_async => false,
+        // Soft keywords, also usable as names, as in `match = pattern.match(line)`
+        Kind::Match | Kind::Case => {
+            let spaced = rest.starts_with(|c: char| c.is_whitespace() || "([{\"-".contains(c));
+            spaced && !head.is_empty() && !head.starts_with('=') && (kind == Kind::Case || !inline)
+        }
+        _ => true,
+    };
+    if !valid || (kind == Kind::Case && !in_match) {
+        return Line::Simple;
+    }
+    let name = match kind {
+        Kind::Def | Kind::Class => split_word(head).0.to_string(),
+        _ => String::new(),
+    };
+    if matches!(kind, Kind::Def | Kind::Class) && name.is_empty() {
+        return Line::Simple;
+    }
+    Line::Header { kind, name, inline }
+}
+
+/// The identifier `code` starts with, and the rest
+fn split_word(code: &str) -> (&str, &str) {
+    let end = code.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(code.len());
+    code.split_at(end)
+}
+
+/// The `:` ending a header, outside brackets, lambdas, and `:=`
+fn header_colon(code: &str) -> Option<usize> {
+    let bytes = code.as_bytes();
+    let is_ident = |i: usize| bytes.get(i).is_some_and(|&b| b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80);
+    let mut depth = 0i32;
+    let mut lambdas = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b':' if depth == 0 && bytes.get(i + 1) == Some(&b'=') => {}
+            b':' if depth == 0 && lambdas > 0 => lambdas -= 1,
+            b':' if depth == 0 => return Some(i),
+            b'l' if depth == 0
+                && code[i..].starts_with("lambda")
+                && (i == 0 || !is_ident(i - 1))
+                && !is_ident(i + "lambda".len()) =>
+            {
+                lambdas += 1
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A compound statement of the file
+struct Block {
+    kind: Kind,
+    /// What a `def` or `class` defines
+    name: String,
+    /// The first line of its header, and the last line of its body
+    first: usize,
+    last: usize,
+    /// How many blocks it is in
+    depth: usize,
+    parent: Option<usize>,
+    /// Lines from the first of its decorators to its header
+    decorators: usize,
+    /// The depth of its deepest statement, past its own for the body
+    deepest: usize,
+}
+
+/// The compound statements of `content`, each after the block it is in; `None`
+/// if the file doesn't tokenize, or a line is indented where no block opens
+/// or dedented to a level no block is at
+fn blocks(content: &str) -> Option<Vec<Block>> {
+    let statements = statements(content)?;
+    let base = statements.first().map_or(0, |s| s.indent);
+    let mut blocks: Vec<Block> = Vec::new();
+    // Open blocks and the indentation of their body, innermost last
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    // A header whose body starts on the next line
+    let mut awaiting: Option<usize> = None;
+    let mut decorated_from: Option<usize> = None;
+
+    for statement in statements {
+        let enclosing = open.last().map_or(base, |&(_, indent)| indent);
+        if let Some(header) = awaiting.take() {
+            if statement.indent <= enclosing {
+                return None;
+            }
+            open.push((header, statement.indent));
+        } else {
+            while open.last().is_some_and(|&(_, indent)| statement.indent < indent) {
+                open.pop();
+            }
+            if statement.indent != open.last().map_or(base, |&(_, indent)| indent) {
+                return None;
+            }
+        }
+
+        let depth = open.len();
+        for &(block, _) in &open {
+            blocks[block].last = statement.last;
+            blocks[block].deepest = blocks[block].deepest.max(depth);
+        }
+        let in_match = open.last().is_some_and(|&(block, _)| blocks[block].kind == Kind::Match);
+        match classify(&statement.code, in_match) {
+            Line::Decorator => {
+                decorated_from.get_or_insert(statement.first);
+            }
+            Line::Header { kind, name, inline } => {
+                let decorators = decorated_from
+                    .take()
+                    .filter(|_| matches!(kind, Kind::Def | Kind::Class))
+                    .map_or(0, |first| statement.first - first);
+                // A body on the header's line is a level in, as on the next
+                if inline {
+                    for &(block, _) in &open {
+                        blocks[block].deepest = blocks[block].deepest.max(depth + 1);
+                    }
+                } else {
+                    awaiting = Some(blocks.len());
+                }
+                blocks.push(Block {
+                    kind,
+                    name,
+                    first: statement.first,
+                    last: statement.last,
+                    depth,
+                    parent: open.last().map(|&(block, _)| block),
+                    decorators,
+                    deepest: depth + usize::from(inline),
+                });
+            }
+            Line::Simple => decorated_from = None,
+        }
+    }
+
+    awaiting.is_none().then_some(blocks)
+}
+
+/// A def's name prefixed with its classes, as in `Outer.Inner.method`, if it
+/// is defined directly in a class body
+fn qualified(blocks: &[Block], def: &Block) -> String {
+    if !def.parent.is_some_and(|p| blocks[p].kind == Kind::Class) {
+        return def.name.clone();
+    }
+    let mut parts = vec![def.name.as_str()];
+    let mut parent = def.parent;
+    while let Some(p) = parent {
+        if blocks[p].kind == Kind::Class {
+            parts.push(&blocks[p].name);
+        }
+        parent = blocks[p].parent;
+    }
+    parts.reverse();
+    parts.join(".")
+}
+
+/// The blocks within the `count` lines from line `first` of the kinds
+/// `wanted` picks, as spans of those lines, each at one more than the number
+/// of those within the lines it is in
+fn spans(blocks: &[Block], first: usize, count: usize, wanted: fn(Kind) -> bool) -> Vec<LoopSpan> {
+    let within = first..first + count;
+    // How many wanted blocks within the lines each block is, or is in
+    let mut levels = vec![0; blocks.len()];
+    let mut spans = Vec::new();
+    for (i, block) in blocks.iter().enumerate() {
+        if !within.contains(&block.first) || !within.contains(&block.last) {
+            continue;
+        }
+        let outer = block.parent.map_or(0, |p| levels[p]);
+        levels[i] = outer + usize::from(wanted(block.kind));
+        if wanted(block.kind) {
+            spans.push(LoopSpan {
+                start: block.first - first,
+                end: block.last - first,
+                depth: outer.min(MAX_OPEN_LOOPS) + 1,
+            });
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(code: &str) -> Vec<(String, usize, usize, usize)> {
+        PythonAstParser::default()
+            .parse_functions(code)
+            .into_iter()
+            .map(|f| (f.name, f.start_line, f.line_count, f.max_nesting))
+            .collect()
+    }
+
+    #[test]
+    fn test_function_boundaries() {
+        let code = "class Shape:\n    def area(self):\n        text = \"\"\"\nat column 0\n\"\"\"\n        return len(\n    text)\n\n    # trailing comment\n\n    @property\n    def name(self): return 'shape'\n\ndef build(): pass\n";
+        assert_eq!(
+            found(code),
+            vec![
+                ("Shape.area".to_string(), 2, 6, 1),
+                ("Shape.name".to_string(), 12, 1, 1),
+                ("build".to_string(), 14, 1, 1),
+            ]
+        );
+
+        let decorated = "@app.route(\n    \"/items)\",\n)\n# a comment\n@login_required\ndef items():\n    pass\n\ndef plain():\n    pass\n";
+        assert_eq!(PythonAstParser::default().decorators(decorated), Some(vec![(6, 5)]));
+    }
+
+    #[test]
+    fn test_nesting_from_blocks() {
+        let spaces = "def f(xs):\n    for x in xs:\n        if x:\n            print(x,\n                  end='')\n";
+        let two = "def f(xs):\n  for x in xs:\n    if x: print(x)\n";
+        let tabs = "def f(xs):\n\tfor x in xs:\n\t\tif x:\n\t\t\tprint(x)\n";
+        for code in [spaces, two, tabs] {
+            assert_eq!(found(code)[0].3, 3, "{}", code);
+        }
+
+        let inner = "def outer(items):\n    def key(item):\n        return item.name\n\n    items.sort(key=key)\n\ndef after():\n    match = None\n    return match\n";
+        assert_eq!(
+            found(inner),
+            vec![
+                ("outer".to_string(), 1, 5, 2),
+                ("key".to_string(), 2, 2, 1),
+                ("after".to_string(), 7, 3, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_control_flow_blocks() {
+        let code = "def handle(event):\n    match event.kind:\n        case 'click':\n            if event.ok: return\n            try:\n                send(event, {'a': 1,\n 'b': 2})\n            except OSError:\n                for attempt in range(3):\n                    retry()\n        case _:\n            match = None\n";
+        let lines: Vec<&str> = code.lines().collect();
+        let parser = PythonAstParser::default();
+        let spans = parser.find_control_flow(&lines).unwrap();
+        let found: Vec<(usize, usize, usize)> = spans.iter().map(|s| (s.start, s.end, s.depth)).collect();
+        assert_eq!(found, vec![(1, 11, 1), (3, 3, 2), (4, 6, 2), (7, 9, 2), (8, 9, 3)]);
+
+        let loops = parser.find_loops(&lines);
+        assert_eq!((loops[0].start, loops[0].depth), (8, 1));
+    }
+
+    #[test]
+    fn test_method_lines_from_parsed_file() {
+        let code = "class Greeter:\n    def greet(self, names):\n        for name in names:\n            print('héllo — ', name)\n";
+        let parser = PythonAstParser::default();
+        let functions = parser.parse_functions(code);
+        assert_eq!(functions[0].name, "Greeter.greet");
+
+        let lines: Vec<&str> = code.lines().skip(1).collect();
+        assert_eq!(parser.last().as_ref().and_then(|parsed| parsed.locate(&lines)), Some(2));
+        let loops = parser.find_loops(&lines);
+        assert_eq!(loops.iter().map(|s| (s.start, s.end, s.depth)).collect::<Vec<_>>(), vec![(1, 2, 1)]);
+    }
+
+    #[test]
+    fn test_unbalanced_source_falls_back() {
+        let code = "def f(:\n    if x:\n        pass\n";
+        assert!(blocks(code).is_none());
+        assert_eq!(found(code), vec![("f".to_string(), 1, 3, 2)]);
+
+        assert!(blocks("def f():\nreturn 1\n").is_none());
+        assert!(blocks("x = 'open\n").is_none());
+    }
+}
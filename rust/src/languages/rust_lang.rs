@@ -1,7 +1,8 @@
-use super::{FunctionInfo, LanguageParser};
+use super::{
+    braced_block, find_brace_loops, split_top_level, FunctionInfo, FunctionStack, LanguageParser, LoopSpan, TypeInfo,
+};
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::path::Path;
 
 pub struct RustParser;
 
@@ -10,135 +11,241 @@ static FN_PATTERN: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^\s*(pub(\([^)]*\))?\s+)?(async\s+)?(unsafe\s+)?fn\s+([a-zA-Z_][a-zA-Z0-9_]*)").unwrap()
 });
 
+// A closure or async block assigned to a variable, e.g. `let handler = move |req| {`
+static LET_BLOCK_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*let\s+(mut\s+)?([a-zA-Z_][a-zA-Z0-9_]*)\s*(:[^=]*)?=\s*(async\s+)?(move\s+)?(\|[^|]*\|\s*(->[^{]*)?)?\{")
+        .unwrap()
+});
+
+static ASYNC_BLOCK_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\basync\s+(move\s+)?\{").unwrap());
+
+// `#[cfg(test)]`, or a test attribute such as `#[test]` or `#[tokio::test(flavor = "multi_thread")]`,
+// which may continue on the following lines
+static TEST_ATTR_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*#\[(cfg\(test\)\]|([a-zA-Z_][a-zA-Z0-9_]*::)*test(\]|\())").unwrap());
+
+static ITEM_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(pub(\([^)]*\))?\s+)?(async\s+)?(unsafe\s+)?(mod|fn|impl)\b").unwrap()
+});
+
+static ENUM_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(pub(\([^)]*\))?\s+)?enum\s+([a-zA-Z_][a-zA-Z0-9_]*)").unwrap()
+});
+
+static LOOP_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*('[a-zA-Z_][a-zA-Z0-9_]*:\s*)?(for\s.*\sin\s|while\s|loop\s*(\{|$))").unwrap()
+});
+
+// Also after `=`, `=>`, or `return`, as in `let kind = match token {`
+static CONTROL_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(.*(=|=>|\breturn)\s*)?('[a-zA-Z_][a-zA-Z0-9_]*:\s*)?(if\b|else\b|match\b|while\b|loop\b|for\s.*\sin\s)").unwrap()
+});
+
 impl LanguageParser for RustParser {
     fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
-        let mut functions = Vec::new();
-        let mut in_func = false;
+        let mut functions = FunctionStack::default();
         let mut brace_depth = 0i32;
-        let mut func_name = String::new();
-        let mut func_start = 0usize;
-        let mut base_depth = 0i32;
-        let mut max_nesting = 0usize;
+        let mut open = Open::None;
 
         for (line_num, line) in content.lines().enumerate() {
             let line_num = line_num + 1;
 
-            // Check for function start
-            if let Some(caps) = FN_PATTERN.captures(line) {
-                // If we were in a function, finish it
-                if in_func && func_start > 0 {
-                    functions.push(FunctionInfo {
-                        name: std::mem::take(&mut func_name),
-                        start_line: func_start,
-                        line_count: line_num - func_start,
-                        max_nesting,
-                    });
-                }
+            let starts_in_text = open != Open::None;
+            let (opens, closes) = count_braces_open(line, &mut open);
 
-                func_name = caps.get(5).map(|m| m.as_str().to_string()).unwrap_or_default();
-                func_start = line_num;
-                in_func = true;
-                base_depth = brace_depth;
-                max_nesting = 0;
-
-                // Count braces on this line
-                let (opens, closes) = count_braces(line);
+            // Check for function start, unless the line is inside a string;
+            // closures and async blocks count once they span lines
+            let name = if starts_in_text {
+                None
+            } else if let Some(caps) = FN_PATTERN.captures(line) {
+                Some(caps.get(5).map(|m| m.as_str().to_string()).unwrap_or_default())
+            } else {
+                (opens > closes).then(|| block_name(line)).flatten()
+            };
+            if let Some(name) = name {
+                functions.start(name, line_num, brace_depth);
                 brace_depth += opens - closes;
                 continue;
             }
 
             // Track braces
-            let (opens, closes) = count_braces(line);
             brace_depth += opens - closes;
+            // Functions end when brace depth returns to their base
+            functions.line(line_num, brace_depth);
+        }
 
-            if in_func {
-                let relative_depth = (brace_depth - base_depth).max(0) as usize;
-                if relative_depth > max_nesting {
-                    max_nesting = relative_depth;
-                }
+        functions.finish(content.lines().count())
+    }
 
-                // Function ends when brace depth returns to base
-                if brace_depth <= base_depth && line_num > func_start {
-                    functions.push(FunctionInfo {
-                        name: std::mem::take(&mut func_name),
-                        start_line: func_start,
-                        line_count: line_num - func_start + 1,
-                        max_nesting,
-                    });
-                    in_func = false;
-                    func_start = 0;
-                    max_nesting = 0;
-                }
-            }
-        }
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        // Build output
+        &["target", ".git"]
+    }
+
+    fn parse_types(&self, content: &str) -> Vec<TypeInfo> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut types = Vec::new();
 
-        // Handle function at end of file
-        if in_func && func_start > 0 {
-            let total_lines = content.lines().count();
-            functions.push(FunctionInfo {
-                name: func_name,
-                start_line: func_start,
-                line_count: total_lines - func_start + 1,
-                max_nesting,
+        for (i, line) in lines.iter().enumerate() {
+            let Some(caps) = ENUM_PATTERN.captures(line) else {
+                continue;
+            };
+            let Some((body, _)) = braced_block(&lines, i) else {
+                continue;
+            };
+            types.push(TypeInfo {
+                name: caps[3].to_string(),
+                start_line: i + 1,
+                variant_count: split_top_level(&body, ',')
+                    .iter()
+                    .filter(|v| !v.trim().is_empty())
+                    .count(),
             });
         }
 
-        functions
+        types
     }
 
-    fn should_skip(&self, path: &Path) -> bool {
-        let path_str = path.to_string_lossy();
+    fn find_loops(&self, lines: &[&str]) -> Vec<LoopSpan> {
+        find_brace_loops(lines, &LOOP_PATTERN, count_braces)
+    }
 
-        // Skip common non-source directories
-        path_str.contains("/target/") || path_str.contains("/.git/")
+    fn find_control_flow(&self, lines: &[&str]) -> Option<Vec<LoopSpan>> {
+        Some(find_brace_loops(lines, &CONTROL_PATTERN, count_braces))
     }
-}
 
-fn count_braces(line: &str) -> (i32, i32) {
-    let mut opens = 0i32;
-    let mut closes = 0i32;
-    let mut in_string = false;
-    let mut in_char = false;
-    let mut escape_next = false;
+    fn is_decorator(&self, line: &str) -> bool {
+        line.trim_start().starts_with("#[")
+    }
 
-    let chars: Vec<char> = line.chars().collect();
-    let mut i = 0;
+    /// The items under a test attribute, through their closing brace; other
+    /// attributes and doc comments may come between
+    fn test_spans(&self, content: &str) -> Vec<(usize, usize)> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut spans = Vec::new();
+        let mut attributed = false;
+        let mut i = 0;
 
-    while i < chars.len() {
-        if escape_next {
-            escape_next = false;
+        while i < lines.len() {
+            let line = lines[i];
+            let item = match TEST_ATTR_PATTERN.find(line) {
+                Some(attr) => {
+                    attributed = true;
+                    // An attribute may share its line with the item, as in `#[cfg(test)] mod tests {`
+                    let rest = &line[attr.end()..];
+                    if attr.as_str().ends_with('(') {
+                        rest.split_once(")]").map_or("", |(_, item)| item)
+                    } else {
+                        rest
+                    }
+                }
+                None => line,
+            };
+            if attributed && ITEM_PATTERN.is_match(item) {
+                attributed = false;
+                // `mod tests;` has its tests in another file
+                if !item.trim_end().ends_with(';') {
+                    if let Some((_, end)) = braced_block(&lines, i) {
+                        spans.push((i + 1, end + 1));
+                        i = end;
+                    }
+                }
+            } else if attributed && (item.trim_end().ends_with(';') || item.contains('{')) {
+                attributed = false;
+            }
             i += 1;
-            continue;
         }
 
-        let c = chars[i];
+        spans
+    }
+}
 
-        // Handle escape
-        if (in_string || in_char) && c == '\\' {
-            escape_next = true;
-            i += 1;
-            continue;
-        }
+/// A string or block comment still open at the end of a line
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Open {
+    None,
+    Str,
+    /// A raw string, closed by `"` and this many `#`
+    Raw(usize),
+    Comment,
+}
 
-        // Check for line comment
-        if !in_string && !in_char && c == '/' && chars.get(i + 1) == Some(&'/') {
-            break;
-        }
+/// The name of a closure or async block opened on a line: its variable's,
+/// or `async block` for one passed along, as to `spawn`
+fn block_name(line: &str) -> Option<String> {
+    if let Some(caps) = LET_BLOCK_PATTERN.captures(line).filter(|caps| caps.get(4).or(caps.get(6)).is_some()) {
+        return Some(caps[2].to_string());
+    }
+    ASYNC_BLOCK_PATTERN.is_match(line).then(|| "async block".to_string())
+}
+
+fn count_braces(line: &str) -> (i32, i32) {
+    count_braces_open(line, &mut Open::None)
+}
+
+/// Count braces outside strings, chars, and comments.
+///
+/// `open` carries a string, raw or not, or a block comment across the lines
+/// it spans.
+fn count_braces_open(line: &str, open: &mut Open) -> (i32, i32) {
+    let mut opens = 0i32;
+    let mut closes = 0i32;
+
+    // Bytes are enough: the delimiters are ASCII, and UTF-8 never uses an
+    // ASCII byte inside a multi-byte character
+    let bytes = line.as_bytes();
+    let mut i = 0;
 
-        // Handle strings and chars
-        if c == '"' && !in_char {
-            in_string = !in_string;
-        } else if c == '\'' && !in_string {
-            in_char = !in_char;
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        match *open {
+            Open::Str => {
+                if c == b'\\' {
+                    i += 1;
+                } else if c == b'"' {
+                    *open = Open::None;
+                }
+                i += 1;
+                continue;
+            }
+            Open::Raw(hashes) => {
+                if c == b'"' && bytes[i + 1..].iter().take(hashes).filter(|&&b| b == b'#').count() == hashes {
+                    *open = Open::None;
+                    i += hashes;
+                }
+                i += 1;
+                continue;
+            }
+            Open::Comment => {
+                if c == b'*' && bytes.get(i + 1) == Some(&b'/') {
+                    *open = Open::None;
+                    i += 1;
+                }
+                i += 1;
+                continue;
+            }
+            Open::None => {}
         }
 
-        // Count braces
-        if !in_string && !in_char {
-            match c {
-                '{' => opens += 1,
-                '}' => closes += 1,
-                _ => {}
+        match c {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => break,
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                *open = Open::Comment;
+                i += 1;
             }
+            // Byte strings are strings with a `b` in front
+            b'"' => *open = Open::Str,
+            b'\'' => i = char_literal_end(line, i),
+            b'r' => {
+                if let Some(hashes) = raw_string_hashes(bytes, i) {
+                    *open = Open::Raw(hashes);
+                    i += hashes + 1;
+                }
+            }
+            b'{' => opens += 1,
+            b'}' => closes += 1,
+            _ => {}
         }
 
         i += 1;
@@ -146,3 +253,173 @@ fn count_braces(line: &str) -> (i32, i32) {
 
     (opens, closes)
 }
+
+/// The closing quote of a char literal starting at `i`, or `i` itself when
+/// the quote starts a lifetime or loop label such as `'a`
+fn char_literal_end(line: &str, i: usize) -> usize {
+    let rest = &line.as_bytes()[i + 1..];
+    if rest.first() == Some(&b'\\') {
+        // An escape such as `'\''` or `'\u{7f}'`: the quote after the escaped character
+        return rest.iter().skip(2).position(|&b| b == b'\'').map_or(i, |p| i + 3 + p);
+    }
+    match line[i + 1..].chars().next() {
+        Some(c) if rest[c.len_utf8()..].first() == Some(&b'\'') => i + 1 + c.len_utf8(),
+        _ => i,
+    }
+}
+
+/// The number of `#` of a raw string whose `r` is at `i`, as in `r"..."`,
+/// `r#"..."#`, or `br"..."`; `None` for an `r` in an identifier or `r#ident`
+fn raw_string_hashes(bytes: &[u8], i: usize) -> Option<usize> {
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let prefix = match i.checked_sub(1).map(|p| bytes[p]) {
+        Some(b'b' | b'c') => i - 1,
+        _ => i,
+    };
+    if prefix > 0 && is_ident(bytes[prefix - 1]) {
+        return None;
+    }
+    let hashes = bytes[i + 1..].iter().take_while(|&&b| b == b'#').count();
+    (bytes.get(i + 1 + hashes) == Some(&b'"')).then_some(hashes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lifetimes_and_chars() {
+        assert_eq!(count_braces("fn get<'a>(s: &'a str) -> &'a str {"), (1, 0));
+        assert_eq!(count_braces("'outer: loop {"), (1, 0));
+        assert_eq!(count_braces("let open = '{'; let close = '}';"), (0, 0));
+        assert_eq!(count_braces(r"if c == '\'' || c == '\u{7b}' || c == b'}' {"), (1, 0));
+        assert_eq!(count_braces("let e = 'é'; {"), (1, 0));
+    }
+
+    #[test]
+    fn test_raw_and_byte_strings() {
+        assert_eq!(count_braces(r##"let json = r#"{"a": "}"}"#; {"##), (1, 0));
+        assert_eq!(count_braces(r#"let s = br"{"; let t = b"}\"{";"#), (0, 0));
+        assert_eq!(count_braces("let r#type = 1; let var = (1); {"), (1, 0));
+        assert_eq!(count_braces(r#"/* "{" */ {"#), (1, 0));
+    }
+
+    #[test]
+    fn test_multiline_strings_do_not_split_functions() {
+        let code = r###"fn first<'a>(s: &'a str) -> &'a str {
+    let template = r#"
+fn fake() {
+"#;
+    let text = "
+    }";
+    s
+}
+
+fn second() {
+    if true {
+    }
+}
+"###;
+        let functions = RustParser.parse_functions(code);
+        let found: Vec<(&str, usize, usize)> = functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.start_line, f.line_count))
+            .collect();
+        assert_eq!(found, vec![("first", 1, 8), ("second", 10, 4)]);
+    }
+
+    #[test]
+    fn test_nested_fns_do_not_end_outer() {
+        let code = "fn outer() {\n    fn helper(x: u32) -> u32 {\n        if x > 0 {\n            x\n        } else {\n            0\n        }\n    }\n    helper(1);\n}\n\nfn after() {}\n";
+        let functions = RustParser.parse_functions(code);
+        let found: Vec<(&str, usize, usize, usize)> = functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.start_line, f.line_count, f.max_nesting))
+            .collect();
+        assert_eq!(found, vec![("outer", 1, 10, 3), ("helper", 2, 7, 2), ("after", 12, 1, 0)]);
+    }
+
+    #[test]
+    fn test_closures_and_async_blocks() {
+        let code = r#"fn serve(rt: &Runtime) {
+    let handler = move |req: Request| -> Response {
+        respond(req)
+    };
+    let double = |x| { x * 2 };
+    let total = {
+        1 + 2
+    };
+    rt.spawn(async move {
+        let fut = async {
+            work().await
+        };
+        fut.await
+    });
+}
+"#;
+        let found: Vec<(String, usize, usize)> =
+            RustParser.parse_functions(code).into_iter().map(|f| (f.name, f.start_line, f.line_count)).collect();
+        let expected = [("serve", 1, 15), ("handler", 2, 3), ("async block", 9, 6), ("fut", 10, 3)];
+        assert_eq!(found, expected.map(|(name, start, count)| (name.to_string(), start, count)));
+    }
+
+    #[test]
+    fn test_test_spans() {
+        let code = r#"pub fn parse() {}
+
+#[test]
+fn parses() {
+    assert!(true);
+}
+
+#[tokio::test(
+    flavor = "multi_thread"
+)]
+/// Runs on a runtime
+async fn serves() {
+}
+
+#[cfg(test)] mod helpers;
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    #[test]
+    fn inner() {}
+}
+
+#[derive(Debug)]
+struct Later;
+fn after() {}
+"#;
+        assert_eq!(RustParser.test_spans(code), vec![(4, 6), (12, 13), (19, 22)]);
+    }
+
+    #[test]
+    fn test_control_flow_depth() {
+        let code = r#"fn rows(items: &[Item]) -> Vec<Row> {
+    let style = Style {
+        border: Border { width: 1 },
+    };
+    items
+        .iter()
+        .map(|item| {
+            let kind = match item.kind {
+                Kind::A => {
+                    if item.ok { row(item, &style) } else { empty() }
+                }
+                _ => empty(),
+            };
+            kind
+        })
+        .collect()
+}
+"#;
+        let functions = RustParser.parse_functions(code);
+        assert_eq!(functions[0].max_nesting, 4);
+        let lines: Vec<&str> = code.lines().collect();
+        let spans = RustParser.find_control_flow(&lines).unwrap();
+        let found: Vec<(usize, usize, usize)> = spans.iter().map(|s| (s.start, s.end, s.depth)).collect();
+        assert_eq!(found, vec![(7, 12, 1), (9, 9, 2)]);
+    }
+}
@@ -0,0 +1,314 @@
+use super::{braced_block, find_brace_loops, split_top_level, FunctionInfo, LanguageParser, LoopSpan, TypeInfo};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+pub struct SwiftParser;
+
+// Functions, initializers, deinitializers, and subscripts, after any
+// attributes and modifiers, e.g. `@objc public static func name(` or
+// `convenience init?(`
+static FUNC_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*(@\w+(\([^)]*\))?\s+)*((public|private|fileprivate|internal|open|package|static|class|final|override|mutating|nonmutating|convenience|required|dynamic|nonisolated|indirect)(\([^)]*\))?\s+)*(func\s+([a-zA-Z_][a-zA-Z0-9_]*|[^\s(<]+)|(init)[?!]?|(deinit)|(subscript))\s*[(<{]"
+    ).unwrap()
+});
+
+static ENUM_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(@\w+\s+)*((public|private|fileprivate|internal|open|package|indirect)\s+)*enum\s+([a-zA-Z_][a-zA-Z0-9_]*)").unwrap()
+});
+
+static LOOP_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(\w+:\s*)?(for\s|while\s|repeat\s*(\{|$))").unwrap());
+
+static CONTROL_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(\w+:\s*)?(if|else|guard|for|while|repeat|switch|do|catch)\b").unwrap());
+
+/// Keywords that may continue a declaration on the following line before its body
+const CONTINUATIONS: &[&str] = &["{", "->", "throws", "rethrows", "async", "where"];
+
+/// Lexer state carried across lines
+#[derive(Default)]
+struct LexState {
+    in_block_comment: bool,
+    in_multiline_string: bool,
+}
+
+/// Braces and parentheses found in the code portion of a line
+#[derive(Default)]
+struct Delimiters {
+    opens: i32,
+    closes: i32,
+    parens: i32,
+}
+
+impl LanguageParser for SwiftParser {
+    fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut functions = Vec::new();
+        let mut state = LexState::default();
+        let mut in_func = false;
+        // Whether the opening brace of the current function has been seen
+        let mut in_body = false;
+        let mut brace_depth = 0i32;
+        let mut paren_depth = 0i32;
+        let mut func_name = String::new();
+        let mut func_start = 0usize;
+        let mut base_depth = 0i32;
+        let mut max_nesting = 0usize;
+
+        for (i, line) in lines.iter().enumerate() {
+            let line_num = i + 1;
+            let at_code = !state.in_block_comment && !state.in_multiline_string;
+            let delims = scan_line(line, &mut state);
+
+            // Closures and nested functions count towards the enclosing function
+            if !in_func && at_code {
+                if let Some(name) = extract_function_name(line) {
+                    func_name = name;
+                    func_start = line_num;
+                    in_func = true;
+                    in_body = false;
+                    paren_depth = 0;
+                    base_depth = brace_depth;
+                    max_nesting = 0;
+                }
+            }
+
+            brace_depth += delims.opens - delims.closes;
+
+            if !in_func {
+                continue;
+            }
+
+            if !in_body {
+                paren_depth += delims.parens;
+                if delims.opens > 0 {
+                    in_body = true;
+                } else {
+                    // Protocol requirements have no body
+                    let continues = paren_depth > 0
+                        || line.trim_end().ends_with("->")
+                        || lines[i + 1..]
+                            .iter()
+                            .find(|l| !l.trim().is_empty())
+                            .is_some_and(|next| CONTINUATIONS.iter().any(|k| next.trim_start().starts_with(k)));
+                    if !continues {
+                        in_func = false;
+                    }
+                    continue;
+                }
+            }
+
+            if line_num > func_start {
+                let relative_depth = (brace_depth - base_depth).max(0) as usize;
+                if relative_depth > max_nesting {
+                    max_nesting = relative_depth;
+                }
+            }
+
+            // Function ends when brace depth returns to base
+            if brace_depth <= base_depth {
+                functions.push(FunctionInfo {
+                    name: std::mem::take(&mut func_name),
+                    start_line: func_start,
+                    line_count: line_num - func_start + 1,
+                    max_nesting,
+                });
+                in_func = false;
+            }
+        }
+
+        // Handle function at end of file
+        if in_func && in_body {
+            functions.push(FunctionInfo {
+                name: func_name,
+                start_line: func_start,
+                line_count: lines.len() - func_start + 1,
+                max_nesting,
+            });
+        }
+
+        functions
+    }
+
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        // Build output and dependencies
+        &[".build", "DerivedData", "Pods", "Carthage", ".git"]
+    }
+
+    fn parse_types(&self, content: &str) -> Vec<TypeInfo> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut types = Vec::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            let Some(caps) = ENUM_PATTERN.captures(line) else {
+                continue;
+            };
+            let Some((body, _)) = braced_block(&lines, i) else {
+                continue;
+            };
+            // Only `case` declarations directly in the enum, not `switch` cases in its methods
+            let variant_count = split_top_level(&body, '\n')
+                .iter()
+                .filter_map(|l| {
+                    let l = l.trim();
+                    l.strip_prefix("indirect ").unwrap_or(l).strip_prefix("case ")
+                })
+                .map(|cases| split_top_level(cases, ',').iter().filter(|c| !c.trim().is_empty()).count())
+                .sum();
+            types.push(TypeInfo {
+                name: caps[4].to_string(),
+                start_line: i + 1,
+                variant_count,
+            });
+        }
+
+        types
+    }
+
+    fn find_loops(&self, lines: &[&str]) -> Vec<LoopSpan> {
+        find_brace_loops(lines, &LOOP_PATTERN, count_braces)
+    }
+
+    fn find_control_flow(&self, lines: &[&str]) -> Option<Vec<LoopSpan>> {
+        Some(find_brace_loops(lines, &CONTROL_PATTERN, count_braces))
+    }
+
+    fn is_decorator(&self, line: &str) -> bool {
+        line.trim_start().starts_with('@')
+    }
+}
+
+fn extract_function_name(line: &str) -> Option<String> {
+    let caps = FUNC_PATTERN.captures(line)?;
+    [7, 8, 9, 10]
+        .into_iter()
+        .find_map(|g| caps.get(g))
+        .map(|m| m.as_str().to_string())
+}
+
+fn count_braces(line: &str) -> (i32, i32) {
+    let delims = scan_line(line, &mut LexState::default());
+    (delims.opens, delims.closes)
+}
+
+/// Count braces and parentheses outside strings and comments
+fn scan_line(line: &str, state: &mut LexState) -> Delimiters {
+    let mut delims = Delimiters::default();
+    let mut in_string = false;
+    let mut escape_next = false;
+
+    let bytes = line.as_bytes();
+    let starts_with = |i: usize, pat: &str| bytes[i..].starts_with(pat.as_bytes());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        if state.in_block_comment {
+            if starts_with(i, "*/") {
+                state.in_block_comment = false;
+                i += 1;
+            }
+            i += 1;
+            continue;
+        }
+
+        if escape_next {
+            escape_next = false;
+            i += 1;
+            continue;
+        }
+
+        if state.in_multiline_string {
+            if c == b'\\' {
+                escape_next = true;
+            } else if starts_with(i, "\"\"\"") {
+                state.in_multiline_string = false;
+                i += 2;
+            }
+            i += 1;
+            continue;
+        }
+
+        if in_string {
+            if c == b'\\' {
+                escape_next = true;
+            } else if c == b'"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        if starts_with(i, "//") {
+            break;
+        }
+        if starts_with(i, "/*") {
+            state.in_block_comment = true;
+            i += 2;
+            continue;
+        }
+        if starts_with(i, "\"\"\"") {
+            state.in_multiline_string = true;
+            i += 3;
+            continue;
+        }
+
+        match c {
+            b'"' => in_string = true,
+            b'{' => delims.opens += 1,
+            b'}' => delims.closes += 1,
+            b'(' => delims.parens += 1,
+            b')' => delims.parens -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    delims
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_functions_closures_and_extensions() {
+        let code = r#"protocol Store {
+    func load() -> Data
+    func save(_ data: Data)
+        throws
+}
+
+extension Cache {
+    @discardableResult
+    public func fetch(
+        key: String
+    ) -> Item? {
+        items.first { item in
+            item.key == key
+        }
+    }
+
+    init?(path: String) {
+        self.path = path
+    }
+}
+"#;
+        let functions = SwiftParser.parse_functions(code);
+        let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["fetch", "init"]);
+        assert_eq!(functions[0].start_line, 9);
+        assert_eq!(functions[0].line_count, 7);
+        assert_eq!(functions[0].max_nesting, 2);
+    }
+
+    #[test]
+    fn test_enum_cases() {
+        let code = "enum Token {\n    case plus, minus\n    indirect case group(Token)\n    var isOp: Bool {\n        switch self {\n        case .plus, .minus: return true\n        default: return false\n        }\n    }\n}\n";
+        let types = SwiftParser.parse_types(code);
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[0].variant_count, 3);
+    }
+}
@@ -0,0 +1,218 @@
+use super::{FunctionInfo, LanguageParser};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Terraform (HCL) parser.
+///
+/// Top-level blocks play the role of functions and are named by their
+/// Terraform address, e.g. `aws_instance.web`, `data.aws_ami.ubuntu`,
+/// `module.vpc`, or `var.region`.
+pub struct TerraformParser;
+
+// Top-level block headers, e.g. `resource "aws_instance" "web" {`, `locals {`
+static BLOCK_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^(resource|data|module|variable|output|provider|locals|terraform|moved|import|check)\b\s*(?:"([^"]*)")?\s*(?:"([^"]*)")?\s*\{"#)
+        .unwrap()
+});
+
+// Heredoc start, e.g. `policy = <<EOF` or `<<-POLICY`
+static HEREDOC_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"<<-?([A-Za-z_][A-Za-z0-9_]*)\s*$").unwrap());
+
+/// Lexer state carried across lines
+#[derive(Default)]
+struct LexState {
+    in_block_comment: bool,
+    /// Terminator of the heredoc being read
+    heredoc: Option<String>,
+}
+
+impl LanguageParser for TerraformParser {
+    fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
+        let mut blocks = Vec::new();
+        let mut state = LexState::default();
+        let mut depth = 0i32;
+        // (name, start line, max nesting) of the open top-level block
+        let mut current: Option<(String, usize, usize)> = None;
+
+        for (line_num, line) in content.lines().enumerate() {
+            let line_num = line_num + 1;
+
+            if let Some(end) = &state.heredoc {
+                if line.trim() == end {
+                    state.heredoc = None;
+                }
+                continue;
+            }
+
+            if depth == 0 && !state.in_block_comment {
+                if let Some(caps) = BLOCK_PATTERN.captures(line) {
+                    let labels: Vec<&str> = [caps.get(2), caps.get(3)]
+                        .into_iter()
+                        .flatten()
+                        .map(|m| m.as_str())
+                        .collect();
+                    current = Some((block_address(&caps[1], &labels), line_num, 0));
+                }
+            }
+
+            let (opens, closes) = count_braces(line, &mut state);
+            depth += opens - closes;
+
+            if let Some((name, start, max_nesting)) = &mut current {
+                // Nested blocks and objects; the block's own attributes are at depth 1
+                if line_num > *start {
+                    *max_nesting = (*max_nesting).max(depth.max(0) as usize);
+                }
+                if depth <= 0 {
+                    blocks.push(FunctionInfo {
+                        name: std::mem::take(name),
+                        start_line: *start,
+                        line_count: line_num - *start + 1,
+                        max_nesting: *max_nesting,
+                    });
+                    current = None;
+                }
+            }
+            depth = depth.max(0);
+        }
+
+        // Unterminated block at end of file
+        if let Some((name, start, max_nesting)) = current {
+            blocks.push(FunctionInfo {
+                name,
+                start_line: start,
+                line_count: content.lines().count() - start + 1,
+                max_nesting,
+            });
+        }
+
+        blocks
+    }
+
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        // Downloaded modules and providers
+        &[".terraform", ".git"]
+    }
+}
+
+/// Terraform address of a top-level block
+fn block_address(kind: &str, labels: &[&str]) -> String {
+    match (kind, labels) {
+        ("resource", [ty, name]) => format!("{}.{}", ty, name),
+        ("data", [ty, name]) => format!("data.{}.{}", ty, name),
+        ("variable", [name]) => format!("var.{}", name),
+        (kind, []) => kind.to_string(),
+        (kind, labels) => format!("{}.{}", kind, labels.join(".")),
+    }
+}
+
+/// Count braces on a line, skipping strings, comments, and heredoc bodies.
+///
+/// Braces inside `${...}` interpolations are part of the string.
+fn count_braces(line: &str, state: &mut LexState) -> (i32, i32) {
+    let bytes = line.as_bytes();
+    let mut opens = 0;
+    let mut closes = 0;
+    let mut in_string = false;
+    let mut interpolation = 0usize;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        let next = bytes.get(i + 1).copied();
+
+        if state.in_block_comment {
+            if c == b'*' && next == Some(b'/') {
+                state.in_block_comment = false;
+                i += 1;
+            }
+        } else if in_string {
+            match c {
+                b'\\' => i += 1,
+                b'$' | b'%' if next == Some(b'{') => {
+                    interpolation += 1;
+                    i += 1;
+                }
+                b'}' if interpolation > 0 => interpolation -= 1,
+                b'"' if interpolation == 0 => in_string = false,
+                _ => {}
+            }
+        } else {
+            match c {
+                b'#' => break,
+                b'/' if next == Some(b'/') => break,
+                b'/' if next == Some(b'*') => {
+                    state.in_block_comment = true;
+                    i += 1;
+                }
+                b'"' => in_string = true,
+                b'{' => opens += 1,
+                b'}' => closes += 1,
+                _ => {}
+            }
+        }
+        i += 1;
+    }
+
+    if !state.in_block_comment {
+        if let Some(caps) = HEREDOC_PATTERN.captures(line) {
+            state.heredoc = Some(caps[1].to_string());
+        }
+    }
+
+    (opens, closes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_addresses() {
+        let code = r#"
+resource "aws_instance" "web" {
+  ami = "ami-123"
+}
+
+data "aws_ami" "ubuntu" {
+  most_recent = true
+}
+
+module "vpc" {
+  source = "./modules/vpc"
+}
+
+variable "region" {}
+"#;
+        let blocks = TerraformParser.parse_functions(code);
+        let names: Vec<&str> = blocks.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["aws_instance.web", "data.aws_ami.ubuntu", "module.vpc", "var.region"]);
+        assert_eq!(blocks[0].start_line, 2);
+        assert_eq!(blocks[0].line_count, 3);
+        assert_eq!(blocks[3].line_count, 1);
+    }
+
+    #[test]
+    fn test_heredoc_and_interpolation_braces_ignored() {
+        let code = r#"resource "aws_iam_policy" "p" {
+  name   = "${var.prefix}-policy"
+  policy = <<EOF
+{
+  "Statement": [{ "Effect": "Allow" }]
+}
+EOF
+
+  dynamic "tag" {
+    for_each = var.tags
+    content {
+      key = tag.key
+    }
+  }
+}
+"#;
+        let blocks = TerraformParser.parse_functions(code);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].line_count, 15);
+        assert_eq!(blocks[0].max_nesting, 3);
+    }
+}
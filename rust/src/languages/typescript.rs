@@ -1,4 +1,7 @@
-use super::{FunctionInfo, LanguageParser};
+use super::{
+    braced_block, find_brace_loops, split_top_level, ClassScopes, FunctionInfo, FunctionStack, LanguageParser, LoopSpan,
+    TypeInfo,
+};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::path::Path;
@@ -14,102 +17,204 @@ static ARROW_PATTERN: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^\s*(export\s+)?(const|let|var)\s+([a-zA-Z_][a-zA-Z0-9_]*)\s*[=:].*=>").unwrap()
 });
 
+static CLASS_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(export\s+)?(default\s+)?(abstract\s+)?class\s+([a-zA-Z_$][a-zA-Z0-9_$]*)").unwrap()
+});
+
+// A method or an arrow-function property, directly in a class body
+static METHOD_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*((public|private|protected|static|async|override|readonly|abstract|get|set)\s+)*\*?\s*(#?[a-zA-Z_$][a-zA-Z0-9_$]*)\s*(<[^>]*>\s*)?(\(|(:[^=]*)?=\s*(async\s+)?(\(|[a-zA-Z_$][a-zA-Z0-9_$]*\s*=>))",
+    )
+    .unwrap()
+});
+
+// A line ending in a brace that opens an object literal
+static OBJECT_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"([=(,:?\[]|\breturn|\bdefault)\s*\{\s*(//.*)?$").unwrap()
+});
+
+// Blocks and type literals that look like one
+static NOT_OBJECT_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(case\b|default\s*:|(export\s+)?(declare\s+)?type\s)").unwrap()
+});
+
+static NAMED_OBJECT_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(((export\s+)?(const|let|var)\s+([a-zA-Z_$][a-zA-Z0-9_$]*)\s*(:[^=]*)?=)|([a-zA-Z_$][a-zA-Z0-9_$]*)\s*:)\s*\{")
+        .unwrap()
+});
+
+// `key: function (...) {` or `key: (...) => {`, in an object literal
+static PROPERTY_FUNCTION_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*([a-zA-Z_$][a-zA-Z0-9_$]*)\s*:\s*(async\s+)?(function\b|(\([^)]*\)|[a-zA-Z_$][a-zA-Z0-9_$]*)\s*(:[^=]*)?=>)",
+    )
+    .unwrap()
+});
+
+static ENUM_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(export\s+)?(declare\s+)?(const\s+)?enum\s+([a-zA-Z_$][a-zA-Z0-9_$]*)").unwrap()
+});
+
+static UNION_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(export\s+)?(declare\s+)?type\s+([a-zA-Z_$][a-zA-Z0-9_$]*)\s*(<[^=]*>)?\s*=(.*)$").unwrap()
+});
+
+static LOOP_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(for\s*(await\s*)?\(|while\s*\(|do\s*(\{|$))").unwrap()
+});
+
+static CONTROL_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(if|else|for|while|do|switch|try|catch|finally)\b").unwrap());
+
 impl LanguageParser for TypeScriptParser {
     fn parse_functions(&self, content: &str) -> Vec<FunctionInfo> {
-        let mut functions = Vec::new();
-        let mut in_func = false;
+        let mut functions = FunctionStack::default();
         let mut brace_depth = 0i32;
-        let mut func_name = String::new();
-        let mut func_start = 0usize;
-        let mut base_depth = 0i32;
-        let mut max_nesting = 0usize;
+        let mut classes = ClassScopes::default();
+        // Object literals, named by the variable or property they are assigned to
+        let mut objects = ClassScopes::default();
+        let mut open = Vec::new();
 
         for (line_num, line) in content.lines().enumerate() {
             let line_num = line_num + 1;
+            // A line starting in a template literal or comment starts no class or function
+            let starts_in_text = matches!(open.last(), Some(Open::Template | Open::Comment));
+
+            if let Some(caps) = CLASS_PATTERN.captures(line).filter(|_| line.contains('{') && !starts_in_text) {
+                classes.enter(&caps[4], brace_depth);
+            }
 
             // Check for function start
-            let name = extract_function_name(line);
-            if let Some(name) = name {
-                // If we were in a function, finish it
-                if in_func && func_start > 0 {
-                    functions.push(FunctionInfo {
-                        name: std::mem::take(&mut func_name),
-                        start_line: func_start,
-                        line_count: line_num - func_start,
-                        max_nesting,
-                    });
-                }
+            let name = if starts_in_text {
+                None
+            } else {
+                extract_function_name(line)
+                    .or_else(|| classes.in_body(brace_depth).then(|| extract_method_name(line)).flatten())
+                    .map(|name| classes.qualify(name, brace_depth))
+                    .or_else(|| {
+                        objects
+                            .in_body(brace_depth)
+                            .then(|| extract_object_method_name(line))
+                            .flatten()
+                            .map(|name| objects.qualify(name, brace_depth))
+                    })
+            };
 
-                func_name = name;
-                func_start = line_num;
-                in_func = true;
-                base_depth = brace_depth;
-                max_nesting = 0;
+            // Track braces
+            let (opens, closes) = count_braces_open(line, &mut open);
+            let line_start_depth = brace_depth;
+            brace_depth += opens - closes;
+            classes.leave(brace_depth);
+            objects.leave(brace_depth);
+            if !starts_in_text && OBJECT_PATTERN.is_match(line) && !NOT_OBJECT_PATTERN.is_match(line) {
+                objects.enter(&object_name(line), brace_depth - 1);
+            }
 
-                // Count braces on this line
-                let (opens, closes) = count_braces(line);
-                brace_depth += opens - closes;
+            if let Some(name) = name {
+                functions.start(name, line_num, line_start_depth);
+                // A body closed on the header line, e.g. `get size() { return 0; },`
+                if opens > 0 && brace_depth <= line_start_depth {
+                    functions.line(line_num, brace_depth);
+                }
                 continue;
             }
 
-            // Track braces
-            let (opens, closes) = count_braces(line);
-            brace_depth += opens - closes;
+            // Functions end when brace depth returns to their base
+            functions.line(line_num, brace_depth);
+        }
 
-            if in_func {
-                let relative_depth = (brace_depth - base_depth).max(0) as usize;
-                if relative_depth > max_nesting {
-                    max_nesting = relative_depth;
-                }
+        functions.finish(content.lines().count())
+    }
 
-                // Function ends when brace depth returns to base
-                if brace_depth <= base_depth && line_num > func_start {
-                    functions.push(FunctionInfo {
-                        name: std::mem::take(&mut func_name),
-                        start_line: func_start,
-                        line_count: line_num - func_start + 1,
-                        max_nesting,
+    fn skip_dirs(&self) -> &'static [&'static str] {
+        // Dependencies and build output
+        &["node_modules", "dist", "build", ".git"]
+    }
+
+    fn should_skip(&self, path: &Path) -> bool {
+        // Skip type definition files
+        path.to_string_lossy().ends_with(".d.ts")
+    }
+
+    fn parse_types(&self, content: &str) -> Vec<TypeInfo> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut types = Vec::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            if let Some(caps) = ENUM_PATTERN.captures(line) {
+                if let Some((body, _)) = braced_block(&lines, i) {
+                    types.push(TypeInfo {
+                        name: caps[4].to_string(),
+                        start_line: i + 1,
+                        variant_count: split_top_level(&body, ',')
+                            .iter()
+                            .filter(|v| !v.trim().is_empty())
+                            .count(),
+                    });
+                }
+            } else if let Some(caps) = UNION_PATTERN.captures(line) {
+                let definition = type_alias_definition(&lines, i, &caps[5]);
+                let members = split_top_level(definition.trim().trim_end_matches(';'), '|')
+                    .iter()
+                    .filter(|m| !m.trim().is_empty())
+                    .count();
+                if members > 1 {
+                    types.push(TypeInfo {
+                        name: caps[3].to_string(),
+                        start_line: i + 1,
+                        variant_count: members,
                     });
-                    in_func = false;
-                    func_start = 0;
-                    max_nesting = 0;
                 }
             }
         }
 
-        // Handle function at end of file
-        if in_func && func_start > 0 {
-            let total_lines = content.lines().count();
-            functions.push(FunctionInfo {
-                name: func_name,
-                start_line: func_start,
-                line_count: total_lines - func_start + 1,
-                max_nesting,
-            });
-        }
+        types
+    }
 
-        functions
+    fn find_loops(&self, lines: &[&str]) -> Vec<LoopSpan> {
+        find_brace_loops(lines, &LOOP_PATTERN, count_braces)
     }
 
-    fn should_skip(&self, path: &Path) -> bool {
-        let path_str = path.to_string_lossy();
-
-        // Skip common non-source directories
-        if path_str.contains("/node_modules/")
-            || path_str.contains("/dist/")
-            || path_str.contains("/build/")
-            || path_str.contains("/.git/")
-        {
-            return true;
-        }
+    fn find_control_flow(&self, lines: &[&str]) -> Option<Vec<LoopSpan>> {
+        Some(find_brace_loops(lines, &CONTROL_PATTERN, count_braces))
+    }
 
-        // Skip type definition files
-        if path_str.ends_with(".d.ts") {
-            return true;
-        }
+    fn is_decorator(&self, line: &str) -> bool {
+        line.trim_start().starts_with('@')
+    }
+}
 
-        false
+/// Collect the right-hand side of a `type X = ...` alias that may span several lines
+fn type_alias_definition(lines: &[&str], start: usize, first: &str) -> String {
+    let strip_comment = |s: &str| s.split("//").next().unwrap_or("").to_string();
+    let mut text = strip_comment(first);
+    let mut i = start;
+
+    loop {
+        let trimmed = text.trim_end();
+        let (opens, closes) = count_braces(&text);
+        let parens = text.matches(['(', '[']).count() as i32 - text.matches([')', ']']).count() as i32;
+        let complete = trimmed.ends_with(';')
+            || (!trimmed.is_empty()
+                && !trimmed.ends_with('|')
+                && !trimmed.ends_with('=')
+                && !trimmed.ends_with('&')
+                && opens == closes
+                && parens <= 0);
+        let continues = lines
+            .get(i + 1)
+            .is_some_and(|next| next.trim_start().starts_with('|'));
+
+        if (complete && !continues) || trimmed.ends_with(';') || i + 1 >= lines.len() {
+            break;
+        }
+        i += 1;
+        text.push('\n');
+        text.push_str(&strip_comment(lines[i]));
     }
+
+    text
 }
 
 fn extract_function_name(line: &str) -> Option<String> {
@@ -137,54 +242,121 @@ fn extract_function_name(line: &str) -> Option<String> {
     None
 }
 
-fn count_braces(line: &str) -> (i32, i32) {
-    let mut opens = 0i32;
-    let mut closes = 0i32;
-    let mut in_string = false;
-    let mut in_char = false;
-    let mut escape_next = false;
+/// The name of a method declared on a line of a class body
+fn extract_method_name(line: &str) -> Option<String> {
+    // Overload signatures and abstract methods have no body
+    if line.trim_end().ends_with(';') || (line.contains("=>") && !line.contains('{')) {
+        return None;
+    }
+    let name = METHOD_PATTERN.captures(line)?.get(3)?.as_str();
+    (!matches!(name, "if" | "for" | "while" | "switch" | "catch" | "return")).then(|| name.to_string())
+}
 
-    let chars: Vec<char> = line.chars().collect();
-    let mut i = 0;
+/// The name of a method declared on a line of an object literal body
+fn extract_object_method_name(line: &str) -> Option<String> {
+    // Type literals declare methods without a body
+    if !line.contains('{') || line.trim_end().ends_with(';') {
+        return None;
+    }
+    match PROPERTY_FUNCTION_PATTERN.captures(line) {
+        Some(caps) => Some(caps[1].to_string()),
+        // Shorthand, e.g. `async save(id) {` or `get size() {`
+        None => extract_method_name(line),
+    }
+}
 
-    while i < chars.len() {
-        if escape_next {
-            escape_next = false;
-            i += 1;
-            continue;
-        }
+/// The variable or property an object literal opened on this line is
+/// assigned to; empty for one passed or returned
+fn object_name(line: &str) -> String {
+    NAMED_OBJECT_PATTERN
+        .captures(line)
+        .and_then(|caps| caps.get(5).or(caps.get(7)))
+        .map_or_else(String::new, |m| m.as_str().to_string())
+}
 
-        let c = chars[i];
+/// What is open at the end of a line and may span the next ones
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Open {
+    /// The text of a template literal
+    Template,
+    /// A `${}` interpolation in a template literal, with this many braces of its own open
+    Interpolation(usize),
+    Comment,
+}
 
-        // Handle escape in strings
-        if (in_string || in_char) && c == '\\' {
-            escape_next = true;
-            i += 1;
-            continue;
-        }
+fn count_braces(line: &str) -> (i32, i32) {
+    count_braces_open(line, &mut Vec::new())
+}
 
-        // Check for line comment
-        if !in_string && !in_char && c == '/' && chars.get(i + 1) == Some(&'/') {
-            break; // Rest of line is comment
-        }
+/// Count braces outside strings and comments.
+///
+/// `open` holds the template literals, interpolations, and block comment
+/// open at the start of the line, innermost last, and those still open at
+/// its end. Braces of code in an interpolation count; the `${` and `}`
+/// around it don't.
+fn count_braces_open(line: &str, open: &mut Vec<Open>) -> (i32, i32) {
+    let mut opens = 0i32;
+    let mut closes = 0i32;
+    // Quoted strings end with their line
+    let mut quote: Option<u8> = None;
 
-        // Handle strings
-        if c == '"' && !in_char {
-            in_string = !in_string;
-        } else if c == '\'' && !in_string {
-            in_char = !in_char;
-        } else if c == '`' && !in_char && !in_string {
-            // Template literals - simplified handling
-            in_string = !in_string;
-        }
+    let bytes = line.as_bytes();
+    let mut i = 0;
 
-        // Count braces
-        if !in_string && !in_char {
-            match c {
-                '{' => opens += 1,
-                '}' => closes += 1,
+    while i < bytes.len() {
+        let c = bytes[i];
+        let next = bytes.get(i + 1).copied();
+
+        match (open.last_mut(), quote) {
+            (Some(Open::Comment), _) => {
+                if c == b'*' && next == Some(b'/') {
+                    open.pop();
+                    i += 1;
+                }
+            }
+            (Some(Open::Template), _) => match c {
+                b'\\' => i += 1,
+                b'`' => {
+                    open.pop();
+                }
+                b'$' if next == Some(b'{') => {
+                    *open.last_mut().unwrap() = Open::Interpolation(0);
+                    i += 1;
+                }
                 _ => {}
+            },
+            (_, Some(q)) => {
+                if c == b'\\' {
+                    i += 1;
+                } else if c == q {
+                    quote = None;
+                }
             }
+            (interpolation, None) => match c {
+                b'/' if next == Some(b'/') => break, // Rest of line is comment
+                b'/' if next == Some(b'*') => {
+                    open.push(Open::Comment);
+                    i += 1;
+                }
+                b'/' if starts_regex(&bytes[..i]) => i = regex_end(bytes, i),
+                b'"' | b'\'' => quote = Some(c),
+                b'`' => open.push(Open::Template),
+                b'{' => {
+                    if let Some(Open::Interpolation(depth)) = interpolation {
+                        *depth += 1;
+                    }
+                    opens += 1;
+                }
+                b'}' => match interpolation {
+                    Some(Open::Interpolation(0)) => *open.last_mut().unwrap() = Open::Template,
+                    Some(Open::Interpolation(depth)) => {
+                        *depth -= 1;
+                        closes += 1;
+                    }
+                    _ => closes += 1,
+                },
+                _ => {}
+            },
         }
 
         i += 1;
@@ -193,6 +365,28 @@ fn count_braces(line: &str) -> (i32, i32) {
     (opens, closes)
 }
 
+/// Whether a `/` after `before` starts a regex literal rather than dividing
+fn starts_regex(before: &[u8]) -> bool {
+    before.iter().rev().find(|b| !b.is_ascii_whitespace()).is_none_or(|b| b"(,=:[!&|?{};".contains(b))
+}
+
+/// The closing `/` of the regex literal starting at `i`, or the end of the line
+fn regex_end(bytes: &[u8], mut i: usize) -> usize {
+    let mut in_class = false;
+    i += 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => in_class = true,
+            b']' => in_class = false,
+            b'/' if !in_class => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,10 +418,152 @@ const greet = () => {
         assert_eq!(functions[0].name, "greet");
     }
 
+    #[test]
+    fn test_closures_in_functions() {
+        let code = r#"
+function setup(items) {
+    const render = (item) => {
+        return item.name;
+    };
+    items.forEach(render);
+    return items.length;
+}
+"#;
+        let functions = TypeScriptParser.parse_functions(code);
+        let found: Vec<(&str, usize, usize)> =
+            functions.iter().map(|f| (f.name.as_str(), f.start_line, f.line_count)).collect();
+        assert_eq!(found, vec![("setup", 2, 7), ("render", 3, 3)]);
+    }
+
+    #[test]
+    fn test_object_literal_methods() {
+        let code = r#"
+export const api = {
+    baseUrl: "/v1",
+    async fetch(id: string): Promise<Item> {
+        return get(id);
+    },
+    get size() { return 0; },
+    save: function (item: Item) {
+        return post(item);
+    },
+    nested: {
+        onClick: (event: Event) => {
+            event.preventDefault();
+        },
+    },
+};
+
+export default {
+    data() {
+        return { open: false };
+    },
+};
+
+type Handlers = {
+    click(e: Event): void
+};
+"#;
+        let functions = TypeScriptParser.parse_functions(code);
+        let found: Vec<(&str, usize)> = functions.iter().map(|f| (f.name.as_str(), f.line_count)).collect();
+        assert_eq!(
+            found,
+            vec![("api.fetch", 3), ("api.size", 1), ("api.save", 3), ("api.nested.onClick", 3), ("data", 3)]
+        );
+    }
+
+    #[test]
+    fn test_class_methods() {
+        let code = r#"
+export class Widget extends Base {
+    private count = 0;
+    abstract size(): number;
+
+    constructor(private readonly name: string) {
+        super();
+    }
+
+    async render(): Promise<void> {
+        if (this.count) {
+            return;
+        }
+    }
+
+    handleClick = (event: Event) => {
+        this.count++;
+    }
+}
+
+function render() {
+    return 1;
+}
+"#;
+        let functions = TypeScriptParser.parse_functions(code);
+        let found: Vec<(&str, usize)> = functions.iter().map(|f| (f.name.as_str(), f.line_count)).collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Widget.constructor", 3),
+                ("Widget.render", 5),
+                ("Widget.handleClick", 3),
+                ("render", 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_union_and_enum_variants() {
+        let parser = TypeScriptParser;
+        let code = r#"
+export type Status =
+    | "idle"
+    | "loading" // in flight
+    | { kind: "error"; message: string };
+
+type Point = { x: number; y: number };
+
+enum Color { Red, Green, Blue }
+"#;
+        let types = parser.parse_types(code);
+        let counts: Vec<(&str, usize)> = types.iter().map(|t| (t.name.as_str(), t.variant_count)).collect();
+        assert_eq!(counts, vec![("Status", 3), ("Color", 3)]);
+    }
+
     #[test]
     fn test_count_braces() {
         assert_eq!(count_braces("function foo() {"), (1, 0));
         assert_eq!(count_braces("}"), (0, 1));
         assert_eq!(count_braces("let x = \"{}\";"), (0, 0)); // braces in string
+        assert_eq!(count_braces("const s = `a { ${b ? `${c}}` : { d: 1 }.d} \\${`;"), (1, 1));
+        assert_eq!(count_braces("if (/[{`/]/.test(s) && a / b > 1) { /* } */"), (1, 0));
+    }
+
+    #[test]
+    fn test_multiline_template_literals() {
+        let code = r#"function render(items: Item[]) {
+    const html = `
+        <ul class="{list}">
+        function fake() {
+        ${items.map((item) => {
+            return `<li>${item.name}}</li>`;
+        }).join("")}
+        </ul>`;
+    return html;
+}
+
+/**
+ * Quote names with a ` (backtick) first.
+ */
+function after() {
+    return `}`;
+}
+"#;
+        let found: Vec<(String, usize, usize)> = TypeScriptParser
+            .parse_functions(code)
+            .into_iter()
+            .map(|f| (f.name, f.start_line, f.line_count))
+            .collect();
+        let expected = [("render", 1, 10), ("after", 15, 3)];
+        assert_eq!(found, expected.map(|(name, start, count)| (name.to_string(), start, count)));
     }
 }
@@ -0,0 +1,485 @@
+//! Detect code smells (long files and functions, deep nesting, nested loops,
+//! and more) across multiple programming languages.
+//!
+//! The `code-smells` binary is a thin layer over this crate. To embed the
+//! analysis in another tool, call [`analyze`] on a project directory:
+//!
+//! ```no_run
+//! # #[cfg(feature = "fs")]
+//! # fn main() -> Result<(), String> {
+//! use std::path::Path;
+//!
+//! let report = code_smells::analyze(Path::new("."), &code_smells::Config::default())?;
+//! for issue in &report.issues {
+//!     println!("{:?} {}", issue.severity, issue.message);
+//! }
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "fs"))]
+//! # fn main() {}
+//! ```
+//!
+//! Without the default `fs` feature there is no directory walking, and the
+//! crate builds for `wasm32-unknown-unknown`. Pass the sources in instead:
+//!
+//! ```
+//! use code_smells::{analyze_sources, Config, SourceFile};
+//!
+//! let files: Vec<SourceFile> = [("src/app.py", "def main():\n    pass\n")]
+//!     .into_iter()
+//!     .filter_map(|(path, content)| SourceFile::in_memory(path, content))
+//!     .collect();
+//! let report = analyze_sources(&files, &Config::default());
+//! assert_eq!(report.files_scanned, 1);
+//! ```
+//!
+//! New languages implement [`LanguageParser`]; see DEVELOPER.md.
+
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod checks;
+pub mod config;
+pub mod detect;
+pub mod languages;
+pub mod options;
+pub mod output;
+pub mod scan;
+#[cfg(test)]
+mod test_support;
+
+// Used by the binary's subcommands and output modes; not a stable API
+#[doc(hidden)]
+pub mod archive;
+#[doc(hidden)]
+pub mod baseline;
+#[doc(hidden)]
+pub mod compare;
+#[doc(hidden)]
+pub mod dashboard;
+#[doc(hidden)]
+pub mod effective;
+#[doc(hidden)]
+pub mod explain;
+#[doc(hidden)]
+pub mod git;
+#[doc(hidden)]
+pub mod history;
+#[doc(hidden)]
+pub mod init;
+#[doc(hidden)]
+pub mod inventory;
+#[doc(hidden)]
+pub mod manifest;
+#[doc(hidden)]
+pub mod merge;
+#[doc(hidden)]
+pub mod scratch;
+#[doc(hidden)]
+pub mod stats;
+#[doc(hidden)]
+pub mod top;
+#[doc(hidden)]
+pub mod trace;
+#[cfg(unix)]
+#[doc(hidden)]
+pub mod tui;
+#[doc(hidden)]
+pub mod whatif;
+
+pub use checks::CustomCheck;
+pub use options::{CheckSelection, CheckType, CountMode, Decorators, NestingMode, PythonIndent, TestCode};
+pub use config::{Overrides, ProjectConfig, ThresholdOverrides};
+pub use detect::DetectedLanguage;
+pub use languages::{FunctionInfo, LanguageParser, LanguageType};
+pub use output::{Counts, Issue, Report, Severity};
+pub use scan::SourceFile;
+
+use std::path::Path;
+#[cfg(feature = "fs")]
+use std::path::PathBuf;
+
+/// What [`analyze`] checks, and with which thresholds
+#[derive(Default)]
+pub struct Config {
+    /// Languages to analyze, each in its conventional source directory
+    /// (default: detected from marker files such as `Cargo.toml`)
+    pub languages: Vec<LanguageType>,
+    /// Checks to run (default: every check that isn't opt-in)
+    pub checks: CheckSelection,
+    /// Threshold overrides, applied over the defaults and the project's `.code-smells.toml`
+    pub thresholds: Overrides,
+    /// Gitignore-style patterns to skip, in addition to `.smellsignore`
+    pub excludes: Vec<String>,
+    /// Skip files larger than this many bytes (default: [`scan::DEFAULT_MAX_FILE_SIZE`])
+    pub max_file_size: Option<u64>,
+    /// Skip only the root config's `[skip-dirs]`, none of the languages' default directories
+    pub no_default_skips: bool,
+    /// Also analyze the crates a Rust workspace lists under `exclude`
+    pub workspace_excluded: bool,
+}
+
+/// Configure and run an analysis one setting at a time:
+///
+/// ```no_run
+/// # #[cfg(feature = "fs")]
+/// # fn main() -> Result<(), String> {
+/// use code_smells::{AnalysisBuilder, CheckType, LanguageType, ThresholdOverrides};
+///
+/// let report = AnalysisBuilder::new()
+///     .dir("path/to/project")
+///     .languages([LanguageType::Python])
+///     .check(CheckType::Nesting)
+///     .thresholds(ThresholdOverrides {
+///         nest_warn: Some(3),
+///         ..Default::default()
+///     })
+///     .analyze()?;
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "fs"))]
+/// # fn main() {}
+/// ```
+#[derive(Default)]
+pub struct AnalysisBuilder {
+    #[cfg(feature = "fs")]
+    dir: Option<PathBuf>,
+    languages: Vec<LanguageType>,
+    checks: Vec<CheckType>,
+    overrides: Overrides,
+    excludes: Vec<String>,
+    max_file_size: Option<u64>,
+    no_default_skips: bool,
+    workspace_excluded: bool,
+}
+
+impl AnalysisBuilder {
+    pub fn new() -> Self {
+        AnalysisBuilder::default()
+    }
+
+    /// Project directory to analyze (default: the current directory)
+    #[cfg(feature = "fs")]
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// Languages to analyze, instead of detecting them
+    pub fn languages(mut self, languages: impl IntoIterator<Item = LanguageType>) -> Self {
+        self.languages.extend(languages);
+        self
+    }
+
+    /// Run a check; once one is given, only the checks given run
+    pub fn check(mut self, check: CheckType) -> Self {
+        if !self.checks.contains(&check) {
+            self.checks.push(check);
+        }
+        self
+    }
+
+    /// Threshold overrides, applied over the defaults and the project's `.code-smells.toml`
+    pub fn thresholds(mut self, thresholds: ThresholdOverrides) -> Self {
+        self.overrides.thresholds = thresholds;
+        self
+    }
+
+    /// Escalate TODO markers older than this many days (per git blame) to errors
+    pub fn todo_max_age(mut self, days: u64) -> Self {
+        self.overrides.todo_max_age_days = Some(days);
+        self
+    }
+
+    /// Also report values within `pct` percent below a warning threshold
+    pub fn near_misses(mut self, pct: usize) -> Self {
+        self.overrides.near_miss_pct = Some(pct);
+        self
+    }
+
+    /// Which lines count toward file and function length (default: every line)
+    pub fn count_mode(mut self, mode: CountMode) -> Self {
+        self.overrides.thresholds.count_mode = Some(mode);
+        self
+    }
+
+    /// What nesting depth counts (default: blocks of every kind)
+    pub fn nesting_mode(mut self, mode: NestingMode) -> Self {
+        self.overrides.thresholds.nesting_mode = Some(mode);
+        self
+    }
+
+    /// Also check functions nested in other functions on their own
+    pub fn nested_functions(mut self) -> Self {
+        self.overrides.thresholds.nested_functions = Some(true);
+        self
+    }
+
+    /// Whether the function checks look at test code (default: they do)
+    pub fn test_code(mut self, mode: TestCode) -> Self {
+        self.overrides.thresholds.test_code = Some(mode);
+        self
+    }
+
+    /// Columns of indentation per level of Python nesting (default: 4)
+    pub fn python_indent(mut self, indent: PythonIndent) -> Self {
+        self.overrides.thresholds.python_indent = Some(indent);
+        self
+    }
+
+    /// Whether function length counts the decorators above a function (default: it doesn't)
+    pub fn decorators(mut self, mode: Decorators) -> Self {
+        self.overrides.thresholds.decorators = Some(mode);
+        self
+    }
+
+    /// Skip files matching a gitignore-style pattern, in addition to `.smellsignore`
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.excludes.push(pattern.into());
+        self
+    }
+
+    /// Skip files larger than this many bytes (default: 2 MiB)
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Collect files from the directories the languages usually skip, such as
+    /// `node_modules`, unless the root config's `[skip-dirs]` lists them
+    pub fn no_default_skips(mut self) -> Self {
+        self.no_default_skips = true;
+        self
+    }
+
+    /// Analyze the crates a Rust workspace lists under `exclude` as well as its members
+    pub fn workspace_excluded(mut self) -> Self {
+        self.workspace_excluded = true;
+        self
+    }
+
+    /// The [`Config`] for these settings
+    pub fn config(&self) -> Config {
+        Config {
+            languages: self.languages.clone(),
+            checks: if self.checks.is_empty() {
+                CheckSelection::all()
+            } else {
+                CheckSelection::new(&self.checks)
+            },
+            thresholds: self.overrides.clone(),
+            excludes: self.excludes.clone(),
+            max_file_size: self.max_file_size,
+            no_default_skips: self.no_default_skips,
+            workspace_excluded: self.workspace_excluded,
+        }
+    }
+
+    #[cfg(feature = "fs")]
+    fn project_dir(&self) -> &Path {
+        self.dir.as_deref().unwrap_or(Path::new("."))
+    }
+
+    /// Run [`analyze`] with these settings
+    #[cfg(feature = "fs")]
+    pub fn analyze(&self) -> Result<Report, String> {
+        analyze(self.project_dir(), &self.config())
+    }
+
+    /// Run [`analyze_with`] with these settings
+    #[cfg(feature = "fs")]
+    pub fn analyze_with(&self, on_issue: impl FnMut(Issue) + Send) -> Result<Report, String> {
+        analyze_with(self.project_dir(), &self.config(), on_issue)
+    }
+
+    /// Run [`analyze_sources`] with these settings
+    pub fn analyze_sources(&self, files: &[SourceFile]) -> Report {
+        analyze_sources(files, &self.config())
+    }
+}
+
+/// A project directory's files and config, ready to analyze
+#[cfg(feature = "fs")]
+#[cfg_attr(not(feature = "capi"), allow(dead_code))]
+struct LoadedProject {
+    dir: std::path::PathBuf,
+    languages: Vec<LanguageType>,
+    files: Vec<SourceFile>,
+    config: ProjectConfig,
+    custom: Vec<CustomCheck>,
+}
+
+#[cfg(feature = "fs")]
+impl LoadedProject {
+    fn analyze(&self, config: &Config) -> Report {
+        analyze_files(&self.files, &self.config, &self.custom, &config.checks, &config.thresholds, |_| {})
+    }
+}
+
+/// Analyze a project directory, honoring its `.code-smells.toml` and `.smellsignore`
+#[cfg(feature = "fs")]
+pub fn analyze(project_dir: &Path, config: &Config) -> Result<Report, String> {
+    Ok(load_project(project_dir, config)?.analyze(config))
+}
+
+/// Like [`analyze`], but hand each issue to `on_issue` as soon as its file
+/// has been checked, so memory doesn't grow with the number of issues.
+///
+/// Files are checked in parallel: their issues arrive a file at a time, in no
+/// particular order of files, followed by those of the cross-file checks. The
+/// returned report has the counts (files scanned, suppressed) and timings,
+/// but no issues.
+#[cfg(feature = "fs")]
+pub fn analyze_with(project_dir: &Path, config: &Config, on_issue: impl FnMut(Issue) + Send) -> Result<Report, String> {
+    let project = load_project(project_dir, config)?;
+    let on_issue = std::sync::Mutex::new(on_issue);
+    Ok(analyze_files(
+        &project.files,
+        &project.config,
+        &project.custom,
+        &config.checks,
+        &config.thresholds,
+        |found: &mut Report| {
+            let mut on_issue = on_issue.lock().unwrap();
+            for issue in found.issues.drain(..) {
+                (*on_issue)(issue);
+            }
+        },
+    ))
+}
+
+#[cfg(feature = "fs")]
+fn load_project(project_dir: &Path, config: &Config) -> Result<LoadedProject, String> {
+    let project_dir = project_dir
+        .canonicalize()
+        .map_err(|e| format!("Cannot access directory '{}': {}", project_dir.display(), e))?;
+    let detected: Vec<DetectedLanguage> = if config.languages.is_empty() {
+        detect::detect_languages_with(&project_dir, config.workspace_excluded)
+    } else {
+        config.languages.iter().map(|l| DetectedLanguage::with_default_dir(*l)).collect()
+    };
+    if detected.is_empty() {
+        return Err(format!("No supported languages detected in {}", project_dir.display()));
+    }
+
+    let excludes = scan::load_excludes(&project_dir, &config.excludes)?;
+    let mut rules = config::ConfigFile::load(&project_dir, None)?.scan_rules()?;
+    rules.no_default_skips |= config.no_default_skips;
+    let mut files = scan::collect_files(&project_dir, &detected, &rules, &excludes);
+    scan::drop_oversized(&mut files, config.max_file_size.unwrap_or(scan::DEFAULT_MAX_FILE_SIZE));
+    let project_config = ProjectConfig::load(&project_dir, None, &files)?;
+    let custom = project_config.custom_checks()?;
+    Ok(LoadedProject {
+        languages: detected.iter().map(|d| d.language).collect(),
+        dir: project_dir,
+        files,
+        config: project_config,
+        custom,
+    })
+}
+
+/// Analyze sources held in memory, such as [`SourceFile::in_memory`] files.
+///
+/// The files' languages come from their extensions; `config.languages` and
+/// `config.excludes` are not used. No `.code-smells.toml` is read, so only the
+/// built-in checks run.
+pub fn analyze_sources(files: &[SourceFile], config: &Config) -> Report {
+    analyze_files(files, &ProjectConfig::default(), &[], &config.checks, &config.thresholds, |_| {})
+}
+
+/// Run the checks on files already collected.
+///
+/// `on_issues` sees each file's findings, and then those of the cross-file
+/// checks, once the config has been applied to them and before they are
+/// merged into the returned report.
+pub fn analyze_files(
+    files: &[SourceFile],
+    config: &ProjectConfig,
+    custom: &[CustomCheck],
+    selection: &CheckSelection,
+    overrides: &Overrides,
+    on_issues: impl Fn(&mut Report) + Sync,
+) -> Report {
+    // Cross-file checks use the root config
+    let thresholds_for = |lang| config.thresholds(lang, Path::new(""), overrides);
+    let finish = |found: &mut Report| {
+        apply_config(config, found);
+        on_issues(found);
+    };
+
+    let (mut report, spread) = checks::check_files(
+        files,
+        selection,
+        |file: &SourceFile| config.thresholds(file.language, &file.rel_path, overrides),
+        custom,
+        &config.generated_markers(),
+        finish,
+    );
+
+    // Cross-file analysis once every file has been seen
+    let mut cross_file = Report::default();
+    if selection.runs(CheckType::ShotgunSurgery) {
+        checks::timed(&mut cross_file, "shotgun-surgery", |report| {
+            checks::check_identifier_spread(&spread, thresholds_for, report)
+        });
+    }
+    if selection.runs(CheckType::ModuleNesting) {
+        checks::timed(&mut cross_file, "module-nesting", |report| {
+            checks::check_module_nesting(files, &thresholds_for(LanguageType::Terraform), report)
+        });
+    }
+    if selection.runs(CheckType::Custom) {
+        checks::timed(&mut cross_file, "custom", |report| checks::check_plugins(custom, files, report));
+    }
+    finish(&mut cross_file);
+    report.merge(cross_file);
+
+    report
+}
+
+/// Drop the checks the config disables and apply its severity overrides
+fn apply_config(config: &ProjectConfig, report: &mut Report) {
+    let before = report.issues.len();
+    report
+        .issues
+        .retain(|issue| !config.is_disabled(&issue.file, issue.check_type));
+    report.suppressed += before - report.issues.len();
+
+    // Team policy overrides the severity the thresholds gave; near misses stay informational
+    for issue in report.issues.iter_mut().filter(|i| i.severity != Severity::Info) {
+        if let Some(severity) = config.severity_for(&issue.file, issue.check_type) {
+            issue.severity = severity;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::fs;
+
+    #[test]
+    fn test_analyze_with_streams_every_issue() {
+        let dir = TempDir::new("lib");
+        for name in ["a.py", "b.py"] {
+            fs::write(dir.join(name), "def f():\n    if a:\n        if b:\n            pass\n").unwrap();
+        }
+        let builder = AnalysisBuilder::new()
+            .dir(dir.path())
+            .languages([LanguageType::Python])
+            .check(CheckType::Nesting)
+            .thresholds(ThresholdOverrides {
+                nest_warn: Some(1),
+                ..Default::default()
+            });
+        let mut streamed = Vec::new();
+        let report = builder.analyze_with(|issue| streamed.push(issue.file)).unwrap();
+        let collected = builder.analyze().unwrap();
+
+        streamed.sort();
+        assert_eq!(streamed, [PathBuf::from("a.py"), PathBuf::from("b.py")]);
+        assert_eq!(collected.issues.len(), 2);
+        assert!(report.issues.is_empty());
+        assert_eq!(report.files_scanned, 2);
+    }
+}
@@ -1,83 +1,700 @@
-mod checks;
 mod cli;
-mod config;
-mod detect;
-mod languages;
-mod output;
 
+#[cfg(unix)]
+use code_smells::tui;
+use code_smells::{
+    archive, baseline, checks, compare, config, dashboard, detect, effective, explain, git, history, init, inventory,
+    languages, manifest, merge, output, scan, scratch, stats, top, trace, whatif,
+};
+use baseline::{Baseline, BASELINE_FILE_NAME};
+use checks::CustomCheck;
 use clap::Parser;
-use cli::{CheckType, Cli};
-use config::Thresholds;
-use detect::{detect_languages, parse_language_list, DetectedLanguage};
+use cli::{Cli, Command, FallbackParser, ProjectArgs, ReportCommand};
+use code_smells::options::{CheckSelection, ExitCodeMap, GroupBy, OutputFormat};
+use config::{ConfigFile, Overrides, ProjectConfig};
+use detect::{apply_source_dirs, detect_languages, detect_languages_with, detect_projects, missing_source_dirs, parse_language_list, DetectedLanguage};
 use languages::LanguageType;
-use output::Report;
+use output::{Report, ReportOptions};
+use scan::{ScanRules, SourceFile};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
 use std::process;
+use std::sync::Mutex;
+
+/// A resolved project: where it is, what it contains, and its config
+struct Project {
+    dir: PathBuf,
+    detected: Vec<DetectedLanguage>,
+    files: Vec<SourceFile>,
+    config: ProjectConfig,
+    /// The root config's `[extensions]` and `[skip-dirs]`, with `--no-default-skips`
+    rules: ScanRules,
+    custom: Vec<CustomCheck>,
+    /// Nested project directories, with `--recursive-projects`
+    projects: Vec<String>,
+    /// Commit analyzed with `--rev`
+    commit: Option<String>,
+    /// Cloned from a repository URL or extracted from an archive into a
+    /// temporary directory, which is removed after the run
+    fetched: bool,
+    /// Temporary clone or extraction, and copies of the files read with `--staged` or `--rev`
+    temp_dirs: Vec<PathBuf>,
+}
+
+impl Project {
+    /// Remove temporary files once the checks are done
+    fn cleanup(&self) {
+        for dir in &self.temp_dirs {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+
+    /// Remove temporary files and exit; `process::exit` runs no destructors
+    fn exit(&self, code: i32) -> ! {
+        self.cleanup();
+        process::exit(code)
+    }
+}
 
 fn main() {
-    let cli = Cli::parse();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // Help and version output are not failures
+        Err(e) if !e.use_stderr() => e.exit(),
+        Err(e) => {
+            let _ = e.print();
+            process::exit(ExitCodeMap::default().failure);
+        }
+    };
+    let failure = cli.exit_code_map.failure;
+
+    // Before anything uses the pool; walking the project directory does
+    if let Some(jobs) = cli.jobs {
+        if let Err(e) = rayon::ThreadPoolBuilder::new().num_threads(jobs.get()).build_global() {
+            eprintln!("Error: Cannot start {} worker threads: {}", jobs, e);
+            process::exit(failure);
+        }
+    }
+
+    if let Some(command) = &cli.command {
+        run_command(command, failure);
+    }
+
+    if cli.schema {
+        if cli.format != OutputFormat::Json {
+            eprintln!("Error: --schema describes the json format; use it with --format json");
+            process::exit(failure);
+        }
+        print!("{}", output::JSON_SCHEMA);
+        process::exit(0);
+    }
+
+    if (cli.quiet || cli.summary_only) && cli.format != OutputFormat::Text && cli.outputs.is_empty() {
+        eprintln!("Error: --quiet and --summary-only apply to the text report; use them with --format text or --output");
+        process::exit(failure);
+    }
+
+    let overrides = cli.thresholds.overrides();
+    let project = load_project(&cli.project, &overrides, failure);
+
+    // Collect unique language types for display
+    let mut lang_types: Vec<LanguageType> = Vec::new();
+    for det in &project.detected {
+        if !lang_types.contains(&det.language) {
+            lang_types.push(det.language);
+        }
+    }
+
+    if cli.print_config {
+        match effective::render(&project.detected, &project.config, &project.rules, &project.custom, &overrides, cli.format) {
+            Ok(rendered) => {
+                print!("{}", rendered);
+                project.exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                project.exit(failure);
+            }
+        }
+    }
+
+    if let Some(path) = &cli.emit_manifest {
+        let settings = manifest::RunSettings {
+            checks: &cli.checks,
+            selection: cli.selection(),
+            overrides: overrides.clone(),
+            format: cli.format,
+            severity_filter: cli.severity_filter(),
+            rev: cli.project.rev.as_deref(),
+        };
+        if let Err(e) =
+            manifest::write_manifest(
+            path,
+            &settings,
+            &project.dir,
+            &project.detected,
+            &project.files,
+            &project.config,
+            &project.custom,
+        )
+        {
+            eprintln!("Error: {}", e);
+            project.exit(failure);
+        }
+    }
+
+    if cli.list_files {
+        if !matches!(cli.format, OutputFormat::Text | OutputFormat::Json) {
+            eprintln!("Error: --list-files supports text and json output");
+            project.exit(failure);
+        }
+        project.cleanup();
+        scan::print_file_list(&project.dir, &project.detected, &project.files, cli.format);
+        process::exit(0);
+    }
+
+    if let Some(n) = cli.top {
+        if !matches!(cli.format, OutputFormat::Text | OutputFormat::Json) {
+            eprintln!("Error: --top supports text and json output");
+            project.exit(failure);
+        }
+        let ranking = top::rank(&project.files, n.get());
+        project.cleanup();
+        top::print_ranking(&project.dir, &ranking, cli.format);
+        process::exit(0);
+    }
+
+    // Only new and worsened issues count once a baseline exists; a new baseline records everything
+    let baseline_path = match &cli.baseline {
+        Some(path) => Some(path.clone()),
+        None => Some(project.dir.join(BASELINE_FILE_NAME)).filter(|p| !cli.no_baseline && p.is_file()),
+    };
+    let baseline = match baseline_path.filter(|_| cli.write_baseline.is_none()) {
+        Some(path) => match Baseline::load(&path) {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                project.exit(failure);
+            }
+        },
+        None => None,
+    };
+    let matcher = baseline.as_ref().map(|b| Mutex::new(b.matcher()));
+    let stream = cli.format == OutputFormat::Ndjson
+        && cli.outputs.is_empty()
+        && cli.write_baseline.is_none()
+        && cli.sort.is_none();
+
+    let mut report = analyze(&project, &cli.selection(), &overrides, |found: &mut Report| {
+        if let Some(matcher) = &matcher {
+            matcher.lock().unwrap().filter(found);
+        }
+        if stream {
+            output::print_ndjson_issues(found, &cli.help_uri_base, &project.custom);
+        }
+    });
+    // The browser previews the files that were checked
+    if !cli.tui {
+        project.cleanup();
+    }
+    for (check, time) in &report.check_time {
+        trace::log(format_args!("time {}: {:.1?}", check, time));
+    }
+    for (language, time) in &report.language_time {
+        trace::log(format_args!("time {} files: {:.1?}", language, time));
+    }
+
+    if let Some(path) = &cli.write_baseline {
+        let baseline = Baseline::from_report(&report);
+        match baseline.write(path) {
+            Ok(()) => {
+                println!("Baseline with {} issues written to {}", baseline.len(), path.display());
+                project.exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                project.exit(failure);
+            }
+        }
+    }
+
+    if let Some(key) = cli.sort {
+        report.sort(key);
+    }
+
+    if cli.tui {
+        let browsed = browse(&report, &project);
+        project.cleanup();
+        if let Err(e) = browsed {
+            eprintln!("Error: {}", e);
+            process::exit(failure);
+        }
+        process::exit(report.exit_code(&cli.exit_code_map, cli.budget()));
+    }
+
+    // Output results
+    let options = ReportOptions {
+        project_dir: &project.dir,
+        languages: &lang_types,
+        filter: cli.severity_filter(),
+        group_by: cli.group_by.unwrap_or(if cli.project.recursive_projects {
+            GroupBy::Project
+        } else {
+            GroupBy::Severity
+        }),
+        summary_only: false,
+        help_base: &cli.help_uri_base,
+        custom: &project.custom,
+        projects: &project.projects,
+        timing: cli.project.verbose,
+    };
+    for target in &cli.outputs {
+        let format = target.format.unwrap_or(cli.format);
+        let written = File::create(&target.path).and_then(|file| {
+            let mut out = BufWriter::new(file);
+            output::write_report(&mut out, &report, format, &options, false)?;
+            out.flush()
+        });
+        if let Err(e) = written {
+            eprintln!("Error: Cannot write '{}': {}", target.path.display(), e);
+            process::exit(failure);
+        }
+    }
+    let terminal = ReportOptions {
+        summary_only: cli.quiet || cli.summary_only,
+        ..options
+    };
+    if stream {
+        output::print_ndjson_summary(&report);
+    } else if cli.quiet && report.error_count() + report.warning_count() == 0 {
+        // Nothing to say about a clean run
+    } else if cli.outputs.is_empty() {
+        output::print_report(&report, cli.format, &terminal, output::use_color(cli.color));
+    } else {
+        // The files get the machine-readable formats; the terminal gets the readable one
+        output::print_report(&report, OutputFormat::Text, &terminal, output::use_color(cli.color));
+    }
+
+    let budget = cli.budget();
+    if cli.max_errors.is_some() && report.error_count() > budget.errors {
+        eprintln!("Too many errors: {} (maximum: {})", report.error_count(), budget.errors);
+    }
+    if cli.max_warnings.is_some() && report.warning_count() > budget.warnings {
+        eprintln!("Too many warnings: {} (maximum: {})", report.warning_count(), budget.warnings);
+    }
+    process::exit(report.exit_code(&cli.exit_code_map, budget));
+}
+
+#[cfg(unix)]
+fn browse(report: &Report, project: &Project) -> Result<(), String> {
+    let sources = project.files.iter().map(|f| (f.rel_path.clone(), f.path.clone())).collect();
+    tui::browse(report, &project.dir, &sources)
+}
+
+#[cfg(not(unix))]
+fn browse(_report: &Report, _project: &Project) -> Result<(), String> {
+    Err("--tui is only available on Unix terminals".to_string())
+}
+
+/// Resolve the directory, languages, files, and config to analyze, exiting on failure
+/// or when the config with `overrides` applied has impossible thresholds
+fn load_project(args: &ProjectArgs, overrides: &Overrides, failure: i32) -> Project {
+    if args.verbose {
+        trace::enable();
+    }
+
+    // A repository URL is cloned, and an archive extracted, then analyzed like a local directory
+    let remote = git::remote_url(&args.directory);
+    let fetch_dir = (remote.is_some() || archive::is_archive(&args.directory)).then(|| match scratch::create("fetch") {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(failure);
+        }
+    });
+    let fetched_root = fetch_dir.as_ref().map(|fetch_dir| {
+        let root = match remote {
+            Some(url) => {
+                trace::log(format_args!("clone {} into {}", url, fetch_dir.display()));
+                // --rev needs the history; otherwise the latest commit is enough
+                git::clone(url, fetch_dir, args.rev.is_none()).map(|()| fetch_dir.clone())
+            }
+            None => {
+                trace::log(format_args!("extract {} into {}", args.directory.display(), fetch_dir.display()));
+                archive::extract(&args.directory, fetch_dir)
+            }
+        };
+        root.unwrap_or_else(|e| {
+            let _ = std::fs::remove_dir_all(fetch_dir);
+            eprintln!("Error: {}", e);
+            process::exit(failure);
+        })
+    });
 
     // Resolve directory to absolute path
-    let project_dir = match cli.directory.canonicalize() {
+    let project_dir = match fetched_root.as_ref().unwrap_or(&args.directory).canonicalize() {
         Ok(p) => p,
         Err(e) => {
-            eprintln!("Error: Cannot access directory '{}': {}", cli.directory.display(), e);
-            process::exit(1);
+            eprintln!("Error: Cannot access directory '{}': {}", args.directory.display(), e);
+            process::exit(failure);
+        }
+    };
+    let discard = |dirs: &[&Option<PathBuf>]| {
+        for dir in dirs.iter().copied().flatten() {
+            let _ = std::fs::remove_dir_all(dir);
         }
     };
 
+    // With --rev, everything below reads the project as it was at that commit
+    let commit = args.rev.as_ref().map(|rev| match git::resolve_commit(&project_dir, rev) {
+        Ok(commit) => commit,
+        Err(e) => {
+            discard(&[&fetch_dir]);
+            eprintln!("Error: {}", e);
+            process::exit(failure);
+        }
+    });
+    let rev_dir = commit.as_ref().map(|commit| {
+        let dir = scratch::create("rev").unwrap_or_else(|e| {
+            discard(&[&fetch_dir]);
+            eprintln!("Error: {}", e);
+            process::exit(failure);
+        });
+        if let Err(e) = git::export_commit(&project_dir, commit, &dir) {
+            discard(&[&fetch_dir, &Some(dir)]);
+            eprintln!("Error: {}", e);
+            process::exit(failure);
+        }
+        dir
+    });
+    let root = rev_dir.clone().unwrap_or_else(|| project_dir.clone());
+    let fail = |message: String| -> ! {
+        discard(&[&fetch_dir, &rev_dir]);
+        eprintln!("{}", message);
+        process::exit(failure);
+    };
+
+    let excludes = match scan::load_excludes(&root, &args.excludes) {
+        Ok(excludes) => excludes,
+        Err(e) => fail(format!("Error: {}", e)),
+    };
+
     // Detect or parse languages
-    let detected: Vec<DetectedLanguage> = match &cli.languages {
-        Some(langs) => parse_language_list(langs),
-        None => detect_languages(&project_dir),
+    let mut projects = Vec::new();
+    let mut detected: Vec<DetectedLanguage> = if args.recursive_projects {
+        let found = detect_projects(&root, &excludes, args.workspace_excluded);
+        projects = found.iter().map(|p| p.dir.clone()).collect();
+        let mut detected: Vec<DetectedLanguage> = Vec::new();
+        // A workspace and its member crates name the same source directories
+        for det in found.into_iter().flat_map(|p| p.languages) {
+            if !detected.iter().any(|d| d.language == det.language && d.source_dir == det.source_dir) {
+                detected.push(det);
+            }
+        }
+        // --lang narrows the languages analyzed in each project
+        if let Some(langs) = &args.languages {
+            let wanted = parse_language_list(langs);
+            detected.retain(|d| wanted.iter().any(|w| w.language == d.language));
+        }
+        detected
+    } else {
+        match &args.languages {
+            Some(langs) => parse_language_list(langs),
+            None => detect_languages_with(&root, args.workspace_excluded),
+        }
     };
 
-    if detected.is_empty() {
+    if detected.is_empty() && args.fallback_parser.is_none() {
         eprintln!("No supported languages detected in {}", project_dir.display());
-        eprintln!("Supported: elixir, dart, typescript, python, rust");
-        process::exit(1);
+        fail("Supported: elixir, dart, typescript, javascript, python, rust, go, java, swift, terraform, julia (or --lang generic for any text file)".to_string());
     }
 
-    // Collect unique language types for display
-    let lang_types: Vec<LanguageType> = detected.iter().map(|d| d.language).collect();
+    apply_source_dirs(&mut detected, &args.source_dirs);
 
-    // Build report
-    let mut report = Report::default();
+    // A missing source directory would otherwise look like a clean run
+    for det in missing_source_dirs(&root, &detected) {
+        eprintln!(
+            "Warning: {} detected but source directory '{}' does not exist; skipping (use --source-dir {}=DIR)",
+            det.language.name(),
+            det.source_dir,
+            det.language.name()
+        );
+    }
 
-    for det in &detected {
-        let source_path = project_dir.join(&det.source_dir);
-        if !source_path.is_dir() {
-            continue;
+    // Enumerate files once, shared by all checks
+    let mut rules = match ConfigFile::load(&root, args.config.as_deref()).and_then(|c| c.scan_rules()) {
+        Ok(rules) => rules,
+        Err(e) => fail(format!("Error: {}", e)),
+    };
+    rules.no_default_skips |= args.no_default_skips;
+    let mut files = scan::collect_files(&root, &detected, &rules, &excludes);
+    match args.fallback_parser {
+        Some(FallbackParser::Generic) => {
+            let before = files.len();
+            scan::collect_fallback_files(&root, &args.fallback_extensions, &rules, &excludes, &mut files);
+            if files.len() > before && !detected.iter().any(|d| d.language == LanguageType::Generic) {
+                detected.push(DetectedLanguage::with_default_dir(LanguageType::Generic));
+            }
         }
+        None => {}
+    }
 
-        let thresholds = Thresholds::for_language(det.language).with_overrides(&cli);
+    if let Some(base) = &args.diff_base {
+        match git::changed_files(&project_dir, base) {
+            Ok(changed) => files.retain(|f| {
+                let keep = changed.contains(&f.rel_path);
+                if !keep {
+                    trace::log(format_args!("skip {}: unchanged since {}", f.rel_path.display(), base));
+                }
+                keep
+            }),
+            Err(e) => fail(format!("Error: {}", e)),
+        }
+    }
 
-        // Run checks based on check type
-        match cli.check_type {
-            CheckType::All => {
-                checks::check_file_length(&source_path, det.language, &thresholds, &mut report);
-                checks::check_function_length(&source_path, det.language, &thresholds, &mut report);
-                checks::check_nesting_depth(&source_path, det.language, &thresholds, &mut report);
+    // Check what is about to be committed, not the working tree
+    let staged_dir = args.staged.then(|| {
+        let dir = scratch::create("staged").unwrap_or_else(|e| fail(format!("Error: {}", e)));
+        let staged = git::staged_files(&project_dir).and_then(|staged| {
+            files.retain(|f| {
+                let keep = staged.contains(&f.rel_path);
+                if !keep {
+                    trace::log(format_args!("skip {}: not staged", f.rel_path.display()));
+                }
+                keep
+            });
+            git::checkout_staged(&project_dir, files.iter().map(|f| f.rel_path.as_path()), &dir)
+        });
+        if let Err(e) = staged {
+            let _ = std::fs::remove_dir_all(&dir);
+            fail(format!("Error: {}", e));
+        }
+        for file in &mut files {
+            file.path = dir.join(&file.rel_path);
+        }
+        dir
+    });
+
+    scan::drop_oversized(&mut files, args.max_file_size);
+    if let Some(shard) = args.shard {
+        scan::keep_shard(&mut files, shard);
+    }
+
+    let languages: Vec<LanguageType> = detected.iter().map(|d| d.language).collect();
+    let (config, custom) = match ProjectConfig::load(&root, args.config.as_deref(), &files)
+        .and_then(|config| config.validate(&languages, overrides).map(|()| config))
+        .and_then(|config| config.custom_checks().map(|custom| (config, custom)))
+    {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            discard(&[&staged_dir]);
+            fail(format!("Error: {}", e))
+        }
+    };
+
+    Project {
+        dir: project_dir,
+        detected,
+        files,
+        config,
+        rules,
+        custom,
+        projects,
+        commit,
+        fetched: fetch_dir.is_some(),
+        temp_dirs: [fetch_dir, rev_dir, staged_dir].into_iter().flatten().collect(),
+    }
+}
+
+/// Run the checks on a loaded project
+fn analyze(
+    project: &Project,
+    selection: &CheckSelection,
+    overrides: &Overrides,
+    on_issues: impl Fn(&mut Report) + Sync,
+) -> Report {
+    code_smells::analyze_files(&project.files, &project.config, &project.custom, selection, overrides, on_issues)
+}
+
+/// Run a subcommand and exit
+fn run_command(command: &Command, failure: i32) -> ! {
+    match command {
+        Command::Checks { format } => {
+            if !matches!(format, OutputFormat::Text | OutputFormat::Json) {
+                eprintln!("Error: checks supports text and json output");
+                process::exit(failure);
+            }
+            explain::print_checks(*format);
+            process::exit(0);
+        }
+        Command::Explain { check } => {
+            if let Err(e) = explain::print_explanation(check) {
+                eprintln!("Error: {}", e);
+                process::exit(failure);
+            }
+            process::exit(0);
+        }
+        Command::Compare { old, new, format } => {
+            if !matches!(format, OutputFormat::Text | OutputFormat::Json) {
+                eprintln!("Error: compare supports text and json output");
+                process::exit(failure);
+            }
+            if let Err(e) = compare::print_comparison(old, new, *format) {
+                eprintln!("Error: {}", e);
+                process::exit(failure);
             }
-            CheckType::FileLength => {
-                checks::check_file_length(&source_path, det.language, &thresholds, &mut report);
+            process::exit(0);
+        }
+        Command::Dashboard { inputs, output } => match dashboard::generate(inputs, output) {
+            Ok(index) => {
+                println!("Dashboard written to {}", index.display());
+                process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(failure);
+            }
+        },
+        Command::Report {
+            command: ReportCommand::Merge { inputs, output },
+        } => {
+            let written = merge::merge_reports(inputs).and_then(|json| match output {
+                Some(path) => {
+                    std::fs::write(path, json + "\n").map_err(|e| format!("Cannot write '{}': {}", path.display(), e))
+                }
+                None => {
+                    println!("{}", json);
+                    Ok(())
+                }
+            });
+            if let Err(e) = written {
+                eprintln!("Error: {}", e);
+                process::exit(failure);
+            }
+            process::exit(0);
+        }
+        Command::Init {
+            directory,
+            languages,
+            force,
+        } => {
+            let detected = match languages {
+                Some(langs) => parse_language_list(langs),
+                None => detect_languages(directory),
+            };
+            if detected.is_empty() {
+                eprintln!("No supported languages detected in {}", directory.display());
+                eprintln!("Use --lang to choose the languages to configure");
+                process::exit(failure);
             }
-            CheckType::Functions => {
-                checks::check_function_length(&source_path, det.language, &thresholds, &mut report);
+            match init::write_starter_config(directory, &detected, *force) {
+                Ok(path) => {
+                    println!("Config written to {}", path.display());
+                    process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(failure);
+                }
+            }
+        }
+        Command::Record {
+            project,
+            history,
+            issues,
+        } => {
+            let project = load_project(project, &Overrides::default(), failure);
+            // The default history file would be written into the clone, and removed with it
+            let path = match history {
+                Some(path) => path.clone(),
+                None if project.fetched => {
+                    project.cleanup();
+                    eprintln!("Error: record needs --history FILE for a repository URL or archive");
+                    process::exit(failure);
+                }
+                None => project.dir.join(history::HISTORY_FILE_NAME),
+            };
+            let report = analyze(&project, &CheckSelection::all(), &Overrides::default(), |_| {});
+            let commit = git::short_commit(&project.dir, project.commit.as_deref().unwrap_or("HEAD"));
+            let recorded = history::record(&path, &report, commit, *issues);
+            project.cleanup();
+            if let Err(e) = recorded {
+                eprintln!("Error: {}", e);
+                process::exit(failure);
+            }
+            println!(
+                "Recorded {} errors and {} warnings in {}",
+                report.error_count(),
+                report.warning_count(),
+                path.display()
+            );
+            process::exit(0);
+        }
+        Command::Trend {
+            directory,
+            history,
+            last,
+            format,
+        } => {
+            if !matches!(format, OutputFormat::Text | OutputFormat::Json) {
+                eprintln!("Error: trend supports text and json output");
+                process::exit(failure);
             }
-            CheckType::Nesting => {
-                checks::check_nesting_depth(&source_path, det.language, &thresholds, &mut report);
+            let path = history.clone().unwrap_or_else(|| directory.join(history::HISTORY_FILE_NAME));
+            if let Err(e) = history::print_trend(&path, *last, *format) {
+                eprintln!("Error: {}", e);
+                process::exit(failure);
             }
+            process::exit(0);
+        }
+        Command::Stats { project, format } => {
+            if !matches!(format, OutputFormat::Text | OutputFormat::Json) {
+                eprintln!("Error: stats supports text and json output");
+                process::exit(failure);
+            }
+            let project = load_project(project, &Overrides::default(), failure);
+            let stats = stats::collect(&project.files);
+            project.cleanup();
+            stats::print_stats(&project.dir, &stats, *format);
+            process::exit(0);
+        }
+        Command::Inventory { project, format } => {
+            if !matches!(format, OutputFormat::Text | OutputFormat::Json | OutputFormat::Ndjson) {
+                eprintln!("Error: inventory supports text, json, and ndjson output");
+                process::exit(failure);
+            }
+            let project = load_project(project, &Overrides::default(), failure);
+            let functions = inventory::collect(&project.files);
+            project.cleanup();
+            inventory::print_inventory(&project.dir, &functions, *format);
+            process::exit(0);
+        }
+        Command::Whatif {
+            project,
+            format,
+            thresholds,
+        } => {
+            if !matches!(format, OutputFormat::Text | OutputFormat::Json) {
+                eprintln!("Error: whatif supports text and json output");
+                process::exit(failure);
+            }
+            let project = load_project(project, &Overrides::default(), failure);
+            let current = analyze(&project, &CheckSelection::all(), &Overrides::default(), |_| {});
+            let thresholds = thresholds.overrides();
+            let proposed = analyze(&project, &CheckSelection::all(), &thresholds, |_| {});
+            project.cleanup();
+            whatif::print_comparison(
+                &project.dir,
+                &project.detected,
+                &project.config,
+                &thresholds,
+                &current,
+                &proposed,
+                *format,
+            );
+            // Informational only: never fail on the findings themselves
+            process::exit(0);
         }
     }
-
-    // Output results
-    output::print_report(
-        &report,
-        &project_dir,
-        &lang_types,
-        cli.format,
-        cli.severity_filter(),
-    );
-
-    process::exit(report.exit_code());
 }
@@ -0,0 +1,197 @@
+use crate::checks::{CustomCheck, CustomCheckDef};
+use crate::config::{Overrides, ProjectConfig, Thresholds};
+use crate::detect::DetectedLanguage;
+use crate::options::{CheckSelection, CheckType, OutputFormat, SeverityFilter};
+use crate::scan::SourceFile;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Everything needed to audit or reproduce a run
+#[derive(Serialize)]
+struct Manifest<'a> {
+    tool: ToolInfo,
+    generated_at: u64,
+    project: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git: Option<GitInfo>,
+    config: ResolvedConfig<'a>,
+    thresholds: BTreeMap<&'static str, Thresholds>,
+    checks: Vec<&'static str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    custom_checks: Vec<&'a CustomCheckDef>,
+    /// Directories whose own config file overrides the thresholds above
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    nested_configs: Vec<String>,
+    files: Vec<FileEntry>,
+}
+
+#[derive(Serialize)]
+struct ToolInfo {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+struct GitInfo {
+    commit: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    branch: Option<String>,
+    dirty: bool,
+}
+
+#[derive(Serialize)]
+struct ResolvedConfig<'a> {
+    check: String,
+    format: String,
+    languages: Vec<LanguageEntry<'a>>,
+    severity_filter: &'static str,
+}
+
+#[derive(Serialize)]
+struct LanguageEntry<'a> {
+    language: &'static str,
+    source_dir: &'a str,
+}
+
+#[derive(Serialize)]
+struct FileEntry {
+    path: String,
+    language: &'static str,
+    sha256: String,
+}
+
+/// The command-line settings a manifest records
+pub struct RunSettings<'a> {
+    /// The `--check` values as given
+    pub checks: &'a [CheckType],
+    /// The checks run, with `--security` added
+    pub selection: CheckSelection,
+    pub overrides: Overrides,
+    pub format: OutputFormat,
+    pub severity_filter: SeverityFilter,
+    /// The `--rev` analyzed
+    pub rev: Option<&'a str>,
+}
+
+/// Write a manifest describing this run to `output`
+pub fn write_manifest(
+    output: &Path,
+    settings: &RunSettings,
+    project_dir: &Path,
+    detected: &[DetectedLanguage],
+    files: &[SourceFile],
+    config: &ProjectConfig,
+    custom: &[CustomCheck],
+) -> Result<(), String> {
+    let mut thresholds = BTreeMap::new();
+    for det in detected {
+        thresholds.insert(
+            det.language.name(),
+            config.thresholds(det.language, Path::new(""), &settings.overrides),
+        );
+    }
+
+    let selection = &settings.selection;
+    let mut checks: Vec<&'static str> = Vec::new();
+    for check in selection.checks() {
+        for id in crate::checks::rules::rules_for(*check) {
+            if !checks.contains(&id) {
+                checks.push(id);
+            }
+        }
+    }
+    if selection.runs(CheckType::Custom) {
+        checks.extend(custom.iter().map(|c| c.id));
+    }
+    let manifest = Manifest {
+        tool: ToolInfo {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+        },
+        generated_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        project: project_dir.display().to_string(),
+        git: git_info(project_dir, settings.rev),
+        config: ResolvedConfig {
+            check: settings.checks.iter().map(|c| c.name()).collect::<Vec<_>>().join(","),
+            format: settings.format.name().to_string(),
+            languages: detected
+                .iter()
+                .map(|d| LanguageEntry {
+                    language: d.language.name(),
+                    source_dir: &d.source_dir,
+                })
+                .collect(),
+            severity_filter: match settings.severity_filter {
+                SeverityFilter::All => "all",
+                SeverityFilter::ErrorsOnly => "errors",
+                SeverityFilter::WarningsOnly => "warnings",
+            },
+        },
+        thresholds,
+        checks,
+        custom_checks: custom.iter().map(|c| &c.def).collect(),
+        nested_configs: config.nested_dirs().map(|d| d.display().to_string()).collect(),
+        files: files
+            .iter()
+            .map(|f| FileEntry {
+                path: f.rel_path.display().to_string(),
+                language: f.language.name(),
+                sha256: sha256_file(&f.path),
+            })
+            .collect(),
+    };
+
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    fs::write(output, json + "\n").map_err(|e| format!("Cannot write '{}': {}", output.display(), e))
+}
+
+fn sha256_file(path: &Path) -> String {
+    match fs::read(path) {
+        Ok(bytes) => Sha256::digest(&bytes)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+        Err(_) => String::new(),
+    }
+}
+
+fn git(project_dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(project_dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The checked-out commit, or the one analyzed with `--rev`
+fn git_info(project_dir: &Path, rev: Option<&str>) -> Option<GitInfo> {
+    if let Some(rev) = rev {
+        // Read from git, so the working tree can't have changed it
+        return Some(GitInfo {
+            commit: crate::git::resolve_commit(project_dir, rev).ok()?,
+            branch: None,
+            dirty: false,
+        });
+    }
+    let commit = git(project_dir, &["rev-parse", "HEAD"])?;
+    let branch = git(project_dir, &["rev-parse", "--abbrev-ref", "HEAD"]).filter(|b| b != "HEAD");
+    let dirty = git(project_dir, &["status", "--porcelain"]).is_some_and(|s| !s.is_empty());
+    Some(GitInfo {
+        commit,
+        branch,
+        dirty,
+    })
+}
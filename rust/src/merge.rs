@@ -0,0 +1,166 @@
+use crate::output::JSON_FORMAT_VERSION;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A `--format json` report, with only the parts a merge looks into typed
+#[derive(Deserialize, Serialize)]
+struct ReportFile {
+    version: u32,
+    project: String,
+    languages: Vec<String>,
+    rules: Vec<RuleRecord>,
+    issues: Vec<IssueRecord>,
+    summary: SummaryRecord,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RuleRecord {
+    id: String,
+    name: String,
+    description: String,
+    category: String,
+    help_uri: String,
+}
+
+#[derive(Deserialize, Serialize)]
+struct IssueRecord {
+    severity: String,
+    file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end_line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(rename = "type")]
+    check_type: String,
+    value: usize,
+    limit: usize,
+    message: String,
+    help_uri: String,
+}
+
+/// The counts of a summary; timing is left out, as shards' times don't add up to a run's
+#[derive(Default, Deserialize, Serialize)]
+struct SummaryRecord {
+    files: usize,
+    errors: usize,
+    warnings: usize,
+    near_misses: usize,
+    baselined: usize,
+    suppressed: usize,
+}
+
+fn load(path: &Path) -> Result<ReportFile, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Cannot read '{}': {}", path.display(), e))?;
+    let report: ReportFile =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid report '{}': {}", path.display(), e))?;
+    if report.version != JSON_FORMAT_VERSION {
+        return Err(format!(
+            "'{}' is a version {} report; this version merges version {}",
+            path.display(),
+            report.version,
+            JSON_FORMAT_VERSION
+        ));
+    }
+    Ok(report)
+}
+
+/// Combine reports, e.g. of the `--shard`s of one project, into one.
+///
+/// Issues keep the order of the inputs; languages and rules are those of
+/// any input, and the project is the first input's, since shards run on
+/// different machines may have analyzed different checkouts of it.
+fn merge(reports: Vec<ReportFile>) -> Option<ReportFile> {
+    let mut reports = reports.into_iter();
+    let mut merged = reports.next()?;
+    for report in reports {
+        for language in report.languages {
+            if !merged.languages.contains(&language) {
+                merged.languages.push(language);
+            }
+        }
+        for rule in report.rules {
+            if !merged.rules.iter().any(|r| r.id == rule.id) {
+                merged.rules.push(rule);
+            }
+        }
+        merged.issues.extend(report.issues);
+        let (total, part) = (&mut merged.summary, report.summary);
+        total.files += part.files;
+        total.errors += part.errors;
+        total.warnings += part.warnings;
+        total.near_misses += part.near_misses;
+        total.baselined += part.baselined;
+        total.suppressed += part.suppressed;
+    }
+    Some(merged)
+}
+
+/// Merge the JSON reports at `inputs` and return the merged report's JSON
+pub fn merge_reports(inputs: &[PathBuf]) -> Result<String, String> {
+    let reports = inputs.iter().map(PathBuf::as_path).map(load).collect::<Result<Vec<_>, _>>()?;
+    let merged = merge(reports).ok_or("No input reports given")?;
+    Ok(serde_json::to_string_pretty(&merged).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shard(languages: &[&str], issues: &[(&str, &str)], files: usize) -> ReportFile {
+        ReportFile {
+            version: JSON_FORMAT_VERSION,
+            project: format!("/ci/{}", files),
+            languages: languages.iter().map(|l| l.to_string()).collect(),
+            rules: vec![RuleRecord {
+                id: "file-length".to_string(),
+                name: "File length".to_string(),
+                description: "File has too many lines".to_string(),
+                category: "Complexity".to_string(),
+                help_uri: String::new(),
+            }],
+            issues: issues
+                .iter()
+                .map(|(severity, file)| IssueRecord {
+                    severity: severity.to_string(),
+                    file: file.to_string(),
+                    line: None,
+                    column: None,
+                    end_line: None,
+                    name: None,
+                    check_type: "file-length".to_string(),
+                    value: 900,
+                    limit: 500,
+                    message: String::new(),
+                    help_uri: String::new(),
+                })
+                .collect(),
+            summary: SummaryRecord {
+                files,
+                errors: issues.iter().filter(|(s, _)| *s == "error").count(),
+                warnings: issues.iter().filter(|(s, _)| *s == "warning").count(),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_merge_shards() {
+        let merged = merge(vec![
+            shard(&["python"], &[("error", "a.py")], 3),
+            shard(&["python", "rust"], &[("warning", "b.rs"), ("error", "c.py")], 4),
+        ])
+        .unwrap();
+        assert_eq!(merged.project, "/ci/3");
+        assert_eq!(merged.languages, ["python", "rust"]);
+        assert_eq!(merged.rules.len(), 1);
+        let files: Vec<&str> = merged.issues.iter().map(|i| i.file.as_str()).collect();
+        assert_eq!(files, ["a.py", "b.rs", "c.py"]);
+        assert_eq!((merged.summary.files, merged.summary.errors, merged.summary.warnings), (7, 2, 1));
+        assert!(merge(Vec::new()).is_none());
+    }
+}
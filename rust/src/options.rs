@@ -0,0 +1,354 @@
+use crate::languages::LanguageType;
+use serde::{Deserialize, Serialize};
+
+/// A `--check` value: one check, or a group of them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckType {
+    All,
+    FileLength,
+    Functions,
+    Nesting,
+    NestedLoops,
+    StringConcat,
+    Identifiers,
+    EnumVariants,
+    ShotgunSurgery,
+    ExitCalls,
+    /// Terraform modules calling modules too deeply
+    ModuleNesting,
+    /// JSX elements nested too deeply in .tsx and .jsx files
+    JsxNesting,
+    /// TODO/FIXME markers (opt-in, not part of `all`)
+    Markers,
+    /// Only the checks defined in the config file
+    Custom,
+    /// Security-sensitive patterns such as eval (opt-in, not part of `all`)
+    Security,
+}
+
+impl CheckType {
+    pub const ALL: [CheckType; 15] = [
+        CheckType::All,
+        CheckType::FileLength,
+        CheckType::Functions,
+        CheckType::Nesting,
+        CheckType::NestedLoops,
+        CheckType::StringConcat,
+        CheckType::Identifiers,
+        CheckType::EnumVariants,
+        CheckType::ShotgunSurgery,
+        CheckType::ExitCalls,
+        CheckType::ModuleNesting,
+        CheckType::JsxNesting,
+        CheckType::Markers,
+        CheckType::Custom,
+        CheckType::Security,
+    ];
+
+    /// The name `--check` takes
+    pub fn name(self) -> &'static str {
+        match self {
+            CheckType::All => "all",
+            CheckType::FileLength => "file-length",
+            CheckType::Functions => "functions",
+            CheckType::Nesting => "nesting",
+            CheckType::NestedLoops => "nested-loops",
+            CheckType::StringConcat => "string-concat",
+            CheckType::Identifiers => "identifiers",
+            CheckType::EnumVariants => "enum-variants",
+            CheckType::ShotgunSurgery => "shotgun-surgery",
+            CheckType::ExitCalls => "exit-calls",
+            CheckType::ModuleNesting => "module-nesting",
+            CheckType::JsxNesting => "jsx-nesting",
+            CheckType::Markers => "markers",
+            CheckType::Custom => "custom",
+            CheckType::Security => "security",
+        }
+    }
+
+    /// The check named, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        CheckType::ALL.into_iter().find(|c| c.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Only run when asked for by name, never as part of `all`
+    pub fn is_opt_in(self) -> bool {
+        matches!(self, CheckType::Markers | CheckType::Security)
+    }
+}
+
+/// The checks a run performs
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckSelection {
+    checks: Vec<CheckType>,
+}
+
+impl CheckSelection {
+    pub fn new(checks: &[CheckType]) -> Self {
+        CheckSelection { checks: checks.to_vec() }
+    }
+
+    /// Every check that isn't opt-in
+    pub fn all() -> Self {
+        CheckSelection::new(&[CheckType::All])
+    }
+
+    /// Add a check, such as the security pack with `--security`
+    pub fn with(mut self, check: CheckType) -> Self {
+        if !self.checks.contains(&check) {
+            self.checks.push(check);
+        }
+        self
+    }
+
+    pub fn runs(&self, check: CheckType) -> bool {
+        self.checks.contains(&check) || (self.checks.contains(&CheckType::All) && !check.is_opt_in())
+    }
+
+    /// The checks as given, e.g. for the manifest
+    pub fn checks(&self) -> &[CheckType] {
+        &self.checks
+    }
+}
+
+impl Default for CheckSelection {
+    fn default() -> Self {
+        CheckSelection::all()
+    }
+}
+
+/// How the report is written
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Sarif,
+    Junit,
+    /// Code Climate engine issues, NUL-separated
+    Codeclimate,
+    /// One JSON object per issue as files finish, then a summary line
+    Ndjson,
+    /// `path:line:col: severity: check: message` lines for editors
+    Compact,
+}
+
+impl OutputFormat {
+    /// The name `--format` takes
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Sarif => "sarif",
+            OutputFormat::Junit => "junit",
+            OutputFormat::Codeclimate => "codeclimate",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Compact => "compact",
+        }
+    }
+}
+
+/// Which lines file-length and function-length count
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CountMode {
+    /// Every line
+    #[default]
+    Physical,
+    /// Lines that aren't blank or only a comment
+    Logical,
+}
+
+/// What nesting-depth counts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NestingMode {
+    /// Blocks of every kind, including struct literals, closures, and match arms
+    #[default]
+    Blocks,
+    /// Conditionals, loops, match/switch, and try blocks, in the languages that
+    /// tell them apart; blocks elsewhere
+    ControlFlow,
+}
+
+/// Whether the function checks look at test code
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestCode {
+    /// Check test functions like any other
+    #[default]
+    Check,
+    /// Leave out test functions and the functions of test modules
+    Skip,
+}
+
+/// Whether function length counts the decorators above a function
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Decorators {
+    /// From the `def` or `fn` line
+    #[default]
+    Exclude,
+    /// From the first decorator, annotation, or attribute line
+    Include,
+}
+
+/// How many columns of indentation make a level of Python nesting
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "IndentSetting", into = "IndentSetting")]
+pub enum PythonIndent {
+    /// The most common indentation step of each file
+    Auto,
+    /// This many columns, a tab counting as one
+    Width(usize),
+}
+
+impl Default for PythonIndent {
+    fn default() -> Self {
+        PythonIndent::Width(4)
+    }
+}
+
+impl PythonIndent {
+    /// The width, or `None` to detect it per file
+    pub fn width(self) -> Option<usize> {
+        match self {
+            PythonIndent::Auto => None,
+            PythonIndent::Width(width) => Some(width),
+        }
+    }
+}
+
+impl std::str::FromStr for PythonIndent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "auto" => Ok(PythonIndent::Auto),
+            _ => match s.parse() {
+                Ok(width) if width > 0 => Ok(PythonIndent::Width(width)),
+                _ => Err(format!("expected 'auto' or a width of at least 1, got '{}'", s)),
+            },
+        }
+    }
+}
+
+/// `python-indent` as a config file has it: a number or `"auto"`
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum IndentSetting {
+    Width(usize),
+    Name(String),
+}
+
+impl TryFrom<IndentSetting> for PythonIndent {
+    type Error = String;
+
+    fn try_from(setting: IndentSetting) -> Result<Self, String> {
+        match setting {
+            IndentSetting::Width(width) => width.to_string().parse(),
+            IndentSetting::Name(name) => name.parse(),
+        }
+    }
+}
+
+impl From<PythonIndent> for IndentSetting {
+    fn from(indent: PythonIndent) -> Self {
+        match indent {
+            PythonIndent::Auto => IndentSetting::Name("auto".to_string()),
+            PythonIndent::Width(width) => IndentSetting::Width(width),
+        }
+    }
+}
+
+/// How the text report groups issues
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupBy {
+    /// ERRORS, WARNINGS, and NEAR MISSES sections
+    Severity,
+    File,
+    Check,
+    /// The directory containing the file
+    Directory,
+    /// The nested project containing the file, with --recursive-projects
+    Project,
+}
+
+/// Order of the reported issues
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    /// Errors first, then warnings, then near misses
+    Severity,
+    /// By path, then line
+    File,
+    /// Largest measured value first
+    Value,
+    /// By check id
+    Check,
+}
+
+/// When the text report is colored
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+/// Which severities the report lists
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeverityFilter {
+    All,
+    ErrorsOnly,
+    WarningsOnly,
+}
+
+/// Process exit code for each outcome of a run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExitCodeMap {
+    /// No warnings or errors
+    pub clean: i32,
+    /// Warnings but no errors
+    pub warnings: i32,
+    /// At least one error
+    pub errors: i32,
+    /// At least one error from the security pack, whatever the budget;
+    /// reported as `errors` unless mapped
+    pub critical: Option<i32>,
+    /// The tool itself failed (bad arguments, unreadable directory, ...)
+    pub failure: i32,
+}
+
+impl Default for ExitCodeMap {
+    fn default() -> Self {
+        ExitCodeMap {
+            clean: 0,
+            warnings: 1,
+            errors: 2,
+            critical: None,
+            failure: 3,
+        }
+    }
+}
+
+/// A `--source-dir` value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceDirOverride {
+    /// Language the directory applies to (default: every language)
+    pub language: Option<LanguageType>,
+    pub dir: String,
+}
+
+/// A `--shard` value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shard {
+    /// From 1 to `count`
+    pub index: u64,
+    pub count: u64,
+}
+
+/// How many errors and warnings a run tolerates before they set the exit code
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Budget {
+    pub errors: usize,
+    pub warnings: usize,
+}
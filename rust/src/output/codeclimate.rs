@@ -0,0 +1,129 @@
+use super::{Issue, Report, Severity};
+use crate::checks::rules::RULES;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::io::{self, Write};
+
+/// Remediation points for any violation; Code Climate rates 50,000 points as a few minutes of work
+const BASE_POINTS: usize = 500_000;
+/// Additional points per unit over the limit (a line, a nesting level, a variant, ...)
+const OVERAGE_POINTS: usize = 50_000;
+
+#[derive(Serialize)]
+struct CodeClimateIssue<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    check_name: &'static str,
+    description: &'a str,
+    categories: [&'static str; 1],
+    location: Location,
+    remediation_points: usize,
+    severity: &'static str,
+    fingerprint: String,
+}
+
+#[derive(Serialize)]
+struct Location {
+    path: String,
+    lines: Lines,
+}
+
+#[derive(Serialize)]
+struct Lines {
+    begin: usize,
+    end: usize,
+}
+
+fn severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "major",
+        Severity::Warning => "minor",
+        Severity::Info => "info",
+    }
+}
+
+/// Category of a check; custom checks count as style
+fn category(check_id: &str) -> &'static str {
+    RULES
+        .iter()
+        .find(|rule| rule.id == check_id)
+        .map_or("Style", |rule| rule.category)
+}
+
+/// Identifies an issue across runs; like the baseline, it leaves out the line number
+fn fingerprint(issue: &Issue) -> String {
+    let key = format!(
+        "{}\0{}\0{}",
+        issue.file.display(),
+        issue.name.as_deref().unwrap_or(""),
+        issue.check_type
+    );
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn issue(issue: &Issue) -> CodeClimateIssue<'_> {
+    let line = issue.line.unwrap_or(1);
+    CodeClimateIssue {
+        kind: "issue",
+        check_name: issue.check_type,
+        description: &issue.message,
+        categories: [category(issue.check_type)],
+        location: Location {
+            path: issue.file.display().to_string().replace('\\', "/"),
+            lines: Lines { begin: line, end: issue.end_line.unwrap_or(line) },
+        },
+        remediation_points: BASE_POINTS + OVERAGE_POINTS * issue.value.saturating_sub(issue.limit),
+        severity: severity(issue.severity),
+        fingerprint: fingerprint(issue),
+    }
+}
+
+/// Write issues as a Code Climate engine would: JSON objects, each terminated by a NUL byte
+pub fn write_codeclimate_report(out: &mut dyn Write, report: &Report) -> io::Result<()> {
+    for i in &report.issues {
+        write!(out, "{}\0", serde_json::to_string(&issue(i)).unwrap())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_issue_fields() {
+        let function = Issue {
+            severity: Severity::Error,
+            file: PathBuf::from("lib/app.rb"),
+            line: Some(12),
+            column: Some(5),
+            end_line: Some(65),
+            name: Some("run".to_string()),
+            check_type: "function-length",
+            value: 54,
+            limit: 50,
+            message: "lib/app.rb:12 run (54 lines)".to_string(),
+        };
+        let json = serde_json::to_value(issue(&function)).unwrap();
+
+        assert_eq!(json["type"], "issue");
+        assert_eq!(json["categories"][0], "Complexity");
+        assert_eq!(json["location"]["lines"]["begin"], 12);
+        assert_eq!(json["location"]["lines"]["end"], 65);
+        assert_eq!(json["remediation_points"], 700_000);
+        assert_eq!(json["severity"], "major");
+
+        // Moving the function keeps its fingerprint
+        let moved = Issue {
+            line: Some(40),
+            end_line: Some(93),
+            ..function
+        };
+        assert_eq!(fingerprint(&moved), json["fingerprint"].as_str().unwrap());
+    }
+}
@@ -0,0 +1,81 @@
+use super::{Issue, Report, Severity};
+use crate::options::SeverityFilter;
+use std::io::{self, Write};
+
+fn severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
+
+/// One `path:line:col: severity: check: message` line, as compilers print them.
+///
+/// The column is 1 for issues that don't have one. The location the message
+/// starts with is dropped, since the line already leads with it.
+fn compact_line(issue: &Issue) -> String {
+    let path = issue.file.display().to_string();
+    let line = issue.line.unwrap_or(1);
+    let located = match issue.line {
+        Some(line) => format!("{}:{} ", path, line),
+        None => format!("{} ", path),
+    };
+    let message = issue.message.strip_prefix(&located).unwrap_or(&issue.message);
+    format!(
+        "{}:{}:{}: {}: {}: {}",
+        path,
+        line,
+        issue.column.unwrap_or(1),
+        severity(issue.severity),
+        issue.check_type,
+        message
+    )
+}
+
+pub fn write_compact_report(out: &mut dyn Write, report: &Report, filter: SeverityFilter) -> io::Result<()> {
+    let shown = report.issues.iter().filter(|issue| match filter {
+        SeverityFilter::All => true,
+        SeverityFilter::ErrorsOnly => issue.severity == Severity::Error,
+        SeverityFilter::WarningsOnly => issue.severity == Severity::Warning,
+    });
+    for issue in shown {
+        writeln!(out, "{}", compact_line(issue))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_compact_line() {
+        let mut issue = Issue {
+            severity: Severity::Warning,
+            file: PathBuf::from("src/app.ts"),
+            line: Some(12),
+            column: Some(17),
+            end_line: Some(53),
+            name: Some("render".to_string()),
+            check_type: "function-length",
+            value: 42,
+            limit: 30,
+            message: "src/app.ts:12 render (42 lines)".to_string(),
+        };
+        assert_eq!(
+            compact_line(&issue),
+            "src/app.ts:12:17: warning: function-length: render (42 lines)"
+        );
+
+        issue.line = None;
+        issue.column = None;
+        issue.check_type = "file-length";
+        issue.message = "src/app.ts (420 lines, limit: 300)".to_string();
+        assert_eq!(
+            compact_line(&issue),
+            "src/app.ts:1:1: warning: file-length: (420 lines, limit: 300)"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use super::{Issue, Report, Severity};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io;
+
+/// Escape text for use in XML attributes and character data
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Render warnings and errors as JUnit XML: one test suite per check, one
+/// failing test case per file with issues from that check.
+///
+/// Near misses are left out. A clean run still yields a single passing test
+/// case, since some CI systems treat a report without tests as a failure.
+pub fn render_junit_report(report: &Report) -> String {
+    // check id -> file -> issues, in a stable order
+    let mut suites: BTreeMap<&str, BTreeMap<String, Vec<&Issue>>> = BTreeMap::new();
+    for issue in report.issues.iter().filter(|i| i.severity != Severity::Info) {
+        suites
+            .entry(issue.check_type)
+            .or_default()
+            .entry(issue.file.display().to_string().replace('\\', "/"))
+            .or_default()
+            .push(issue);
+    }
+
+    let total: usize = suites.values().map(|files| files.len()).sum();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    if suites.is_empty() {
+        xml.push_str("<testsuites name=\"code-smells\" tests=\"1\" failures=\"0\">\n");
+        xml.push_str("  <testsuite name=\"code-smells\" tests=\"1\" failures=\"0\">\n");
+        let _ = writeln!(
+            xml,
+            "    <testcase classname=\"code-smells\" name=\"{} files scanned\"/>",
+            report.files_scanned
+        );
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        return xml;
+    }
+
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"code-smells\" tests=\"{0}\" failures=\"{0}\">",
+        total
+    );
+    for (check, files) in &suites {
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{0}\" tests=\"{1}\" failures=\"{1}\">",
+            escape(check),
+            files.len()
+        );
+        for (file, issues) in files {
+            let worst = issues.iter().map(|i| i.severity).max().unwrap_or(Severity::Warning);
+            let summary = match issues.len() {
+                1 => issues[0].message.clone(),
+                n => format!("{} {} issues", n, check),
+            };
+            let _ = writeln!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{}\" file=\"{}\">",
+                escape(check),
+                escape(file),
+                escape(file)
+            );
+            let _ = writeln!(
+                xml,
+                "      <failure type=\"{}\" message=\"{}\">",
+                if worst == Severity::Error { "error" } else { "warning" },
+                escape(&summary)
+            );
+            for issue in issues {
+                let label = if issue.severity == Severity::Error { "ERROR" } else { "WARN" };
+                let _ = writeln!(xml, "{} {}", label, escape(&issue.message));
+            }
+            xml.push_str("      </failure>\n    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+pub fn write_junit_report(out: &mut dyn io::Write, report: &Report) -> io::Result<()> {
+    out.write_all(render_junit_report(report).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn issue(file: &str, check_type: &'static str, severity: Severity, message: &str) -> Issue {
+        Issue {
+            severity,
+            file: PathBuf::from(file),
+            line: Some(1),
+            column: None,
+            end_line: None,
+            name: None,
+            check_type,
+            value: 0,
+            limit: 0,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_issues_grouped_by_check_and_file() {
+        let report = Report {
+            issues: vec![
+                issue("a.rs", "function-length", Severity::Warning, "a.rs:1 parse (40 lines)"),
+                issue("a.rs", "function-length", Severity::Error, "a.rs:9 run (90 lines)"),
+                issue("b.rs", "nesting-depth", Severity::Warning, "b.rs:3 <T> & co (depth 5)"),
+                issue("c.rs", "function-length", Severity::Info, "near miss"),
+            ],
+            files_scanned: 3,
+            ..Default::default()
+        };
+        let xml = render_junit_report(&report);
+
+        assert!(xml.contains("<testsuites name=\"code-smells\" tests=\"2\" failures=\"2\">"));
+        assert!(xml.contains("<testsuite name=\"function-length\" tests=\"1\" failures=\"1\">"));
+        assert!(xml.contains("<failure type=\"error\" message=\"2 function-length issues\">"));
+        assert!(xml.contains("&lt;T&gt; &amp; co"));
+        assert!(!xml.contains("c.rs"));
+    }
+
+    #[test]
+    fn test_clean_run_has_passing_case() {
+        let report = Report {
+            files_scanned: 4,
+            ..Default::default()
+        };
+        let xml = render_junit_report(&report);
+        assert!(xml.contains("<testcase classname=\"code-smells\" name=\"4 files scanned\"/>"));
+        assert!(!xml.contains("<failure"));
+    }
+}
@@ -0,0 +1,849 @@
+mod codeclimate;
+mod compact;
+mod junit;
+mod sarif;
+
+use crate::checks::rules::{help_uri, RULES};
+use crate::languages::LanguageType;
+use crate::checks::CustomCheck;
+use crate::options::{Budget, ColorChoice, ExitCodeMap, GroupBy, OutputFormat, SeverityFilter, SortKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Ordered from least to most severe
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    #[serde(serialize_with = "serialize_path")]
+    pub file: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Column of `line`, from 1, where the issue starts, e.g. a function's name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// Last line of what the issue covers, e.g. a function's closing line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub check_type: &'static str,
+    pub value: usize,
+    pub limit: usize,
+    pub message: String,
+}
+
+/// An issue as saved, before its check id is made static again
+#[derive(Deserialize)]
+struct SavedIssue {
+    severity: Severity,
+    file: PathBuf,
+    #[serde(default)]
+    line: Option<usize>,
+    #[serde(default)]
+    column: Option<usize>,
+    #[serde(default)]
+    end_line: Option<usize>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(rename = "type")]
+    check_type: String,
+    value: usize,
+    limit: usize,
+    message: String,
+}
+
+impl<'de> Deserialize<'de> for Issue {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let saved = SavedIssue::deserialize(d)?;
+        // Built-in checks keep their static ids; others are leaked, as when a custom check is compiled
+        let known = RULES.iter().map(|rule| rule.id).find(|id| *id == saved.check_type);
+        Ok(Issue {
+            severity: saved.severity,
+            file: saved.file,
+            line: saved.line,
+            column: saved.column,
+            end_line: saved.end_line,
+            name: saved.name,
+            check_type: known.unwrap_or_else(|| Box::leak(saved.check_type.into_boxed_str())),
+            value: saved.value,
+            limit: saved.limit,
+            message: saved.message,
+        })
+    }
+}
+
+fn serialize_path<S>(path: &Path, s: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    s.serialize_str(&path.display().to_string())
+}
+
+/// The results of a run, which can be saved with serde and merged with those of other runs
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Report {
+    pub issues: Vec<Issue>,
+    pub files_scanned: usize,
+    /// Known issues left out because they are in the baseline
+    pub baselined: usize,
+    /// Issues left out by ignore-file directives and disabled checks
+    pub suppressed: usize,
+    /// Time spent in each check (and in parsing), summed over all files; not saved
+    #[serde(skip)]
+    pub check_time: BTreeMap<&'static str, Duration>,
+    /// Time spent on the files of each language, from reading to the last check; not saved
+    #[serde(skip)]
+    pub language_time: BTreeMap<&'static str, Duration>,
+}
+
+/// Findings of each severity, for a breakdown of a report
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Counts {
+    pub errors: usize,
+    pub warnings: usize,
+    pub near_misses: usize,
+}
+
+impl Counts {
+    fn add(&mut self, severity: Severity) {
+        match severity {
+            Severity::Error => self.errors += 1,
+            Severity::Warning => self.warnings += 1,
+            Severity::Info => self.near_misses += 1,
+        }
+    }
+}
+
+impl Report {
+    pub fn error_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == Severity::Error)
+            .count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == Severity::Warning)
+            .count()
+    }
+
+    pub fn near_miss_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == Severity::Info)
+            .count()
+    }
+
+    /// Exit code for the findings, once those within the budget are tolerated
+    pub fn exit_code(&self, codes: &ExitCodeMap, budget: Budget) -> i32 {
+        let critical = codes.critical.filter(|_| {
+            self.issues.iter().any(|i| i.severity == Severity::Error && i.check_type == "security-pattern")
+        });
+        if let Some(code) = critical {
+            code
+        } else if self.error_count() > budget.errors {
+            codes.errors
+        } else if self.warning_count() > budget.warnings {
+            codes.warnings
+        } else {
+            codes.clean
+        }
+    }
+
+    pub fn add_issue(&mut self, issue: Issue) {
+        self.issues.push(issue);
+    }
+
+    /// Reorder the issues; ties keep their current order
+    pub fn sort(&mut self, key: SortKey) {
+        match key {
+            SortKey::Severity => self.issues.sort_by_key(|i| std::cmp::Reverse(i.severity)),
+            SortKey::File => self.issues.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line))),
+            SortKey::Value => self.issues.sort_by_key(|i| std::cmp::Reverse(i.value)),
+            SortKey::Check => self.issues.sort_by_key(|i| i.check_type),
+        }
+    }
+
+    /// Findings per check id
+    pub fn by_check(&self) -> BTreeMap<&'static str, Counts> {
+        let mut counts: BTreeMap<&'static str, Counts> = BTreeMap::new();
+        for issue in &self.issues {
+            counts.entry(issue.check_type).or_default().add(issue.severity);
+        }
+        counts
+    }
+
+    /// Findings per language, by file extension; `generic` for the rest
+    pub fn by_language(&self) -> BTreeMap<&'static str, Counts> {
+        let mut counts: BTreeMap<&'static str, Counts> = BTreeMap::new();
+        for issue in &self.issues {
+            let language = LanguageType::for_path(&issue.file).unwrap_or(LanguageType::Generic);
+            counts.entry(language.name()).or_default().add(issue.severity);
+        }
+        counts
+    }
+
+    /// Append the results of another report, e.g. one produced for a single file or shard
+    pub fn merge(&mut self, other: Report) {
+        self.issues.extend(other.issues);
+        self.files_scanned += other.files_scanned;
+        self.baselined += other.baselined;
+        self.suppressed += other.suppressed;
+        for (check, time) in other.check_time {
+            *self.check_time.entry(check).or_default() += time;
+        }
+        for (language, time) in other.language_time {
+            *self.language_time.entry(language).or_default() += time;
+        }
+    }
+}
+
+// ANSI color codes
+const RED: &str = "\x1b[0;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const GREEN: &str = "\x1b[0;32m";
+const BLUE: &str = "\x1b[0;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+fn is_terminal() -> bool {
+    std::io::IsTerminal::is_terminal(&std::io::stdout())
+}
+
+/// Whether stdout gets colored text, per `--color` and the NO_COLOR and CLICOLOR_FORCE conventions
+pub fn use_color(choice: ColorChoice) -> bool {
+    color_enabled(
+        choice,
+        std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()),
+        std::env::var_os("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0"),
+        is_terminal(),
+    )
+}
+
+fn color_enabled(choice: ColorChoice, no_color: bool, force: bool, terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        // NO_COLOR wins over CLICOLOR_FORCE
+        ColorChoice::Auto => !no_color && (force || terminal),
+    }
+}
+
+/// What a report is about and which issues to show, shared by every format
+pub struct ReportOptions<'a> {
+    pub project_dir: &'a Path,
+    pub languages: &'a [LanguageType],
+    pub filter: SeverityFilter,
+    pub group_by: GroupBy,
+    /// Leave out everything but the summary of the text report
+    pub summary_only: bool,
+    pub help_base: &'a str,
+    pub custom: &'a [CustomCheck],
+    /// Nested project directories found with `--recursive-projects`
+    pub projects: &'a [String],
+    /// Add the time per check and per language to the JSON summary
+    pub timing: bool,
+}
+
+/// Write a report in the given format, with ANSI colors in the text format if `color` is set
+pub fn write_report(
+    out: &mut dyn Write,
+    report: &Report,
+    format: OutputFormat,
+    options: &ReportOptions,
+    color: bool,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Text => write_text_report(out, report, options, color),
+        OutputFormat::Json => write_json_report(out, report, options),
+        OutputFormat::Sarif => sarif::write_sarif_report(out, report, options.help_base, options.custom),
+        OutputFormat::Junit => junit::write_junit_report(out, report),
+        OutputFormat::Codeclimate => codeclimate::write_codeclimate_report(out, report),
+        OutputFormat::Compact => compact::write_compact_report(out, report, options.filter),
+        OutputFormat::Ndjson => {
+            write_ndjson_issues(out, report, options.help_base, options.custom)?;
+            write_ndjson_summary(out, report)
+        }
+    }
+}
+
+/// Write a report to stdout
+pub fn print_report(report: &Report, format: OutputFormat, options: &ReportOptions, color: bool) {
+    // Nothing sensible is left to do if stdout is gone, e.g. piped into `head`
+    let _ = write_report(&mut io::stdout().lock(), report, format, options, color);
+}
+
+/// Documentation link for an issue, preferring the one configured for a custom check
+fn issue_help_uri(help_base: &str, custom: &[CustomCheck], id: &str) -> String {
+    custom
+        .iter()
+        .find(|c| c.id == id)
+        .and_then(|c| c.def.help_uri.clone())
+        .unwrap_or_else(|| help_uri(help_base, id))
+}
+
+/// Name of the group an issue belongs to
+fn group_key(issue: &Issue, group_by: GroupBy, projects: &[String]) -> String {
+    match group_by {
+        GroupBy::Severity => format!("{:?}", issue.severity),
+        GroupBy::File => issue.file.display().to_string(),
+        GroupBy::Check => issue.check_type.to_string(),
+        GroupBy::Directory => match issue.file.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.display().to_string(),
+            _ => ".".to_string(),
+        },
+        // The innermost project containing the file
+        GroupBy::Project => projects
+            .iter()
+            .filter(|dir| dir.as_str() == "." || issue.file.starts_with(dir))
+            .max_by_key(|dir| if dir.as_str() == "." { 0 } else { dir.len() })
+            .cloned()
+            .unwrap_or_else(|| ".".to_string()),
+    }
+}
+
+/// Write one section per file, check, directory, or project, in name order
+fn write_groups(
+    out: &mut dyn Write,
+    report: &Report,
+    filter: SeverityFilter,
+    group_by: GroupBy,
+    projects: &[String],
+    color: bool,
+) -> io::Result<()> {
+    let (bold, reset, red, yellow, blue) = if color {
+        (BOLD, RESET, RED, YELLOW, BLUE)
+    } else {
+        ("", "", "", "", "")
+    };
+    let shown = report.issues.iter().filter(|issue| match issue.severity {
+        Severity::Error => !matches!(filter, SeverityFilter::WarningsOnly),
+        Severity::Warning => !matches!(filter, SeverityFilter::ErrorsOnly),
+        Severity::Info => matches!(filter, SeverityFilter::All),
+    });
+    let mut groups: BTreeMap<String, Vec<&Issue>> = BTreeMap::new();
+    for issue in shown {
+        groups.entry(group_key(issue, group_by, projects)).or_default().push(issue);
+    }
+
+    for (name, issues) in &groups {
+        writeln!(out)?;
+        writeln!(out, "{}--- {} ({}) ---{}", bold, name, issues.len(), reset)?;
+        for issue in issues {
+            match issue.severity {
+                Severity::Error => writeln!(out, "{}ERROR{}  {}", red, reset, issue.message)?,
+                Severity::Warning => writeln!(out, "{}WARN{}   {}", yellow, reset, issue.message)?,
+                Severity::Info => writeln!(out, "{}INFO{}   {}", blue, reset, issue.message)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_text_report(out: &mut dyn Write, report: &Report, options: &ReportOptions, color: bool) -> io::Result<()> {
+    let ReportOptions {
+        project_dir,
+        languages,
+        filter,
+        group_by,
+        summary_only,
+        ..
+    } = *options;
+    let (bold, reset, red, yellow, blue) = if color {
+        (BOLD, RESET, RED, YELLOW, BLUE)
+    } else {
+        ("", "", "", "", "")
+    };
+
+    if summary_only {
+        return write_summary(out, report, color);
+    }
+
+    // Header
+    writeln!(out, "{}=== Code Smells Report ==={}", bold, reset)?;
+    writeln!(out, "Project: {}", project_dir.display())?;
+    let lang_names: Vec<&str> = languages.iter().map(|l| l.name()).collect();
+    writeln!(out, "Languages: {}", lang_names.join(", "))?;
+
+    // Collect errors and warnings
+    let errors: Vec<&Issue> = report
+        .issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .collect();
+    let warnings: Vec<&Issue> = report
+        .issues
+        .iter()
+        .filter(|i| i.severity == Severity::Warning)
+        .collect();
+    let near_misses: Vec<&Issue> = report
+        .issues
+        .iter()
+        .filter(|i| i.severity == Severity::Info)
+        .collect();
+
+    if group_by != GroupBy::Severity {
+        write_groups(out, report, filter, group_by, options.projects, color)?;
+    }
+
+    // Print errors
+    if group_by == GroupBy::Severity && !matches!(filter, SeverityFilter::WarningsOnly) && !errors.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "{}--- ERRORS ({}) ---{}", bold, errors.len(), reset)?;
+        for issue in &errors {
+            writeln!(out, "{}ERROR{}  {}", red, reset, issue.message)?;
+        }
+    }
+
+    // Print warnings
+    if group_by == GroupBy::Severity && !matches!(filter, SeverityFilter::ErrorsOnly) && !warnings.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "{}--- WARNINGS ({}) ---{}", bold, warnings.len(), reset)?;
+        for issue in &warnings {
+            writeln!(out, "{}WARN{}   {}", yellow, reset, issue.message)?;
+        }
+    }
+
+    // Print near misses
+    if group_by == GroupBy::Severity && matches!(filter, SeverityFilter::All) && !near_misses.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "{}--- NEAR MISSES ({}) ---{}", bold, near_misses.len(), reset)?;
+        for issue in &near_misses {
+            writeln!(out, "{}INFO{}   {}", blue, reset, issue.message)?;
+        }
+    }
+
+    writeln!(out)?;
+    write_summary(out, report, color)
+}
+
+fn write_summary(out: &mut dyn Write, report: &Report, color: bool) -> io::Result<()> {
+    let (bold, reset, red, yellow, green) = if color {
+        (BOLD, RESET, RED, YELLOW, GREEN)
+    } else {
+        ("", "", "", "", "")
+    };
+    writeln!(out, "{}--- SUMMARY ---{}", bold, reset)?;
+    writeln!(out, "Files scanned: {}", report.files_scanned)?;
+    if report.error_count() > 0 {
+        writeln!(out, "Errors: {}{}{}", red, report.error_count(), reset)?;
+    } else {
+        writeln!(out, "Errors: {}0{}", green, reset)?;
+    }
+    if report.warning_count() > 0 {
+        writeln!(out, "Warnings: {}{}{}", yellow, report.warning_count(), reset)?;
+    } else {
+        writeln!(out, "Warnings: {}0{}", green, reset)?;
+    }
+    if report.near_miss_count() > 0 {
+        writeln!(out, "Near misses: {}", report.near_miss_count())?;
+    }
+    if report.baselined > 0 {
+        writeln!(out, "Known issues (baseline): {}", report.baselined)?;
+    }
+    Ok(())
+}
+
+/// Version of the `--format json` structure, bumped on incompatible changes
+pub const JSON_FORMAT_VERSION: u32 = 1;
+
+/// JSON Schema describing the `--format json` output
+pub const JSON_SCHEMA: &str = include_str!("../../../docs/report.schema.json");
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    version: u32,
+    project: String,
+    languages: Vec<&'a str>,
+    rules: Vec<RuleMeta>,
+    issues: Vec<JsonIssue<'a>>,
+    summary: JsonSummary,
+}
+
+/// A check that can report issues, as listed in JSON and SARIF output
+#[derive(Serialize)]
+struct RuleMeta {
+    id: &'static str,
+    name: String,
+    description: String,
+    category: &'static str,
+    help_uri: String,
+}
+
+/// Metadata for the built-in checks followed by the custom ones
+fn rule_metadata(help_base: &str, custom: &[CustomCheck]) -> Vec<RuleMeta> {
+    let builtin = RULES.iter().map(|rule| RuleMeta {
+        id: rule.id,
+        name: rule.name.to_string(),
+        description: rule.description.to_string(),
+        category: rule.category,
+        help_uri: help_uri(help_base, rule.id),
+    });
+    let configured = custom.iter().map(|check| RuleMeta {
+        id: check.id,
+        name: check.def.name.clone(),
+        description: check.def.message.clone().unwrap_or_else(|| match &check.def.pattern {
+            Some(pattern) => format!("Matches of /{}/", pattern),
+            None => format!("Reported by `{}`", check.def.command.join(" ")),
+        }),
+        category: "Style",
+        help_uri: issue_help_uri(help_base, custom, check.id),
+    });
+    builtin.chain(configured).collect()
+}
+
+#[derive(Serialize)]
+struct JsonIssue<'a> {
+    #[serde(flatten)]
+    issue: &'a Issue,
+    help_uri: String,
+}
+
+#[derive(Serialize)]
+struct JsonSummary {
+    files: usize,
+    errors: usize,
+    warnings: usize,
+    near_misses: usize,
+    baselined: usize,
+    suppressed: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timing: Option<JsonTiming>,
+}
+
+impl JsonSummary {
+    fn new(report: &Report) -> Self {
+        JsonSummary {
+            files: report.files_scanned,
+            errors: report.error_count(),
+            warnings: report.warning_count(),
+            near_misses: report.near_miss_count(),
+            baselined: report.baselined,
+            suppressed: report.suppressed,
+            timing: None,
+        }
+    }
+}
+
+/// Milliseconds spent per check and per language, summed over all threads
+#[derive(Serialize)]
+struct JsonTiming {
+    checks: BTreeMap<&'static str, f64>,
+    languages: BTreeMap<&'static str, f64>,
+}
+
+impl JsonTiming {
+    fn new(report: &Report) -> Self {
+        let millis = |times: &BTreeMap<&'static str, Duration>| {
+            times
+                .iter()
+                .map(|(id, time)| (*id, (time.as_secs_f64() * 1e6).round() / 1e3))
+                .collect()
+        };
+        JsonTiming {
+            checks: millis(&report.check_time),
+            languages: millis(&report.language_time),
+        }
+    }
+}
+
+fn json_report<'a>(
+    report: &'a Report,
+    project_dir: &Path,
+    languages: &'a [LanguageType],
+    help_base: &str,
+    custom: &[CustomCheck],
+    timing: bool,
+) -> JsonReport<'a> {
+    JsonReport {
+        version: JSON_FORMAT_VERSION,
+        project: project_dir.display().to_string(),
+        languages: languages.iter().map(|l| l.name()).collect(),
+        rules: rule_metadata(help_base, custom),
+        issues: report
+            .issues
+            .iter()
+            .map(|issue| JsonIssue {
+                issue,
+                help_uri: issue_help_uri(help_base, custom, issue.check_type),
+            })
+            .collect(),
+        summary: JsonSummary {
+            timing: timing.then(|| JsonTiming::new(report)),
+            ..JsonSummary::new(report)
+        },
+    }
+}
+
+fn write_json_report(out: &mut dyn Write, report: &Report, options: &ReportOptions) -> io::Result<()> {
+    let json_report = json_report(
+        report,
+        options.project_dir,
+        options.languages,
+        options.help_base,
+        options.custom,
+        options.timing,
+    );
+    writeln!(out, "{}", serde_json::to_string_pretty(&json_report).unwrap())
+}
+
+fn write_ndjson_issues(out: &mut dyn Write, report: &Report, help_base: &str, custom: &[CustomCheck]) -> io::Result<()> {
+    for issue in &report.issues {
+        let line = JsonIssue {
+            issue,
+            help_uri: issue_help_uri(help_base, custom, issue.check_type),
+        };
+        writeln!(out, "{}", serde_json::to_string(&line).unwrap())?;
+    }
+    Ok(())
+}
+
+/// Print one JSON line per issue to stdout, keeping a file's issues together
+pub fn print_ndjson_issues(report: &Report, help_base: &str, custom: &[CustomCheck]) {
+    let _ = write_ndjson_issues(&mut io::stdout().lock(), report, help_base, custom);
+}
+
+#[derive(Serialize)]
+struct NdjsonSummary {
+    summary: JsonSummary,
+}
+
+/// Final line of an NDJSON stream
+fn write_ndjson_summary(out: &mut dyn Write, report: &Report) -> io::Result<()> {
+    let line = NdjsonSummary {
+        summary: JsonSummary::new(report),
+    };
+    writeln!(out, "{}", serde_json::to_string(&line).unwrap())
+}
+
+/// End an NDJSON stream whose issues were printed with `print_ndjson_issues`
+pub fn print_ndjson_summary(report: &Report) {
+    let _ = write_ndjson_summary(&mut io::stdout().lock(), report);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    /// Check an object's keys against a schema object's `required` and `properties`
+    fn assert_keys(value: &Value, schema: &Value) {
+        let object = value.as_object().unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        for key in schema["required"].as_array().unwrap() {
+            assert!(object.contains_key(key.as_str().unwrap()), "missing {}", key);
+        }
+        for key in object.keys() {
+            assert!(properties.contains_key(key), "{} not in schema", key);
+        }
+    }
+
+    #[test]
+    fn test_color_enabled() {
+        assert!(color_enabled(ColorChoice::Auto, false, false, true));
+        assert!(!color_enabled(ColorChoice::Auto, false, false, false));
+        // Piping through `less -R`
+        assert!(color_enabled(ColorChoice::Auto, false, true, false));
+        assert!(!color_enabled(ColorChoice::Auto, true, true, true));
+        assert!(color_enabled(ColorChoice::Always, true, false, false));
+        assert!(!color_enabled(ColorChoice::Never, false, true, true));
+    }
+
+    #[test]
+    fn test_json_report_matches_schema() {
+        let schema: Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        let report = Report {
+            issues: vec![Issue {
+                severity: Severity::Warning,
+                file: PathBuf::from("a.py"),
+                line: Some(3),
+                column: Some(5),
+                end_line: Some(42),
+                name: Some("parse".to_string()),
+                check_type: "function-length",
+                value: 40,
+                limit: 30,
+                message: "a.py:3 parse (40 lines)".to_string(),
+            }],
+            files_scanned: 1,
+            ..Default::default()
+        };
+        let languages = [LanguageType::Python];
+        let json = serde_json::to_value(json_report(&report, Path::new("/p"), &languages, "", &[], true)).unwrap();
+        assert_eq!(json["issues"][0]["column"], 5);
+        assert_eq!(json["issues"][0]["end_line"], 42);
+
+        assert_keys(&json, &schema);
+        assert_eq!(json["version"], schema["properties"]["version"]["const"]);
+        assert_keys(&json["rules"][0], &schema["$defs"]["rule"]);
+        assert_keys(&json["issues"][0], &schema["$defs"]["issue"]);
+        assert_keys(&json["summary"], &schema["$defs"]["summary"]);
+        assert_keys(&json["summary"]["timing"], &schema["$defs"]["summary"]["properties"]["timing"]);
+
+        let categories = schema["$defs"]["rule"]["properties"]["category"]["enum"].as_array().unwrap();
+        for rule in RULES {
+            assert!(categories.iter().any(|c| c == rule.category), "{}", rule.id);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_and_breakdowns() {
+        let issue = |file: &str, check_type, severity| Issue {
+            severity,
+            file: PathBuf::from(file),
+            line: None,
+            column: None,
+            end_line: None,
+            name: None,
+            check_type,
+            value: 1,
+            limit: 0,
+            message: String::new(),
+        };
+        let shard = Report {
+            issues: vec![
+                issue("a.rs", "nesting-depth", Severity::Warning),
+                issue("b.py", "team-rule", Severity::Error),
+            ],
+            files_scanned: 2,
+            ..Default::default()
+        };
+        let mut report: Report = serde_json::from_str(&serde_json::to_string(&shard).unwrap()).unwrap();
+        report.merge(Report {
+            issues: vec![issue("c.rs", "nesting-depth", Severity::Info)],
+            files_scanned: 1,
+            ..Default::default()
+        });
+
+        assert_eq!(report.files_scanned, 3);
+        assert_eq!(report.issues[1].check_type, "team-rule");
+        let nesting = Counts {
+            warnings: 1,
+            near_misses: 1,
+            ..Default::default()
+        };
+        assert_eq!(report.by_check()["nesting-depth"], nesting);
+        assert_eq!(report.by_language()["rust"], nesting);
+        assert_eq!(report.by_language()["python"].errors, 1);
+    }
+
+    #[test]
+    fn test_sort() {
+        let issue = |file: &str, line, check_type, severity, value| Issue {
+            severity,
+            file: PathBuf::from(file),
+            line: Some(line),
+            column: None,
+            end_line: None,
+            name: None,
+            check_type,
+            value,
+            limit: 0,
+            message: String::new(),
+        };
+        let mut report = Report {
+            issues: vec![
+                issue("b.rs", 1, "nesting-depth", Severity::Warning, 5),
+                issue("a.rs", 9, "function-length", Severity::Error, 90),
+                issue("a.rs", 2, "nesting-depth", Severity::Error, 7),
+            ],
+            ..Default::default()
+        };
+        let order = |report: &Report| -> Vec<usize> { report.issues.iter().map(|i| i.value).collect() };
+
+        report.sort(SortKey::Value);
+        assert_eq!(order(&report), [90, 7, 5]);
+        report.sort(SortKey::File);
+        assert_eq!(order(&report), [7, 90, 5]);
+        report.sort(SortKey::Check);
+        assert_eq!(order(&report), [90, 7, 5]);
+        report.sort(SortKey::Severity);
+        assert_eq!(order(&report), [90, 7, 5]);
+    }
+
+    #[test]
+    fn test_group_by_file() {
+        let issue = |file: &str, severity| Issue {
+            severity,
+            file: PathBuf::from(file),
+            line: None,
+            column: None,
+            end_line: None,
+            name: None,
+            check_type: "file-length",
+            value: 0,
+            limit: 0,
+            message: file.to_string(),
+        };
+        let report = Report {
+            issues: vec![
+                issue("b.rs", Severity::Error),
+                issue("a.rs", Severity::Warning),
+                issue("b.rs", Severity::Warning),
+            ],
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        write_groups(&mut out, &report, SeverityFilter::All, GroupBy::File, &[], false).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text, "\n--- a.rs (1) ---\nWARN   a.rs\n\n--- b.rs (2) ---\nERROR  b.rs\nWARN   b.rs\n");
+        assert_eq!(group_key(&report.issues[0], GroupBy::Directory, &[]), ".");
+
+        let projects = [".".to_string(), "apps/web".to_string(), "apps/web/admin".to_string()];
+        let key = |file: &str| group_key(&issue(file, Severity::Error), GroupBy::Project, &projects);
+        assert_eq!(key("apps/web/admin/src/a.ts"), "apps/web/admin");
+        assert_eq!(key("apps/web/src/a.ts"), "apps/web");
+        assert_eq!(key("apps/webhooks/a.ts"), ".");
+    }
+
+    #[test]
+    fn test_exit_code_budget() {
+        let issue = |severity| Issue {
+            severity,
+            file: PathBuf::from("a.rs"),
+            line: None,
+            column: None,
+            end_line: None,
+            name: None,
+            check_type: "file-length",
+            value: 0,
+            limit: 0,
+            message: String::new(),
+        };
+        let report = Report {
+            issues: vec![issue(Severity::Error), issue(Severity::Warning), issue(Severity::Warning)],
+            ..Default::default()
+        };
+        let codes = ExitCodeMap::default();
+        assert_eq!(report.exit_code(&codes, Budget::default()), codes.errors);
+        let budget = Budget { errors: 1, warnings: 1 };
+        assert_eq!(report.exit_code(&codes, budget), codes.warnings);
+        let budget = Budget { errors: 1, warnings: 2 };
+        assert_eq!(report.exit_code(&codes, budget), codes.clean);
+
+        // Security errors are critical once mapped, even within the budget
+        let codes = ExitCodeMap {
+            critical: Some(4),
+            ..codes
+        };
+        assert_eq!(report.exit_code(&codes, Budget::default()), codes.errors);
+        let mut report = report;
+        report.issues[0].check_type = "security-pattern";
+        assert_eq!(report.exit_code(&codes, budget), 4);
+    }
+}
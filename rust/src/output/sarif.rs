@@ -0,0 +1,155 @@
+use super::{rule_metadata, Issue, Report, Severity};
+use crate::checks::CustomCheck;
+use serde::Serialize;
+use std::io::{self, Write};
+
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun<'a>>,
+}
+
+#[derive(Serialize)]
+struct SarifRun<'a> {
+    tool: SarifTool,
+    results: Vec<SarifResult<'a>>,
+}
+
+#[derive(Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRule {
+    id: &'static str,
+    name: String,
+    short_description: SarifMessage,
+    help_uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    rule_id: &'static str,
+    level: &'static str,
+    message: SarifMessage,
+    locations: Vec<SarifLocation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<SarifProperties<'a>>,
+}
+
+#[derive(Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<SarifRegion>,
+}
+
+#[derive(Serialize)]
+struct SarifArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_line: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct SarifProperties<'a> {
+    name: &'a str,
+    value: usize,
+    limit: usize,
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
+
+fn result(issue: &Issue) -> SarifResult<'_> {
+    SarifResult {
+        rule_id: issue.check_type,
+        level: level(issue.severity),
+        message: SarifMessage {
+            text: issue.message.clone(),
+        },
+        locations: vec![SarifLocation {
+            physical_location: SarifPhysicalLocation {
+                artifact_location: SarifArtifactLocation {
+                    uri: issue.file.display().to_string().replace('\\', "/"),
+                },
+                region: issue.line.map(|start_line| SarifRegion {
+                    start_line,
+                    start_column: issue.column,
+                    end_line: issue.end_line,
+                }),
+            },
+        }],
+        properties: Some(SarifProperties {
+            name: issue.name.as_deref().unwrap_or(""),
+            value: issue.value,
+            limit: issue.limit,
+        }),
+    }
+}
+
+pub fn write_sarif_report(out: &mut dyn Write, report: &Report, help_base: &str, custom: &[CustomCheck]) -> io::Result<()> {
+    let rules = rule_metadata(help_base, custom).into_iter().map(|rule| SarifRule {
+        id: rule.id,
+        name: rule.name,
+        short_description: SarifMessage { text: rule.description },
+        help_uri: rule.help_uri,
+    });
+
+    let log = SarifLog {
+        schema: "https://json.schemastore.org/sarif-2.1.0.json",
+        version: "2.1.0",
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "code-smells",
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: "https://github.com/byronsalty/code-smells",
+                    rules: rules.collect(),
+                },
+            },
+            results: report.issues.iter().map(result).collect(),
+        }],
+    };
+
+    writeln!(out, "{}", serde_json::to_string_pretty(&log).unwrap())
+}
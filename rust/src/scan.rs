@@ -0,0 +1,629 @@
+use crate::detect::DetectedLanguage;
+use crate::languages::{LanguageParser, LanguageType};
+use crate::options::{OutputFormat, Shard};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(feature = "fs")]
+use {
+    crate::languages::get_parser,
+    crate::trace,
+    ignore::gitignore::{Gitignore, GitignoreBuilder},
+    ignore::{WalkBuilder, WalkState},
+    std::collections::HashSet,
+    std::io::Read,
+    std::sync::Mutex,
+};
+
+/// Ignore file looked up in the project directory
+pub const IGNORE_FILE_NAME: &str = ".smellsignore";
+
+/// Files larger than this many bytes are skipped unless `--max-file-size` says otherwise
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Languages for file extensions, from the root config's `[extensions]`; they
+/// replace the built-in ones, and `None` leaves the files out of every language
+pub type ExtensionMap = BTreeMap<String, Option<LanguageType>>;
+
+/// What the root config and the command line change about which files are collected
+#[derive(Debug, Default)]
+pub struct ScanRules {
+    pub extensions: ExtensionMap,
+    /// Directories to skip for each language, from the root config's
+    /// `[skip-dirs]`; a `!` before a name keeps a default one
+    pub skip_dirs: HashMap<LanguageType, Vec<String>>,
+    /// Skip only the `skip_dirs` given, none of the languages' own
+    pub no_default_skips: bool,
+}
+
+impl ScanRules {
+    /// The directories skipped for `language`: its parser's defaults that
+    /// aren't kept, then the configured ones
+    pub fn skipped_dirs<'a>(&'a self, language: LanguageType, parser: &dyn LanguageParser) -> Vec<&'a str> {
+        let configured = self.skip_dirs.get(&language).map_or(&[][..], Vec::as_slice);
+        let names = configured.iter().map(String::as_str);
+        let kept: Vec<&str> = names.clone().filter_map(|name| name.strip_prefix('!')).collect();
+        let defaults = match self.no_default_skips {
+            true => &[][..],
+            false => parser.skip_dirs(),
+        };
+        defaults
+            .iter()
+            .copied()
+            .filter(|dir| !kept.contains(dir))
+            .chain(names.filter(|name| !name.starts_with('!')))
+            .collect()
+    }
+
+    /// The skipped directory that `rel_path` is in, for `language`
+    #[cfg(feature = "fs")]
+    fn skipped_dir<'a>(&'a self, language: LanguageType, parser: &dyn LanguageParser, rel_path: &Path) -> Option<&'a str> {
+        let path = format!("/{}", rel_path.to_string_lossy());
+        self.skipped_dirs(language, parser)
+            .into_iter()
+            .find(|dir| path.contains(&format!("/{}/", dir)))
+    }
+}
+
+/// A source file selected for analysis
+#[derive(Debug)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    /// Path relative to the project root, used in reports
+    pub rel_path: PathBuf,
+    pub language: LanguageType,
+    /// Content supplied by the caller, read instead of `path` (see `in_memory`)
+    pub content: Option<Arc<str>>,
+}
+
+impl SourceFile {
+    /// A file that exists only in memory, for callers without a filesystem
+    /// (such as a browser build). The language comes from the extension, or
+    /// for a file without one from its `#!` line; None if neither gives one.
+    pub fn in_memory(rel_path: impl Into<PathBuf>, content: impl Into<Arc<str>>) -> Option<Self> {
+        let rel_path = rel_path.into();
+        let content: Arc<str> = content.into();
+        let language = match rel_path.extension() {
+            Some(_) => LanguageType::for_path(&rel_path)?,
+            None => LanguageType::for_shebang(content.lines().next()?)?,
+        };
+        Some(SourceFile {
+            path: rel_path.clone(),
+            rel_path,
+            language,
+            content: Some(content),
+        })
+    }
+
+    /// The file's content, from memory or from disk
+    pub fn read(&self) -> io::Result<String> {
+        match &self.content {
+            Some(content) => Ok(content.to_string()),
+            None => fs::read_to_string(&self.path),
+        }
+    }
+}
+
+/// Build the exclusion rules from the project's `.smellsignore` and `--exclude` patterns.
+///
+/// Both use gitignore syntax, relative to the project root.
+#[cfg(feature = "fs")]
+pub fn load_excludes(project_dir: &Path, patterns: &[String]) -> Result<Gitignore, String> {
+    let mut builder = GitignoreBuilder::new(project_dir);
+
+    let ignore_file = project_dir.join(IGNORE_FILE_NAME);
+    if ignore_file.is_file() {
+        if let Some(e) = builder.add(&ignore_file) {
+            return Err(format!("Invalid ignore file '{}': {}", ignore_file.display(), e));
+        }
+    }
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern)
+            .map_err(|e| format!("Invalid --exclude pattern '{}': {}", pattern, e))?;
+    }
+
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// Enumerate the files to analyze for all detected languages.
+///
+/// Overlapping source directories are walked once, and each file goes to the
+/// first detected language whose source directory holds it, whose extension it
+/// has (or, without one, whose interpreter its `#!` line names), and whose
+/// skip rules keep it. An extension in the rules' `extensions` belongs to its
+/// language there instead, and the rules' `skip_dirs` change which directories
+/// are left out. Files and directories matching `excludes` are skipped.
+#[cfg(feature = "fs")]
+pub fn collect_files(
+    project_dir: &Path,
+    detected: &[DetectedLanguage],
+    rules: &ScanRules,
+    excludes: &Gitignore,
+) -> Vec<SourceFile> {
+    let sources: Vec<(&DetectedLanguage, PathBuf, Box<dyn LanguageParser>)> = detected
+        .iter()
+        .map(|det| (det, project_dir.join(&det.source_dir), get_parser(det.language)))
+        .filter(|(_, source_path, _)| source_path.is_dir())
+        .collect();
+
+    // Sorted, a directory comes before those nested in it
+    let mut roots: Vec<&Path> = sources.iter().map(|(_, source_path, _)| source_path.as_path()).collect();
+    roots.sort();
+    roots.dedup();
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        // Already walked from an enclosing directory, unless that walk never
+        // got here (a hidden or gitignored directory)
+        if !paths.iter().any(|p| p.starts_with(root)) {
+            paths.extend(walk(root, excludes));
+        }
+    }
+    paths.sort();
+
+    let mut owned: Vec<(usize, PathBuf)> = Vec::new();
+    for path in paths {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let script = match path.extension() {
+            None => script_language(&path),
+            Some(_) => None,
+        };
+        let owner = sources.iter().position(|(det, source_path, parser)| {
+            let matches = match rules.extensions.get(ext) {
+                Some(mapped) => *mapped == Some(det.language),
+                None => det.language.matches_extension(ext) || script == Some(det.language),
+            };
+            if !path.starts_with(source_path) || !matches {
+                return false;
+            }
+            let rel_path = path.strip_prefix(project_dir).unwrap_or(&path);
+            if let Some(dir) = rules.skipped_dir(det.language, parser.as_ref(), rel_path) {
+                trace::log(format_args!("skip {}: in a {} skip directory ({})", path.display(), det.language.name(), dir));
+                return false;
+            }
+            let skip = parser.should_skip(&path);
+            if skip {
+                trace::log(format_args!("skip {}: {} skip rules (tests, generated code, ...)", path.display(), det.language.name()));
+            }
+            !skip
+        });
+        if let Some(owner) = owner {
+            owned.push((owner, path));
+        }
+    }
+    // Grouped by language in detection order, as reports have always listed them
+    owned.sort_by_key(|(owner, _)| *owner);
+
+    let mut files = Vec::new();
+    let mut seen = HashSet::new();
+    for (owner, path) in &owned {
+        let path = path.as_path();
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if !seen.insert(canonical) {
+            trace::log(format_args!("skip {}: already collected", path.display()));
+            continue;
+        }
+
+        files.push(SourceFile {
+            path: path.to_path_buf(),
+            rel_path: path.strip_prefix(project_dir).unwrap_or(path).to_path_buf(),
+            language: sources[*owner].0.language,
+            content: None,
+        });
+    }
+
+    files
+}
+
+/// The language an extensionless file is a script in, by its `#!` line
+#[cfg(feature = "fs")]
+fn script_language(path: &Path) -> Option<LanguageType> {
+    let mut head = [0u8; 128];
+    let read = fs::File::open(path).and_then(|mut file| file.read(&mut head)).ok()?;
+    let first_line = head[..read].split(|&b| b == b'\n').next()?;
+    LanguageType::for_shebang(std::str::from_utf8(first_line).ok()?)
+}
+
+/// A directory walker that skips hidden files and honors `.gitignore` files,
+/// whether or not the tree is a git repository (`--rev` exports and extracted
+/// archives are not)
+#[cfg(feature = "fs")]
+pub(crate) fn walker(dir: &Path) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(dir);
+    builder.require_git(false);
+    builder
+}
+
+/// The files beneath `dir`, sorted by path, leaving out excluded files and directories.
+///
+/// Directories are read in parallel, on as many threads as the rayon pool has.
+#[cfg(feature = "fs")]
+fn walk(dir: &Path, excludes: &Gitignore) -> Vec<PathBuf> {
+    let excludes = excludes.clone();
+    let found = Mutex::new(Vec::new());
+    walker(dir)
+        .threads(rayon::current_num_threads())
+        .filter_entry(move |e| {
+            let is_dir = e.file_type().is_some_and(|t| t.is_dir());
+            let excluded = excludes.matched(e.path(), is_dir).is_ignore();
+            if excluded {
+                trace::log(format_args!("skip {}: excluded", e.path().display()));
+            }
+            !excluded
+        })
+        .build_parallel()
+        .run(|| {
+            Box::new(|entry| {
+                if let Ok(entry) = entry {
+                    if entry.file_type().is_some_and(|t| t.is_file()) {
+                        found.lock().unwrap().push(entry.into_path());
+                    }
+                }
+                WalkState::Continue
+            })
+        });
+    // The order a single-threaded walk in name order would give
+    let mut found = found.into_inner().unwrap();
+    found.sort();
+    found
+}
+
+/// Leave out files larger than `max_bytes`, before anything reads them
+#[cfg(feature = "fs")]
+pub fn drop_oversized(files: &mut Vec<SourceFile>, max_bytes: u64) {
+    files.retain(|file| {
+        // A file that can't be inspected is reported when it's read
+        let size = match &file.content {
+            Some(content) => content.len() as u64,
+            None => fs::metadata(&file.path).map_or(0, |m| m.len()),
+        };
+        if size > max_bytes {
+            trace::log(format_args!(
+                "skip {}: {} bytes, over the {} byte limit",
+                file.rel_path.display(),
+                size,
+                max_bytes
+            ));
+        }
+        size <= max_bytes
+    });
+}
+
+/// Whether a file belongs to a shard: by a hash of its relative path, with
+/// `/` separators, so every machine and platform splits a tree the same way
+pub fn in_shard(rel_path: &Path, shard: Shard) -> bool {
+    let path: Vec<_> = rel_path.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+    let digest = Sha256::digest(path.join("/").as_bytes());
+    let hash = u64::from_be_bytes(digest[..8].try_into().expect("SHA-256 is 32 bytes"));
+    hash % shard.count == shard.index - 1
+}
+
+/// Leave out the files of other shards
+#[cfg(feature = "fs")]
+pub fn keep_shard(files: &mut Vec<SourceFile>, shard: Shard) {
+    files.retain(|file| {
+        let keep = in_shard(&file.rel_path, shard);
+        if !keep {
+            trace::log(format_args!("skip {}: not in shard {}/{}", file.rel_path.display(), shard.index, shard.count));
+        }
+        keep
+    });
+}
+
+/// Add the project's files with one of `extensions` (given with or without
+/// the dot) that no language collected, to be parsed with the generic fallback.
+#[cfg(feature = "fs")]
+pub fn collect_fallback_files(
+    project_dir: &Path,
+    extensions: &[String],
+    rules: &ScanRules,
+    excludes: &Gitignore,
+    files: &mut Vec<SourceFile>,
+) {
+    let extensions: Vec<&str> = extensions.iter().map(|e| e.trim_start_matches('.')).collect();
+    let collected: HashSet<PathBuf> = files.iter().map(|f| f.rel_path.clone()).collect();
+    let parser = get_parser(LanguageType::Generic);
+
+    for path in walk(project_dir, excludes) {
+        let path = path.as_path();
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let rel_path = path.strip_prefix(project_dir).unwrap_or(path).to_path_buf();
+        if !extensions.contains(&ext)
+            || rules.skipped_dir(LanguageType::Generic, parser.as_ref(), &rel_path).is_some()
+            || parser.should_skip(path)
+            || collected.contains(&rel_path)
+        {
+            continue;
+        }
+        trace::log(format_args!("fallback {}: generic parser", rel_path.display()));
+        files.push(SourceFile {
+            path: path.to_path_buf(),
+            rel_path,
+            language: LanguageType::Generic,
+            content: None,
+        });
+    }
+}
+
+#[derive(Serialize)]
+struct LanguageFiles<'a> {
+    language: &'static str,
+    source_dirs: Vec<&'a str>,
+    files: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct FileList<'a> {
+    project: String,
+    languages: Vec<LanguageFiles<'a>>,
+    total: usize,
+}
+
+/// Print the files that would be analyzed, per detected language
+pub fn print_file_list(project_dir: &Path, detected: &[DetectedLanguage], files: &[SourceFile], format: OutputFormat) {
+    let mut languages: Vec<LanguageFiles> = Vec::new();
+    for det in detected {
+        match languages.iter_mut().find(|l| l.language == det.language.name()) {
+            Some(lang) => lang.source_dirs.push(&det.source_dir),
+            None => languages.push(LanguageFiles {
+                language: det.language.name(),
+                source_dirs: vec![&det.source_dir],
+                files: files
+                    .iter()
+                    .filter(|f| f.language == det.language)
+                    .map(|f| f.rel_path.display().to_string())
+                    .collect(),
+            }),
+        }
+    }
+
+    if format == OutputFormat::Json {
+        let list = FileList {
+            project: project_dir.display().to_string(),
+            languages,
+            total: files.len(),
+        };
+        println!("{}", serde_json::to_string_pretty(&list).unwrap());
+        return;
+    }
+
+    println!("=== Files to Analyze ===");
+    println!("Project: {}", project_dir.display());
+    for lang in &languages {
+        println!();
+        println!("--- {} ({}): {} files ---", lang.language, lang.source_dirs.join(", "), lang.files.len());
+        for file in &lang.files {
+            println!("{}", file);
+        }
+    }
+    println!();
+    println!("Total: {} files", files.len());
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::fs;
+
+    #[test]
+    fn test_excludes_from_ignore_file_and_cli() {
+        let dir = TempDir::new("scan");
+        for rel in [
+            "app/main.py",
+            "app/generated/models.py",
+            "app/models_pb2.py",
+            "scripts/tool.py",
+            "app/build/out.py",
+            ".tox/env.py",
+        ] {
+            let path = dir.join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "x = 1\n").unwrap();
+        }
+        fs::write(dir.join(IGNORE_FILE_NAME), "# comment\n**/generated/**\n*_pb2.py\n").unwrap();
+        // Not a git repository, but its .gitignore still counts
+        fs::write(dir.join("app/.gitignore"), "build/\n").unwrap();
+
+        let excludes = load_excludes(&dir, &["scripts/".to_string()]).unwrap();
+        let detected = vec![DetectedLanguage {
+            language: LanguageType::Python,
+            source_dir: ".".to_string(),
+        }];
+        let files = collect_files(&dir, &detected, &ScanRules::default(), &excludes);
+
+        let paths: Vec<PathBuf> = files.into_iter().map(|f| f.rel_path).collect();
+        assert_eq!(paths, vec![PathBuf::from("app/main.py")]);
+    }
+
+    #[test]
+    fn test_overlapping_source_dirs() {
+        let dir = TempDir::new("overlap");
+        for rel in ["main.py", "notes.txt", "src/app.ts", "src/util.py"] {
+            let path = dir.join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "x\n").unwrap();
+        }
+        let excludes = load_excludes(&dir, &[]).unwrap();
+        let detected: Vec<DetectedLanguage> = [
+            (LanguageType::Python, "."),
+            (LanguageType::TypeScript, "src"),
+            (LanguageType::Python, "src"),
+            (LanguageType::Generic, "."),
+        ]
+        .into_iter()
+        .map(|(language, source_dir)| DetectedLanguage {
+            language,
+            source_dir: source_dir.to_string(),
+        })
+        .collect();
+        let files = collect_files(&dir, &detected, &ScanRules::default(), &excludes);
+
+        let found: Vec<(PathBuf, LanguageType)> = files.into_iter().map(|f| (f.rel_path, f.language)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (PathBuf::from("main.py"), LanguageType::Python),
+                (PathBuf::from("src/util.py"), LanguageType::Python),
+                (PathBuf::from("src/app.ts"), LanguageType::TypeScript),
+                (PathBuf::from("notes.txt"), LanguageType::Generic),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shebang_scripts() {
+        let dir = TempDir::new("shebang");
+        for (rel, content) in [
+            ("bin/deploy", "#!/usr/bin/env python3\nimport sys\n"),
+            ("bin/serve", "#!/usr/bin/env -S deno run --allow-net\n"),
+            ("bin/setup", "#!/bin/bash\nset -e\n"),
+            ("bin/tool.sh", "#!/usr/bin/python\n"),
+            ("Makefile", "all:\n"),
+        ] {
+            let path = dir.join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let excludes = load_excludes(&dir, &[]).unwrap();
+        let detected: Vec<DetectedLanguage> = [LanguageType::Python, LanguageType::TypeScript]
+            .into_iter()
+            .map(|language| DetectedLanguage {
+                language,
+                source_dir: ".".to_string(),
+            })
+            .collect();
+        let files = collect_files(&dir, &detected, &ScanRules::default(), &excludes);
+
+        let found: Vec<(PathBuf, LanguageType)> = files.into_iter().map(|f| (f.rel_path, f.language)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (PathBuf::from("bin/deploy"), LanguageType::Python),
+                (PathBuf::from("bin/serve"), LanguageType::TypeScript),
+            ]
+        );
+        assert_eq!(LanguageType::for_shebang("#!/usr/local/bin/python3.11 -u"), Some(LanguageType::Python));
+        assert_eq!(LanguageType::for_shebang("#!/usr/bin/env NODE_ENV=production node"), Some(LanguageType::JavaScript));
+        assert_eq!(LanguageType::for_shebang("# not a shebang"), None);
+    }
+
+    #[test]
+    fn test_mapped_extensions() {
+        let dir = TempDir::new("ext");
+        for rel in ["app.py", "stubs.pyi", "build.mts", "page.tsx", "view.heex"] {
+            fs::write(dir.join(rel), "x\n").unwrap();
+        }
+        let excludes = load_excludes(&dir, &[]).unwrap();
+        let detected: Vec<DetectedLanguage> = [LanguageType::Python, LanguageType::TypeScript]
+            .into_iter()
+            .map(|language| DetectedLanguage {
+                language,
+                source_dir: ".".to_string(),
+            })
+            .collect();
+        let extensions = [
+            ("pyi", Some(LanguageType::Python)),
+            ("mts", Some(LanguageType::TypeScript)),
+            ("tsx", None),
+        ]
+        .into_iter()
+        .map(|(ext, language)| (ext.to_string(), language))
+        .collect();
+        let rules = ScanRules {
+            extensions,
+            ..Default::default()
+        };
+        let files = collect_files(&dir, &detected, &rules, &excludes);
+
+        let found: Vec<(PathBuf, LanguageType)> = files.into_iter().map(|f| (f.rel_path, f.language)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (PathBuf::from("app.py"), LanguageType::Python),
+                (PathBuf::from("stubs.pyi"), LanguageType::Python),
+                (PathBuf::from("build.mts"), LanguageType::TypeScript),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skip_dirs() {
+        let dir = TempDir::new("skipdirs");
+        for rel in ["lib/app.ex", "deps/plug/conn.ex", "_build/dev/x.ex", "priv/static/gen.ex", "lib/priv/static/ok.ex"] {
+            let path = dir.join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "x\n").unwrap();
+        }
+        let excludes = load_excludes(&dir, &[]).unwrap();
+        let detected = vec![DetectedLanguage {
+            language: LanguageType::Elixir,
+            source_dir: ".".to_string(),
+        }];
+        let collect = |rules: &ScanRules| -> Vec<PathBuf> {
+            collect_files(&dir, &detected, rules, &excludes).into_iter().map(|f| f.rel_path).collect()
+        };
+        let mut rules = ScanRules::default();
+        let defaults = collect(&rules);
+
+        // A path of several directories matches at any depth; `!` keeps a default
+        rules.skip_dirs.insert(LanguageType::Elixir, vec!["priv/static".to_string(), "!deps".to_string()]);
+        let configured = collect(&rules);
+        rules.no_default_skips = true;
+        rules.skip_dirs.clear();
+        let all = collect(&rules);
+
+        let paths = |rels: &[&str]| rels.iter().map(PathBuf::from).collect::<Vec<_>>();
+        assert_eq!(defaults, paths(&["lib/app.ex", "lib/priv/static/ok.ex", "priv/static/gen.ex"]));
+        assert_eq!(configured, paths(&["deps/plug/conn.ex", "lib/app.ex"]));
+        assert_eq!(all.len(), 5);
+    }
+
+    #[test]
+    fn test_fallback_files() {
+        let dir = TempDir::new("fallback");
+        for rel in ["api.proto", "src/main.py", "src/schema.proto", "notes.txt", "vendor/x.proto"] {
+            let path = dir.join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "x\n").unwrap();
+        }
+        let excludes = load_excludes(&dir, &["vendor/".to_string()]).unwrap();
+        let detected = vec![DetectedLanguage {
+            language: LanguageType::Python,
+            source_dir: "src".to_string(),
+        }];
+        let mut files = collect_files(&dir, &detected, &ScanRules::default(), &excludes);
+        // `py` is already Python's, so main.py is not collected twice
+        let extensions = [".proto".to_string(), "py".to_string()];
+        collect_fallback_files(&dir, &extensions, &ScanRules::default(), &excludes, &mut files);
+
+        let found: Vec<(PathBuf, LanguageType)> = files.into_iter().map(|f| (f.rel_path, f.language)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (PathBuf::from("src/main.py"), LanguageType::Python),
+                (PathBuf::from("api.proto"), LanguageType::Generic),
+                (PathBuf::from("src/schema.proto"), LanguageType::Generic),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shards_partition_files() {
+        let paths: Vec<PathBuf> = (0..50).map(|i| PathBuf::from(format!("src/mod{}/file{}.rs", i % 7, i))).collect();
+        let count = 3;
+        for path in &paths {
+            let owners = (1..=count).filter(|&index| in_shard(path, Shard { index, count })).count();
+            assert_eq!(owners, 1, "{}", path.display());
+        }
+        // Every shard gets some of a reasonably sized tree
+        for index in 1..=count {
+            assert!(paths.iter().any(|p| in_shard(p, Shard { index, count })));
+        }
+        assert!(paths.iter().all(|p| in_shard(p, Shard { index: 1, count: 1 })));
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::hash_map::RandomState;
+use std::fs::DirBuilder;
+use std::hash::{BuildHasher, Hasher};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Names tried before giving up, in case another process keeps taking them
+const ATTEMPTS: usize = 32;
+
+/// A name nobody can guess in advance: `RandomState` is seeded randomly per process
+fn random_name(label: &str) -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    hasher.write_u64(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+    hasher.write_u128(nanos);
+    format!("code-smells-{}-{:016x}", label, hasher.finish())
+}
+
+/// Create a new, empty directory under the system temp dir for this run.
+///
+/// The directory is created here rather than found, so a directory or link
+/// someone else put at the name in advance is never used; on Unix only the
+/// current user can read it.
+pub fn create(label: &str) -> Result<PathBuf, String> {
+    let mut builder = DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    let temp_dir = std::env::temp_dir();
+    for _ in 0..ATTEMPTS {
+        let dir = temp_dir.join(random_name(label));
+        match builder.create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Cannot create a temporary directory in '{}': {}", temp_dir.display(), e)),
+        }
+    }
+    Err(format!("Cannot create a temporary directory in '{}': every name tried is taken", temp_dir.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_makes_new_private_dirs() {
+        let first = create("test").unwrap();
+        let second = create("test").unwrap();
+        assert_ne!(first, second);
+        assert!(first.is_dir() && second.is_dir());
+        assert!(first.file_name().unwrap().to_string_lossy().starts_with("code-smells-test-"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(first.metadata().unwrap().permissions().mode() & 0o777, 0o700);
+        }
+        for dir in [first, second] {
+            std::fs::remove_dir(dir).unwrap();
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::languages::LanguageType;
+use crate::options::OutputFormat;
+use crate::scan::SourceFile;
+use crate::top::{self, Entry};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Spread of one measurement over a language's files or functions
+#[derive(Debug, PartialEq, Serialize)]
+struct Distribution {
+    count: usize,
+    min: usize,
+    median: usize,
+    p90: usize,
+    max: usize,
+}
+
+/// Nearest-rank summary of the values; None without any
+fn distribution(mut values: Vec<usize>) -> Option<Distribution> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let rank = |pct: usize| values[(values.len() * pct).div_ceil(100).max(1) - 1];
+    Some(Distribution {
+        count: values.len(),
+        min: values[0],
+        median: rank(50),
+        p90: rank(90),
+        max: values[values.len() - 1],
+    })
+}
+
+#[derive(Serialize)]
+pub struct LanguageStats {
+    language: &'static str,
+    file_length: Option<Distribution>,
+    function_length: Option<Distribution>,
+    nesting_depth: Option<Distribution>,
+}
+
+/// Measure every file and summarize the measurements per language
+pub fn collect(files: &[SourceFile]) -> Vec<LanguageStats> {
+    let measured: Vec<(LanguageType, top::Ranking)> =
+        files.par_iter().map(|file| (file.language, top::measure_file(file))).collect();
+
+    let mut by_language: BTreeMap<&'static str, top::Ranking> = BTreeMap::new();
+    for (language, ranking) in measured {
+        let all = by_language.entry(language.name()).or_default();
+        all.longest_files.extend(ranking.longest_files);
+        all.longest_functions.extend(ranking.longest_functions);
+        all.deepest_functions.extend(ranking.deepest_functions);
+    }
+
+    let values = |entries: Vec<Entry>| entries.into_iter().map(|e| e.value).collect();
+    by_language
+        .into_iter()
+        .map(|(language, ranking)| LanguageStats {
+            language,
+            file_length: distribution(values(ranking.longest_files)),
+            function_length: distribution(values(ranking.longest_functions)),
+            nesting_depth: distribution(values(ranking.deepest_functions)),
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+struct JsonStats<'a> {
+    project: String,
+    languages: &'a [LanguageStats],
+}
+
+/// Print the distributions, per language
+pub fn print_stats(project_dir: &Path, stats: &[LanguageStats], format: OutputFormat) {
+    if format == OutputFormat::Json {
+        let report = JsonStats {
+            project: project_dir.display().to_string(),
+            languages: stats,
+        };
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return;
+    }
+
+    println!("=== Code Metrics ===");
+    println!("Project: {}", project_dir.display());
+    for lang in stats {
+        println!();
+        println!("--- {} ---", lang.language);
+        println!("{:<16} {:>6} {:>6} {:>6} {:>6} {:>6}", "", "count", "min", "median", "p90", "max");
+        for (label, dist) in [
+            ("File length", &lang.file_length),
+            ("Function length", &lang.function_length),
+            ("Nesting depth", &lang.nesting_depth),
+        ] {
+            match dist {
+                Some(d) => println!(
+                    "{:<16} {:>6} {:>6} {:>6} {:>6} {:>6}",
+                    label, d.count, d.min, d.median, d.p90, d.max
+                ),
+                None => println!("{:<16} {:>6}", label, 0),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution() {
+        assert_eq!(distribution(Vec::new()), None);
+        assert_eq!(
+            distribution((1..=10).rev().collect()),
+            Some(Distribution {
+                count: 10,
+                min: 1,
+                median: 5,
+                p90: 9,
+                max: 10,
+            })
+        );
+        assert_eq!(distribution(vec![7]).unwrap().p90, 7);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::languages::{FunctionInfo, LanguageType};
+use crate::options::OutputFormat;
+use crate::scan::SourceFile;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// A file or function and how large it measures
+#[derive(Debug, Serialize)]
+pub struct Entry {
+    file: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    pub value: usize,
+}
+
+/// The largest files and functions of a project, regardless of thresholds
+#[derive(Debug, Default, Serialize)]
+pub struct Ranking {
+    pub longest_files: Vec<Entry>,
+    pub longest_functions: Vec<Entry>,
+    pub deepest_functions: Vec<Entry>,
+}
+
+impl Ranking {
+    fn merge(&mut self, other: Ranking) {
+        self.longest_files.extend(other.longest_files);
+        self.longest_functions.extend(other.longest_functions);
+        self.deepest_functions.extend(other.deepest_functions);
+    }
+
+    /// Keep the `n` largest entries of each list, largest first
+    fn truncate(&mut self, n: usize) {
+        for entries in [&mut self.longest_files, &mut self.longest_functions, &mut self.deepest_functions] {
+            // Ties keep scan order, so the ranking is the same on every run
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.value));
+            entries.truncate(n);
+        }
+    }
+}
+
+/// Read and parse a file: its line count and functions, or None when it
+/// cannot be read or is binary
+pub fn parse_file(file: &SourceFile) -> Option<(usize, Vec<FunctionInfo>)> {
+    let content = file.read().ok()?;
+    // As in the checks, the generic fallback leaves out binary files
+    if file.language == LanguageType::Generic && content.contains('\0') {
+        return None;
+    }
+    // Without real function boundaries there are no functions to measure
+    let functions = if file.language == LanguageType::Generic {
+        Vec::new()
+    } else {
+        let mut functions = crate::languages::get_parser(file.language).parse_functions(&content);
+        crate::languages::drop_nested(&mut functions);
+        functions
+    };
+    Some((content.lines().count(), functions))
+}
+
+/// Measure a single file: its length, and the length and depth of its functions
+pub fn measure_file(file: &SourceFile) -> Ranking {
+    let mut ranking = Ranking::default();
+    let (lines, functions) = match parse_file(file) {
+        Some(parsed) => parsed,
+        None => return ranking,
+    };
+
+    ranking.longest_files.push(Entry {
+        file: file.rel_path.clone(),
+        line: None,
+        name: None,
+        value: lines,
+    });
+    for func in functions {
+        let entry = |value| Entry {
+            file: file.rel_path.clone(),
+            line: Some(func.start_line),
+            name: Some(func.name.clone()),
+            value,
+        };
+        ranking.longest_functions.push(entry(func.line_count));
+        ranking.deepest_functions.push(entry(func.max_nesting));
+    }
+    ranking
+}
+
+/// Rank the `n` longest files, longest functions, and most deeply nested functions
+pub fn rank(files: &[SourceFile], n: usize) -> Ranking {
+    let measured: Vec<Ranking> = files.par_iter().map(measure_file).collect();
+    let mut ranking = Ranking::default();
+    for file_ranking in measured {
+        ranking.merge(file_ranking);
+    }
+    ranking.truncate(n);
+    ranking
+}
+
+#[derive(Serialize)]
+struct JsonTop<'a> {
+    project: String,
+    #[serde(flatten)]
+    ranking: &'a Ranking,
+}
+
+fn print_section(title: &str, entries: &[Entry], unit: &str) {
+    println!();
+    println!("--- {} ---", title);
+    if entries.is_empty() {
+        println!("None found");
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        let location = match (entry.line, &entry.name) {
+            (Some(line), Some(name)) => format!("{}:{} {}", entry.file.display(), line, name),
+            _ => entry.file.display().to_string(),
+        };
+        println!("{:>3}. {} ({} {})", i + 1, location, entry.value, unit);
+    }
+}
+
+/// Print the ranking of a project
+pub fn print_ranking(project_dir: &Path, ranking: &Ranking, format: OutputFormat) {
+    if format == OutputFormat::Json {
+        let report = JsonTop {
+            project: project_dir.display().to_string(),
+            ranking,
+        };
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return;
+    }
+
+    println!("=== Worst Offenders ===");
+    println!("Project: {}", project_dir.display());
+    print_section("LONGEST FILES", &ranking.longest_files, "lines");
+    print_section("LONGEST FUNCTIONS", &ranking.longest_functions, "lines");
+    print_section("DEEPEST NESTING", &ranking.deepest_functions, "levels");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(file: &str, value: usize) -> Entry {
+        Entry {
+            file: PathBuf::from(file),
+            line: None,
+            name: None,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_truncate_keeps_largest_in_order() {
+        let mut ranking = Ranking {
+            longest_files: vec![entry("a", 10), entry("b", 30), entry("c", 20), entry("d", 30)],
+            ..Default::default()
+        };
+        ranking.truncate(3);
+        let files: Vec<_> = ranking.longest_files.iter().map(|e| e.file.to_str().unwrap()).collect();
+        assert_eq!(files, ["b", "d", "c"]);
+    }
+}
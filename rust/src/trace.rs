@@ -0,0 +1,18 @@
+use std::fmt::Arguments;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turn on `--verbose` tracing for the rest of the run
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Print a trace line to stderr if tracing is on.
+///
+/// Takes `format_args!` so the message is only formatted when it is printed.
+pub fn log(message: Arguments) {
+    if ENABLED.load(Ordering::Relaxed) {
+        eprintln!("[trace] {}", message);
+    }
+}
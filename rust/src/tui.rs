@@ -0,0 +1,383 @@
+use crate::output::{Issue, Report, Severity};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+const REVERSE: &str = "\x1b[7m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// A key press, decoded from the terminal's input bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Key {
+    Char(char),
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Enter,
+    Backspace,
+    Escape,
+}
+
+/// Decode the keys in one read from the terminal; unknown sequences are dropped
+fn parse_keys(input: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < input.len() {
+        let rest = &input[i..];
+        let (key, len) = match rest {
+            [0x1b, b'[', b'A', ..] => (Some(Key::Up), 3),
+            [0x1b, b'[', b'B', ..] => (Some(Key::Down), 3),
+            [0x1b, b'[', b'5', b'~', ..] => (Some(Key::PageUp), 4),
+            [0x1b, b'[', b'6', b'~', ..] => (Some(Key::PageDown), 4),
+            [0x1b, b'[', ..] => (None, 3),
+            [0x1b, ..] => (Some(Key::Escape), 1),
+            [b'\r' | b'\n', ..] => (Some(Key::Enter), 1),
+            [0x7f | 0x08, ..] => (Some(Key::Backspace), 1),
+            [b, ..] if b.is_ascii_graphic() || *b == b' ' => (Some(Key::Char(*b as char)), 1),
+            _ => (None, 1),
+        };
+        keys.extend(key);
+        i += len;
+    }
+    keys
+}
+
+/// Which issues are listed
+#[derive(Debug, Default)]
+struct Filters {
+    severity: Option<Severity>,
+    check: Option<&'static str>,
+    /// Substring of the file path
+    file: String,
+}
+
+impl Filters {
+    fn matches(&self, issue: &Issue) -> bool {
+        self.severity.is_none_or(|s| issue.severity == s)
+            && self.check.is_none_or(|c| issue.check_type == c)
+            && issue.file.to_string_lossy().contains(&self.file)
+    }
+}
+
+/// Next value in a cycle that starts and ends with "no filter"
+fn cycle<T: Copy + PartialEq>(current: Option<T>, values: &[T]) -> Option<T> {
+    match current.and_then(|c| values.iter().position(|v| *v == c)) {
+        None => values.first().copied(),
+        Some(i) => values.get(i + 1).copied(),
+    }
+}
+
+struct Browser<'a> {
+    issues: &'a [Issue],
+    checks: Vec<&'static str>,
+    filters: Filters,
+    /// Indexes of the issues that pass the filters
+    visible: Vec<usize>,
+    selected: usize,
+    /// First visible row of the list
+    offset: usize,
+    /// Typing a file filter
+    editing: bool,
+    sources: &'a HashMap<PathBuf, PathBuf>,
+    project_dir: &'a Path,
+    cache: HashMap<PathBuf, Vec<String>>,
+}
+
+impl<'a> Browser<'a> {
+    fn new(report: &'a Report, project_dir: &'a Path, sources: &'a HashMap<PathBuf, PathBuf>) -> Self {
+        let mut checks: Vec<&'static str> = report.issues.iter().map(|i| i.check_type).collect();
+        checks.sort_unstable();
+        checks.dedup();
+        let mut browser = Browser {
+            issues: &report.issues,
+            checks,
+            filters: Filters::default(),
+            visible: Vec::new(),
+            selected: 0,
+            offset: 0,
+            editing: false,
+            sources,
+            project_dir,
+            cache: HashMap::new(),
+        };
+        browser.refilter();
+        browser
+    }
+
+    fn refilter(&mut self) {
+        self.visible = (0..self.issues.len()).filter(|&i| self.filters.matches(&self.issues[i])).collect();
+        self.selected = self.selected.min(self.visible.len().saturating_sub(1));
+    }
+
+    /// Apply a key; returns false to quit
+    fn handle(&mut self, key: Key, page: usize) -> bool {
+        if self.editing {
+            match key {
+                Key::Enter | Key::Escape => self.editing = false,
+                Key::Backspace => {
+                    self.filters.file.pop();
+                }
+                Key::Char(c) => self.filters.file.push(c),
+                _ => {}
+            }
+            self.refilter();
+            return true;
+        }
+        let last = self.visible.len().saturating_sub(1);
+        match key {
+            Key::Char('q') | Key::Escape => return false,
+            Key::Char('j') | Key::Down => self.selected = (self.selected + 1).min(last),
+            Key::Char('k') | Key::Up => self.selected = self.selected.saturating_sub(1),
+            Key::PageDown => self.selected = (self.selected + page).min(last),
+            Key::PageUp => self.selected = self.selected.saturating_sub(page),
+            Key::Char('g') => self.selected = 0,
+            Key::Char('G') => self.selected = last,
+            Key::Char('s') => {
+                self.filters.severity = cycle(self.filters.severity, &[Severity::Error, Severity::Warning, Severity::Info]);
+                self.refilter();
+            }
+            Key::Char('c') => {
+                self.filters.check = cycle(self.filters.check, &self.checks);
+                self.refilter();
+            }
+            Key::Char('/') => {
+                self.editing = true;
+                self.filters.file.clear();
+                self.refilter();
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Lines of a reported file, read once
+    fn source(&mut self, rel_path: &Path) -> &[String] {
+        let path = self
+            .sources
+            .get(rel_path)
+            .cloned()
+            .unwrap_or_else(|| self.project_dir.join(rel_path));
+        self.cache.entry(rel_path.to_path_buf()).or_insert_with(|| {
+            fs::read_to_string(path)
+                .map(|content| content.lines().map(|l| l.replace('\t', "    ")).collect())
+                .unwrap_or_default()
+        })
+    }
+
+    fn render(&mut self, out: &mut dyn Write, width: usize, height: usize) -> io::Result<()> {
+        let fit = |text: &str| text.chars().take(width).collect::<String>();
+        let list_rows = height.saturating_sub(3) / 2;
+        let preview_rows = height.saturating_sub(3) - list_rows;
+
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + list_rows {
+            self.offset = self.selected + 1 - list_rows;
+        }
+
+        write!(out, "\x1b[H\x1b[2J")?;
+        let severity = match self.filters.severity {
+            None => "all",
+            Some(Severity::Error) => "errors",
+            Some(Severity::Warning) => "warnings",
+            Some(Severity::Info) => "near misses",
+        };
+        let header = format!(
+            "{} of {} issues | severity: {} | check: {} | file: {}{}",
+            self.visible.len(),
+            self.issues.len(),
+            severity,
+            self.filters.check.unwrap_or("all"),
+            if self.filters.file.is_empty() && !self.editing { "*" } else { &self.filters.file },
+            if self.editing { "_" } else { "" }
+        );
+        write!(out, "{}{}{}\r\n", BOLD, fit(&header), RESET)?;
+
+        for row in 0..list_rows {
+            let Some(&index) = self.visible.get(self.offset + row) else {
+                write!(out, "\r\n")?;
+                continue;
+            };
+            let issue = &self.issues[index];
+            let label = match issue.severity {
+                Severity::Error => "ERROR",
+                Severity::Warning => "WARN ",
+                Severity::Info => "INFO ",
+            };
+            let line = fit(&format!("{}  {}", label, issue.message));
+            if self.offset + row == self.selected {
+                write!(out, "{}{:<width$}{}\r\n", REVERSE, line, RESET, width = width)?;
+            } else {
+                write!(out, "{}\r\n", line)?;
+            }
+        }
+
+        write!(out, "{}\r\n", "-".repeat(width))?;
+        let selected = self.visible.get(self.selected).map(|&i| &self.issues[i]);
+        let mut preview = Vec::new();
+        if let Some(issue) = selected {
+            let target = issue.line.unwrap_or(1);
+            let lines = self.source(&issue.file);
+            // Start a little above the reported line
+            let first = target.saturating_sub(3).max(1);
+            for number in first..first + preview_rows {
+                let Some(text) = lines.get(number - 1) else {
+                    break;
+                };
+                let marker = if number == target { '>' } else { ' ' };
+                preview.push(fit(&format!("{}{:>5} | {}", marker, number, text)));
+            }
+        }
+        for row in 0..preview_rows {
+            write!(out, "{}\r\n", preview.get(row).map_or("", |l| l.as_str()))?;
+        }
+
+        let help = if self.editing {
+            "type a path substring, Enter to apply"
+        } else {
+            "j/k move  PgUp/PgDn page  g/G first/last  s severity  c check  / file  q quit"
+        };
+        write!(out, "{}{}{}", BOLD, fit(help), RESET)?;
+        out.flush()
+    }
+}
+
+/// Puts the terminal in raw mode for as long as it lives
+struct RawMode {
+    original: libc::termios,
+}
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        // SAFETY: termios is plain data, filled in by tcgetattr before use
+        let mut original: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = original;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(RawMode { original })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+    }
+}
+
+/// Terminal width and height, with a fallback for terminals that won't say
+fn terminal_size() -> (usize, usize) {
+    // SAFETY: winsize is plain data, filled in by the ioctl
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0 && size.ws_col > 0 {
+        (size.ws_col as usize, size.ws_row as usize)
+    } else {
+        (80, 24)
+    }
+}
+
+/// Browse a report interactively until the user quits.
+///
+/// `sources` maps report paths to the files that were read, so the preview
+/// shows what was checked (e.g. the staged copy with `--staged`).
+pub fn browse(report: &Report, project_dir: &Path, sources: &HashMap<PathBuf, PathBuf>) -> Result<(), String> {
+    let is_terminal = unsafe { libc::isatty(libc::STDIN_FILENO) == 1 && libc::isatty(libc::STDOUT_FILENO) == 1 };
+    if !is_terminal {
+        return Err("--tui needs an interactive terminal".to_string());
+    }
+
+    let mut browser = Browser::new(report, project_dir, sources);
+    let fail = |e: io::Error| format!("Terminal error: {}", e);
+    let raw = RawMode::enable().map_err(fail)?;
+    let mut stdout = io::stdout();
+    // Alternate screen, hidden cursor
+    write!(stdout, "\x1b[?1049h\x1b[?25l").map_err(fail)?;
+
+    let mut input = [0u8; 64];
+    let result = loop {
+        let (width, height) = terminal_size();
+        if let Err(e) = browser.render(&mut stdout, width, height) {
+            break Err(e);
+        }
+        let read = match io::stdin().read(&mut input) {
+            Ok(0) => break Ok(()),
+            Ok(n) => n,
+            Err(e) => break Err(e),
+        };
+        let page = height.saturating_sub(3) / 2;
+        if !parse_keys(&input[..read]).into_iter().all(|key| browser.handle(key, page.max(1))) {
+            break Ok(());
+        }
+    };
+
+    let _ = write!(stdout, "\x1b[?25h\x1b[?1049l");
+    let _ = stdout.flush();
+    drop(raw);
+    result.map_err(fail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(file: &str, check_type: &'static str, severity: Severity) -> Issue {
+        Issue {
+            severity,
+            file: PathBuf::from(file),
+            line: Some(1),
+            column: None,
+            end_line: None,
+            name: None,
+            check_type,
+            value: 0,
+            limit: 0,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(
+            parse_keys(b"j\x1b[A\x1b[6~/\x7f\r\x1b"),
+            [Key::Char('j'), Key::Up, Key::PageDown, Key::Char('/'), Key::Backspace, Key::Enter, Key::Escape]
+        );
+    }
+
+    #[test]
+    fn test_filters() {
+        let report = Report {
+            issues: vec![
+                issue("src/a.rs", "function-length", Severity::Error),
+                issue("src/b.rs", "nesting-depth", Severity::Warning),
+                issue("lib/c.rs", "function-length", Severity::Warning),
+            ],
+            ..Default::default()
+        };
+        let sources = HashMap::new();
+        let mut browser = Browser::new(&report, Path::new("."), &sources);
+
+        browser.handle(Key::Char('s'), 10);
+        assert_eq!(browser.visible, [0]);
+        browser.handle(Key::Char('s'), 10);
+        browser.handle(Key::Char('c'), 10);
+        assert_eq!(browser.visible, [2]);
+
+        // Severity, then check, cycle back to no filter
+        browser.handle(Key::Char('s'), 10);
+        browser.handle(Key::Char('s'), 10);
+        browser.handle(Key::Char('c'), 10);
+        browser.handle(Key::Char('c'), 10);
+        for key in parse_keys(b"/src\r") {
+            browser.handle(key, 10);
+        }
+        assert_eq!(browser.visible, [0, 1]);
+        assert!(!browser.handle(Key::Char('q'), 10));
+    }
+}
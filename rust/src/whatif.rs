@@ -0,0 +1,244 @@
+use crate::config::{Overrides, ProjectConfig};
+use crate::detect::DetectedLanguage;
+use crate::options::OutputFormat;
+use crate::output::{Issue, Report, Severity};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// A threshold that differs between the current and proposed configuration
+#[derive(Debug, PartialEq, Serialize)]
+struct ThresholdChange {
+    language: &'static str,
+    threshold: String,
+    current: serde_json::Value,
+    proposed: serde_json::Value,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct Counts {
+    errors: usize,
+    warnings: usize,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct CheckDelta {
+    current: Counts,
+    proposed: Counts,
+}
+
+/// The difference between two runs over the same files
+#[derive(Serialize)]
+struct Comparison<'a> {
+    current: Counts,
+    proposed: Counts,
+    by_check: BTreeMap<&'static str, CheckDelta>,
+    /// Issues only reported with the proposed thresholds
+    new_issues: Vec<&'a Issue>,
+    /// Number of issues only reported with the current thresholds
+    resolved: usize,
+}
+
+#[derive(Serialize)]
+struct JsonWhatif<'a> {
+    project: String,
+    changes: Vec<ThresholdChange>,
+    #[serde(flatten)]
+    comparison: Comparison<'a>,
+}
+
+fn counts(report: &Report) -> Counts {
+    Counts {
+        errors: report.error_count(),
+        warnings: report.warning_count(),
+    }
+}
+
+/// Identity of an issue across runs; the measured value and limit may differ
+fn key(issue: &Issue) -> (&Path, Option<usize>, Option<&str>, &'static str, Severity) {
+    (&issue.file, issue.line, issue.name.as_deref(), issue.check_type, issue.severity)
+}
+
+fn compare<'a>(current: &Report, proposed: &'a Report) -> Comparison<'a> {
+    let mut by_check: BTreeMap<&'static str, CheckDelta> = BTreeMap::new();
+    for (report, is_proposed) in [(current, false), (proposed, true)] {
+        for issue in &report.issues {
+            let delta = by_check.entry(issue.check_type).or_default();
+            let counts = if is_proposed { &mut delta.proposed } else { &mut delta.current };
+            match issue.severity {
+                Severity::Error => counts.errors += 1,
+                Severity::Warning => counts.warnings += 1,
+                Severity::Info => {}
+            }
+        }
+    }
+    by_check.retain(|_, d| d.current != d.proposed);
+
+    let current_keys: HashSet<_> = current.issues.iter().filter(|i| i.severity != Severity::Info).map(key).collect();
+    let proposed_keys: HashSet<_> = proposed.issues.iter().filter(|i| i.severity != Severity::Info).map(key).collect();
+
+    Comparison {
+        current: counts(current),
+        proposed: counts(proposed),
+        by_check,
+        new_issues: proposed
+            .issues
+            .iter()
+            .filter(|i| i.severity != Severity::Info && !current_keys.contains(&key(i)))
+            .collect(),
+        resolved: current_keys.difference(&proposed_keys).count(),
+    }
+}
+
+/// Thresholds that the proposed overrides change, per detected language
+fn threshold_changes(detected: &[DetectedLanguage], config: &ProjectConfig, proposed: &Overrides) -> Vec<ThresholdChange> {
+    let mut changes = Vec::new();
+    for det in detected {
+        let current = config.thresholds(det.language, Path::new(""), &Overrides::default());
+        let proposed = config.thresholds(det.language, Path::new(""), proposed);
+        let (Ok(serde_json::Value::Object(current)), Ok(serde_json::Value::Object(proposed))) =
+            (serde_json::to_value(&current), serde_json::to_value(&proposed))
+        else {
+            continue;
+        };
+        for (name, value) in proposed {
+            let before = current.get(&name).cloned().unwrap_or(serde_json::Value::Null);
+            if before != value {
+                changes.push(ThresholdChange {
+                    language: det.language.name(),
+                    threshold: name,
+                    current: before,
+                    proposed: value,
+                });
+            }
+        }
+    }
+    changes
+}
+
+fn signed(current: usize, proposed: usize) -> String {
+    if proposed >= current {
+        format!("+{}", proposed - current)
+    } else {
+        format!("-{}", current - proposed)
+    }
+}
+
+/// Print how the proposed thresholds would change the findings of a project
+pub fn print_comparison(
+    project_dir: &Path,
+    detected: &[DetectedLanguage],
+    config: &ProjectConfig,
+    thresholds: &Overrides,
+    current: &Report,
+    proposed: &Report,
+    format: OutputFormat,
+) {
+    let changes = threshold_changes(detected, config, thresholds);
+    let comparison = compare(current, proposed);
+
+    if format == OutputFormat::Json {
+        let report = JsonWhatif {
+            project: project_dir.display().to_string(),
+            changes,
+            comparison,
+        };
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return;
+    }
+
+    println!("=== What-if Report ===");
+    println!("Project: {}", project_dir.display());
+
+    println!();
+    println!("--- PROPOSED CHANGES ---");
+    if changes.is_empty() {
+        println!("No thresholds changed (pass overrides such as --func-error 40)");
+    }
+    for change in &changes {
+        println!("{}: {} {} -> {}", change.language, change.threshold, change.current, change.proposed);
+    }
+
+    println!();
+    println!("--- IMPACT ---");
+    println!("{:<10} {:>8} {:>9} {:>7}", "", "Current", "Proposed", "Change");
+    for (label, before, after) in [
+        ("Errors", comparison.current.errors, comparison.proposed.errors),
+        ("Warnings", comparison.current.warnings, comparison.proposed.warnings),
+    ] {
+        println!("{:<10} {:>8} {:>9} {:>7}", label, before, after, signed(before, after));
+    }
+
+    if !comparison.by_check.is_empty() {
+        println!();
+        println!("--- BY CHECK ---");
+        for (check, delta) in &comparison.by_check {
+            println!(
+                "{}: errors {} -> {} ({}), warnings {} -> {} ({})",
+                check,
+                delta.current.errors,
+                delta.proposed.errors,
+                signed(delta.current.errors, delta.proposed.errors),
+                delta.current.warnings,
+                delta.proposed.warnings,
+                signed(delta.current.warnings, delta.proposed.warnings)
+            );
+        }
+    }
+
+    if !comparison.new_issues.is_empty() {
+        println!();
+        println!("--- NEW FINDINGS ({}) ---", comparison.new_issues.len());
+        for issue in &comparison.new_issues {
+            let label = if issue.severity == Severity::Error { "ERROR" } else { "WARN " };
+            println!("{}  {}", label, issue.message);
+        }
+    }
+
+    if comparison.resolved > 0 {
+        println!();
+        println!("Findings that would no longer be reported: {}", comparison.resolved);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn issue(line: usize, severity: Severity) -> Issue {
+        Issue {
+            severity,
+            file: PathBuf::from("a.py"),
+            line: Some(line),
+            column: None,
+            end_line: None,
+            name: Some(format!("f{}", line)),
+            check_type: "function-length",
+            value: 45,
+            limit: 40,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let current = Report {
+            issues: vec![issue(1, Severity::Warning), issue(10, Severity::Warning)],
+            files_scanned: 1,
+            ..Default::default()
+        };
+        let proposed = Report {
+            issues: vec![issue(1, Severity::Error), issue(10, Severity::Warning), issue(20, Severity::Warning)],
+            files_scanned: 1,
+            ..Default::default()
+        };
+
+        let comparison = compare(&current, &proposed);
+        assert_eq!(comparison.proposed, Counts { errors: 1, warnings: 2 });
+        // An escalated issue counts as new, and its old severity as resolved
+        assert_eq!(comparison.new_issues.len(), 2);
+        assert_eq!(comparison.resolved, 1);
+        assert_eq!(comparison.by_check.len(), 1);
+    }
+}
@@ -0,0 +1,377 @@
+//! End-to-end runs of the `code-smells` binary
+
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A project directory under the system temp dir, removed on drop
+struct Project(PathBuf);
+
+impl Project {
+    fn new(label: &str) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("csmells-cli-{}-{}-{}", label, std::process::id(), id));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Project(dir)
+    }
+
+    /// A Python project with one short, clean file
+    fn python(label: &str) -> Self {
+        let project = Project::new(label);
+        project.write("requirements.txt", "");
+        project.write("app.py", "def main():\n    return 1\n");
+        project
+    }
+
+    fn write(&self, name: &str, content: &str) {
+        let path = self.0.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// Run the binary on the project with `args`, in a clean environment
+    fn run(&self, args: &[&str]) -> Output {
+        self.run_with_env(args, &[])
+    }
+
+    fn run_with_env(&self, args: &[&str], env: &[(&str, &str)]) -> Output {
+        code_smells(&self.0, args, env)
+    }
+
+    /// Run git in the project, committing as a fixed test user
+    fn git(&self, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(&self.0)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com", "-c", "commit.gpgsign=false"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(status.status.success(), "git {:?}: {}", args, stderr(&status));
+    }
+
+    /// A git repository with everything written so far committed
+    fn commit_all(&self, message: &str) {
+        if !self.0.join(".git").exists() {
+            self.git(&["init", "--quiet"]);
+        }
+        self.git(&["add", "-A"]);
+        self.git(&["commit", "--quiet", "-m", message]);
+    }
+
+    fn is_empty(&self) -> bool {
+        fs::read_dir(&self.0).unwrap().next().is_none()
+    }
+}
+
+/// Run the binary on `target`, a directory, URL, or archive, in a clean environment
+fn code_smells(target: impl AsRef<OsStr>, args: &[&str], env: &[(&str, &str)]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_code-smells"));
+    for (key, _) in std::env::vars() {
+        if key.starts_with("CODE_SMELLS_") || key == "RAYON_NUM_THREADS" {
+            command.env_remove(key);
+        }
+    }
+    command.arg(target).args(args).envs(env.iter().copied()).output().unwrap()
+}
+
+/// A Python function of `lines` lines
+fn function(lines: usize) -> String {
+    format!("def f():\n{}    return x\n", "    x = 1\n".repeat(lines - 2))
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+impl Drop for Project {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn test_config_count_mode_without_flags() {
+    let project = Project::python("count-mode");
+    project.write(".code-smells.toml", "[thresholds]\ncount-mode = \"logical\"\nfunc-warn = 3\nfunc-error = 20\n");
+    project.write("app.py", "def main():\n    # one\n\n    # two\n    x = 1\n    return x\n");
+    let output = project.run(&[]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(project.run(&["--count-mode", "physical"]).status.code(), Some(1));
+}
+
+#[test]
+fn test_config_nesting_mode_without_flags() {
+    let project = Project::new("nesting-mode");
+    project.write(".code-smells.toml", "[thresholds]\nnesting-mode = \"control-flow\"\nnest-warn = 2\nnest-error = 20\n");
+    project.write("Cargo.toml", "[package]\nname = \"app\"\n");
+    project.write("src/lib.rs", "fn build() -> Vec<Point> {\n    vec![Point {\n        x: Some(Inner {\n            y: 1,\n        }),\n    }]\n}\n");
+    let output = project.run(&[]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(project.run(&["--nesting-mode", "blocks"]).status.code(), Some(1));
+}
+
+#[test]
+fn test_config_nested_functions_without_flags() {
+    let project = Project::python("nested-functions");
+    project.write(".code-smells.toml", "[thresholds]\nnested-functions = true\nfunc-warn = 3\nfunc-error = 20\n");
+    project.write("app.py", "def outer():\n    def inner():\n        a = 1\n        b = 2\n        return a + b\n    return inner\n");
+    let output = project.run(&["--format", "compact"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("inner"), "{}", stdout);
+
+    project.write(".code-smells.toml", "[thresholds]\nfunc-warn = 3\nfunc-error = 20\n");
+    let output = project.run(&["--format", "compact"]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("inner"));
+}
+
+#[test]
+fn test_jobs_sets_worker_threads() {
+    let project = Project::python("jobs");
+    for args in [&["--jobs", "2"][..], &["-j", "1"]] {
+        let output = project.run(args);
+        assert_eq!(output.status.code(), Some(0), "{:?}: {}", args, stderr(&output));
+    }
+
+    let output = project.run_with_env(&[], &[("CODE_SMELLS_JOBS", "2")]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+}
+
+#[test]
+fn test_threads_alias_and_rayon_env() {
+    let project = Project::python("threads");
+    for threads in ["1", "3"] {
+        let output = project.run(&["--threads", threads]);
+        assert_eq!(output.status.code(), Some(0), "--threads {}: {}", threads, stderr(&output));
+    }
+
+    let output = project.run_with_env(&[], &[("RAYON_NUM_THREADS", "1")]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+
+    let output = project.run(&["--threads", "0"]);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn test_print_config_shows_every_setting() {
+    let project = Project::python("print-config");
+    project.write(
+        ".code-smells.toml",
+        "[thresholds]\ncount-mode = \"logical\"\n\n[[paths]]\nglob = \"tests/**\"\nthresholds = { func-warn = 40 }\n\n[extensions]\npyw = \"python\"\n\n[skip-dirs]\npython = [\"generated\"]\n",
+    );
+    let output = project.run(&["--print-config"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for expected in ["count-mode = \"logical\"", "glob = \"tests/**\"", "func-warn = 40", "pyw = \"python\"", "\"generated\""] {
+        assert!(stdout.contains(expected), "missing {}: {}", expected, stdout);
+    }
+}
+
+#[test]
+fn test_impossible_thresholds_exit_with_failure_code() {
+    let project = Project::python("impossible");
+    project.write(".code-smells.toml", "[thresholds]\nnest-warn = 7\nnest-error = 6\n");
+    let output = project.run(&[]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("nest-warn"), "{}", stderr(&output));
+
+    project.write(".code-smells.toml", "[[paths]]\nglob = \"tests/**\"\nthresholds = { func-warn = 80 }\n");
+    assert_eq!(project.run(&[]).status.code(), Some(3));
+    assert_eq!(project.run(&["--func-error", "90"]).status.code(), Some(0));
+}
+
+#[test]
+fn test_near_misses_are_notes_and_keep_exit_code() {
+    let project = Project::python("near-misses");
+    project.write("app.py", &function(30));
+    for args in [&["--near-misses"][..], &["--near-misses", "--near-miss-pct", "0"]] {
+        let output = project.run(&[args, &["--format", "compact"]].concat());
+        assert_eq!(output.status.code(), Some(0), "{:?}: {}", args, stderr(&output));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("note: function-length"), "{:?}: {}", args, stdout);
+        assert!(!stdout.contains("warning:"), "{:?}: {}", args, stdout);
+    }
+
+    project.write("app.py", &function(29));
+    let output = project.run(&["--near-misses", "--near-miss-pct", "0", "--format", "compact"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("function-length"));
+}
+
+#[test]
+fn test_env_ranks_between_config_and_flags() {
+    let project = Project::python("env");
+    project.write(".code-smells.toml", "[thresholds]\nfunc-warn = 10\nfunc-error = 20\ncount-mode = \"logical\"\n");
+    project.write("app.py", "def main():\n    # one\n\n    # two\n    x = 1\n    return x\n");
+    assert_eq!(project.run(&[]).status.code(), Some(0));
+
+    // The environment overrides the config file
+    let env = [("CODE_SMELLS_FUNC_WARN", "2")];
+    assert_eq!(project.run_with_env(&[], &env).status.code(), Some(1));
+    let mode = [("CODE_SMELLS_FUNC_WARN", "4"), ("CODE_SMELLS_COUNT_MODE", "physical")];
+    assert_eq!(project.run_with_env(&[], &mode).status.code(), Some(1));
+
+    // An explicit flag overrides the environment
+    assert_eq!(project.run_with_env(&["--func-warn", "10"], &env).status.code(), Some(0));
+    assert_eq!(project.run_with_env(&["--count-mode", "logical"], &mode).status.code(), Some(0));
+}
+
+#[test]
+fn test_env_switches_and_values() {
+    let project = Project::python("env-values");
+    project.write("app.py", "def main():\n    x = 1\n    return x\n");
+    let output = project.run_with_env(&[], &[("CODE_SMELLS_FORMAT", "compact"), ("CODE_SMELLS_FUNC_WARN", "3"), ("CODE_SMELLS_NEAR_MISSES", "true")]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("note: function-length"));
+
+    let output = project.run_with_env(&["--format", "compact"], &[("CODE_SMELLS_FUNC_WARN", "3"), ("CODE_SMELLS_NEAR_MISSES", "0")]);
+    assert!(String::from_utf8_lossy(&output.stdout).is_empty());
+
+    let output = project.run_with_env(&[], &[("CODE_SMELLS_FUNC_WARN", "many")]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("--func-warn"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_function_column_and_end_line() {
+    let project = Project::python("columns");
+    project.write("app.py", "x = 1\n\nasync def fetch():\n    a = 1\n    b = 2\n    return a + b\n");
+    let run = |format| {
+        let output = project.run(&["--func-warn", "3", "--format", format]);
+        assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert!(run("compact").starts_with("app.py:3:11: warning: function-length: fetch"));
+
+    let json: serde_json::Value = serde_json::from_str(&run("json")).unwrap();
+    let issue = &json["issues"][0];
+    assert_eq!((issue["line"].as_u64(), issue["column"].as_u64(), issue["end_line"].as_u64()), (Some(3), Some(11), Some(6)));
+
+    let sarif: serde_json::Value = serde_json::from_str(&run("sarif")).unwrap();
+    let region = &sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(region, &serde_json::json!({"startLine": 3, "startColumn": 11, "endLine": 6}));
+}
+
+#[test]
+fn test_repository_url_clone() {
+    let repo = Project::python("clone-source");
+    repo.write("app.py", &function(40));
+    repo.commit_all("long function");
+    let tmp = Project::new("clone-tmp");
+    let url = format!("file://{}", path_str(&repo.0));
+
+    let output = code_smells(&url, &["--format", "compact"], &[("TMPDIR", path_str(&tmp.0))]);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("app.py:1:5: warning: function-length"));
+    assert!(tmp.is_empty(), "clone left behind");
+
+    let output = code_smells(format!("{}-missing", url), &[], &[("TMPDIR", path_str(&tmp.0))]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("Cannot clone"), "{}", stderr(&output));
+    assert!(tmp.is_empty(), "failed clone left behind");
+}
+
+#[test]
+fn test_tar_archive_extraction() {
+    let fixture = Project::new("tar-fixture");
+    fixture.write("app-1.0/requirements.txt", "");
+    fixture.write("app-1.0/app.py", &function(40));
+    let status = Command::new("tar").arg("-cf").arg(fixture.0.join("app-1.0.tar")).arg("-C").arg(&fixture.0).arg("app-1.0").status().unwrap();
+    assert!(status.success());
+    let tmp = Project::new("tar-tmp");
+
+    let output = code_smells(fixture.0.join("app-1.0.tar"), &["--format", "compact"], &[("TMPDIR", path_str(&tmp.0))]);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+    // The archive's single top-level directory is the project
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("app.py:1:5: warning"));
+    assert!(tmp.is_empty(), "extraction left behind");
+}
+
+#[test]
+fn test_rev_reads_an_old_commit() {
+    let repo = Project::python("rev");
+    repo.write("app.py", &function(40));
+    repo.commit_all("long function");
+    repo.write("app.py", &function(10));
+    repo.commit_all("shorter function");
+    let tmp = Project::new("rev-tmp");
+    let env = [("TMPDIR", path_str(&tmp.0))];
+
+    assert_eq!(repo.run_with_env(&[], &env).status.code(), Some(0));
+    let output = repo.run_with_env(&["--rev", "HEAD~1"], &env);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+    assert_eq!(fs::read_to_string(repo.0.join("app.py")).unwrap(), function(10));
+    assert!(tmp.is_empty(), "--rev copy left behind");
+
+    assert_eq!(repo.run_with_env(&["--rev", "no-such-branch"], &env).status.code(), Some(3));
+    assert!(tmp.is_empty());
+}
+
+#[test]
+fn test_staged_reads_the_index() {
+    let repo = Project::python("staged");
+    repo.commit_all("clean");
+    repo.write("app.py", &function(40));
+    repo.git(&["add", "app.py"]);
+    repo.write("app.py", &function(10));
+    let tmp = Project::new("staged-tmp");
+    let env = [("TMPDIR", path_str(&tmp.0))];
+
+    assert_eq!(repo.run_with_env(&[], &env).status.code(), Some(0));
+    let output = repo.run_with_env(&["--staged"], &env);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+    assert!(tmp.is_empty(), "--staged copy left behind");
+}
+
+#[test]
+fn test_recursive_projects() {
+    let monorepo = Project::new("monorepo");
+    monorepo.write("api/requirements.txt", "");
+    monorepo.write("api/app.py", &function(40));
+    monorepo.write("core/Cargo.toml", "[package]\nname = \"core\"\n");
+    monorepo.write("core/src/lib.rs", "pub fn core() {}\n");
+
+    assert_eq!(monorepo.run(&[]).status.code(), Some(3));
+    let output = monorepo.run(&["--recursive-projects"]);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Languages: python, rust"), "{}", stdout);
+    assert!(stdout.contains("--- api (1) ---"), "{}", stdout);
+    assert!(stdout.contains("Files scanned: 2"), "{}", stdout);
+}
+
+#[test]
+fn test_ndjson_lines_are_json() {
+    let project = Project::python("ndjson");
+    project.write("app.py", &format!("{}\n\n{}", function(40), function(60).replace("def f", "def g")));
+    let output = project.run(&["--format", "ndjson"]);
+    assert_eq!(output.status.code(), Some(2), "{}", stderr(&output));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<serde_json::Value> = stdout.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+    assert_eq!(lines.len(), 3, "{}", stdout);
+    let issues: Vec<(&str, &str)> = lines[..2]
+        .iter()
+        .map(|issue| (issue["name"].as_str().unwrap(), issue["severity"].as_str().unwrap()))
+        .collect();
+    assert_eq!(issues, [("f", "warning"), ("g", "error")]);
+    assert!(lines[..2].iter().all(|issue| issue["type"] == "function-length"));
+    let summary = &lines[2]["summary"];
+    assert_eq!((summary["files"].as_u64(), summary["errors"].as_u64(), summary["warnings"].as_u64()), (Some(1), Some(1), Some(1)));
+
+    // A clean run still ends with the summary line
+    project.write("app.py", &function(5));
+    let output = project.run(&["--format", "ndjson"]);
+    let line: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(line["summary"]["warnings"], 0);
+}